@@ -0,0 +1,513 @@
+//! A [PersistedGraph] that stores the task graph on disk, so a new session can
+//! continue from the state of the previous one and only needs to re-execute
+//! tasks whose inputs changed.
+//!
+//! The graph is kept in memory while in use and written to a single snapshot
+//! file in the cache directory when the backend stops (or [flush] is called).
+//! Snapshots written by a different binary or with a different set of
+//! registered functions and types are discarded.
+//!
+//! [flush]: DiskPersistedGraph::flush
+
+mod mapping;
+mod state;
+
+use std::{
+    cell::Cell,
+    collections::HashSet,
+    env,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{bail, Context, Result};
+use parking_lot::Mutex;
+use turbo_tasks::{
+    backend::PersistentTaskType,
+    persisted_graph::{
+        ActivateResult, DeactivateResult, PersistResult, PersistTaskState, PersistedGraph,
+        PersistedGraphApi, ReadTaskState, TaskData,
+    },
+    registry, with_task_id_mapping, IdMapping, RawVc, TaskId,
+};
+use turbo_tasks_hash::{DeterministicHasher, Xxh3Hash64Hasher};
+
+use crate::{
+    mapping::{Inner, PersistedIdMapping},
+    state::{GraphState, PersistedId},
+};
+
+const SNAPSHOT_FILE_NAME: &str = "graph.bin";
+
+pub struct DiskPersistedGraph {
+    path: PathBuf,
+    fingerprint: u64,
+    inner: Mutex<Inner>,
+}
+
+impl DiskPersistedGraph {
+    /// Opens the persisted graph in `directory`.
+    ///
+    /// All turbo-tasks functions and value types must be registered before
+    /// calling this, as they are part of the snapshot fingerprint.
+    pub fn new(directory: impl AsRef<Path>) -> Result<Self> {
+        let path = directory.as_ref().join(SNAPSHOT_FILE_NAME);
+        let fingerprint = fingerprint();
+        let mut state = GraphState::load(&path, fingerprint)?;
+        if let Err(err) = check_task_types(&state) {
+            tracing::warn!("discarding persisted graph {}: {err:#}", path.display());
+            state = GraphState::default();
+        }
+        Ok(Self {
+            path,
+            fingerprint,
+            inner: Mutex::new(Inner {
+                state,
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// Marks all restored tasks whose name (e.g. `DiskFileSystem::read`)
+    /// matches `predicate` as dirty. Use this for tasks that read external
+    /// state which might have changed while no session was running.
+    pub fn invalidate_on_startup(self, predicate: impl Fn(&str) -> bool) -> Self {
+        self.inner.lock().state.invalidate(predicate);
+        self
+    }
+
+    /// Writes the current state of the graph to disk.
+    pub fn flush(&self) -> Result<()> {
+        let mut inner = self.inner.lock();
+        inner.retain_restorable();
+        inner.state.save(&self.path, self.fingerprint)
+    }
+
+    fn with_mapping<T>(
+        &self,
+        api: &dyn PersistedGraphApi,
+        func: impl FnOnce(&PersistedIdMapping) -> T,
+    ) -> T {
+        let mut inner = self.inner.lock();
+        let mapping = PersistedIdMapping::new(&mut inner, api);
+        with_task_id_mapping(&mapping, || func(&mapping))
+    }
+}
+
+/// Identifies the binary and the registry the snapshot was written with.
+fn fingerprint() -> u64 {
+    let mut hasher = Xxh3Hash64Hasher::new();
+    hasher.write_bytes(env!("CARGO_PKG_VERSION").as_bytes());
+    if let Some(metadata) = env::current_exe().ok().and_then(|exe| exe.metadata().ok()) {
+        hasher.write_u64(metadata.len());
+        if let Ok(modified) = metadata.modified() {
+            let since_epoch = modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+            hasher.write_u128(since_epoch.as_nanos());
+        }
+    }
+    for name in registry::all_global_names() {
+        hasher.write_bytes(name.as_bytes());
+        hasher.write_u8(0);
+    }
+    hasher.finish()
+}
+
+impl PersistedGraph for DiskPersistedGraph {
+    fn read(
+        &self,
+        task: TaskId,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<(TaskData, ReadTaskState)>> {
+        self.with_mapping(api, |mapping| {
+            let id = mapping.id(task);
+            let (bytes, clean, keeps_external_active) = {
+                let state = mapping.state();
+                let Some(entry) = state.tasks.get(&id) else {
+                    return Ok(None);
+                };
+                let Some(bytes) = entry.data.clone() else {
+                    return Ok(None);
+                };
+                (bytes, entry.clean, entry.internal_active_parents > 0)
+            };
+            let Some(data) = mapping.deserialize::<TaskData>(&bytes) else {
+                tracing::warn!("dropping unreadable persisted data of task {id}");
+                if let Some(entry) = mapping.state().tasks.get_mut(&id) {
+                    entry.data = None;
+                }
+                return Ok(None);
+            };
+            Ok(Some((
+                data,
+                ReadTaskState {
+                    clean,
+                    keeps_external_active,
+                },
+            )))
+        })
+    }
+
+    fn lookup(
+        &self,
+        _partial_task_type: &PersistentTaskType,
+        _api: &dyn PersistedGraphApi,
+    ) -> Result<bool> {
+        // Task types are stored by their serialized form, which doesn't allow
+        // prefix lookups. Callers fall back to `lookup_one`.
+        Ok(false)
+    }
+
+    fn lookup_one(
+        &self,
+        task_type: &PersistentTaskType,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<TaskId>> {
+        self.with_mapping(api, |mapping| {
+            let Some(bytes) = mapping.serialize(task_type) else {
+                return Ok(None);
+            };
+            let id = {
+                let state = mapping.state();
+                state.cache.get(&bytes).copied().filter(|id| {
+                    state
+                        .tasks
+                        .get(id)
+                        .is_some_and(|entry| entry.is_persisted())
+                })
+            };
+            id.map(|id| mapping.task(id)).transpose()
+        })
+    }
+
+    fn is_persisted(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<bool> {
+        self.with_mapping(api, |mapping| {
+            let id = mapping.id(task);
+            let state = mapping.state();
+            Ok(state.entry(id)?.is_persisted())
+        })
+    }
+
+    fn persist(
+        &self,
+        task: TaskId,
+        data: TaskData,
+        state: PersistTaskState,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<PersistResult>> {
+        self.with_mapping(api, |mapping| {
+            mapping.take_failed();
+            let id = mapping.id(task);
+            let children = data
+                .children
+                .iter()
+                .map(|&child| mapping.id(child))
+                .collect::<Vec<_>>();
+            let dependencies = data
+                .dependencies
+                .iter()
+                .map(|&vc| mapping.vc(vc))
+                .collect::<Vec<_>>();
+            let bytes = mapping.serialize(&data);
+            let (Some(bytes), false) = (bytes, mapping.take_failed()) else {
+                // The task or something it references isn't serializable
+                return Ok(None);
+            };
+
+            let mut tasks_to_activate = Vec::new();
+            let mut tasks_to_deactivate = Vec::new();
+            {
+                let mut graph = mapping.state();
+                graph.set_dependencies(id, dependencies);
+                let entry = graph.entry_mut(id)?;
+                let old_children = std::mem::replace(&mut entry.children, children);
+                entry.data = Some(bytes);
+                entry.clean = true;
+                entry.externally_active = state.externally_active;
+                entry.externally_active_renewed |= state.externally_active;
+                if entry.should_be_active() && !entry.active {
+                    tasks_to_activate.push(id);
+                }
+                if entry.active {
+                    let new_children = entry.children.clone();
+                    let old = old_children.iter().copied().collect::<HashSet<_>>();
+                    let new = new_children.iter().copied().collect::<HashSet<_>>();
+                    for &child in new_children.iter().filter(|child| !old.contains(child)) {
+                        if graph.increment_active_parents(child) {
+                            tasks_to_activate.push(child);
+                        }
+                    }
+                    for &child in old_children.iter().filter(|child| !new.contains(child)) {
+                        if graph.decrement_active_parents(child) {
+                            tasks_to_deactivate.push(child);
+                        }
+                    }
+                }
+            }
+
+            Ok(Some(PersistResult {
+                tasks_to_activate: mapping.tasks(tasks_to_activate)?,
+                tasks_to_deactivate: mapping.tasks(tasks_to_deactivate)?,
+            }))
+        })
+    }
+
+    fn activate_when_needed(
+        &self,
+        task: TaskId,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<ActivateResult>> {
+        self.with_mapping(api, |mapping| {
+            let id = mapping.id(task);
+            let mut more_tasks_to_activate = Vec::new();
+            let result = {
+                let mut graph = mapping.state();
+                let entry = graph.entry_mut(id)?;
+                if !entry.should_be_active() {
+                    return Ok(None);
+                }
+                let children = if entry.active {
+                    Vec::new()
+                } else {
+                    entry.active = true;
+                    if entry.is_persisted() {
+                        entry.children.clone()
+                    } else {
+                        Vec::new()
+                    }
+                };
+                let keeps_external_active = entry.internal_active_parents > 0;
+                let external = !entry.is_persisted();
+                let dirty = entry.is_persisted() && !entry.clean;
+                for child in children {
+                    if graph.increment_active_parents(child) {
+                        more_tasks_to_activate.push(child);
+                    }
+                }
+                (keeps_external_active, external, dirty)
+            };
+            let (keeps_external_active, external, dirty) = result;
+            Ok(Some(ActivateResult {
+                keeps_external_active,
+                external,
+                dirty,
+                more_tasks_to_activate: mapping.tasks(more_tasks_to_activate)?,
+            }))
+        })
+    }
+
+    fn deactivate_when_needed(
+        &self,
+        task: TaskId,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<DeactivateResult>> {
+        self.with_mapping(api, |mapping| {
+            let id = mapping.id(task);
+            let mut more_tasks_to_deactivate = Vec::new();
+            {
+                let mut graph = mapping.state();
+                let entry = graph.entry_mut(id)?;
+                if entry.internal_active_parents > 0 {
+                    // Still kept active by persisted parents
+                    return Ok(None);
+                }
+                if entry.active && !entry.should_be_active() {
+                    entry.active = false;
+                    let children = if entry.is_persisted() {
+                        entry.children.clone()
+                    } else {
+                        Vec::new()
+                    };
+                    for child in children {
+                        if graph.decrement_active_parents(child) {
+                            more_tasks_to_deactivate.push(child);
+                        }
+                    }
+                }
+            }
+            Ok(Some(DeactivateResult {
+                more_tasks_to_deactivate: mapping.tasks(more_tasks_to_deactivate)?,
+            }))
+        })
+    }
+
+    fn set_externally_active(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<bool> {
+        self.with_mapping(api, |mapping| {
+            let id = mapping.id(task);
+            let mut graph = mapping.state();
+            let entry = graph.entry_mut(id)?;
+            entry.externally_active = true;
+            entry.externally_active_renewed = true;
+            Ok(!entry.active)
+        })
+    }
+
+    fn unset_externally_active(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<bool> {
+        self.with_mapping(api, |mapping| {
+            let id = mapping.id(task);
+            let mut graph = mapping.state();
+            let entry = graph.entry_mut(id)?;
+            entry.externally_active = false;
+            Ok(entry.active && !entry.should_be_active())
+        })
+    }
+
+    fn remove_outdated_externally_active(
+        &self,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Vec<TaskId>> {
+        self.with_mapping(api, |mapping| {
+            let outdated = {
+                let mut graph = mapping.state();
+                let mut outdated = Vec::new();
+                for (&id, entry) in graph.tasks.iter_mut() {
+                    if entry.externally_active && !entry.externally_active_renewed {
+                        entry.externally_active = false;
+                        if entry.active && !entry.should_be_active() {
+                            outdated.push(id);
+                        }
+                    }
+                }
+                outdated
+            };
+            mapping.tasks(outdated)
+        })
+    }
+
+    fn make_dirty(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<bool> {
+        self.with_mapping(api, |mapping| {
+            let id = mapping.id(task);
+            let mut graph = mapping.state();
+            let entry = graph.entry_mut(id)?;
+            if !entry.is_persisted() {
+                return Ok(false);
+            }
+            entry.clean = false;
+            Ok(entry.active)
+        })
+    }
+
+    fn make_clean(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<()> {
+        self.with_mapping(api, |mapping| {
+            let id = mapping.id(task);
+            let mut graph = mapping.state();
+            let entry = graph.entry_mut(id)?;
+            entry.clean = true;
+            Ok(())
+        })
+    }
+
+    fn make_dependent_dirty(&self, vc: RawVc, api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>> {
+        self.with_mapping(api, |mapping| {
+            let vc = mapping.vc(vc);
+            let active_dependents = {
+                let mut graph = mapping.state();
+                let dependents = graph
+                    .dependents
+                    .get(&vc)
+                    .map(|dependents| dependents.iter().copied().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                let mut active_dependents = Vec::new();
+                for id in dependents {
+                    let entry = graph.entry_mut(id)?;
+                    entry.clean = false;
+                    if entry.active {
+                        active_dependents.push(id);
+                    }
+                }
+                active_dependents
+            };
+            mapping.tasks(active_dependents)
+        })
+    }
+
+    fn get_active_external_tasks(&self, api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>> {
+        self.with_mapping(api, |mapping| {
+            let ids = collect_ids(&mapping.state(), |entry| {
+                !entry.is_persisted() && entry.internal_active_parents > 0
+            });
+            mapping.tasks(ids)
+        })
+    }
+
+    fn get_dirty_active_tasks(&self, api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>> {
+        self.with_mapping(api, |mapping| {
+            let ids = collect_ids(&mapping.state(), |entry| {
+                entry.is_persisted() && entry.active && !entry.clean
+            });
+            mapping.tasks(ids)
+        })
+    }
+
+    fn get_pending_active_update(
+        &self,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<(Vec<TaskId>, Vec<TaskId>)> {
+        self.with_mapping(api, |mapping| {
+            let (to_activate, to_deactivate) = {
+                let graph = mapping.state();
+                (
+                    collect_ids(&graph, |entry| entry.should_be_active() && !entry.active),
+                    collect_ids(&graph, |entry| entry.active && !entry.should_be_active()),
+                )
+            };
+            Ok((mapping.tasks(to_activate)?, mapping.tasks(to_deactivate)?))
+        })
+    }
+
+    fn stop(&self, _api: &dyn PersistedGraphApi) -> Result<()> {
+        self.flush()
+    }
+}
+
+/// Checks that the task types of all restored tasks can be read with the
+/// registry of the current binary.
+fn check_task_types(state: &GraphState) -> Result<()> {
+    struct Check<'a> {
+        state: &'a GraphState,
+        unknown: Cell<Option<usize>>,
+    }
+
+    impl IdMapping<TaskId> for Check<'_> {
+        fn forward(&self, task: TaskId) -> usize {
+            *task
+        }
+
+        fn backward(&self, id: usize) -> TaskId {
+            if !self.state.tasks.contains_key(&(id as PersistedId)) {
+                self.unknown.set(Some(id));
+            }
+            // The task types are only checked and discarded afterwards.
+            TaskId::from(usize::MAX)
+        }
+    }
+
+    let check = Check {
+        state,
+        unknown: Cell::new(None),
+    };
+    with_task_id_mapping(&check, || {
+        for (id, entry) in state.tasks.iter() {
+            let Some(task_type) = &entry.task_type else {
+                continue;
+            };
+            postcard::from_bytes::<PersistentTaskType>(task_type)
+                .with_context(|| format!("task {id} has an invalid task type"))?;
+            if let Some(unknown) = check.unknown.take() {
+                bail!("the task type of task {id} refers to the unknown task {unknown}");
+            }
+        }
+        Ok(())
+    })
+}
+
+fn collect_ids(
+    state: &GraphState,
+    predicate: impl Fn(&state::TaskEntry) -> bool,
+) -> Vec<PersistedId> {
+    state
+        .tasks
+        .iter()
+        .filter(|(_, entry)| predicate(entry))
+        .map(|(&id, _)| id)
+        .collect()
+}
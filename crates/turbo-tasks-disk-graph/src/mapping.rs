@@ -0,0 +1,174 @@
+use std::cell::{Cell, RefCell, RefMut};
+
+use anyhow::{bail, Context, Result};
+use rustc_hash::FxHashMap;
+use serde::{de::DeserializeOwned, Serialize};
+use turbo_tasks::{
+    backend::PersistentTaskType, persisted_graph::PersistedGraphApi, IdMapping, RawVc, TaskId,
+};
+
+use crate::state::{GraphState, PersistedId, PersistedVc};
+
+/// Everything that is guarded by the lock of the persisted graph.
+#[derive(Default)]
+pub struct Inner {
+    pub state: GraphState,
+    /// Mapping between task ids of the current session and persisted ids.
+    pub task_to_id: FxHashMap<TaskId, PersistedId>,
+    pub id_to_task: FxHashMap<PersistedId, TaskId>,
+}
+
+impl Inner {
+    /// Removes the tasks that can't be restored in a later session and
+    /// forgets their ids, so they get a new one when they are used again.
+    pub fn retain_restorable(&mut self) {
+        for id in self.state.retain_restorable() {
+            if let Some(task) = self.id_to_task.remove(&id) {
+                self.task_to_id.remove(&task);
+            }
+        }
+    }
+}
+
+/// Maps [TaskId]s of the current session to [PersistedId]s and back. Unknown
+/// tasks are looked up by their serialized task type, so a task from a
+/// previous session gets the same [PersistedId] again.
+pub struct PersistedIdMapping<'a> {
+    inner: RefCell<&'a mut Inner>,
+    api: &'a dyn PersistedGraphApi,
+    /// Set when a task type couldn't be serialized or a task couldn't be
+    /// restored.
+    failed: Cell<bool>,
+}
+
+impl<'a> PersistedIdMapping<'a> {
+    pub fn new(inner: &'a mut Inner, api: &'a dyn PersistedGraphApi) -> Self {
+        Self {
+            inner: RefCell::new(inner),
+            api,
+            failed: Cell::new(false),
+        }
+    }
+
+    /// Access to the locked state. The returned guard must be dropped before
+    /// any other method of the mapping is called.
+    pub fn inner(&self) -> RefMut<'_, &'a mut Inner> {
+        self.inner.borrow_mut()
+    }
+
+    pub fn state(&self) -> RefMut<'_, GraphState> {
+        RefMut::map(self.inner.borrow_mut(), |inner| &mut inner.state)
+    }
+
+    /// Returns the [PersistedId] of a task, creating one when the task isn't
+    /// known yet.
+    pub fn id(&self, task: TaskId) -> PersistedId {
+        if let Some(&id) = self.inner().task_to_id.get(&task) {
+            return id;
+        }
+        let task_type = self.api.lookup_task_type(task);
+        // Serializing the task type might map further task ids, so the state
+        // must not be borrowed here.
+        let serialized = self.serialize(task_type);
+        let mut inner = self.inner();
+        let id = match serialized
+            .as_ref()
+            .and_then(|bytes| inner.state.cache.get(bytes))
+        {
+            Some(&id) => id,
+            None => inner.state.create_task(serialized, task_type.to_string()),
+        };
+        inner.task_to_id.insert(task, id);
+        inner.id_to_task.insert(id, task);
+        id
+    }
+
+    /// Returns the [TaskId] of a persisted task, recreating the task from its
+    /// persisted task type when it's not known to the current session.
+    pub fn task(&self, id: PersistedId) -> Result<TaskId> {
+        let task_type = {
+            let inner = self.inner();
+            if let Some(&task) = inner.id_to_task.get(&id) {
+                return Ok(task);
+            }
+            inner
+                .state
+                .entry(id)?
+                .task_type
+                .clone()
+                .with_context(|| format!("persisted task {id} has no task type"))?
+        };
+        let Some(task_type) = self.deserialize::<PersistentTaskType>(&task_type) else {
+            bail!("persisted task {id} has an invalid task type");
+        };
+        let task = self.api.get_or_create_task_type(task_type);
+        let mut inner = self.inner();
+        inner.task_to_id.insert(task, id);
+        inner.id_to_task.insert(id, task);
+        Ok(task)
+    }
+
+    pub fn vc(&self, vc: RawVc) -> PersistedVc {
+        match vc {
+            RawVc::TaskOutput(task) => PersistedVc::Output(self.id(task)),
+            RawVc::TaskCell(task, cell) => PersistedVc::Cell(self.id(task), cell),
+        }
+    }
+
+    pub fn tasks(&self, ids: impl IntoIterator<Item = PersistedId>) -> Result<Vec<TaskId>> {
+        ids.into_iter().map(|id| self.task(id)).collect()
+    }
+
+    /// Serializes a value, mapping all contained task ids. Returns `None` when
+    /// the value or any referenced task type isn't serializable.
+    pub fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Option<Vec<u8>> {
+        let failed_before = self.failed.replace(false);
+        let result = postcard::to_allocvec(value).ok();
+        let failed = self.failed.replace(failed_before) || result.is_none();
+        if failed {
+            self.failed.set(true);
+            None
+        } else {
+            result
+        }
+    }
+
+    /// Deserializes a value, mapping all contained task ids. Returns `None`
+    /// when the value or any referenced task can't be restored.
+    pub fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Option<T> {
+        let failed_before = self.failed.replace(false);
+        let result = postcard::from_bytes(bytes).ok();
+        let failed = self.failed.replace(failed_before) || result.is_none();
+        if failed {
+            self.failed.set(true);
+            None
+        } else {
+            result
+        }
+    }
+
+    /// Resets the failure flag and returns whether any serialization failed
+    /// since the last call.
+    pub fn take_failed(&self) -> bool {
+        self.failed.replace(false)
+    }
+}
+
+impl<'a> IdMapping<TaskId> for PersistedIdMapping<'a> {
+    fn forward(&self, task: TaskId) -> usize {
+        self.id(task) as usize
+    }
+
+    fn backward(&self, id: usize) -> TaskId {
+        match self.task(id as PersistedId) {
+            Ok(task) => task,
+            Err(err) => {
+                tracing::warn!("{err:#}");
+                // The value being deserialized is discarded, so this id is
+                // never used.
+                self.failed.set(true);
+                TaskId::from(usize::MAX)
+            }
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    path::Path,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::CellId;
+
+/// Id of a task in the persisted graph. Unlike a [turbo_tasks::TaskId] it is
+/// stable across sessions.
+pub type PersistedId = u64;
+
+/// A [turbo_tasks::RawVc] with the task id replaced by its [PersistedId].
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PersistedVc {
+    Output(PersistedId),
+    Cell(PersistedId, CellId),
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct TaskEntry {
+    /// The serialized `PersistentTaskType`. `None` when the task type isn't
+    /// serializable. These tasks are only known for the current session.
+    pub task_type: Option<Vec<u8>>,
+    /// Human readable name of the task, used by startup invalidation.
+    pub name: String,
+    /// The serialized `TaskData`. `None` when the task only lives in memory.
+    pub data: Option<Vec<u8>>,
+    pub children: Vec<PersistedId>,
+    pub dependencies: Vec<PersistedVc>,
+    pub clean: bool,
+    /// The task is active in the persisted graph.
+    pub active: bool,
+    /// Number of active persisted tasks that have this task as child.
+    pub internal_active_parents: u32,
+    /// The task is kept active by the memory graph.
+    pub externally_active: bool,
+    /// `externally_active` has been set during the current session.
+    #[serde(skip)]
+    pub externally_active_renewed: bool,
+}
+
+impl TaskEntry {
+    pub fn is_persisted(&self) -> bool {
+        self.data.is_some()
+    }
+
+    pub fn should_be_active(&self) -> bool {
+        self.externally_active || self.internal_active_parents > 0
+    }
+}
+
+/// The whole persisted graph. It's kept in memory while the graph is in use
+/// and written to disk as a single snapshot.
+#[derive(Serialize, Deserialize, Default)]
+pub struct GraphState {
+    pub next_id: PersistedId,
+    pub tasks: FxHashMap<PersistedId, TaskEntry>,
+    /// Serialized task type -> task. Derived from `tasks`.
+    #[serde(skip)]
+    pub cache: FxHashMap<Vec<u8>, PersistedId>,
+    /// Vc -> tasks that read it. Derived from `tasks`.
+    #[serde(skip)]
+    pub dependents: FxHashMap<PersistedVc, FxHashSet<PersistedId>>,
+}
+
+/// Written in front of the snapshot. A snapshot with a different header is
+/// discarded.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct Header {
+    format_version: u32,
+    fingerprint: u64,
+}
+
+/// Bump this when the layout of [GraphState] changes.
+const FORMAT_VERSION: u32 = 1;
+
+impl GraphState {
+    /// Reads a snapshot from `path`. Returns an empty state when there is no
+    /// snapshot or it was written by a different binary or registry
+    /// (`fingerprint`).
+    pub fn load(path: &Path, fingerprint: u64) -> Result<Self> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to read persisted graph {}", path.display()))
+            }
+        };
+        let expected = Header {
+            format_version: FORMAT_VERSION,
+            fingerprint,
+        };
+        let state = match postcard::take_from_bytes::<Header>(&bytes) {
+            Ok((header, rest)) if header == expected => postcard::from_bytes::<GraphState>(rest),
+            Ok((header, _)) => {
+                tracing::info!(
+                    "discarding persisted graph {} ({header:?} does not match {expected:?})",
+                    path.display()
+                );
+                return Ok(Self::default());
+            }
+            Err(err) => Err(err),
+        };
+        let mut state = match state {
+            Ok(state) => state,
+            Err(err) => {
+                tracing::warn!(
+                    "discarding unreadable persisted graph {}: {err}",
+                    path.display()
+                );
+                return Ok(Self::default());
+            }
+        };
+        if let Err(err) = state.validate() {
+            tracing::warn!(
+                "discarding inconsistent persisted graph {}: {err}",
+                path.display()
+            );
+            return Ok(Self::default());
+        }
+        state.rebuild_indices();
+        Ok(state)
+    }
+
+    /// Checks that a loaded snapshot only contains restorable tasks and only
+    /// refers to tasks it contains.
+    fn validate(&self) -> Result<()> {
+        let mut task_types = FxHashSet::default();
+        for (&id, entry) in self.tasks.iter() {
+            if id >= self.next_id {
+                bail!("task {id} is not below the next id {}", self.next_id);
+            }
+            let Some(task_type) = &entry.task_type else {
+                bail!("task {id} has no task type");
+            };
+            if !task_types.insert(task_type) {
+                bail!("task {id} has the same task type as another task");
+            }
+            let dependencies = entry
+                .dependencies
+                .iter()
+                .map(|dependency| match dependency {
+                    PersistedVc::Output(id) | PersistedVc::Cell(id, _) => id,
+                });
+            for child in entry.children.iter().chain(dependencies) {
+                if !self.tasks.contains_key(child) {
+                    bail!("task {id} refers to the unknown task {child}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a snapshot to `path`. The file is replaced atomically.
+    pub fn save(&self, path: &Path, fingerprint: u64) -> Result<()> {
+        let header = Header {
+            format_version: FORMAT_VERSION,
+            fingerprint,
+        };
+        let mut bytes = postcard::to_allocvec(&header)?;
+        bytes.extend(postcard::to_allocvec(self).context("failed to serialize persisted graph")?);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&temp_path)
+            .with_context(|| format!("failed to create {}", temp_path.display()))?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temp_path, path)
+            .with_context(|| format!("failed to write persisted graph {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Removes tasks that can't be restored in a later session. These are
+    /// never referenced by persisted tasks, as persisting fails for them.
+    /// Returns the ids of the removed tasks.
+    pub fn retain_restorable(&mut self) -> Vec<PersistedId> {
+        let mut removed = Vec::new();
+        self.tasks.retain(|&id, entry| {
+            let restorable = entry.task_type.is_some();
+            if !restorable {
+                removed.push(id);
+            }
+            restorable
+        });
+        removed
+    }
+
+    fn rebuild_indices(&mut self) {
+        self.cache.clear();
+        self.dependents.clear();
+        for (&id, entry) in self.tasks.iter() {
+            if let Some(task_type) = &entry.task_type {
+                self.cache.insert(task_type.clone(), id);
+            }
+            for &dependency in entry.dependencies.iter() {
+                self.dependents.entry(dependency).or_default().insert(id);
+            }
+        }
+    }
+
+    pub fn entry(&self, id: PersistedId) -> Result<&TaskEntry> {
+        self.tasks
+            .get(&id)
+            .ok_or_else(|| anyhow!("persisted graph has no task {id}"))
+    }
+
+    pub fn entry_mut(&mut self, id: PersistedId) -> Result<&mut TaskEntry> {
+        self.tasks
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("persisted graph has no task {id}"))
+    }
+
+    pub fn create_task(&mut self, task_type: Option<Vec<u8>>, name: String) -> PersistedId {
+        let id = self.next_id;
+        self.next_id += 1;
+        if let Some(task_type) = &task_type {
+            self.cache.insert(task_type.clone(), id);
+        }
+        self.tasks.insert(
+            id,
+            TaskEntry {
+                task_type,
+                name,
+                ..Default::default()
+            },
+        );
+        id
+    }
+
+    /// Marks all persisted tasks matching `predicate` as dirty.
+    pub fn invalidate(&mut self, predicate: impl Fn(&str) -> bool) {
+        for entry in self.tasks.values_mut() {
+            if entry.is_persisted() && predicate(&entry.name) {
+                entry.clean = false;
+            }
+        }
+    }
+
+    pub fn set_dependencies(&mut self, id: PersistedId, dependencies: Vec<PersistedVc>) {
+        let Some(entry) = self.tasks.get_mut(&id) else {
+            return;
+        };
+        let old_dependencies = std::mem::replace(&mut entry.dependencies, dependencies);
+        for dependency in old_dependencies {
+            if let Some(dependents) = self.dependents.get_mut(&dependency) {
+                dependents.remove(&id);
+                if dependents.is_empty() {
+                    self.dependents.remove(&dependency);
+                }
+            }
+        }
+        for &dependency in self.tasks[&id].dependencies.iter() {
+            self.dependents.entry(dependency).or_default().insert(id);
+        }
+    }
+
+    /// Adds an active parent to a task. Returns true when the task needs to
+    /// be activated.
+    pub fn increment_active_parents(&mut self, id: PersistedId) -> bool {
+        let Some(entry) = self.tasks.get_mut(&id) else {
+            return false;
+        };
+        entry.internal_active_parents += 1;
+        !entry.active
+    }
+
+    /// Removes an active parent from a task. Returns true when the task needs
+    /// to be deactivated.
+    pub fn decrement_active_parents(&mut self, id: PersistedId) -> bool {
+        let Some(entry) = self.tasks.get_mut(&id) else {
+            return false;
+        };
+        entry.internal_active_parents = entry.internal_active_parents.saturating_sub(1);
+        entry.active && !entry.should_be_active()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_with_tasks() -> GraphState {
+        let mut state = GraphState::default();
+        let a = state.create_task(Some(vec![1]), "a".to_string());
+        let b = state.create_task(Some(vec![2]), "b".to_string());
+        state.create_task(None, "transient".to_string());
+        let entry = state.tasks.get_mut(&a).unwrap();
+        entry.data = Some(vec![42]);
+        entry.clean = true;
+        entry.children = vec![b];
+        state.set_dependencies(a, vec![PersistedVc::Output(b)]);
+        state
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("graph.bin");
+        let mut state = state_with_tasks();
+        state.retain_restorable();
+        state.save(&path, 7)?;
+
+        let loaded = GraphState::load(&path, 7)?;
+        assert_eq!(loaded.next_id, 3);
+        assert_eq!(loaded.tasks.len(), 2);
+        assert_eq!(loaded.cache.get(&vec![1]), Some(&0));
+        assert_eq!(loaded.tasks[&0].data, Some(vec![42]));
+        assert_eq!(loaded.tasks[&0].children, vec![1]);
+        assert!(loaded.dependents[&PersistedVc::Output(1)].contains(&0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fingerprint_mismatch_discards_snapshot() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("graph.bin");
+        state_with_tasks().save(&path, 7)?;
+
+        let loaded = GraphState::load(&path, 8)?;
+        assert!(loaded.tasks.is_empty());
+        assert_eq!(loaded.next_id, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupt_snapshot_is_discarded() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("graph.bin");
+        let mut bytes = postcard::to_allocvec(&Header {
+            format_version: FORMAT_VERSION,
+            fingerprint: 7,
+        })?;
+        bytes.extend([0xff; 3]);
+        fs::write(&path, bytes)?;
+
+        assert!(GraphState::load(&path, 7)?.tasks.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_inconsistent_snapshot_is_discarded() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("graph.bin");
+        let mut state = state_with_tasks();
+        state.retain_restorable();
+        state.tasks.get_mut(&0).unwrap().children.push(5);
+        state.save(&path, 7)?;
+
+        assert!(GraphState::load(&path, 7)?.tasks.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_transient_tasks_are_not_restorable() {
+        let mut state = state_with_tasks();
+        assert!(state.validate().is_err());
+        assert_eq!(state.retain_restorable(), vec![2]);
+        assert!(state.validate().is_ok());
+        assert!(state.entry(2).is_err());
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let mut state = state_with_tasks();
+        state.invalidate(|name| name == "a");
+        assert!(!state.tasks[&0].clean);
+    }
+
+    #[test]
+    fn test_set_dependencies_updates_dependents() {
+        let mut state = state_with_tasks();
+        state.set_dependencies(0, vec![PersistedVc::Output(2)]);
+        assert!(!state.dependents.contains_key(&PersistedVc::Output(1)));
+        assert!(state.dependents[&PersistedVc::Output(2)].contains(&0));
+    }
+}
@@ -0,0 +1,81 @@
+#![feature(arbitrary_self_types)]
+
+use std::{
+    fs,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::Result;
+use turbo_tasks::{TurboTasks, Vc};
+use turbo_tasks_disk_graph::DiskPersistedGraph;
+use turbo_tasks_memory::MemoryBackendWithPersistedGraph;
+use turbo_tasks_testing::register;
+
+register!();
+
+static DOUBLE_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+static TRIPLE_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[turbo_tasks::function]
+fn double(value: u32) -> Vc<u32> {
+    DOUBLE_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    Vc::cell(value * 2)
+}
+
+#[turbo_tasks::function]
+fn triple(value: u32) -> Vc<u32> {
+    TRIPLE_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    Vc::cell(value * 3)
+}
+
+/// Runs `func` in a new session on top of the persisted graph in `directory`
+/// and stops the session afterwards, which writes the graph to disk.
+async fn session(directory: &Path, func: fn(u32) -> Vc<u32>) -> Result<u32> {
+    let tt = TurboTasks::new(MemoryBackendWithPersistedGraph::new(
+        DiskPersistedGraph::new(directory)?,
+    ));
+    let result = tt
+        .run_once(async move { Ok(*func(21).strongly_consistent().await?) })
+        .await;
+    tt.stop_and_wait().await;
+    result
+}
+
+#[allow(clippy::no_effect)] // for *REGISTER
+#[tokio::test]
+async fn restores_tasks_after_restart() -> Result<()> {
+    *REGISTER;
+    let directory = tempfile::tempdir()?;
+
+    assert_eq!(session(directory.path(), double).await?, 42);
+    assert_eq!(DOUBLE_EXECUTIONS.load(Ordering::SeqCst), 1);
+    assert!(directory.path().join("graph.bin").exists());
+
+    // The task is restored from disk instead of being executed again.
+    assert_eq!(session(directory.path(), double).await?, 42);
+    assert_eq!(DOUBLE_EXECUTIONS.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[allow(clippy::no_effect)] // for *REGISTER
+#[tokio::test]
+async fn corrupt_graph_falls_back_to_a_cold_start() -> Result<()> {
+    *REGISTER;
+    let directory = tempfile::tempdir()?;
+    let path = directory.path().join("graph.bin");
+
+    fs::write(&path, b"not a persisted graph")?;
+    assert_eq!(session(directory.path(), triple).await?, 63);
+    assert_eq!(TRIPLE_EXECUTIONS.load(Ordering::SeqCst), 1);
+
+    // Keep the header, but corrupt the tasks.
+    let mut bytes = fs::read(&path)?;
+    let half = bytes.len() / 2;
+    for byte in &mut bytes[half..] {
+        *byte ^= 0xff;
+    }
+    fs::write(&path, bytes)?;
+    assert_eq!(session(directory.path(), triple).await?, 63);
+    Ok(())
+}
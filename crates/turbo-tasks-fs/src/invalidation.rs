@@ -128,3 +128,47 @@ impl InvalidationReasonKind for WriteKind {
         )
     }
 }
+
+/// Invalidation was caused by an in-memory override of an
+/// [OverlayFileSystem](crate::OverlayFileSystem) being set or removed
+#[derive(PartialEq, Eq, Hash)]
+pub struct OverlayChange {
+    pub path: String,
+}
+
+impl InvalidationReason for OverlayChange {
+    fn kind(&self) -> Option<StaticOrArc<dyn InvalidationReasonKind>> {
+        Some(StaticOrArc::Static(&OVERLAY_CHANGE_KIND))
+    }
+}
+
+impl Display for OverlayChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} overridden", self.path)
+    }
+}
+
+/// Invalidation kind for [OverlayChange]
+#[derive(PartialEq, Eq, Hash)]
+struct OverlayChangeKind;
+
+static OVERLAY_CHANGE_KIND: OverlayChangeKind = OverlayChangeKind;
+
+impl InvalidationReasonKind for OverlayChangeKind {
+    fn fmt(
+        &self,
+        reasons: &IndexSet<StaticOrArc<dyn InvalidationReason>>,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "{} files overridden ({}, ...)",
+            reasons.len(),
+            reasons[0]
+                .as_any()
+                .downcast_ref::<OverlayChange>()
+                .unwrap()
+                .path
+        )
+    }
+}
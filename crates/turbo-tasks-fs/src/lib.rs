@@ -13,6 +13,7 @@ mod invalidation;
 mod invalidator_map;
 pub mod json;
 mod mutex_map;
+pub(crate) mod overlay_fs;
 mod read_glob;
 mod retry;
 pub mod rope;
@@ -49,6 +50,7 @@ use invalidator_map::InvalidatorMap;
 use jsonc_parser::{parse_to_serde_value, ParseOptions};
 use mime::Mime;
 use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+pub use overlay_fs::OverlayFileSystem;
 use read_glob::read_glob;
 pub use read_glob::ReadGlobResult;
 use serde::{Deserialize, Serialize};
@@ -0,0 +1,498 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem::take,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{bail, Result};
+use auto_hash_map::AutoMap;
+use turbo_tasks::{mark_stateful, Completion, ValueToString, Vc};
+
+use crate::{
+    invalidation::OverlayChange, invalidator_map::InvalidatorMap, util::normalize_path,
+    DirectoryContent, DirectoryEntry, File, FileContent, FileMeta, FileSystem, FileSystemEntryType,
+    FileSystemPath, LinkContent,
+};
+
+/// An in-memory override of a path of an [OverlayFileSystem].
+#[derive(Clone)]
+enum OverlayEntry {
+    File(File),
+    /// A directory listing the entries of the inner [FileSystem] at the same
+    /// path, if any, and the overrides below it.
+    Directory,
+    /// A path below a file override, which hides the inner [FileSystem] at
+    /// that path. Only returned by [overlay_entry], never set as an override.
+    NotFound,
+}
+
+/// A [FileSystem] which serves in-memory overrides of files and directories,
+/// e.g. the unsaved buffers of an editor, and falls through to an inner
+/// [FileSystem] for every other path.
+///
+/// Tasks which read a path, or list one of its parent directories, are
+/// invalidated when an override of that path, or of one of its parents, is set
+/// or removed. Only changing the content of a file override just invalidates
+/// the tasks reading that file. Paths below a file override don't exist. Writes
+/// go to the inner [FileSystem] and stay shadowed by an override of the same
+/// path.
+#[turbo_tasks::value(cell = "new", eq = "manual")]
+pub struct OverlayFileSystem {
+    pub name: String,
+    inner: Vc<Box<dyn FileSystem>>,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    #[serde(skip)]
+    overrides: Arc<Mutex<HashMap<String, OverlayEntry>>>,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    invalidator_map: Arc<InvalidatorMap>,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    dir_invalidator_map: Arc<InvalidatorMap>,
+}
+
+#[turbo_tasks::value_impl]
+impl OverlayFileSystem {
+    #[turbo_tasks::function]
+    pub fn new(name: String, inner: Vc<Box<dyn FileSystem>>) -> Vc<Self> {
+        mark_stateful();
+        OverlayFileSystem {
+            name,
+            inner,
+            overrides: Default::default(),
+            invalidator_map: Arc::new(InvalidatorMap::new()),
+            dir_invalidator_map: Arc::new(InvalidatorMap::new()),
+        }
+        .cell()
+    }
+}
+
+impl OverlayFileSystem {
+    /// Overrides the file at `path`, relative to the root of the file system,
+    /// with `content`.
+    pub fn set_file(&self, path: &str, content: File) -> Result<()> {
+        self.set_override(path, Some(OverlayEntry::File(content)))
+    }
+
+    /// Overrides `path`, relative to the root of the file system, with a
+    /// directory. Entries of the inner [FileSystem] in that directory remain
+    /// visible.
+    pub fn set_directory(&self, path: &str) -> Result<()> {
+        self.set_override(path, Some(OverlayEntry::Directory))
+    }
+
+    /// Removes the override of `path`, exposing the inner [FileSystem] at that
+    /// path again.
+    pub fn remove(&self, path: &str) -> Result<()> {
+        self.set_override(path, None)
+    }
+
+    /// Removes all overrides.
+    pub fn clear(&self) {
+        let overrides = take(&mut *self.overrides.lock().unwrap());
+        for path in overrides.keys() {
+            self.invalidate_path(path);
+        }
+    }
+
+    fn set_override(&self, path: &str, entry: Option<OverlayEntry>) -> Result<()> {
+        let Some(path) = normalize_path(path) else {
+            bail!("path {} is outside of the file system {}", path, self.name);
+        };
+        if path.is_empty() {
+            bail!(
+                "the root of the file system {} can't be overridden",
+                self.name
+            );
+        }
+        let previous = {
+            let mut overrides = self.overrides.lock().unwrap();
+            match &entry {
+                Some(entry) => overrides.insert(path.clone(), entry.clone()),
+                None => match overrides.remove(&path) {
+                    Some(previous) => Some(previous),
+                    None => return Ok(()),
+                },
+            }
+        };
+        if changes_structure(previous.as_ref(), entry.as_ref()) {
+            self.invalidate_path(&path);
+        } else if let Some(OverlayEntry::File(_)) = entry {
+            self.invalidate_file(&path);
+        }
+        Ok(())
+    }
+
+    /// Invalidates the tasks reading `path` after only the content of its
+    /// file override changed. Its parents list the same entries as before.
+    fn invalidate_file(&self, path: &str) {
+        let invalidators = self.invalidator_map.lock().unwrap().remove(path);
+        for invalidator in invalidators.into_iter().flatten() {
+            invalidator.invalidate_with_reason(OverlayChange {
+                path: path.to_string(),
+            });
+        }
+    }
+
+    /// Invalidates the tasks depending on `path`, any of its parent
+    /// directories or any path below it, as setting or removing an override
+    /// of `path` can turn its parents into (implicit) directories and hides or
+    /// exposes everything below it.
+    fn invalidate_path(&self, path: &str) {
+        let mut invalidators = Vec::new();
+        {
+            let mut invalidator_map = self.invalidator_map.lock().unwrap();
+            let mut dir_invalidator_map = self.dir_invalidator_map.lock().unwrap();
+            let prefix = format!("{path}/");
+            for map in [&mut *invalidator_map, &mut *dir_invalidator_map] {
+                map.retain(|key, key_invalidators| {
+                    if !key.starts_with(&prefix) {
+                        return true;
+                    }
+                    invalidators.extend(key_invalidators.drain());
+                    false
+                });
+            }
+            let mut current = Some(path);
+            while let Some(path) = current {
+                invalidators.extend(invalidator_map.remove(path).into_iter().flatten());
+                invalidators.extend(dir_invalidator_map.remove(path).into_iter().flatten());
+                current = match path.rsplit_once('/') {
+                    Some((parent, _)) => Some(parent),
+                    None if !path.is_empty() => Some(""),
+                    None => None,
+                };
+            }
+        }
+        for invalidator in invalidators {
+            invalidator.invalidate_with_reason(OverlayChange {
+                path: path.to_string(),
+            });
+        }
+    }
+
+    /// registers the path as an invalidator for the current task,
+    /// has to be called within a turbo-tasks function
+    fn register_invalidator(&self, path: &str) {
+        let invalidator = turbo_tasks::get_invalidator();
+        self.invalidator_map.insert(path.to_string(), invalidator);
+    }
+
+    /// registers the path as a directory invalidator for the current task,
+    /// has to be called within a turbo-tasks function
+    fn register_dir_invalidator(&self, path: &str) {
+        let invalidator = turbo_tasks::get_invalidator();
+        self.dir_invalidator_map
+            .insert(path.to_string(), invalidator);
+    }
+
+    fn overlay_entry(&self, path: &str) -> Option<OverlayEntry> {
+        overlay_entry(&self.overrides.lock().unwrap(), path)
+    }
+
+    fn inner_path(&self, path: &str) -> Vc<FileSystemPath> {
+        self.inner.root().join(path.to_string())
+    }
+}
+
+/// Returns the path of `fs_path` relative to the root of `fs`.
+async fn overlay_path(fs: Vc<OverlayFileSystem>, fs_path: Vc<FileSystemPath>) -> Result<String> {
+    let fs_path = fs_path.await?;
+    let self_fs: Vc<Box<dyn FileSystem>> = Vc::upcast(fs);
+    if fs_path.fs != self_fs {
+        bail!(
+            "path fs does not match (expected {}, got {})",
+            self_fs.to_string().await?,
+            fs_path.fs.to_string().await?
+        )
+    }
+    Ok(fs_path.path.clone())
+}
+
+/// Whether replacing the override `previous` of a path with `entry` can change
+/// what exists at that path or below it, or what its parents list, rather than
+/// only the content of a file.
+fn changes_structure(previous: Option<&OverlayEntry>, entry: Option<&OverlayEntry>) -> bool {
+    !matches!(
+        (previous, entry),
+        (Some(OverlayEntry::File(_)), Some(OverlayEntry::File(_)))
+            | (Some(OverlayEntry::Directory), Some(OverlayEntry::Directory))
+    )
+}
+
+/// Returns the override of `path`. A path with overrides below it is a
+/// directory, even when it has been overridden with a file itself, and a path
+/// below a file override doesn't exist.
+fn overlay_entry(overrides: &HashMap<String, OverlayEntry>, path: &str) -> Option<OverlayEntry> {
+    let prefix = format!("{path}/");
+    if overrides.keys().any(|key| key.starts_with(&prefix)) {
+        return Some(OverlayEntry::Directory);
+    }
+    if let Some(entry) = overrides.get(path) {
+        return Some(entry.clone());
+    }
+    let mut current = path;
+    while let Some((parent, _)) = current.rsplit_once('/') {
+        if let Some(OverlayEntry::File(_)) = overlay_entry(overrides, parent) {
+            return Some(OverlayEntry::NotFound);
+        }
+        current = parent;
+    }
+    None
+}
+
+/// Returns the names and types of the overridden entries of the directory
+/// `dir`, including the directories implied by overrides further below.
+fn overlay_children(
+    overrides: &HashMap<String, OverlayEntry>,
+    dir: &str,
+) -> BTreeMap<String, FileSystemEntryType> {
+    let mut children = BTreeMap::new();
+    for (key, entry) in overrides {
+        let relative = if dir.is_empty() {
+            Some(key.as_str())
+        } else {
+            key.strip_prefix(dir)
+                .and_then(|relative| relative.strip_prefix('/'))
+        };
+        let Some(relative) = relative else {
+            continue;
+        };
+        match relative.split_once('/') {
+            Some((name, _)) => {
+                children.insert(name.to_string(), FileSystemEntryType::Directory);
+            }
+            None => {
+                let ty = match entry {
+                    OverlayEntry::File(_) => FileSystemEntryType::File,
+                    OverlayEntry::Directory => FileSystemEntryType::Directory,
+                    OverlayEntry::NotFound => continue,
+                };
+                children.entry(relative.to_string()).or_insert(ty);
+            }
+        }
+    }
+    children
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for OverlayFileSystem {
+    #[turbo_tasks::function]
+    async fn read(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileContent>> {
+        let path = overlay_path(self, fs_path).await?;
+        let this = self.await?;
+        this.register_invalidator(&path);
+
+        Ok(match this.overlay_entry(&path) {
+            Some(OverlayEntry::File(file)) => FileContent::new(file).cell(),
+            Some(OverlayEntry::Directory | OverlayEntry::NotFound) => FileContent::NotFound.cell(),
+            None => this.inner_path(&path).read(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn read_link(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<LinkContent>> {
+        let path = overlay_path(self, fs_path).await?;
+        let this = self.await?;
+        this.register_invalidator(&path);
+
+        Ok(match this.overlay_entry(&path) {
+            Some(_) => LinkContent::NotFound.cell(),
+            None => this.inner_path(&path).read_link(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<DirectoryContent>> {
+        let path = overlay_path(self, fs_path).await?;
+        let this = self.await?;
+        this.register_dir_invalidator(&path);
+
+        let (overridden, children) = {
+            let overrides = this.overrides.lock().unwrap();
+            (
+                overlay_entry(&overrides, &path),
+                overlay_children(&overrides, &path),
+            )
+        };
+        if let Some(OverlayEntry::File(_) | OverlayEntry::NotFound) = overridden {
+            return Ok(DirectoryContent::not_found());
+        }
+
+        let mut entries = match &*this.inner_path(&path).read_dir().await? {
+            DirectoryContent::Entries(inner_entries) => {
+                let mut entries = AutoMap::with_capacity(inner_entries.len() + children.len());
+                for (name, entry) in inner_entries {
+                    use DirectoryEntry::*;
+
+                    let child = fs_path.join(name.clone());
+                    let entry = match entry {
+                        File(_) => File(child),
+                        Directory(_) => Directory(child),
+                        Symlink(_) => Symlink(child),
+                        Other(_) => Other(child),
+                        Error => Error,
+                    };
+                    entries.insert(name.clone(), entry);
+                }
+                entries
+            }
+            DirectoryContent::NotFound if overridden.is_none() && children.is_empty() => {
+                return Ok(DirectoryContent::not_found());
+            }
+            DirectoryContent::NotFound => AutoMap::with_capacity(children.len()),
+        };
+
+        for (name, ty) in children {
+            let child = fs_path.join(name.clone());
+            let entry = match ty {
+                FileSystemEntryType::Directory => DirectoryEntry::Directory(child),
+                _ => DirectoryEntry::File(child),
+            };
+            entries.insert(name, entry);
+        }
+
+        Ok(DirectoryContent::new(entries))
+    }
+
+    #[turbo_tasks::function]
+    async fn track(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<Completion>> {
+        let path = overlay_path(self, fs_path).await?;
+        let this = self.await?;
+        this.register_invalidator(&path);
+
+        Ok(match this.overlay_entry(&path) {
+            Some(_) => Completion::new(),
+            None => this.inner_path(&path).track(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn write(
+        self: Vc<Self>,
+        fs_path: Vc<FileSystemPath>,
+        content: Vc<FileContent>,
+    ) -> Result<Vc<Completion>> {
+        let path = overlay_path(self, fs_path).await?;
+        Ok(self.await?.inner_path(&path).write(content))
+    }
+
+    #[turbo_tasks::function]
+    async fn write_link(
+        self: Vc<Self>,
+        fs_path: Vc<FileSystemPath>,
+        target: Vc<LinkContent>,
+    ) -> Result<Vc<Completion>> {
+        let path = overlay_path(self, fs_path).await?;
+        Ok(self.await?.inner_path(&path).write_link(target))
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileMeta>> {
+        let path = overlay_path(self, fs_path).await?;
+        let this = self.await?;
+        this.register_invalidator(&path);
+
+        Ok(match this.overlay_entry(&path) {
+            Some(OverlayEntry::File(file)) => FileMeta::cell(file.meta().clone()),
+            Some(OverlayEntry::Directory) => FileMeta::default().cell(),
+            Some(OverlayEntry::NotFound) => {
+                bail!("reading metadata for {}: not found", path)
+            }
+            None => this.inner_path(&path).metadata(),
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for OverlayFileSystem {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<String> {
+        Vc::cell(self.name.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::{changes_structure, overlay_children, overlay_entry, OverlayEntry};
+    use crate::{File, FileSystemEntryType};
+
+    fn overrides() -> HashMap<String, OverlayEntry> {
+        HashMap::from([
+            ("index.js".to_string(), OverlayEntry::File(File::from("a"))),
+            ("src/a.js".to_string(), OverlayEntry::File(File::from("b"))),
+            ("src/lib".to_string(), OverlayEntry::Directory),
+            (
+                "src/deep/b.js".to_string(),
+                OverlayEntry::File(File::from("c")),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_overlay_entry() {
+        let overrides = overrides();
+
+        assert!(matches!(
+            overlay_entry(&overrides, "index.js"),
+            Some(OverlayEntry::File(_))
+        ));
+        assert!(matches!(
+            overlay_entry(&overrides, "src"),
+            Some(OverlayEntry::Directory)
+        ));
+        assert!(matches!(
+            overlay_entry(&overrides, "src/lib"),
+            Some(OverlayEntry::Directory)
+        ));
+        assert!(matches!(
+            overlay_entry(&overrides, "index.js/a.js"),
+            Some(OverlayEntry::NotFound)
+        ));
+        assert!(matches!(
+            overlay_entry(&overrides, "src/a.js/b/c.js"),
+            Some(OverlayEntry::NotFound)
+        ));
+        assert!(overlay_entry(&overrides, "sr").is_none());
+        assert!(overlay_entry(&overrides, "src/b.js").is_none());
+        assert!(overlay_entry(&overrides, "src/lib/a.js").is_none());
+    }
+
+    #[test]
+    fn test_overlay_children() {
+        let overrides = overrides();
+
+        assert_eq!(
+            overlay_children(&overrides, ""),
+            BTreeMap::from([
+                ("index.js".to_string(), FileSystemEntryType::File),
+                ("src".to_string(), FileSystemEntryType::Directory),
+            ])
+        );
+        assert_eq!(
+            overlay_children(&overrides, "src"),
+            BTreeMap::from([
+                ("a.js".to_string(), FileSystemEntryType::File),
+                ("deep".to_string(), FileSystemEntryType::Directory),
+                ("lib".to_string(), FileSystemEntryType::Directory),
+            ])
+        );
+        assert_eq!(overlay_children(&overrides, "src/lib"), BTreeMap::new());
+        assert_eq!(overlay_children(&overrides, "lib"), BTreeMap::new());
+    }
+
+    #[test]
+    fn test_changes_structure() {
+        let file = OverlayEntry::File(File::from("a"));
+        let other_file = OverlayEntry::File(File::from("b"));
+        let directory = OverlayEntry::Directory;
+
+        assert!(!changes_structure(Some(&file), Some(&other_file)));
+        assert!(!changes_structure(Some(&directory), Some(&directory)));
+        assert!(changes_structure(None, Some(&file)));
+        assert!(changes_structure(None, Some(&directory)));
+        assert!(changes_structure(Some(&file), None));
+        assert!(changes_structure(Some(&file), Some(&directory)));
+        assert!(changes_structure(Some(&directory), Some(&file)));
+    }
+}
@@ -0,0 +1,167 @@
+#![feature(arbitrary_self_types)]
+
+use std::{collections::BTreeMap, fs, sync::Mutex};
+
+use anyhow::Result;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::{
+    DirectoryContent, DiskFileSystem, File, FileContent, FileSystem, FileSystemPath,
+    OverlayFileSystem,
+};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+/// How often `read` and `list` ran for each path.
+static EXECUTIONS: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+
+fn executions(path: &str) -> usize {
+    EXECUTIONS
+        .lock()
+        .unwrap()
+        .get(path)
+        .copied()
+        .unwrap_or_default()
+}
+
+async fn count(path: Vc<FileSystemPath>) -> Result<()> {
+    let path = path.to_string().await?;
+    *EXECUTIONS
+        .lock()
+        .unwrap()
+        .entry(path.clone_value())
+        .or_default() += 1;
+    Ok(())
+}
+
+#[turbo_tasks::function]
+async fn read(path: Vc<FileSystemPath>) -> Result<Vc<String>> {
+    count(path).await?;
+    Ok(Vc::cell(match &*path.read().await? {
+        FileContent::Content(file) => file.content().to_str()?.into_owned(),
+        FileContent::NotFound => "not found".to_string(),
+    }))
+}
+
+#[turbo_tasks::function]
+async fn list(path: Vc<FileSystemPath>) -> Result<Vc<String>> {
+    count(path).await?;
+    Ok(Vc::cell(match &*path.read_dir().await? {
+        DirectoryContent::Entries(entries) => {
+            let mut names = entries
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            names.sort();
+            names.join(",")
+        }
+        DirectoryContent::NotFound => "not found".to_string(),
+    }))
+}
+
+fn overlay(name: &str) -> Result<(tempfile::TempDir, Vc<OverlayFileSystem>)> {
+    let dir = tempfile::tempdir()?;
+    fs::create_dir_all(dir.path().join("a"))?;
+    fs::create_dir_all(dir.path().join("c"))?;
+    fs::write(dir.path().join("a/x"), "inner x")?;
+    fs::write(dir.path().join("b"), "inner b")?;
+    fs::write(dir.path().join("c/d"), "inner d")?;
+
+    let inner = DiskFileSystem::new(
+        format!("{name}-inner"),
+        dir.path().to_str().unwrap().to_string(),
+    );
+    let overlay = OverlayFileSystem::new(name.to_string(), Vc::upcast(inner));
+    Ok((dir, overlay))
+}
+
+#[tokio::test]
+async fn overrides_invalidate_their_readers() {
+    turbo_tasks_fs::register();
+    run! {
+        let (_dir, overlay) = overlay("invalidate")?;
+        let root = overlay.root();
+        let read_b = read(root.join("b".to_string()));
+        let read_d = read(root.join("c/d".to_string()));
+        let list_root = list(root);
+
+        assert_eq!(&*read_b.strongly_consistent().await?, "inner b");
+        assert_eq!(&*read_d.strongly_consistent().await?, "inner d");
+        assert_eq!(&*list_root.strongly_consistent().await?, "a,b,c");
+
+        overlay.await?.set_file("b", File::from("overlay b"))?;
+        assert_eq!(&*read_b.strongly_consistent().await?, "overlay b");
+        assert_eq!(executions("[invalidate]/b"), 2);
+        // The listing is read again, but didn't change
+        assert_eq!(&*list_root.strongly_consistent().await?, "a,b,c");
+        assert_eq!(executions("[invalidate]/"), 1);
+
+        overlay.await?.set_file("e", File::from("overlay e"))?;
+        assert_eq!(&*list_root.strongly_consistent().await?, "a,b,c,e");
+        assert_eq!(executions("[invalidate]/"), 2);
+
+        overlay.await?.remove("b")?;
+        assert_eq!(&*read_b.strongly_consistent().await?, "inner b");
+        assert_eq!(executions("[invalidate]/b"), 3);
+
+        overlay.await?.set_file("b", File::from("overlay b"))?;
+        assert_eq!(&*read_b.strongly_consistent().await?, "overlay b");
+        overlay.await?.clear();
+        assert_eq!(&*read_b.strongly_consistent().await?, "inner b");
+        assert_eq!(&*list_root.strongly_consistent().await?, "a,b,c");
+        assert_eq!(executions("[invalidate]/b"), 5);
+        assert_eq!(executions("[invalidate]/"), 3);
+
+        // None of the overrides affected c/d
+        assert_eq!(&*read_d.strongly_consistent().await?, "inner d");
+        assert_eq!(executions("[invalidate]/c/d"), 1);
+    }
+}
+
+#[tokio::test]
+async fn content_changes_invalidate_only_the_file() {
+    turbo_tasks_fs::register();
+    run! {
+        let (_dir, overlay) = overlay("content")?;
+        let root = overlay.root();
+        let read_b = read(root.join("b".to_string()));
+        let list_root = list(root);
+
+        overlay.await?.set_file("b", File::from("first b"))?;
+        assert_eq!(&*read_b.strongly_consistent().await?, "first b");
+        assert_eq!(&*list_root.strongly_consistent().await?, "a,b,c");
+
+        overlay.await?.set_file("b", File::from("second b"))?;
+        assert_eq!(&*read_b.strongly_consistent().await?, "second b");
+        assert_eq!(executions("[content]/b"), 2);
+        assert_eq!(&*list_root.strongly_consistent().await?, "a,b,c");
+        assert_eq!(executions("[content]/"), 1);
+
+        // Turning the file into a directory is still seen by its readers
+        overlay.await?.set_directory("b")?;
+        assert_eq!(&*read_b.strongly_consistent().await?, "not found");
+        assert_eq!(executions("[content]/b"), 3);
+    }
+}
+
+#[tokio::test]
+async fn file_override_hides_the_directory_below() {
+    turbo_tasks_fs::register();
+    run! {
+        let (_dir, overlay) = overlay("shadow")?;
+        let root = overlay.root();
+        let read_x = read(root.join("a/x".to_string()));
+        let list_a = list(root.join("a".to_string()));
+
+        assert_eq!(&*read_x.strongly_consistent().await?, "inner x");
+        assert_eq!(&*list_a.strongly_consistent().await?, "x");
+
+        overlay.await?.set_file("a", File::from("overlay a"))?;
+        assert_eq!(&*read_x.strongly_consistent().await?, "not found");
+        assert_eq!(&*list_a.strongly_consistent().await?, "not found");
+
+        overlay.await?.remove("a")?;
+        assert_eq!(&*read_x.strongly_consistent().await?, "inner x");
+        assert_eq!(&*list_a.strongly_consistent().await?, "x");
+    }
+}
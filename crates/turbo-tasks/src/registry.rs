@@ -145,3 +145,25 @@ pub fn get_trait(id: TraitTypeId) -> &'static TraitType {
 pub fn get_trait_type_global_name(id: TraitTypeId) -> &'static str {
     &TRAIT_TYPES.get(*id).unwrap().1
 }
+
+/// Returns the global names of all registered functions, value types and
+/// trait types, sorted. Persisted data refers to these by name, so a change in
+/// this list means that persisted data might no longer be readable.
+pub fn all_global_names() -> Vec<String> {
+    let mut names = FUNCTIONS_BY_NAME
+        .iter()
+        .map(|entry| format!("function {}", entry.key()))
+        .chain(
+            VALUE_TYPES_BY_NAME
+                .iter()
+                .map(|entry| format!("value {}", entry.key())),
+        )
+        .chain(
+            TRAIT_TYPES_BY_NAME
+                .iter()
+                .map(|entry| format!("trait {}", entry.key())),
+        )
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
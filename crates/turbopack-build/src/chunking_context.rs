@@ -10,7 +10,8 @@ use turbopack_core::{
     chunk::{
         availability_info::AvailabilityInfo,
         chunk_group::{make_chunk_group, MakeChunkGroupResult},
-        Chunk, ChunkItem, ChunkableModule, ChunkingContext, EvaluatableAssets, ModuleId,
+        Chunk, ChunkItem, ChunkableModule, ChunkingConfig, ChunkingContext, EvaluatableAssets,
+        ModuleId,
     },
     environment::Environment,
     ident::AssetIdent,
@@ -23,8 +24,14 @@ use turbopack_ecmascript::{
 };
 use turbopack_ecmascript_runtime::RuntimeType;
 
-use crate::ecmascript::node::{
-    chunk::EcmascriptBuildNodeChunk, entry::chunk::EcmascriptBuildNodeEntryChunk,
+use crate::{
+    content_hashed_asset::ContentHashedAsset,
+    ecmascript::{
+        browser::{
+            chunk::EcmascriptBuildBrowserChunk, evaluate::EcmascriptBuildBrowserEvaluateChunk,
+        },
+        node::{chunk::EcmascriptBuildNodeChunk, entry::chunk::EcmascriptBuildNodeEntryChunk},
+    },
 };
 
 #[derive(
@@ -49,6 +56,32 @@ pub enum MinifyType {
     NoMinify,
 }
 
+/// Where the chunks of a build are executed.
+#[derive(
+    Debug,
+    Default,
+    TaskInput,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+    DeterministicHash,
+)]
+pub enum BuildTarget {
+    /// Chunks are `require`d by an entry chunk in Node.js.
+    #[default]
+    NodeJs,
+    /// Chunks are loaded with `<script>` and `<link>` tags by an evaluated
+    /// chunk group. Chunk filenames include a hash of their content.
+    Browser,
+}
+
 /// A builder for [`Vc<BuildChunkingContext>`].
 pub struct BuildChunkingContextBuilder {
     chunking_context: BuildChunkingContext,
@@ -70,6 +103,16 @@ impl BuildChunkingContextBuilder {
         self
     }
 
+    pub fn target(mut self, target: BuildTarget) -> Self {
+        self.chunking_context.target = target;
+        self
+    }
+
+    pub fn chunking_config(mut self, chunking_config: Vc<ChunkingConfig>) -> Self {
+        self.chunking_context.chunking_config = chunking_config;
+        self
+    }
+
     /// Builds the chunking context.
     pub fn build(self) -> Vc<BuildChunkingContext> {
         BuildChunkingContext::new(Value::new(self.chunking_context))
@@ -99,6 +142,10 @@ pub struct BuildChunkingContext {
     runtime_type: RuntimeType,
     /// Whether to minify resulting chunks
     minify_type: MinifyType,
+    /// Where the resulting chunks are executed
+    target: BuildTarget,
+    /// How chunk items are split into chunks
+    chunking_config: Vc<ChunkingConfig>,
 }
 
 impl BuildChunkingContext {
@@ -122,6 +169,8 @@ impl BuildChunkingContext {
                 environment,
                 runtime_type: Default::default(),
                 minify_type: MinifyType::Minify,
+                target: BuildTarget::NodeJs,
+                chunking_config: Default::default(),
             },
         }
     }
@@ -139,6 +188,10 @@ impl BuildChunkingContext {
     pub fn minify_type(&self) -> MinifyType {
         self.minify_type
     }
+
+    pub fn target(&self) -> BuildTarget {
+        self.target
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -199,15 +252,27 @@ impl BuildChunkingContext {
         self: Vc<Self>,
         chunk: Vc<Box<dyn Chunk>>,
     ) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let target = self.await?.target;
         Ok(
             if let Some(ecmascript_chunk) =
                 Vc::try_resolve_downcast_type::<EcmascriptChunk>(chunk).await?
             {
-                Vc::upcast(EcmascriptBuildNodeChunk::new(self, ecmascript_chunk))
+                match target {
+                    BuildTarget::NodeJs => {
+                        Vc::upcast(EcmascriptBuildNodeChunk::new(self, ecmascript_chunk))
+                    }
+                    BuildTarget::Browser => {
+                        Vc::upcast(EcmascriptBuildBrowserChunk::new(self, ecmascript_chunk))
+                    }
+                }
             } else if let Some(output_asset) =
                 Vc::try_resolve_sidecast::<Box<dyn OutputAsset>>(chunk).await?
             {
-                output_asset
+                match target {
+                    BuildTarget::NodeJs => output_asset,
+                    // e.g. CSS chunks, which can't hash their content themselves
+                    BuildTarget::Browser => Vc::upcast(ContentHashedAsset::new(output_asset)),
+                }
             } else {
                 bail!("Unable to generate output asset for chunk");
             },
@@ -215,6 +280,30 @@ impl BuildChunkingContext {
     }
 }
 
+/// Returns `path` with the start of `content_hash` inserted before its
+/// extension.
+#[turbo_tasks::function]
+pub(crate) async fn content_hashed_path(
+    path: Vc<FileSystemPath>,
+    content_hash: Vc<String>,
+) -> Result<Vc<FileSystemPath>> {
+    let path_ref = path.await?;
+    let content_hash = content_hash.await?;
+    let file_name = path_ref.file_name();
+    let file_name = match path_ref.extension_ref() {
+        Some(ext) => format!(
+            "{basename}.{content_hash}.{ext}",
+            basename = &file_name[..file_name.len() - ext.len() - 1],
+            content_hash = &content_hash[..8]
+        ),
+        None => format!(
+            "{file_name}.{content_hash}",
+            content_hash = &content_hash[..8]
+        ),
+    };
+    Ok(path.parent().join(file_name))
+}
+
 #[turbo_tasks::value_impl]
 impl ChunkingContext for BuildChunkingContext {
     #[turbo_tasks::function]
@@ -232,6 +321,11 @@ impl ChunkingContext for BuildChunkingContext {
         self.environment
     }
 
+    #[turbo_tasks::function]
+    fn chunking_config(&self) -> Vc<ChunkingConfig> {
+        self.chunking_config
+    }
+
     #[turbo_tasks::function]
     async fn asset_url(self: Vc<Self>, ident: Vc<AssetIdent>) -> Result<Vc<String>> {
         let this = self.await?;
@@ -341,12 +435,54 @@ impl ChunkingContext for BuildChunkingContext {
     #[turbo_tasks::function]
     async fn evaluated_chunk_group(
         self: Vc<Self>,
-        _ident: Vc<AssetIdent>,
-        _evaluatable_assets: Vc<EvaluatableAssets>,
+        ident: Vc<AssetIdent>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
     ) -> Result<Vc<OutputAssets>> {
         // TODO(alexkirsz) This method should be part of a separate trait that is
         // only implemented for client/edge runtimes.
-        bail!("the build chunking context does not support evaluated chunk groups")
+        if self.await?.target != BuildTarget::Browser {
+            bail!(
+                "the build chunking context only supports evaluated chunk groups when targeting \
+                 the browser"
+            )
+        }
+
+        let span = {
+            let ident = ident.to_string().await?;
+            tracing::info_span!("chunking", chunking_type = "evaluated", ident = *ident)
+        };
+        async move {
+            let entries = evaluatable_assets
+                .await?
+                .iter()
+                .map(|&evaluatable| Vc::upcast(evaluatable))
+                .collect::<Vec<_>>();
+
+            let MakeChunkGroupResult { chunks } =
+                make_chunk_group(Vc::upcast(self), entries, AvailabilityInfo::Root).await?;
+
+            let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = chunks
+                .iter()
+                .map(|chunk| self.generate_chunk(*chunk))
+                .collect();
+
+            let other_assets = Vc::cell(assets.clone());
+            assets.push(Vc::upcast(EcmascriptBuildBrowserEvaluateChunk::new(
+                self,
+                ident,
+                other_assets,
+                evaluatable_assets,
+            )));
+
+            // Resolve assets
+            for asset in assets.iter_mut() {
+                *asset = asset.resolve().await?;
+            }
+
+            Ok(Vc::cell(assets))
+        }
+        .instrument(span)
+        .await
     }
 
     #[turbo_tasks::function]
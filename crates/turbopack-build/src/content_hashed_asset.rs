@@ -0,0 +1,64 @@
+use anyhow::{bail, Result};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileContent;
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+};
+
+use crate::chunking_context::content_hashed_path;
+
+/// Emits an [OutputAsset] under a path that contains a hash of its content.
+///
+/// The references of the wrapped asset are kept as is, so any source map it
+/// points to is still emitted next to the original path.
+#[turbo_tasks::value]
+pub(crate) struct ContentHashedAsset {
+    asset: Vc<Box<dyn OutputAsset>>,
+}
+
+#[turbo_tasks::value_impl]
+impl ContentHashedAsset {
+    #[turbo_tasks::function]
+    pub fn new(asset: Vc<Box<dyn OutputAsset>>) -> Vc<Self> {
+        ContentHashedAsset { asset }.cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn content_hash(&self) -> Result<Vc<String>> {
+        let AssetContent::File(file) = &*self.asset.content().await? else {
+            bail!("ContentHashedAsset: unsupported asset content");
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            bail!("ContentHashedAsset: asset content not found");
+        };
+        Ok(Vc::cell(encode_hex(hash_xxh3_hash64(file.content()))))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for ContentHashedAsset {
+    #[turbo_tasks::function]
+    async fn ident(self: Vc<Self>) -> Result<Vc<AssetIdent>> {
+        let path = self.await?.asset.ident().path();
+        Ok(AssetIdent::from_path(content_hashed_path(
+            path,
+            self.content_hash(),
+        )))
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<OutputAssets> {
+        self.asset.references()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ContentHashedAsset {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        self.asset.content()
+    }
+}
@@ -0,0 +1,216 @@
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use indexmap::IndexSet;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::File;
+use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{Chunk, ChunkingContext},
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    introspect::{Introspectable, IntrospectableChildren},
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+};
+use turbopack_ecmascript::{chunk::EcmascriptChunk, utils::StringifyJs};
+
+use crate::{
+    chunking_context::{content_hashed_path, MinifyType},
+    ecmascript::{minify::minify, node::content::chunk_items},
+    BuildChunkingContext,
+};
+
+/// Production Ecmascript chunk targeting browsers.
+///
+/// The chunk registers its module factories with the browser build runtime
+/// when executed. Its filename contains a hash of its chunk items.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptBuildBrowserChunk {
+    chunking_context: Vc<BuildChunkingContext>,
+    chunk: Vc<EcmascriptChunk>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildBrowserChunk {
+    /// Creates a new [`Vc<EcmascriptBuildBrowserChunk>`].
+    #[turbo_tasks::function]
+    pub fn new(chunking_context: Vc<BuildChunkingContext>, chunk: Vc<EcmascriptChunk>) -> Vc<Self> {
+        EcmascriptBuildBrowserChunk {
+            chunking_context,
+            chunk,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptBuildBrowserChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("Ecmascript Build Browser Chunk".to_string()))
+    }
+}
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("ecmascript build browser chunk".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildBrowserChunk {
+    /// Hashes the chunk items rather than the final code, as the latter
+    /// contains the (hashed) path of the chunk itself.
+    #[turbo_tasks::function]
+    async fn content_hash(self: Vc<Self>) -> Result<Vc<String>> {
+        let this = self.await?;
+        let chunk_items = chunk_items(this.chunk.chunk_content()).await?;
+        let mut hasher = Xxh3Hash64Hasher::new();
+        hasher.write_ref(&this.chunking_context.await?.minify_type());
+        hasher.write_value(chunk_items.len());
+        for (module_id, code) in &chunk_items {
+            hasher.write_value((module_id, code.source_code()));
+        }
+        Ok(Vc::cell(encode_hex(hasher.finish())))
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+        let output_root = this.chunking_context.output_root().await?;
+        let chunk_path_vc = self.ident().path();
+        let chunk_path = chunk_path_vc.await?;
+        let Some(chunk_public_path) = output_root.get_path_to(&chunk_path) else {
+            bail!(
+                "chunk path {} is not in output root {}",
+                chunk_path.to_string(),
+                output_root.to_string()
+            );
+        };
+
+        let mut code = CodeBuilder::default();
+
+        writeln!(
+            code,
+            "(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([{}, {{",
+            StringifyJs(chunk_public_path)
+        )?;
+
+        for (id, item_code) in chunk_items(this.chunk.chunk_content()).await? {
+            write!(code, "{}: ", StringifyJs(&id))?;
+            code.push_code(&item_code);
+            writeln!(code, ",")?;
+        }
+
+        write!(code, "}}]);")?;
+
+        if code.has_source_map() {
+            let filename = chunk_path.file_name();
+            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
+        }
+
+        let code = code.build().cell();
+        if matches!(
+            this.chunking_context.await?.minify_type(),
+            MinifyType::Minify
+        ) {
+            return Ok(minify(chunk_path_vc, code));
+        }
+
+        Ok(code)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptBuildBrowserChunk {
+    #[turbo_tasks::function]
+    async fn ident(self: Vc<Self>) -> Result<Vc<AssetIdent>> {
+        let this = self.await?;
+        let ident = this.chunk.ident().with_modifier(modifier());
+        let path = this.chunking_context.chunk_path(ident, ".js".to_string());
+        Ok(AssetIdent::from_path(content_hashed_path(
+            path,
+            self.content_hash(),
+        )))
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let chunk_references = this.chunk.references().await?;
+        let include_source_map = *this
+            .chunking_context
+            .reference_chunk_source_maps(Vc::upcast(self))
+            .await?;
+        let mut references =
+            Vec::with_capacity(chunk_references.len() + if include_source_map { 1 } else { 0 });
+
+        for reference in &*chunk_references {
+            references.push(*reference);
+        }
+
+        if include_source_map {
+            references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))));
+        }
+
+        Ok(Vc::cell(references))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptBuildBrowserChunk {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let code = self.code().await?;
+        Ok(AssetContent::file(
+            File::from(code.source_code().clone()).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptBuildBrowserChunk {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
+
+#[turbo_tasks::function]
+fn introspectable_type() -> Vc<String> {
+    Vc::cell("ecmascript build browser chunk".to_string())
+}
+
+#[turbo_tasks::function]
+fn introspectable_details() -> Vc<String> {
+    Vc::cell("generates a production EcmaScript chunk targeting browsers".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl Introspectable for EcmascriptBuildBrowserChunk {
+    #[turbo_tasks::function]
+    fn ty(&self) -> Vc<String> {
+        introspectable_type()
+    }
+
+    #[turbo_tasks::function]
+    fn title(self: Vc<Self>) -> Vc<String> {
+        self.ident().to_string()
+    }
+
+    #[turbo_tasks::function]
+    fn details(&self) -> Vc<String> {
+        introspectable_details()
+    }
+
+    #[turbo_tasks::function]
+    async fn children(&self) -> Result<Vc<IntrospectableChildren>> {
+        let mut children = IndexSet::new();
+        let introspectable_chunk = Vc::upcast::<Box<dyn Introspectable>>(self.chunk)
+            .resolve()
+            .await?;
+        children.insert((Vc::cell("chunk".to_string()), introspectable_chunk));
+        Ok(Vc::cell(children))
+    }
+}
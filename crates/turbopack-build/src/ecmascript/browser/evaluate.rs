@@ -0,0 +1,295 @@
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use indoc::writedoc;
+use serde::Serialize;
+use turbo_tasks::{ReadRef, TryJoinIterExt, Value, ValueToString, Vc};
+use turbo_tasks_fs::File;
+use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        ChunkData, ChunkItemExt, ChunkableModule, ChunkingContext, ChunksData, EvaluatableAssets,
+        ModuleId,
+    },
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    module::Module,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+};
+use turbopack_ecmascript::{
+    chunk::{EcmascriptChunkData, EcmascriptChunkPlaceable},
+    utils::StringifyJs,
+};
+use turbopack_ecmascript_runtime::RuntimeType;
+
+use crate::{
+    chunking_context::{content_hashed_path, MinifyType},
+    ecmascript::minify::minify,
+    BuildChunkingContext,
+};
+
+/// An Ecmascript chunk that:
+/// * Contains the Turbopack browser build runtime code; and
+/// * Evaluates a list of runtime entries once the other chunks of its chunk
+///   group have been loaded.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptBuildBrowserEvaluateChunk {
+    chunking_context: Vc<BuildChunkingContext>,
+    ident: Vc<AssetIdent>,
+    other_chunks: Vc<OutputAssets>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildBrowserEvaluateChunk {
+    /// Creates a new [`Vc<EcmascriptBuildBrowserEvaluateChunk>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        chunking_context: Vc<BuildChunkingContext>,
+        ident: Vc<AssetIdent>,
+        other_chunks: Vc<OutputAssets>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Vc<Self> {
+        EcmascriptBuildBrowserEvaluateChunk {
+            chunking_context,
+            ident,
+            other_chunks,
+            evaluatable_assets,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn chunks_data(self: Vc<Self>) -> Result<Vc<ChunksData>> {
+        let this = self.await?;
+        Ok(ChunkData::from_assets(
+            this.chunking_context.output_root(),
+            this.other_chunks,
+        ))
+    }
+
+    /// The serialized [`EcmascriptBuildBrowserChunkRuntimeParams`] of this
+    /// chunk.
+    #[turbo_tasks::function]
+    async fn params(self: Vc<Self>) -> Result<Vc<String>> {
+        let this = self.await?;
+
+        let other_chunks_data = self.chunks_data().await?;
+        let other_chunks_data = other_chunks_data.iter().try_join().await?;
+        let other_chunks_data: Vec<_> = other_chunks_data
+            .iter()
+            .map(|chunk_data| EcmascriptChunkData::new(chunk_data))
+            .collect();
+
+        let runtime_module_ids = this
+            .evaluatable_assets
+            .await?
+            .iter()
+            .map({
+                let chunking_context = this.chunking_context;
+                move |entry| async move {
+                    if let Some(placeable) =
+                        Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(*entry)
+                            .await?
+                    {
+                        Ok(Some(
+                            placeable
+                                .as_chunk_item(Vc::upcast(chunking_context))
+                                .id()
+                                .await?,
+                        ))
+                    } else {
+                        Ok(None)
+                    }
+                }
+            })
+            .try_join()
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let params = EcmascriptBuildBrowserChunkRuntimeParams {
+            other_chunks: &other_chunks_data,
+            runtime_module_ids,
+        };
+
+        Ok(Vc::cell(StringifyJs(&params).to_string()))
+    }
+
+    #[turbo_tasks::function]
+    async fn runtime_code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+        let chunking_context = this.chunking_context.await?;
+
+        Ok(match chunking_context.runtime_type() {
+            RuntimeType::Default => turbopack_ecmascript_runtime::get_build_browser_runtime_code(
+                this.chunking_context.environment(),
+                this.chunking_context.asset_prefix(),
+            ),
+            #[cfg(feature = "test")]
+            RuntimeType::Dummy => {
+                Code::cell(turbopack_ecmascript_runtime::get_dummy_runtime_code())
+            }
+        })
+    }
+
+    /// Hashes the params and the runtime code rather than the final code, as
+    /// the latter contains the (hashed) path of the chunk itself.
+    #[turbo_tasks::function]
+    async fn content_hash(self: Vc<Self>) -> Result<Vc<String>> {
+        let this = self.await?;
+        let mut hasher = Xxh3Hash64Hasher::new();
+        hasher.write_ref(&this.chunking_context.await?.minify_type());
+        hasher.write_ref(&*self.params().await?);
+        hasher.write_ref(self.runtime_code().await?.source_code());
+        Ok(Vc::cell(encode_hex(hasher.finish())))
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+
+        let output_root = this.chunking_context.output_root().await?;
+        let chunk_path_vc = self.ident().path();
+        let chunk_path = chunk_path_vc.await?;
+        let chunk_public_path = if let Some(path) = output_root.get_path_to(&chunk_path) {
+            path
+        } else {
+            bail!(
+                "chunk path {} is not in output root {}",
+                chunk_path.to_string(),
+                output_root.to_string()
+            );
+        };
+
+        let params = self.params().await?;
+
+        let mut code = CodeBuilder::default();
+
+        writedoc!(
+            code,
+            r#"
+                (globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+                    {},
+                    {{}},
+                    {}
+                ]);
+            "#,
+            StringifyJs(&chunk_public_path),
+            &*params,
+        )?;
+
+        code.push_code(&*self.runtime_code().await?);
+
+        if code.has_source_map() {
+            let filename = chunk_path.file_name();
+            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
+        }
+
+        let code = code.build().cell();
+        if matches!(
+            this.chunking_context.await?.minify_type(),
+            MinifyType::Minify
+        ) {
+            return Ok(minify(chunk_path_vc, code));
+        }
+
+        Ok(code)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptBuildBrowserEvaluateChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(
+            "Ecmascript Build Browser Evaluate Chunk".to_string(),
+        ))
+    }
+}
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("ecmascript build browser evaluate chunk".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptBuildBrowserEvaluateChunk {
+    #[turbo_tasks::function]
+    async fn ident(self: Vc<Self>) -> Result<Vc<AssetIdent>> {
+        let this = self.await?;
+        let mut ident = this.ident.await?.clone_value();
+
+        ident.add_modifier(modifier());
+
+        let evaluatable_assets = this.evaluatable_assets.await?;
+        ident.modifiers.extend(
+            evaluatable_assets
+                .iter()
+                .map(|entry| entry.ident().to_string()),
+        );
+
+        let ident = AssetIdent::new(Value::new(ident));
+        let path = this.chunking_context.chunk_path(ident, ".js".to_string());
+        Ok(AssetIdent::from_path(content_hashed_path(
+            path,
+            self.content_hash(),
+        )))
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let mut references = Vec::new();
+
+        let include_source_map = *this
+            .chunking_context
+            .reference_chunk_source_maps(Vc::upcast(self))
+            .await?;
+
+        if include_source_map {
+            references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))));
+        }
+
+        for chunk_data in &*self.chunks_data().await? {
+            references.extend(chunk_data.references().await?.iter().copied());
+        }
+
+        Ok(Vc::cell(references))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptBuildBrowserEvaluateChunk {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let code = self.code().await?;
+        Ok(AssetContent::file(
+            File::from(code.source_code().clone()).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptBuildBrowserEvaluateChunk {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EcmascriptBuildBrowserChunkRuntimeParams<'a, T> {
+    /// Other chunks in the chunk group this chunk belongs to. Does not include
+    /// the chunk itself.
+    ///
+    /// These chunks must be loaded before the runtime modules can be
+    /// instantiated.
+    other_chunks: &'a [T],
+    /// List of module IDs that this chunk should instantiate when executed.
+    runtime_module_ids: Vec<ReadRef<ModuleId>>,
+}
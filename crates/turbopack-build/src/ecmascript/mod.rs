@@ -1,2 +1,3 @@
+pub(crate) mod browser;
 pub(crate) mod minify;
 pub(crate) mod node;
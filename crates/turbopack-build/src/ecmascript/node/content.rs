@@ -44,7 +44,7 @@ impl EcmascriptBuildNodeChunkContent {
     }
 }
 
-pub(super) async fn chunk_items(
+pub(crate) async fn chunk_items(
     content: Vc<EcmascriptChunkContent>,
 ) -> Result<Vec<(ReadRef<ModuleId>, ReadRef<Code>)>> {
     content
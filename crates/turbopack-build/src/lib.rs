@@ -3,9 +3,14 @@
 #![feature(arbitrary_self_types)]
 
 pub(crate) mod chunking_context;
+pub(crate) mod content_hashed_asset;
 pub(crate) mod ecmascript;
+pub(crate) mod manifest;
 
-pub use chunking_context::{BuildChunkingContext, BuildChunkingContextBuilder, MinifyType};
+pub use chunking_context::{
+    BuildChunkingContext, BuildChunkingContextBuilder, BuildTarget, MinifyType,
+};
+pub use manifest::BuildManifestAsset;
 
 pub fn register() {
     turbo_tasks::register();
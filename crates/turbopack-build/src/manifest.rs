@@ -0,0 +1,126 @@
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
+use serde::Serialize;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    reference::all_assets_from_entries,
+};
+
+/// A JSON manifest listing the chunks of each entry of a build, as well as
+/// every file emitted by the build.
+///
+/// Paths are relative to the output root. This lets a server or an HTML
+/// generator reference content-hashed chunks without knowing their names.
+#[turbo_tasks::value(shared)]
+pub struct BuildManifestAsset {
+    path: Vc<FileSystemPath>,
+    output_root: Vc<FileSystemPath>,
+    entries: Vec<(String, Vc<OutputAssets>)>,
+}
+
+impl BuildManifestAsset {
+    /// Creates a new [`Vc<BuildManifestAsset>`] from the chunk groups of the
+    /// named entries of a build.
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        output_root: Vc<FileSystemPath>,
+        entries: Vec<(String, Vc<OutputAssets>)>,
+    ) -> Vc<Self> {
+        BuildManifestAsset {
+            path,
+            output_root,
+            entries,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl BuildManifestAsset {
+    #[turbo_tasks::function]
+    async fn entry_assets(&self) -> Result<Vc<OutputAssets>> {
+        let mut assets = Vec::new();
+        for (_, chunk_group) in &self.entries {
+            assets.extend(chunk_group.await?.iter().copied());
+        }
+        Ok(Vc::cell(assets))
+    }
+}
+
+#[derive(Serialize)]
+struct BuildManifest<'a> {
+    entries: IndexMap<&'a str, BuildManifestEntry>,
+    files: Vec<String>,
+}
+
+#[derive(Default, Serialize)]
+struct BuildManifestEntry {
+    js: Vec<String>,
+    css: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for BuildManifestAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    fn references(self: Vc<Self>) -> Vc<OutputAssets> {
+        self.entry_assets()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for BuildManifestAsset {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let this = self.await?;
+        let output_root = this.output_root.await?;
+
+        let relative_path = |asset: Vc<Box<dyn OutputAsset>>| {
+            let output_root = &output_root;
+            async move {
+                let path = asset.ident().path().await?;
+                let Some(relative_path) = output_root.get_path_to(&path) else {
+                    bail!(
+                        "asset path {} is not in output root {}",
+                        path.to_string(),
+                        output_root.to_string()
+                    );
+                };
+                Ok(relative_path.to_string())
+            }
+        };
+
+        let mut entries = IndexMap::new();
+        for (name, chunk_group) in &this.entries {
+            let mut entry = BuildManifestEntry::default();
+            for &asset in &*chunk_group.await? {
+                let path = relative_path(asset).await?;
+                if path.ends_with(".js") {
+                    entry.js.push(path);
+                } else if path.ends_with(".css") {
+                    entry.css.push(path);
+                }
+            }
+            entries.insert(name.as_str(), entry);
+        }
+
+        let mut files = Vec::new();
+        for &asset in &*all_assets_from_entries(self.entry_assets()).await? {
+            files.push(relative_path(asset).await?);
+        }
+        files.sort();
+
+        let manifest = BuildManifest { entries, files };
+        Ok(AssetContent::file(
+            File::from(serde_json::to_string_pretty(&manifest)?).into(),
+        ))
+    }
+}
@@ -3,7 +3,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use clap::{Args, Parser};
+use clap::{Args, Parser, ValueEnum};
 use turbopack_cli_utils::issue::IssueSeverityCliOption;
 
 #[derive(Debug, Parser)]
@@ -61,6 +61,11 @@ pub struct CommonArguments {
     /// MB.
     #[clap(long)]
     pub memory_limit: Option<usize>,
+
+    /// Persist the task graph in this directory, so following runs only
+    /// re-execute work affected by changes.
+    #[clap(long, value_parser)]
+    pub persistent_cache_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Args)]
@@ -107,4 +112,16 @@ pub struct BuildArguments {
     /// Don't minify build output.
     #[clap(long)]
     pub no_minify: bool,
+
+    /// The environment the build output runs in.
+    #[clap(long, value_enum, default_value_t = BuildTargetCliOption::Node)]
+    pub target: BuildTargetCliOption,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BuildTargetCliOption {
+    /// An `<entry>.entry.js` per entry that can be run with Node.js.
+    Node,
+    /// Content-hashed chunks and a `manifest.json` to load them from HTML.
+    Browser,
 }
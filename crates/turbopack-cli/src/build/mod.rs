@@ -6,15 +6,15 @@ use std::{
 };
 
 use anyhow::{bail, Context, Result};
-use turbo_tasks::{TransientInstance, TryJoinIterExt, TurboTasks, Value, Vc};
+use turbo_tasks::{backend::Backend, TransientInstance, TryJoinIterExt, TurboTasks, Value, Vc};
 use turbo_tasks_fs::FileSystem;
-use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_memory::{MemoryBackend, MemoryBackendWithPersistedGraph};
 use turbopack::ecmascript::EcmascriptModuleAsset;
-use turbopack_build::{BuildChunkingContext, MinifyType};
+use turbopack_build::{BuildChunkingContext, BuildManifestAsset, BuildTarget, MinifyType};
 use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
 use turbopack_core::{
     asset::Asset,
-    chunk::{ChunkableModule, ChunkingContextExt, EvaluatableAssets},
+    chunk::{ChunkableModule, ChunkingContext, ChunkingContextExt, EvaluatableAssets},
     environment::{BrowserEnvironment, Environment, ExecutionEnvironment},
     issue::{handle_issues, IssueReporter, IssueSeverity},
     module::Module,
@@ -30,11 +30,11 @@ use turbopack_env::dotenv::load_env;
 use turbopack_node::execution_context::ExecutionContext;
 
 use crate::{
-    arguments::BuildArguments,
+    arguments::{BuildArguments, BuildTargetCliOption},
     contexts::{get_client_asset_context, get_client_compile_time_info, NodeEnv},
     util::{
-        normalize_dirs, normalize_entries, output_fs, project_fs, EntryRequest, EntryRequests,
-        NormalizedDirs,
+        normalize_dirs, normalize_entries, output_fs, persisted_graph, project_fs, EntryRequest,
+        EntryRequests, NormalizedDirs,
     },
 };
 
@@ -43,8 +43,8 @@ pub fn register() {
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
 
-pub struct TurbopackBuildBuilder {
-    turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
+pub struct TurbopackBuildBuilder<B: Backend + 'static = MemoryBackend> {
+    turbo_tasks: Arc<TurboTasks<B>>,
     project_dir: String,
     root_dir: String,
     entry_requests: Vec<EntryRequest>,
@@ -53,14 +53,11 @@ pub struct TurbopackBuildBuilder {
     show_all: bool,
     log_detail: bool,
     minify_type: MinifyType,
+    target: BuildTarget,
 }
 
-impl TurbopackBuildBuilder {
-    pub fn new(
-        turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
-        project_dir: String,
-        root_dir: String,
-    ) -> Self {
+impl<B: Backend + 'static> TurbopackBuildBuilder<B> {
+    pub fn new(turbo_tasks: Arc<TurboTasks<B>>, project_dir: String, root_dir: String) -> Self {
         TurbopackBuildBuilder {
             turbo_tasks,
             project_dir,
@@ -71,6 +68,7 @@ impl TurbopackBuildBuilder {
             show_all: false,
             log_detail: false,
             minify_type: MinifyType::Minify,
+            target: BuildTarget::NodeJs,
         }
     }
 
@@ -104,6 +102,11 @@ impl TurbopackBuildBuilder {
         self
     }
 
+    pub fn target(mut self, target: BuildTarget) -> Self {
+        self.target = target;
+        self
+    }
+
     pub async fn build(self) -> Result<()> {
         let task = self.turbo_tasks.spawn_once_task::<(), _>(async move {
             let build_result = build_internal(
@@ -119,6 +122,7 @@ impl TurbopackBuildBuilder {
                 .cell(),
                 self.browserslist_query,
                 self.minify_type,
+                self.target,
             );
 
             // Await the result to propagate any errors.
@@ -158,6 +162,7 @@ async fn build_internal(
     entry_requests: Vc<EntryRequests>,
     browserslist_query: String,
     minify_type: MinifyType,
+    target: BuildTarget,
 ) -> Result<Vc<()>> {
     let env = Environment::new(Value::new(ExecutionEnvironment::Browser(
         BrowserEnvironment {
@@ -188,6 +193,7 @@ async fn build_internal(
             env,
         )
         .minify_type(minify_type)
+        .target(target)
         .build(),
     );
 
@@ -241,50 +247,68 @@ async fn build_internal(
     let entry_chunk_groups = entries
         .into_iter()
         .map(|entry_module| async move {
-            Ok(
-                if let Some(ecmascript) =
-                    Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(entry_module).await?
-                {
-                    Vc::cell(vec![Vc::try_resolve_downcast_type::<BuildChunkingContext>(
-                        chunking_context,
-                    )
+            let name = entry_module
+                .ident()
+                .path()
+                .file_stem()
+                .await?
+                .as_deref()
+                .unwrap()
+                .to_string();
+            let chunk_group = if let Some(ecmascript) =
+                Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(entry_module).await?
+            {
+                match target {
+                    BuildTarget::Browser => chunking_context.evaluated_chunk_group(
+                        ecmascript.ident(),
+                        EvaluatableAssets::one(Vc::upcast(ecmascript)),
+                    ),
+                    BuildTarget::NodeJs => Vc::cell(vec![Vc::try_resolve_downcast_type::<
+                        BuildChunkingContext,
+                    >(chunking_context)
                     .await?
                     .unwrap()
                     .entry_chunk_group(
                         build_output_root
-                            .join(
-                                ecmascript
-                                    .ident()
-                                    .path()
-                                    .file_stem()
-                                    .await?
-                                    .as_deref()
-                                    .unwrap()
-                                    .to_string(),
-                            )
+                            .join(name.clone())
                             .with_extension("entry.js".to_string()),
                         Vc::upcast(ecmascript),
                         EvaluatableAssets::one(Vc::upcast(ecmascript)),
-                    )])
-                } else if let Some(chunkable) =
-                    Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(entry_module).await?
-                {
-                    chunking_context.root_chunk_group(chunkable)
-                } else {
-                    // TODO convert into a serve-able asset
-                    bail!(
-                        "Entry module is not chunkable, so it can't be used to bootstrap the \
-                         application"
-                    )
-                },
-            )
+                    )]),
+                }
+            } else if let Some(chunkable) =
+                Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(entry_module).await?
+            {
+                chunking_context.root_chunk_group(chunkable)
+            } else {
+                // TODO convert into a serve-able asset
+                bail!(
+                    "Entry module is not chunkable, so it can't be used to bootstrap the \
+                     application"
+                )
+            };
+            Ok((name, chunk_group))
         })
         .try_join()
         .await?;
 
     let mut chunks: HashSet<Vc<Box<dyn OutputAsset>>> = HashSet::new();
-    for chunk_group in entry_chunk_groups {
-        chunks.extend(&*all_assets_from_entries(chunk_group).await?);
+    match target {
+        // The manifest references every chunk, so pages can look up the
+        // content-hashed filenames to load
+        BuildTarget::Browser => {
+            let manifest = BuildManifestAsset::new(
+                build_output_root.join("manifest.json".to_string()),
+                build_output_root,
+                entry_chunk_groups,
+            );
+            chunks.extend(&*all_assets_from_entries(Vc::cell(vec![Vc::upcast(manifest)])).await?);
+        }
+        BuildTarget::NodeJs => {
+            for (_, chunk_group) in entry_chunk_groups {
+                chunks.extend(&*all_assets_from_entries(chunk_group).await?);
+            }
+        }
     }
 
     chunks
@@ -297,17 +321,33 @@ async fn build_internal(
 }
 
 pub async fn build(args: &BuildArguments) -> Result<()> {
+    if let Some(cache_dir) = &args.common.persistent_cache_dir {
+        let tt = TurboTasks::new(MemoryBackendWithPersistedGraph::new(persisted_graph(
+            cache_dir,
+        )?));
+        let result = build_with(tt.clone(), args).await;
+        // Stopping writes the persisted graph to disk
+        tt.stop_and_wait().await;
+        result
+    } else {
+        let tt = TurboTasks::new(MemoryBackend::new(
+            args.common
+                .memory_limit
+                .map_or(usize::MAX, |l| l * 1024 * 1024),
+        ));
+        build_with(tt, args).await
+    }
+}
+
+async fn build_with<B: Backend + 'static>(
+    tt: Arc<TurboTasks<B>>,
+    args: &BuildArguments,
+) -> Result<()> {
     let NormalizedDirs {
         project_dir,
         root_dir,
     } = normalize_dirs(&args.common.dir, &args.common.root)?;
 
-    let tt = TurboTasks::new(MemoryBackend::new(
-        args.common
-            .memory_limit
-            .map_or(usize::MAX, |l| l * 1024 * 1024),
-    ));
-
     let mut builder = TurbopackBuildBuilder::new(tt, project_dir, root_dir)
         .log_detail(args.common.log_detail)
         .log_level(
@@ -320,6 +360,10 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
         } else {
             MinifyType::Minify
         })
+        .target(match args.target {
+            BuildTargetCliOption::Node => BuildTarget::NodeJs,
+            BuildTargetCliOption::Browser => BuildTarget::Browser,
+        })
         .show_all(args.common.show_all);
 
     for entry in normalize_entries(&args.common.entries) {
@@ -12,12 +12,14 @@ use std::{
 use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
 use turbo_tasks::{
+    backend::Backend,
+    persisted_graph::PersistedGraph,
     util::{FormatBytes, FormatDuration},
     StatsType, TransientInstance, TurboTasks, TurboTasksBackendApi, UpdateInfo, Value, Vc,
 };
 use turbo_tasks_fs::FileSystem;
 use turbo_tasks_malloc::TurboMalloc;
-use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_memory::{MemoryBackend, MemoryBackendWithPersistedGraph};
 use turbopack::evaluate_context::node_build_environment;
 use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
 use turbopack_core::{
@@ -43,15 +45,35 @@ use crate::{
     arguments::DevArguments,
     contexts::NodeEnv,
     util::{
-        normalize_dirs, normalize_entries, output_fs, project_fs, EntryRequest, NormalizedDirs,
+        normalize_dirs, normalize_entries, output_fs, persisted_graph, project_fs, EntryRequest,
+        NormalizedDirs,
     },
 };
 
 pub(crate) mod turbo_tasks_viz;
 pub(crate) mod web_entry_source;
 
-pub struct TurbopackDevServerBuilder {
-    turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
+/// A backend the dev server can run on.
+pub trait DevServerBackend: Backend + Sized + 'static {
+    /// The task graph visualization at `/__turbo_tasks__` reads the in-memory
+    /// backend's stats, so it is only served when running on it.
+    fn visualized(turbo_tasks: &Arc<TurboTasks<Self>>) -> Option<Arc<TurboTasks<MemoryBackend>>>;
+}
+
+impl DevServerBackend for MemoryBackend {
+    fn visualized(turbo_tasks: &Arc<TurboTasks<Self>>) -> Option<Arc<TurboTasks<MemoryBackend>>> {
+        Some(turbo_tasks.clone())
+    }
+}
+
+impl<P: PersistedGraph + 'static> DevServerBackend for MemoryBackendWithPersistedGraph<P> {
+    fn visualized(_turbo_tasks: &Arc<TurboTasks<Self>>) -> Option<Arc<TurboTasks<MemoryBackend>>> {
+        None
+    }
+}
+
+pub struct TurbopackDevServerBuilder<B: DevServerBackend = MemoryBackend> {
+    turbo_tasks: Arc<TurboTasks<B>>,
     project_dir: String,
     root_dir: String,
     entry_requests: Vec<EntryRequest>,
@@ -66,12 +88,8 @@ pub struct TurbopackDevServerBuilder {
     allow_retry: bool,
 }
 
-impl TurbopackDevServerBuilder {
-    pub fn new(
-        turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
-        project_dir: String,
-        root_dir: String,
-    ) -> TurbopackDevServerBuilder {
+impl<B: DevServerBackend> TurbopackDevServerBuilder<B> {
+    pub fn new(turbo_tasks: Arc<TurboTasks<B>>, project_dir: String, root_dir: String) -> Self {
         TurbopackDevServerBuilder {
             turbo_tasks,
             project_dir,
@@ -91,55 +109,52 @@ impl TurbopackDevServerBuilder {
         }
     }
 
-    pub fn entry_request(mut self, entry_asset_path: EntryRequest) -> TurbopackDevServerBuilder {
+    pub fn entry_request(mut self, entry_asset_path: EntryRequest) -> Self {
         self.entry_requests.push(entry_asset_path);
         self
     }
 
-    pub fn eager_compile(mut self, eager_compile: bool) -> TurbopackDevServerBuilder {
+    pub fn eager_compile(mut self, eager_compile: bool) -> Self {
         self.eager_compile = eager_compile;
         self
     }
 
-    pub fn hostname(mut self, hostname: IpAddr) -> TurbopackDevServerBuilder {
+    pub fn hostname(mut self, hostname: IpAddr) -> Self {
         self.hostname = Some(hostname);
         self
     }
 
-    pub fn port(mut self, port: u16) -> TurbopackDevServerBuilder {
+    pub fn port(mut self, port: u16) -> Self {
         self.port = Some(port);
         self
     }
 
-    pub fn browserslist_query(mut self, browserslist_query: String) -> TurbopackDevServerBuilder {
+    pub fn browserslist_query(mut self, browserslist_query: String) -> Self {
         self.browserslist_query = browserslist_query;
         self
     }
 
-    pub fn log_level(mut self, log_level: IssueSeverity) -> TurbopackDevServerBuilder {
+    pub fn log_level(mut self, log_level: IssueSeverity) -> Self {
         self.log_level = log_level;
         self
     }
 
-    pub fn show_all(mut self, show_all: bool) -> TurbopackDevServerBuilder {
+    pub fn show_all(mut self, show_all: bool) -> Self {
         self.show_all = show_all;
         self
     }
 
-    pub fn allow_retry(mut self, allow_retry: bool) -> TurbopackDevServerBuilder {
+    pub fn allow_retry(mut self, allow_retry: bool) -> Self {
         self.allow_retry = allow_retry;
         self
     }
 
-    pub fn log_detail(mut self, log_detail: bool) -> TurbopackDevServerBuilder {
+    pub fn log_detail(mut self, log_detail: bool) -> Self {
         self.log_detail = log_detail;
         self
     }
 
-    pub fn issue_reporter(
-        mut self,
-        issue_reporter: Box<dyn IssueReporterProvider>,
-    ) -> TurbopackDevServerBuilder {
+    pub fn issue_reporter(mut self, issue_reporter: Box<dyn IssueReporterProvider>) -> Self {
         self.issue_reporter = Some(issue_reporter);
         self
     }
@@ -190,7 +205,7 @@ impl TurbopackDevServerBuilder {
 
         let server = self.find_port(host, port, 10)?;
 
-        let turbo_tasks = self.turbo_tasks;
+        let turbo_tasks = B::visualized(&self.turbo_tasks);
         let project_dir = self.project_dir;
         let root_dir = self.root_dir;
         let eager_compile = self.eager_compile;
@@ -205,7 +220,7 @@ impl TurbopackDevServerBuilder {
             log_level: self.log_level,
         });
         let entry_requests = Arc::new(self.entry_requests);
-        let tasks = turbo_tasks.clone();
+        let tasks = self.turbo_tasks;
         let issue_provider = self.issue_reporter.unwrap_or_else(|| {
             // Initialize a ConsoleUi reporter if no custom reporter was provided
             Box::new(move || Vc::upcast(ConsoleUi::new(log_args.clone().into())))
@@ -217,7 +232,7 @@ impl TurbopackDevServerBuilder {
                 project_dir.clone(),
                 entry_requests.clone().into(),
                 eager_compile,
-                turbo_tasks.clone().into(),
+                turbo_tasks.clone().map(Into::into),
                 browserslist_query.clone(),
             )
         };
@@ -233,7 +248,7 @@ async fn source(
     project_dir: String,
     entry_requests: TransientInstance<Vec<EntryRequest>>,
     eager_compile: bool,
-    turbo_tasks: TransientInstance<TurboTasks<MemoryBackend>>,
+    turbo_tasks: Option<TransientInstance<TurboTasks<MemoryBackend>>>,
     browserslist_query: String,
 ) -> Result<Vc<Box<dyn ContentSource>>> {
     let project_relative = project_dir.strip_prefix(&root_dir).unwrap();
@@ -285,7 +300,6 @@ async fn source(
         NodeEnv::Development.cell(),
         browserslist_query,
     );
-    let viz = Vc::upcast(turbo_tasks_viz::TurboTasksSource::new(turbo_tasks.into()));
     let static_source = Vc::upcast(StaticAssetsContentSource::new(
         String::new(),
         project_path.join("public".to_string()),
@@ -298,12 +312,14 @@ async fn source(
         .cell(),
     );
     let main_source = Vc::upcast(main_source);
+    let mut routes = vec![("__turbopack__".to_string(), introspect)];
+    if let Some(turbo_tasks) = turbo_tasks {
+        let viz = Vc::upcast(turbo_tasks_viz::TurboTasksSource::new(turbo_tasks.into()));
+        routes.push(("__turbo_tasks__".to_string(), viz));
+    }
     let source = Vc::upcast(PrefixedRouterContentSource::new(
         Default::default(),
-        vec![
-            ("__turbopack__".to_string(), introspect),
-            ("__turbo_tasks__".to_string(), viz),
-        ],
+        routes,
         main_source,
     ));
 
@@ -323,17 +339,38 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
     console_subscriber::init();
     register();
 
+    if let Some(cache_dir) = &args.common.persistent_cache_dir {
+        let tt = TurboTasks::new(MemoryBackendWithPersistedGraph::new(persisted_graph(
+            cache_dir,
+        )?));
+        // The server runs until interrupted, stopping then writes the persisted
+        // graph to disk
+        let result = tokio::select! {
+            result = start_server_with(tt.clone(), args, start) => result,
+            _ = tokio::signal::ctrl_c() => Ok(()),
+        };
+        tt.stop_and_wait().await;
+        result
+    } else {
+        let tt = TurboTasks::new(MemoryBackend::new(
+            args.common
+                .memory_limit
+                .map_or(usize::MAX, |l| l * 1024 * 1024),
+        ));
+        start_server_with(tt, args, start).await
+    }
+}
+
+async fn start_server_with<B: DevServerBackend>(
+    tt: Arc<TurboTasks<B>>,
+    args: &DevArguments,
+    start: Instant,
+) -> Result<()> {
     let NormalizedDirs {
         project_dir,
         root_dir,
     } = normalize_dirs(&args.common.dir, &args.common.root)?;
 
-    let tt = TurboTasks::new(MemoryBackend::new(
-        args.common
-            .memory_limit
-            .map_or(usize::MAX, |l| l * 1024 * 1024),
-    ));
-
     let stats_type = match args.common.full_stats {
         true => StatsType::Full,
         false => StatsType::Essential,
@@ -467,7 +504,10 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
 #[cfg(feature = "profile")]
 // When profiling, exits the process when no new updates have been received for
 // a given timeout and there are no more tasks in progress.
-async fn profile_timeout<T>(tt: &TurboTasks<MemoryBackend>, future: impl Future<Output = T>) -> T {
+async fn profile_timeout<B: Backend + 'static, T>(
+    tt: &TurboTasks<B>,
+    future: impl Future<Output = T>,
+) -> T {
     /// How long to wait in between updates before force-exiting the process
     /// during profiling.
     const PROFILE_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
@@ -486,8 +526,8 @@ async fn profile_timeout<T>(tt: &TurboTasks<MemoryBackend>, future: impl Future<
 }
 
 #[cfg(not(feature = "profile"))]
-fn profile_timeout<T>(
-    _tt: &TurboTasks<MemoryBackend>,
+fn profile_timeout<B: Backend + 'static, T>(
+    _tt: &TurboTasks<B>,
     future: impl Future<Output = T>,
 ) -> impl Future<Output = T> {
     future
@@ -1,8 +1,12 @@
-use std::{env::current_dir, path::PathBuf};
+use std::{
+    env::current_dir,
+    path::{Path, PathBuf},
+};
 
 use anyhow::{Context, Result};
 use dunce::canonicalize;
 use turbo_tasks::Vc;
+use turbo_tasks_disk_graph::DiskPersistedGraph;
 use turbo_tasks_fs::{DiskFileSystem, FileSystem};
 
 #[turbo_tasks::value(transparent)]
@@ -59,6 +63,13 @@ pub fn normalize_entries(entries: &Option<Vec<String>>) -> Vec<String> {
         .unwrap_or_else(|| vec!["src/entry".to_owned()])
 }
 
+/// Opens the task graph persisted in `cache_dir`. Files might have changed
+/// while Turbopack wasn't running, so tasks reading them are invalidated.
+pub fn persisted_graph(cache_dir: &Path) -> Result<DiskPersistedGraph> {
+    Ok(DiskPersistedGraph::new(cache_dir)?
+        .invalidate_on_startup(|name| name.starts_with("<DiskFileSystem as ")))
+}
+
 #[turbo_tasks::function]
 pub async fn project_fs(project_dir: String) -> Result<Vc<Box<dyn FileSystem>>> {
     let disk_fs = DiskFileSystem::new("project".to_string(), project_dir.to_string());
@@ -1,29 +1,36 @@
-use std::{
-    borrow::Cow,
-    mem::{replace, take},
-    pin::Pin,
-};
+use std::{borrow::Cow, hash::Hash, mem::take};
 
 use anyhow::Result;
-use futures::Future;
 use indexmap::IndexMap;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use tracing::Level;
 use turbo_tasks::{ReadRef, TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
 
-use super::{AsyncModuleInfo, Chunk, ChunkItem, ChunkType, ChunkingContext};
+use super::{
+    availability_info::AvailabilityInfo, chunk_content, AsyncModuleInfo, CacheGroup,
+    CacheGroupTest, Chunk, ChunkItem, ChunkType, ChunkingConfig, ChunkingContext,
+};
 use crate::output::OutputAssets;
 
-/// Creates chunks based on heuristics for the passed `chunk_items`. Also
-/// attaches `referenced_output_assets` to the first chunk.
+/// Creates chunks based on heuristics for the passed `chunk_items`, as
+/// configured by the [ChunkingConfig] of the `chunking_context`. Also attaches
+/// `referenced_output_assets` to the first chunk that isn't a cache group or
+/// shared chunk.
 #[tracing::instrument(level = Level::TRACE, skip_all)]
 pub async fn make_chunks(
     chunking_context: Vc<Box<dyn ChunkingContext>>,
     chunk_items: impl IntoIterator<Item = (Vc<Box<dyn ChunkItem>>, Option<Vc<AsyncModuleInfo>>)>,
     key_prefix: &str,
-    mut referenced_output_assets: Vc<OutputAssets>,
+    referenced_output_assets: Vc<OutputAssets>,
 ) -> Result<Vec<Vc<Box<dyn Chunk>>>> {
+    let config = chunking_context.chunking_config().await?;
+    let shared_chunk_items = if config.shared_chunk_groups.is_empty() {
+        None
+    } else {
+        Some(shared_chunk_items(chunking_context, &config).await?)
+    };
     let chunk_items = chunk_items
         .into_iter()
         .map(|(chunk_item, async_info)| async move {
@@ -37,118 +44,349 @@ pub async fn make_chunks(
         map.entry(ty).or_default().push((chunk_item, async_info));
     }
 
-    let mut chunks = Vec::new();
+    let mut split_chunks = Vec::new();
     for (ty, chunk_items) in map {
         let ty_name = ty.to_string().await?;
 
+        let shared_chunk_items = shared_chunk_items.as_ref();
         let chunk_items = chunk_items
             .into_iter()
             .map(|(chunk_item, async_info)| async move {
-                Ok((
+                let asset_ident = chunk_item.asset_ident();
+                let chunk_groups = match shared_chunk_items {
+                    Some(shared_chunk_items) => shared_chunk_items
+                        .get(&chunk_item.resolve().await?)
+                        .cloned()
+                        .unwrap_or_default(),
+                    None => Vec::new(),
+                };
+                Ok(ChunkItemWithInfo {
                     chunk_item,
                     async_info,
-                    *ty.chunk_item_size(chunking_context, chunk_item, async_info)
+                    size: *ty
+                        .chunk_item_size(chunking_context, chunk_item, async_info)
                         .await?,
-                    chunk_item.asset_ident().to_string().await?,
-                ))
+                    asset_ident: asset_ident.to_string().await?,
+                    path: asset_ident.path().await?,
+                    chunk_groups,
+                })
             })
             .try_join()
             .await?;
 
         let mut split_context = SplitContext {
             ty,
-            chunking_context,
-            chunks: &mut chunks,
-            referenced_output_assets: &mut referenced_output_assets,
-            empty_referenced_output_assets: OutputAssets::empty().resolve().await?,
+            config: &config,
+            chunks: &mut split_chunks,
         };
 
-        app_vendors_split(
-            chunk_items,
-            format!("{key_prefix}{ty_name}"),
-            &mut split_context,
+        let name = format!("{key_prefix}{ty_name}");
+        let chunk_items = cache_groups_split(chunk_items, &name, &mut split_context);
+        let chunk_items = shared_split(chunk_items, &name, &mut split_context);
+        app_vendors_split(chunk_items, name, &mut split_context);
+    }
+
+    if let Some(max_parallel_requests) = config.max_parallel_requests {
+        merge_smallest_chunks(&mut split_chunks, max_parallel_requests);
+    }
+
+    // Cache group and shared chunks must be identical in every chunk group
+    // using them, so they never carry the referenced output assets.
+    let referencing_chunk = split_chunks
+        .iter()
+        .position(|chunk| !chunk.explicit)
+        .unwrap_or(0);
+    let empty_referenced_output_assets = OutputAssets::empty().resolve().await?;
+    Ok(split_chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            chunk.ty.chunk(
+                chunking_context,
+                chunk
+                    .chunk_items
+                    .into_iter()
+                    .map(|item| (item.chunk_item, item.async_info))
+                    .collect(),
+                if i == referencing_chunk {
+                    referenced_output_assets
+                } else {
+                    empty_referenced_output_assets
+                },
+            )
+        })
+        .collect())
+}
+
+/// Finds the chunk items shared between the chunk groups of the
+/// [ChunkingConfig]. Returns the chunk groups, as indices into
+/// [ChunkingConfig::shared_chunk_groups], of every chunk item that is used by
+/// more than one of them.
+// This can't be a turbo-tasks function, as functions in this module can't
+// depend on `chunk_content` being `Send`.
+async fn shared_chunk_items(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    config: &ChunkingConfig,
+) -> Result<IndexMap<Vc<Box<dyn ChunkItem>>, Vec<usize>>> {
+    let mut map = IndexMap::<_, Vec<usize>>::new();
+    for (i, entries) in config.shared_chunk_groups.iter().enumerate() {
+        let content = chunk_content(
+            chunking_context,
+            entries.await?.iter().copied(),
+            AvailabilityInfo::Root,
         )
         .await?;
+        for chunk_item in content.chunk_items {
+            let chunk_groups = map.entry(chunk_item.resolve().await?).or_default();
+            if chunk_groups.last() != Some(&i) {
+                chunk_groups.push(i);
+            }
+        }
     }
+    map.retain(|_, chunk_groups| chunk_groups.len() > 1);
+    Ok(map)
+}
 
-    Ok(chunks)
+/// The properties of a chunk item that decide which chunk it is placed into.
+trait SplitItem {
+    /// The size of the chunk item in bytes.
+    fn size(&self) -> usize;
+    /// The asset ident of the chunk item as string.
+    fn ident(&self) -> &str;
+    /// The path of the chunk item, relative to the root of its file system.
+    fn path(&self) -> &str;
+    /// The shared chunk groups using the chunk item, if it is used by more
+    /// than one of them.
+    fn chunk_groups(&self) -> &[usize];
 }
 
-type ChunkItemWithInfo = (
-    Vc<Box<dyn ChunkItem>>,
-    Option<Vc<AsyncModuleInfo>>,
-    usize,
-    ReadRef<String>,
-);
+struct ChunkItemWithInfo {
+    chunk_item: Vc<Box<dyn ChunkItem>>,
+    async_info: Option<Vc<AsyncModuleInfo>>,
+    size: usize,
+    asset_ident: ReadRef<String>,
+    path: ReadRef<FileSystemPath>,
+    chunk_groups: Vec<usize>,
+}
 
-struct SplitContext<'a> {
-    ty: Vc<Box<dyn ChunkType>>,
-    chunking_context: Vc<Box<dyn ChunkingContext>>,
-    chunks: &'a mut Vec<Vc<Box<dyn Chunk>>>,
-    referenced_output_assets: &'a mut Vc<OutputAssets>,
-    empty_referenced_output_assets: Vc<OutputAssets>,
+impl SplitItem for ChunkItemWithInfo {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn ident(&self) -> &str {
+        &self.asset_ident
+    }
+
+    fn path(&self) -> &str {
+        &self.path.path
+    }
+
+    fn chunk_groups(&self) -> &[usize] {
+        &self.chunk_groups
+    }
+}
+
+/// The chunk items of a chunk that is yet to be created.
+struct SplitChunk<T, I> {
+    ty: T,
+    chunk_items: Vec<I>,
+    /// Whether the chunk was split out on purpose, i. e. it is a cache group
+    /// or shared chunk. These are never merged with other chunks.
+    explicit: bool,
+}
+
+struct SplitContext<'a, T, I> {
+    ty: T,
+    config: &'a ChunkingConfig,
+    chunks: &'a mut Vec<SplitChunk<T, I>>,
+}
+
+impl<T, I> SplitContext<'_, T, I> {
+    /// Marks all chunks created since there were `first` chunks as explicit.
+    fn mark_explicit(&mut self, first: usize) {
+        for chunk in &mut self.chunks[first..] {
+            chunk.explicit = true;
+        }
+    }
 }
 
 /// Handle chunk items based on their total size. If the total size is too
 /// small, they will be pushed into `remaining`, if possible. If the total size
 /// is too large, it will return `false` and the caller should hand of the chunk
 /// items to be further split. Otherwise it creates a chunk.
-async fn handle_split_group(
-    chunk_items: &mut Vec<ChunkItemWithInfo>,
+fn handle_split_group<T: Copy, I: SplitItem>(
+    chunk_items: &mut Vec<I>,
     key: &mut String,
-    split_context: &mut SplitContext<'_>,
-    remaining: Option<&mut Vec<ChunkItemWithInfo>>,
-) -> Result<bool> {
-    Ok(match (chunk_size(chunk_items), remaining) {
+    split_context: &mut SplitContext<'_, T, I>,
+    remaining: Option<&mut Vec<I>>,
+) -> bool {
+    match (
+        chunk_size(total_size(chunk_items), split_context.config),
+        remaining,
+    ) {
         (ChunkSize::Large, _) => false,
         (ChunkSize::Perfect, _) | (ChunkSize::Small, None) => {
-            make_chunk(take(chunk_items), key, split_context).await?;
+            make_chunk(take(chunk_items), key, split_context);
             true
         }
         (ChunkSize::Small, Some(remaining)) => {
             remaining.extend(take(chunk_items));
             true
         }
-    })
+    }
 }
 
 /// Creates a chunk with the given `chunk_items. `key` should be unique and is
 /// used with [keyed_cell] to place the chunk items into a cell.
 #[tracing::instrument(level = Level::TRACE, skip(chunk_items, split_context))]
-async fn make_chunk(
-    chunk_items: Vec<ChunkItemWithInfo>,
+fn make_chunk<T: Copy, I>(
+    chunk_items: Vec<I>,
     key: &mut String,
-    split_context: &mut SplitContext<'_>,
-) -> Result<()> {
-    split_context.chunks.push(
-        split_context.ty.chunk(
-            split_context.chunking_context,
-            chunk_items
-                .into_iter()
-                .map(|(chunk_item, async_info, ..)| (chunk_item, async_info))
-                .collect(),
-            replace(
-                split_context.referenced_output_assets,
-                split_context.empty_referenced_output_assets,
-            ),
-        ),
-    );
-    Ok(())
+    split_context: &mut SplitContext<'_, T, I>,
+) {
+    split_context.chunks.push(SplitChunk {
+        ty: split_context.ty,
+        chunk_items,
+        explicit: false,
+    });
+}
+
+/// Moves chunk items matching a cache group of the [ChunkingConfig] into
+/// chunks of their own. Returns the chunk items that don't match any cache
+/// group.
+#[tracing::instrument(level = Level::TRACE, skip(chunk_items, split_context))]
+fn cache_groups_split<T: Copy, I: SplitItem>(
+    chunk_items: Vec<I>,
+    name: &str,
+    split_context: &mut SplitContext<'_, T, I>,
+) -> Vec<I> {
+    let config = split_context.config;
+    let cache_groups = &config.cache_groups;
+    if cache_groups.is_empty() {
+        return chunk_items;
+    }
+    let mut groups = cache_groups
+        .iter()
+        .map(|_| Vec::new())
+        .collect::<Vec<Vec<I>>>();
+    let mut remaining = Vec::new();
+    for item in chunk_items {
+        match cache_groups
+            .iter()
+            .position(|cache_group| matches_cache_group(&item, cache_group))
+        {
+            Some(i) => groups[i].push(item),
+            None => remaining.push(item),
+        }
+    }
+    let first = split_context.chunks.len();
+    for (cache_group, mut list) in cache_groups.iter().zip(groups) {
+        if list.is_empty() {
+            continue;
+        }
+        let mut key = format!("{}-{}", name, cache_group.name);
+        if !handle_split_group(&mut list, &mut key, split_context, None) {
+            folder_split(list, 0, key.into(), split_context);
+        }
+    }
+    split_context.mark_explicit(first);
+    remaining
+}
+
+/// Returns `true` if the given chunk item belongs to the `cache_group`.
+fn matches_cache_group(item: &impl SplitItem, cache_group: &CacheGroup) -> bool {
+    match &cache_group.test {
+        CacheGroupTest::Package(glob) => {
+            let package_name = package_name(item.ident());
+            !package_name.is_empty() && glob.execute(package_name)
+        }
+        CacheGroupTest::Path(glob) => glob.execute(item.path()),
+    }
+}
+
+/// Moves chunk items used by more than one of the shared chunk groups into
+/// shared chunks, one for every combination of chunk groups using them. Every
+/// chunk group then references the same shared chunk. Returns the chunk items
+/// that are not shared.
+#[tracing::instrument(level = Level::TRACE, skip(chunk_items, split_context))]
+fn shared_split<T: Copy, I: SplitItem>(
+    chunk_items: Vec<I>,
+    name: &str,
+    split_context: &mut SplitContext<'_, T, I>,
+) -> Vec<I> {
+    let mut shared = IndexMap::<Vec<usize>, Vec<I>>::new();
+    let mut remaining = Vec::new();
+    for item in chunk_items {
+        if item.chunk_groups().is_empty() {
+            remaining.push(item);
+        } else {
+            shared
+                .entry(item.chunk_groups().to_vec())
+                .or_default()
+                .push(item);
+        }
+    }
+    let first = split_context.chunks.len();
+    for (chunk_groups, mut list) in shared {
+        // The chunk groups find their chunk items in different orders.
+        list.sort_by(|a, b| a.ident().cmp(b.ident()));
+        let chunk_groups = chunk_groups
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("-");
+        let mut key = format!("{}-shared-{}", name, chunk_groups);
+        make_chunk(list, &mut key, split_context);
+    }
+    split_context.mark_explicit(first);
+    remaining
+}
+
+/// Merges the two smallest chunks of the same chunk type until there are no
+/// more than `max_chunks` chunks of each type. Explicit chunks are never
+/// merged, so there can be more chunks if they are the majority.
+fn merge_smallest_chunks<T: Copy + Eq + Hash, I: SplitItem>(
+    chunks: &mut Vec<SplitChunk<T, I>>,
+    max_chunks: usize,
+) {
+    let max_chunks = max_chunks.max(1);
+    loop {
+        let mut count_by_type = IndexMap::<_, (usize, Vec<usize>)>::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let (count, mergeable) = count_by_type.entry(chunk.ty).or_default();
+            *count += 1;
+            if !chunk.explicit {
+                mergeable.push(i);
+            }
+        }
+        let Some(mut indices) = count_by_type
+            .into_values()
+            .find(|(count, mergeable)| *count > max_chunks && mergeable.len() > 1)
+            .map(|(_, mergeable)| mergeable)
+        else {
+            return;
+        };
+        indices.sort_by_key(|&i| total_size(&chunks[i].chunk_items));
+        let target = indices[0].min(indices[1]);
+        let source = indices[0].max(indices[1]);
+        let SplitChunk { chunk_items, .. } = chunks.remove(source);
+        chunks[target].chunk_items.extend(chunk_items);
+    }
 }
 
 /// Split chunk items into app code and vendor code. Continues splitting with
 /// [package_name_split] if necessary.
 #[tracing::instrument(level = Level::TRACE, skip(chunk_items, split_context))]
-async fn app_vendors_split(
-    chunk_items: Vec<ChunkItemWithInfo>,
+fn app_vendors_split<T: Copy, I: SplitItem>(
+    chunk_items: Vec<I>,
     mut name: String,
-    split_context: &mut SplitContext<'_>,
-) -> Result<()> {
+    split_context: &mut SplitContext<'_, T, I>,
+) {
     let mut app_chunk_items = Vec::new();
     let mut vendors_chunk_items = Vec::new();
     for item in chunk_items {
-        let (_, _, _, asset_ident) = &item;
-        if is_app_code(asset_ident) {
+        if is_app_code(item.ident()) {
             app_chunk_items.push(item);
         } else {
             vendors_chunk_items.push(item);
@@ -161,10 +399,8 @@ async fn app_vendors_split(
         &mut key,
         split_context,
         Some(&mut remaining),
-    )
-    .await?
-    {
-        folder_split(app_chunk_items, 0, key.into(), split_context).await?;
+    ) {
+        folder_split(app_chunk_items, 0, key.into(), split_context);
     }
     let mut key = format!("{}-vendors", name);
     if !handle_split_group(
@@ -172,31 +408,26 @@ async fn app_vendors_split(
         &mut key,
         split_context,
         Some(&mut remaining),
-    )
-    .await?
-    {
-        package_name_split(vendors_chunk_items, key, split_context).await?;
+    ) {
+        package_name_split(vendors_chunk_items, key, split_context);
     }
-    if !remaining.is_empty()
-        && !handle_split_group(&mut remaining, &mut name, split_context, None).await?
+    if !remaining.is_empty() && !handle_split_group(&mut remaining, &mut name, split_context, None)
     {
-        package_name_split(remaining, name, split_context).await?;
+        package_name_split(remaining, name, split_context);
     }
-    Ok(())
 }
 
 /// Split chunk items by node_modules package name. Continues splitting with
 /// [folder_split] if necessary.
 #[tracing::instrument(level = Level::TRACE, skip(chunk_items, split_context))]
-async fn package_name_split(
-    chunk_items: Vec<ChunkItemWithInfo>,
+fn package_name_split<T: Copy, I: SplitItem>(
+    chunk_items: Vec<I>,
     mut name: String,
-    split_context: &mut SplitContext<'_>,
-) -> Result<()> {
-    let mut map = IndexMap::<_, Vec<ChunkItemWithInfo>>::new();
+    split_context: &mut SplitContext<'_, T, I>,
+) {
+    let mut map = IndexMap::<_, Vec<I>>::new();
     for item in chunk_items {
-        let (_, _, _, asset_ident) = &item;
-        let package_name = package_name(asset_ident);
+        let package_name = package_name(item.ident());
         if let Some(list) = map.get_mut(package_name) {
             list.push(item);
         } else {
@@ -206,41 +437,28 @@ async fn package_name_split(
     let mut remaining = Vec::new();
     for (package_name, mut list) in map {
         let mut key = format!("{}-{}", name, package_name);
-        if !handle_split_group(&mut list, &mut key, split_context, Some(&mut remaining)).await? {
-            folder_split(list, 0, key.into(), split_context).await?;
+        if !handle_split_group(&mut list, &mut key, split_context, Some(&mut remaining)) {
+            folder_split(list, 0, key.into(), split_context);
         }
     }
-    if !remaining.is_empty()
-        && !handle_split_group(&mut remaining, &mut name, split_context, None).await?
+    if !remaining.is_empty() && !handle_split_group(&mut remaining, &mut name, split_context, None)
     {
-        folder_split(remaining, 0, name.into(), split_context).await?;
+        folder_split(remaining, 0, name.into(), split_context);
     }
-    Ok(())
-}
-
-/// A boxed version of [folder_split] for recursion.
-fn folder_split_boxed<'a, 'b>(
-    chunk_items: Vec<ChunkItemWithInfo>,
-    location: usize,
-    name: Cow<'a, str>,
-    split_context: &'a mut SplitContext<'b>,
-) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
-    Box::pin(folder_split(chunk_items, location, name, split_context))
 }
 
 /// Split chunk items by folder structure.
 #[tracing::instrument(level = Level::TRACE, skip(chunk_items, split_context))]
-async fn folder_split(
-    mut chunk_items: Vec<ChunkItemWithInfo>,
+fn folder_split<T: Copy, I: SplitItem>(
+    mut chunk_items: Vec<I>,
     mut location: usize,
     name: Cow<'_, str>,
-    split_context: &mut SplitContext<'_>,
-) -> Result<()> {
-    let mut map = IndexMap::<_, (_, Vec<ChunkItemWithInfo>)>::new();
+    split_context: &mut SplitContext<'_, T, I>,
+) {
+    let mut map = IndexMap::<_, (_, Vec<I>)>::new();
     loop {
         for item in chunk_items {
-            let (_, _, _, asset_ident) = &item;
-            let (folder_name, new_location) = folder_name(asset_ident, location);
+            let (folder_name, new_location) = folder_name(item.ident(), location);
             if let Some((_, list)) = map.get_mut(folder_name) {
                 list.push(item);
             } else {
@@ -257,8 +475,8 @@ async fn folder_split(
                 continue;
             } else {
                 let mut key = format!("{}-{}", name, folder_name);
-                make_chunk(list, &mut key, split_context).await?;
-                return Ok(());
+                make_chunk(list, &mut key, split_context);
+                return;
             }
         } else {
             break;
@@ -267,22 +485,20 @@ async fn folder_split(
     let mut remaining = Vec::new();
     for (folder_name, (new_location, mut list)) in map {
         let mut key = format!("{}-{}", name, folder_name);
-        if !handle_split_group(&mut list, &mut key, split_context, Some(&mut remaining)).await? {
+        if !handle_split_group(&mut list, &mut key, split_context, Some(&mut remaining)) {
             if let Some(new_location) = new_location {
-                folder_split_boxed(list, new_location, Cow::Borrowed(&name), split_context).await?;
+                folder_split(list, new_location, Cow::Borrowed(&name), split_context);
             } else {
-                make_chunk(list, &mut key, split_context).await?;
+                make_chunk(list, &mut key, split_context);
             }
         }
     }
     if !remaining.is_empty() {
-        let (_, _, _, asset_ident) = &remaining[0];
-        let mut key = format!("{}-{}", name, &asset_ident[..location]);
-        if !handle_split_group(&mut remaining, &mut key, split_context, None).await? {
-            make_chunk(remaining, &mut key, split_context).await?;
+        let mut key = format!("{}-{}", name, &remaining[0].ident()[..location]);
+        if !handle_split_group(&mut remaining, &mut key, split_context, None) {
+            make_chunk(remaining, &mut key, split_context);
         }
     }
-    Ok(())
 }
 
 /// Returns `true` if the given `ident` is app code.
@@ -312,27 +528,281 @@ fn folder_name(ident: &str, location: usize) -> (&str, Option<usize>) {
     }
 }
 
-const LARGE_CHUNK: usize = 1_000_000;
-const SMALL_CHUNK: usize = 100_000;
-
+#[derive(Debug, PartialEq, Eq)]
 enum ChunkSize {
     Large,
     Perfect,
     Small,
 }
 
-/// Determines the total size of the passed chunk items. Returns too small, too
-/// large or perfect fit.
-fn chunk_size(chunk_items: &[ChunkItemWithInfo]) -> ChunkSize {
-    let mut total_size = 0;
-    for (_, _, size, _) in chunk_items {
-        total_size += size;
-    }
-    if total_size >= LARGE_CHUNK {
+/// Returns the total size of the passed chunk items.
+fn total_size(chunk_items: &[impl SplitItem]) -> usize {
+    chunk_items.iter().map(|item| item.size()).sum()
+}
+
+/// Determines whether chunk items with the given total size are too small, too
+/// large or a perfect fit for a chunk.
+fn chunk_size(total_size: usize, config: &ChunkingConfig) -> ChunkSize {
+    if total_size >= config.max_chunk_size {
         ChunkSize::Large
-    } else if total_size > SMALL_CHUNK {
+    } else if total_size > config.min_chunk_size {
         ChunkSize::Perfect
     } else {
         ChunkSize::Small
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestItem {
+        ident: &'static str,
+        size: usize,
+        chunk_groups: Vec<usize>,
+    }
+
+    impl SplitItem for TestItem {
+        fn size(&self) -> usize {
+            self.size
+        }
+
+        fn ident(&self) -> &str {
+            self.ident
+        }
+
+        fn path(&self) -> &str {
+            &self.ident[self.ident.find('/').unwrap() + 1..]
+        }
+
+        fn chunk_groups(&self) -> &[usize] {
+            &self.chunk_groups
+        }
+    }
+
+    fn item(ident: &'static str, size: usize) -> TestItem {
+        TestItem {
+            ident,
+            size,
+            chunk_groups: Vec::new(),
+        }
+    }
+
+    fn shared_item(ident: &'static str, chunk_groups: &[usize]) -> TestItem {
+        TestItem {
+            ident,
+            size: 1,
+            chunk_groups: chunk_groups.to_vec(),
+        }
+    }
+
+    fn chunk(ty: u32, explicit: bool, chunk_items: Vec<TestItem>) -> SplitChunk<u32, TestItem> {
+        SplitChunk {
+            ty,
+            chunk_items,
+            explicit,
+        }
+    }
+
+    fn idents(chunk: &SplitChunk<u32, TestItem>) -> Vec<&str> {
+        chunk.chunk_items.iter().map(|item| item.ident).collect()
+    }
+
+    fn config(cache_groups: Vec<CacheGroup>) -> ChunkingConfig {
+        ChunkingConfig {
+            min_chunk_size: 10,
+            max_chunk_size: 100,
+            cache_groups,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn chunk_size_thresholds() {
+        let config = config(Vec::new());
+        assert_eq!(chunk_size(0, &config), ChunkSize::Small);
+        assert_eq!(chunk_size(10, &config), ChunkSize::Small);
+        assert_eq!(chunk_size(11, &config), ChunkSize::Perfect);
+        assert_eq!(chunk_size(99, &config), ChunkSize::Perfect);
+        assert_eq!(chunk_size(100, &config), ChunkSize::Large);
+    }
+
+    #[test]
+    fn matches_package_cache_group() {
+        let react = CacheGroup::package("react".to_string(), "react*").unwrap();
+        let scoped = CacheGroup::package("mui".to_string(), "@mui/*").unwrap();
+        assert!(matches_cache_group(
+            &item("[project]/node_modules/react-dom/index.js", 1),
+            &react
+        ));
+        assert!(matches_cache_group(
+            &item("[project]/node_modules/@mui/material/index.js", 1),
+            &scoped
+        ));
+        assert!(!matches_cache_group(
+            &item("[project]/node_modules/lodash/index.js", 1),
+            &react
+        ));
+        // App code has no package name.
+        assert!(!matches_cache_group(
+            &item("[project]/src/react.js", 1),
+            &react
+        ));
+    }
+
+    #[test]
+    fn matches_path_cache_group() {
+        let icons = CacheGroup::path("icons".to_string(), "src/icons/**").unwrap();
+        assert!(matches_cache_group(
+            &item("[project]/src/icons/arrow.js", 1),
+            &icons
+        ));
+        assert!(!matches_cache_group(
+            &item("[project]/src/index.js", 1),
+            &icons
+        ));
+    }
+
+    #[test]
+    fn cache_groups_split_moves_matching_items_into_explicit_chunks() {
+        let config = config(vec![
+            CacheGroup::package("react".to_string(), "react*").unwrap(),
+            CacheGroup::package("vendors".to_string(), "*").unwrap(),
+        ]);
+        let mut chunks = Vec::new();
+        let mut split_context = SplitContext {
+            ty: 0,
+            config: &config,
+            chunks: &mut chunks,
+        };
+        let remaining = cache_groups_split(
+            vec![
+                item("[project]/src/index.js", 1),
+                item("[project]/node_modules/react-is/index.js", 1),
+                item("[project]/node_modules/lodash/index.js", 1),
+                item("[project]/node_modules/react-dom/index.js", 1),
+            ],
+            "test",
+            &mut split_context,
+        );
+        assert_eq!(remaining, vec![item("[project]/src/index.js", 1)]);
+        assert_eq!(chunks.len(), 2);
+        // Only the first matching cache group applies.
+        assert_eq!(
+            idents(&chunks[0]),
+            vec![
+                "[project]/node_modules/react-is/index.js",
+                "[project]/node_modules/react-dom/index.js"
+            ]
+        );
+        assert_eq!(
+            idents(&chunks[1]),
+            vec!["[project]/node_modules/lodash/index.js"]
+        );
+        assert!(chunks.iter().all(|chunk| chunk.explicit));
+    }
+
+    #[test]
+    fn cache_groups_split_splits_large_groups() {
+        let config = config(vec![
+            CacheGroup::package("vendors".to_string(), "*").unwrap()
+        ]);
+        let mut chunks = Vec::new();
+        let mut split_context = SplitContext {
+            ty: 0,
+            config: &config,
+            chunks: &mut chunks,
+        };
+        let remaining = cache_groups_split(
+            vec![
+                item("[project]/node_modules/a/index.js", 60),
+                item("[project]/node_modules/b/index.js", 60),
+            ],
+            "test",
+            &mut split_context,
+        );
+        assert!(remaining.is_empty());
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.iter().all(|chunk| chunk.explicit));
+    }
+
+    #[test]
+    fn shared_split_groups_items_by_chunk_groups() {
+        let config = config(Vec::new());
+        let mut chunks = Vec::new();
+        let mut split_context = SplitContext {
+            ty: 0,
+            config: &config,
+            chunks: &mut chunks,
+        };
+        let remaining = shared_split(
+            vec![
+                shared_item("[project]/src/b.js", &[0, 1]),
+                item("[project]/src/index.js", 1),
+                shared_item("[project]/node_modules/c/index.js", &[0, 1, 2]),
+                shared_item("[project]/src/a.js", &[0, 1]),
+            ],
+            "test",
+            &mut split_context,
+        );
+        assert_eq!(remaining, vec![item("[project]/src/index.js", 1)]);
+        assert_eq!(chunks.len(), 2);
+        // App modules are shared as well, in a stable order.
+        assert_eq!(
+            idents(&chunks[0]),
+            vec!["[project]/src/a.js", "[project]/src/b.js"]
+        );
+        assert_eq!(
+            idents(&chunks[1]),
+            vec!["[project]/node_modules/c/index.js"]
+        );
+        assert!(chunks.iter().all(|chunk| chunk.explicit));
+    }
+
+    #[test]
+    fn merge_smallest_chunks_merges_the_smallest() {
+        let mut chunks = vec![
+            chunk(0, false, vec![item("a", 3)]),
+            chunk(0, false, vec![item("b", 1)]),
+            chunk(0, false, vec![item("c", 2)]),
+        ];
+        merge_smallest_chunks(&mut chunks, 2);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(idents(&chunks[0]), vec!["a"]);
+        assert_eq!(idents(&chunks[1]), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn merge_smallest_chunks_counts_chunk_types_separately() {
+        let mut chunks = vec![
+            chunk(0, false, vec![item("a", 1)]),
+            chunk(1, false, vec![item("b", 1)]),
+            chunk(0, false, vec![item("c", 1)]),
+            chunk(1, false, vec![item("d", 1)]),
+        ];
+        merge_smallest_chunks(&mut chunks, 2);
+        assert_eq!(chunks.len(), 4);
+        merge_smallest_chunks(&mut chunks, 1);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(idents(&chunks[0]), vec!["a", "c"]);
+        assert_eq!(idents(&chunks[1]), vec!["b", "d"]);
+    }
+
+    #[test]
+    fn merge_smallest_chunks_skips_explicit_chunks() {
+        let mut chunks = vec![
+            chunk(0, true, vec![item("react", 1)]),
+            chunk(0, false, vec![item("a", 5)]),
+            chunk(0, true, vec![item("shared", 2)]),
+            chunk(0, false, vec![item("b", 4)]),
+            chunk(0, false, vec![item("c", 3)]),
+        ];
+        merge_smallest_chunks(&mut chunks, 2);
+        // The explicit chunks are kept, even if that exceeds the limit.
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(idents(&chunks[0]), vec!["react"]);
+        assert_eq!(idents(&chunks[1]), vec!["a", "b", "c"]);
+        assert_eq!(idents(&chunks[2]), vec!["shared"]);
+    }
+}
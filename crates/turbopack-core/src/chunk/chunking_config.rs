@@ -0,0 +1,85 @@
+use anyhow::Result;
+use turbo_tasks::{ValueDefault, Vc};
+use turbo_tasks_fs::glob::Glob;
+
+use crate::module::Modules;
+
+/// Configures how [make_chunks](super::chunking::make_chunks) splits the chunk
+/// items of a chunk group into chunks.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct ChunkingConfig {
+    /// Chunks smaller than this (in bytes) are merged with other small chunks
+    /// when possible.
+    pub min_chunk_size: usize,
+    /// Chunks larger than this (in bytes) are split further when possible.
+    pub max_chunk_size: usize,
+    /// The maximum number of chunks per chunk type a chunk group should
+    /// consist of. The smallest chunks are merged until the limit is met.
+    pub max_parallel_requests: Option<usize>,
+    /// Chunk items matching a cache group are placed in chunks of their own,
+    /// named after the group. Only the first matching group applies.
+    pub cache_groups: Vec<CacheGroup>,
+    /// The entries of the chunk groups of the output. Chunk items used by more
+    /// than one of these chunk groups, app code as well as `node_modules`,
+    /// are moved into shared chunks which all of them reference.
+    pub shared_chunk_groups: Vec<Vc<Modules>>,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        ChunkingConfig {
+            min_chunk_size: 100_000,
+            max_chunk_size: 1_000_000,
+            max_parallel_requests: None,
+            cache_groups: Vec::new(),
+            shared_chunk_groups: Vec::new(),
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueDefault for ChunkingConfig {
+    #[turbo_tasks::function]
+    fn value_default() -> Vc<Self> {
+        Self::default().cell()
+    }
+}
+
+/// A named set of chunk items that are kept together in separate chunks, e.g.
+/// to cache rarely changing dependencies independently of the app code.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct CacheGroup {
+    pub name: String,
+    pub test: CacheGroupTest,
+}
+
+impl CacheGroup {
+    /// Creates a cache group matching `node_modules` packages whose name
+    /// matches the `pattern` glob, e.g. `react*` or `@mui/*`.
+    pub fn package(name: String, pattern: &str) -> Result<Self> {
+        Ok(CacheGroup {
+            name,
+            test: CacheGroupTest::Package(Glob::try_from(pattern)?),
+        })
+    }
+
+    /// Creates a cache group matching modules whose path, relative to the
+    /// root of their file system, matches the `pattern` glob.
+    pub fn path(name: String, pattern: &str) -> Result<Self> {
+        Ok(CacheGroup {
+            name,
+            test: CacheGroupTest::Path(Glob::try_from(pattern)?),
+        })
+    }
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub enum CacheGroupTest {
+    /// Matches the name of the `node_modules` package a module belongs to.
+    Package(Glob),
+    /// Matches the path of a module.
+    Path(Glob),
+}
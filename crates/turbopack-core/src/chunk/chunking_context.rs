@@ -1,8 +1,10 @@
 use anyhow::Result;
-use turbo_tasks::{Upcast, Value, ValueToString, Vc};
+use turbo_tasks::{Upcast, Value, ValueDefault, ValueToString, Vc};
 use turbo_tasks_fs::FileSystemPath;
 
-use super::{availability_info::AvailabilityInfo, ChunkableModule, EvaluatableAssets};
+use super::{
+    availability_info::AvailabilityInfo, ChunkableModule, ChunkingConfig, EvaluatableAssets,
+};
 use crate::{
     chunk::{ChunkItem, ModuleId},
     environment::Environment,
@@ -51,6 +53,11 @@ pub trait ChunkingContext {
         Vc::cell(false)
     }
 
+    /// Returns how chunk items are split into chunks.
+    fn chunking_config(self: Vc<Self>) -> Vc<ChunkingConfig> {
+        ChunkingConfig::value_default()
+    }
+
     fn async_loader_chunk_item(
         &self,
         module: Vc<Box<dyn ChunkableModule>>,
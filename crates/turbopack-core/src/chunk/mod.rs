@@ -2,6 +2,7 @@ pub mod availability_info;
 pub mod available_chunk_items;
 pub mod chunk_group;
 pub mod chunking;
+pub(crate) mod chunking_config;
 pub(crate) mod chunking_context;
 pub(crate) mod containment_tree;
 pub(crate) mod data;
@@ -32,6 +33,7 @@ use turbo_tasks_hash::DeterministicHash;
 
 use self::availability_info::AvailabilityInfo;
 pub use self::{
+    chunking_config::{CacheGroup, CacheGroupTest, ChunkingConfig},
     chunking_context::{ChunkingContext, ChunkingContextExt},
     data::{ChunkData, ChunkDataOption, ChunksData},
     evaluate::{EvaluatableAsset, EvaluatableAssetExt, EvaluatableAssets},
@@ -0,0 +1,383 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Result};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turborepo_lockfiles::{
+    BerryLockfile, BerryManifest, Lockfile, NpmLockfile, PnpmLockfile, Yarn1Lockfile,
+};
+
+use super::options::LockedVersions;
+use crate::package_json::read_package_json;
+
+const DEPENDENCY_FIELDS: [&str; 3] = ["dependencies", "devDependencies", "optionalDependencies"];
+const BERRY_METADATA: &[u8] = b"__metadata:";
+
+#[turbo_tasks::value_impl]
+impl LockedVersions {
+    /// Reads the versions locked for the given `workspaces` (directories
+    /// containing a package.json) and all of their transitive dependencies
+    /// from an npm, pnpm or yarn lockfile.
+    #[turbo_tasks::function]
+    pub async fn from_lockfile(
+        lockfile: Vc<FileSystemPath>,
+        workspaces: Vec<Vc<FileSystemPath>>,
+    ) -> Result<Vc<Self>> {
+        let lockfile_dir = lockfile.parent().await?;
+        let lockfile = parse_lockfile(lockfile, lockfile.parent()).await?;
+
+        let mut locked_versions = LockedVersions::default();
+        for workspace in workspaces {
+            let workspace_value = workspace.await?;
+            // The root workspace is the directory of the lockfile itself
+            let workspace_path = if workspace_value == lockfile_dir {
+                Some("")
+            } else {
+                lockfile_dir.get_path_to(&workspace_value)
+            };
+            let Some(workspace_path) = workspace_path else {
+                bail!(
+                    "workspace {} is not in the directory of the lockfile {}",
+                    workspace_value.to_string(),
+                    lockfile_dir.to_string()
+                );
+            };
+            let unresolved_dependencies =
+                package_json_dependencies(workspace.join("package.json".to_string())).await?;
+            let dependencies = resolve_dependencies(
+                &*lockfile,
+                workspace_path,
+                unresolved_dependencies,
+                &mut locked_versions.packages,
+            )?;
+            locked_versions
+                .workspaces
+                .insert(workspace_value.path.clone(), dependencies);
+        }
+
+        Ok(locked_versions.cell())
+    }
+}
+
+/// Parses the lockfile with the parser matching its file name.
+async fn parse_lockfile(
+    lockfile: Vc<FileSystemPath>,
+    root: Vc<FileSystemPath>,
+) -> Result<Box<dyn Lockfile>> {
+    let lockfile_value = lockfile.await?;
+    let FileContent::Content(file) = &*lockfile.read().await? else {
+        bail!("lockfile {} not found", lockfile_value.to_string());
+    };
+    let content = file.content().to_bytes()?;
+    let parsed: Box<dyn Lockfile> = match lockfile_value.file_name() {
+        "package-lock.json" => Box::new(NpmLockfile::load(&content)?),
+        "pnpm-lock.yaml" => Box::new(PnpmLockfile::from_bytes(&content)?),
+        // yarn berry lockfiles are YAML and start with a metadata entry
+        "yarn.lock"
+            if content
+                .windows(BERRY_METADATA.len())
+                .any(|w| w == BERRY_METADATA) =>
+        {
+            let resolutions = match &*read_package_json(root.join("package.json".to_string()))
+                .await?
+            {
+                Some(package_json) => package_json
+                    .get("resolutions")
+                    .and_then(|resolutions| resolutions.as_object())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(name, range)| Some((name.clone(), range.as_str()?.to_string())))
+                    .collect(),
+                None => Vec::new(),
+            };
+            Box::new(BerryLockfile::load(
+                &content,
+                Some(BerryManifest::with_resolutions(resolutions)),
+            )?)
+        }
+        "yarn.lock" => Box::new(Yarn1Lockfile::from_bytes(&content)?),
+        name => bail!("unsupported lockfile {}", name),
+    };
+    Ok(parsed)
+}
+
+/// Returns the dependencies of a package.json mapped to their version ranges.
+async fn package_json_dependencies(
+    package_json_path: Vc<FileSystemPath>,
+) -> Result<HashMap<String, String>> {
+    let package_json = read_package_json(package_json_path).await?;
+    let Some(package_json) = &*package_json else {
+        bail!(
+            "no package.json found at {}",
+            package_json_path.await?.to_string()
+        );
+    };
+    Ok(DEPENDENCY_FIELDS
+        .iter()
+        .filter_map(|field| package_json.get(field)?.as_object())
+        .flatten()
+        .filter_map(|(name, range)| Some((name.clone(), range.as_str()?.to_string())))
+        .collect())
+}
+
+/// Resolves the `unresolved_dependencies` of a workspace to their locked
+/// versions, and records the locked versions of the dependencies of every
+/// package in their transitive closure in `packages`.
+fn resolve_dependencies(
+    lockfile: &dyn Lockfile,
+    workspace_path: &str,
+    unresolved_dependencies: HashMap<String, String>,
+    packages: &mut BTreeMap<String, BTreeMap<String, String>>,
+) -> Result<BTreeMap<String, String>> {
+    let closure = turborepo_lockfiles::named_transitive_closure(
+        lockfile,
+        workspace_path,
+        unresolved_dependencies,
+    )?;
+    let mut dependencies = BTreeMap::new();
+    for package in closure {
+        if package.direct {
+            dependencies.insert(package.name.clone(), package.package.version.clone());
+        }
+        packages
+            .entry(format!("{}@{}", package.name, package.package.version))
+            .or_insert(package.dependencies);
+    }
+
+    Ok(dependencies)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use anyhow::Result;
+    use rstest::*;
+    use turbo_tasks::{TurboTasks, Vc};
+    use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath};
+    use turbo_tasks_memory::MemoryBackend;
+    use turborepo_lockfiles::{BerryLockfile, Lockfile, NpmLockfile, PnpmLockfile, Yarn1Lockfile};
+
+    use super::resolve_dependencies;
+    use crate::resolve::{
+        find_package,
+        options::{LockedVersions, ResolveModules, ResolveModulesOptions},
+    };
+
+    /// Every fixture locks the same dependencies: the `web` workspace depends
+    /// on `a@^1.0.0` and `b@^2.0.0`, and `a` depends on `b@^1.0.0`.
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/lockfile");
+
+    const NPM_LOCKFILE: &str = r#"{
+        "name": "root",
+        "lockfileVersion": 3,
+        "requires": true,
+        "packages": {
+            "": { "name": "root", "dependencies": { "a": "^1.0.0", "b": "^2.0.0" } },
+            "node_modules/a": { "version": "1.0.0", "dependencies": { "b": "^1.0.0" } },
+            "node_modules/a/node_modules/b": { "version": "1.0.0" },
+            "node_modules/b": { "version": "2.1.0" }
+        }
+    }"#;
+
+    fn versions(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_dependencies() {
+        let lockfile = NpmLockfile::load(NPM_LOCKFILE.as_bytes()).unwrap();
+        let mut packages = BTreeMap::new();
+        let dependencies = resolve_dependencies(
+            &lockfile,
+            "",
+            HashMap::from([
+                ("a".to_string(), "^1.0.0".to_string()),
+                ("b".to_string(), "^2.0.0".to_string()),
+            ]),
+            &mut packages,
+        )
+        .unwrap();
+
+        assert_eq!(dependencies, versions(&[("a", "1.0.0"), ("b", "2.1.0")]));
+        assert_eq!(
+            packages,
+            BTreeMap::from([
+                ("a@1.0.0".to_string(), versions(&[("b", "1.0.0")])),
+                ("b@1.0.0".to_string(), versions(&[])),
+                ("b@2.1.0".to_string(), versions(&[])),
+            ])
+        );
+    }
+
+    fn web_dependencies() -> HashMap<String, String> {
+        HashMap::from([
+            ("a".to_string(), "^1.0.0".to_string()),
+            ("b".to_string(), "^2.0.0".to_string()),
+        ])
+    }
+
+    fn locked_packages() -> BTreeMap<String, BTreeMap<String, String>> {
+        BTreeMap::from([
+            ("a@1.0.0".to_string(), versions(&[("b", "1.0.0")])),
+            ("b@1.0.0".to_string(), versions(&[])),
+            ("b@2.1.0".to_string(), versions(&[])),
+        ])
+    }
+
+    fn fixture_lockfile(name: &str) -> Box<dyn Lockfile> {
+        let content = std::fs::read(format!("{FIXTURES}/{name}")).unwrap();
+        match name {
+            "npm/package-lock.json" => Box::new(NpmLockfile::load(&content).unwrap()),
+            "pnpm/pnpm-lock.yaml" => Box::new(PnpmLockfile::from_bytes(&content).unwrap()),
+            "yarn1/yarn.lock" => Box::new(Yarn1Lockfile::from_bytes(&content).unwrap()),
+            "berry/yarn.lock" => Box::new(BerryLockfile::load(&content, None).unwrap()),
+            name => panic!("unknown fixture {name}"),
+        }
+    }
+
+    // pnpm and berry resolve every package through the workspace, including
+    // the transitive `b@1.0.0` that the workspace itself doesn't depend on
+    #[rstest]
+    #[case::npm("npm/package-lock.json")]
+    #[case::pnpm("pnpm/pnpm-lock.yaml")]
+    #[case::yarn1("yarn1/yarn.lock")]
+    #[case::berry("berry/yarn.lock")]
+    fn test_resolve_workspace_dependencies(#[case] name: &str) {
+        let lockfile = fixture_lockfile(name);
+        let mut packages = BTreeMap::new();
+        let dependencies =
+            resolve_dependencies(&*lockfile, "apps/web", web_dependencies(), &mut packages)
+                .unwrap();
+
+        assert_eq!(dependencies, versions(&[("a", "1.0.0"), ("b", "2.1.0")]));
+        assert_eq!(packages, locked_packages());
+    }
+
+    async fn run(test: impl std::future::Future<Output = Result<()>> + Send + 'static) {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(test).await.unwrap();
+    }
+
+    fn fixtures_root() -> Vc<FileSystemPath> {
+        DiskFileSystem::new("fixtures".to_string(), FIXTURES.to_string()).root()
+    }
+
+    #[rstest]
+    #[case::npm("npm", "package-lock.json")]
+    #[case::pnpm("pnpm", "pnpm-lock.yaml")]
+    #[case::yarn1("yarn1", "yarn.lock")]
+    #[case::berry("berry", "yarn.lock")]
+    #[tokio::test]
+    async fn test_from_lockfile(#[case] directory: &'static str, #[case] lockfile: &'static str) {
+        run(async move {
+            let root = fixtures_root().join(directory.to_string());
+            let locked_versions = LockedVersions::from_lockfile(
+                root.join(lockfile.to_string()),
+                vec![root, root.join("apps/web".to_string())],
+            )
+            .await?;
+
+            assert_eq!(
+                locked_versions.workspaces,
+                BTreeMap::from([
+                    (directory.to_string(), versions(&[("c", "3.0.0")])),
+                    (
+                        format!("{directory}/apps/web"),
+                        versions(&[("a", "1.0.0"), ("b", "2.1.0")])
+                    ),
+                ])
+            );
+            let mut packages = locked_packages();
+            packages.insert("c@3.0.0".to_string(), versions(&[]));
+            assert_eq!(locked_versions.packages, packages);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_find_package_in_registry() {
+        run(async {
+            let fixtures = fixtures_root();
+            let root = fixtures.join("npm".to_string());
+            let registry = fixtures.join("registry".to_string());
+            let locked_versions = LockedVersions::from_lockfile(
+                root.join("package-lock.json".to_string()),
+                vec![root, root.join("apps/web".to_string())],
+            );
+            let options = ResolveModulesOptions {
+                modules: vec![ResolveModules::Registry(registry, locked_versions)],
+            }
+            .cell();
+
+            let find = |lookup_path: Vc<FileSystemPath>, package_name: &str| {
+                let package_name = package_name.to_string();
+                async move {
+                    let result = find_package(lookup_path, package_name, options).await?;
+                    let mut packages = Vec::new();
+                    for package in &result.packages {
+                        packages.push(package.await?.path.clone());
+                    }
+                    anyhow::Ok(packages)
+                }
+            };
+
+            // A workspace gets its own locked version
+            assert_eq!(
+                find(root.join("apps/web/src".to_string()), "b").await?,
+                vec!["registry/b/2.1.0".to_string()]
+            );
+            // A package in the registry gets the version locked for it
+            assert_eq!(
+                find(registry.join("a/1.0.0/lib".to_string()), "b").await?,
+                vec!["registry/b/1.0.0".to_string()]
+            );
+            // Packages that aren't locked, or missing from the registry, aren't
+            // found
+            assert!(find(root.join("apps/web".to_string()), "d")
+                .await?
+                .is_empty());
+            assert!(find(root, "c").await?.is_empty());
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_locked_versions_lookup() {
+        let locked_versions = LockedVersions {
+            workspaces: BTreeMap::from([
+                ("repo".to_string(), versions(&[("a", "1.0.0")])),
+                ("repo/apps/web".to_string(), versions(&[("a", "2.0.0")])),
+            ]),
+            packages: BTreeMap::from([("@scope/a@2.0.0".to_string(), versions(&[("b", "3.0.0")]))]),
+        };
+
+        assert_eq!(
+            locked_versions.workspace_dependency("repo/src", "a"),
+            Some("1.0.0")
+        );
+        assert_eq!(
+            locked_versions.workspace_dependency("repo/apps/web/src", "a"),
+            Some("2.0.0")
+        );
+        assert_eq!(
+            locked_versions.workspace_dependency("repo/apps/website", "a"),
+            Some("1.0.0")
+        );
+        assert_eq!(locked_versions.workspace_dependency("other", "a"), None);
+        assert_eq!(
+            locked_versions.package_dependency("@scope/a/2.0.0/lib/index.js", "b"),
+            Some("3.0.0")
+        );
+        assert_eq!(
+            locked_versions.package_dependency("@scope/a/1.0.0/index.js", "b"),
+            None
+        );
+    }
+}
@@ -43,6 +43,7 @@ use crate::{
 };
 
 mod alias_map;
+pub mod lockfile;
 pub mod node;
 pub mod options;
 pub mod origin;
@@ -908,7 +909,28 @@ async fn find_package(
                     packages.push(package_dir.resolve().await?);
                 }
             }
-            ResolveModules::Registry(_, _) => todo!(),
+            ResolveModules::Registry(registry, locked_versions) => {
+                let registry_value = registry.await?;
+                let lookup_path_value = lookup_path.await?;
+                let locked_versions = locked_versions.await?;
+                // Packages in the registry depend on the versions locked for
+                // them, everything else on the versions locked for its
+                // workspace
+                let version =
+                    if let Some(registry_path) = registry_value.get_path_to(&lookup_path_value) {
+                        locked_versions.package_dependency(registry_path, &package_name)
+                    } else {
+                        locked_versions.workspace_dependency(&lookup_path_value.path, &package_name)
+                    };
+                if let Some(version) = version {
+                    let package_dir = registry.join(format!("{package_name}/{version}"));
+                    if let Some(package_dir) =
+                        dir_exists(package_dir, &mut affecting_sources).await?
+                    {
+                        packages.push(package_dir);
+                    }
+                }
+            }
         }
     }
     Ok(FindPackageResult::cell(FindPackageResult {
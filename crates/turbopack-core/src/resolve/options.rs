@@ -13,9 +13,54 @@ use super::{
 };
 use crate::resolve::{parse::Request, plugin::ResolvePlugin};
 
+/// The versions of packages pinned by a lockfile, as used by
+/// [ResolveModules::Registry]. Usually created with
+/// [LockedVersions::from_lockfile].
 #[turbo_tasks::value(shared)]
-#[derive(Hash, Debug)]
-pub struct LockedVersions {}
+#[derive(Hash, Debug, Default)]
+pub struct LockedVersions {
+    /// Versions of the direct dependencies of each workspace, keyed by the
+    /// path of the workspace directory.
+    pub workspaces: BTreeMap<String, BTreeMap<String, String>>,
+    /// Versions of the dependencies of each locked package, keyed by
+    /// `<name>@<version>`.
+    pub packages: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl LockedVersions {
+    /// Returns the locked version of the `package_name` dependency of the
+    /// workspace containing `lookup_path`.
+    pub fn workspace_dependency(&self, lookup_path: &str, package_name: &str) -> Option<&str> {
+        self.workspaces
+            .iter()
+            .filter(|(workspace, _)| {
+                workspace.is_empty()
+                    || lookup_path == workspace.as_str()
+                    || lookup_path
+                        .strip_prefix(workspace.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|(workspace, _)| workspace.len())
+            .and_then(|(_, dependencies)| dependencies.get(package_name))
+            .map(|version| version.as_str())
+    }
+
+    /// Returns the locked version of the `package_name` dependency of the
+    /// package at `registry_path`, a path inside of the registry in the
+    /// `@scope/module/version/<path-in-package>` form.
+    pub fn package_dependency(&self, registry_path: &str, package_name: &str) -> Option<&str> {
+        let mut segments = registry_path.split('/');
+        let name = match segments.next()? {
+            scope if scope.starts_with('@') => format!("{}/{}", scope, segments.next()?),
+            name => name.to_string(),
+        };
+        let version = segments.next()?;
+        self.packages
+            .get(&format!("{name}@{version}"))?
+            .get(package_name)
+            .map(|version| version.as_str())
+    }
+}
 
 /// A location where to resolve modules.
 #[derive(
@@ -0,0 +1 @@
+module.exports = "a@1.0.0";
@@ -0,0 +1 @@
+module.exports = "b@2.1.0";
@@ -34,7 +34,7 @@ use turbopack_core::{
     reference_type::ImportContext,
     resolve::origin::ResolveOrigin,
     source::Source,
-    source_map::{GenerateSourceMap, OptionSourceMap},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMap},
 };
 use turbopack_swc_utils::emitter::IssueEmitter;
 
@@ -260,6 +260,10 @@ pub enum ParseCssResult {
 
         #[turbo_tasks(trace_ignore)]
         options: ParserOptions<'static, 'static>,
+
+        /// The source map of the source itself, when it was generated from
+        /// another language such as Sass
+        original_source_map: Option<Vc<SourceMap>>,
     },
     Unparseable,
     NotFound,
@@ -372,12 +376,13 @@ pub async fn finalize_css(
             url_references,
             ..
         } => {
-            let mut stylesheet = match &*parse_result.await? {
+            let (mut stylesheet, original_source_map) = match &*parse_result.await? {
                 ParseCssResult::Ok {
                     stylesheet,
                     options,
+                    original_source_map,
                     ..
-                } => stylesheet.to_static(options.clone()),
+                } => (stylesheet.to_static(options.clone()), *original_source_map),
                 ParseCssResult::Unparseable => return Ok(FinalCssResult::Unparseable.into()),
                 ParseCssResult::NotFound => return Ok(FinalCssResult::NotFound.into()),
             };
@@ -400,7 +405,10 @@ pub async fn finalize_css(
             Ok(FinalCssResult::Ok {
                 output_code: result.code,
                 exports: result.exports,
-                source_map: srcmap.unwrap().cell(),
+                source_map: srcmap
+                    .unwrap()
+                    .with_original_source_map(original_source_map)
+                    .cell(),
             }
             .into())
         }
@@ -438,6 +446,11 @@ pub async fn parse_css(
     };
     async move {
         let content = source.content();
+        let original_source_map =
+            match Vc::try_resolve_sidecast::<Box<dyn GenerateSourceMap>>(source).await? {
+                Some(generate) => *generate.generate_source_map().await?,
+                None => None,
+            };
         let fs_path = &*source.ident().path().await?;
         let ident_str = &*source.ident().to_string().await?;
         Ok(match &*content.await? {
@@ -456,6 +469,7 @@ pub async fn parse_css(
                             import_context,
                             ty,
                             use_lightningcss,
+                            original_source_map,
                         )
                         .await?
                     }
@@ -476,6 +490,7 @@ async fn process_content(
     import_context: Vc<ImportContext>,
     ty: CssModuleAssetType,
     use_lightningcss: bool,
+    original_source_map: Option<Vc<SourceMap>>,
 ) -> Result<Vc<ParseCssResult>> {
     #[allow(clippy::needless_lifetimes)]
     fn without_warnings<'o, 'i>(config: ParserOptions<'o, 'i>) -> ParserOptions<'o, 'static> {
@@ -596,6 +611,7 @@ async fn process_content(
         references: Vc::cell(references),
         url_references: Vc::cell(url_references),
         options: config,
+        original_source_map,
     }
     .into())
 }
@@ -615,6 +631,10 @@ pub enum ParseCssResultSourceMap {
         /// (SWC) SourceMap.
         #[turbo_tasks(debug_ignore, trace_ignore)]
         mappings: Vec<(BytePos, LineCol)>,
+
+        /// The source map of the parsed source, which the generated map is
+        /// traced back through.
+        original_source_map: Option<Vc<SourceMap>>,
     },
 }
 
@@ -636,6 +656,24 @@ impl ParseCssResultSourceMap {
         ParseCssResultSourceMap::Swc {
             source_map,
             mappings,
+            original_source_map: None,
+        }
+    }
+
+    /// Traces the generated map back through the source map of the parsed
+    /// source. Only supported for SWC, lightningcss maps are left as is.
+    pub fn with_original_source_map(self, original_source_map: Option<Vc<SourceMap>>) -> Self {
+        match self {
+            ParseCssResultSourceMap::Swc {
+                source_map,
+                mappings,
+                ..
+            } => ParseCssResultSourceMap::Swc {
+                source_map,
+                mappings,
+                original_source_map,
+            },
+            parcel => parcel,
         }
     }
 }
@@ -643,8 +681,8 @@ impl ParseCssResultSourceMap {
 #[turbo_tasks::value_impl]
 impl GenerateSourceMap for ParseCssResultSourceMap {
     #[turbo_tasks::function]
-    fn generate_source_map(&self) -> Vc<OptionSourceMap> {
-        match self {
+    async fn generate_source_map(&self) -> Result<Vc<OptionSourceMap>> {
+        Ok(match self {
             ParseCssResultSourceMap::Parcel { source_map } => {
                 let mut builder = SourceMapBuilder::new(None);
 
@@ -675,17 +713,25 @@ impl GenerateSourceMap for ParseCssResultSourceMap {
             ParseCssResultSourceMap::Swc {
                 source_map,
                 mappings,
+                original_source_map,
             } => {
+                let original_source_map = match original_source_map {
+                    Some(original) => Some(original.await?.to_source_map().await?),
+                    None => None,
+                };
+                let input_map = original_source_map
+                    .as_ref()
+                    .and_then(|map| map.as_regular_source_map());
                 let map = source_map.build_source_map_with_config(
                     mappings,
-                    None,
+                    input_map.as_deref(),
                     InlineSourcesContentConfig {},
                 );
                 Vc::cell(Some(
                     turbopack_core::source_map::SourceMap::new_regular(map).cell(),
                 ))
             }
-        }
+        })
     }
 }
 
@@ -0,0 +1,723 @@
+//! Compilation of Sass stylesheets to CSS.
+//!
+//! `@use`, `@forward` and `@import` requests are resolved with turbopack's
+//! resolver before compiling, so every stylesheet that is loaded is read
+//! through turbo-tasks and invalidates the compilation when it changes. The
+//! compiled CSS is then handed to the regular CSS rules (PostCSS, CSS
+//! modules) under a renamed ident in the same directory, along with a source
+//! map back to the stylesheets it was compiled from.
+
+use std::{
+    collections::HashMap,
+    io,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+    sync::OnceLock,
+};
+
+use anyhow::{bail, Result};
+use regex::Regex;
+use swc_core::base::sourcemap::SourceMapBuilder;
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    issue::{
+        Issue, IssueDescriptionExt, IssueExt, IssueSeverity, OptionStyledString, StyledString,
+    },
+    reference_type::{CssReferenceSubType, ReferenceType},
+    resolve::{
+        options::{
+            ConditionValue, ResolutionConditions, ResolveIntoPackage, ResolveModules,
+            ResolveOptions,
+        },
+        parse::Request,
+        resolve,
+    },
+    source::Source,
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMap},
+    source_transform::SourceTransform,
+    SOURCE_MAP_ROOT_NAME,
+};
+
+/// Resolve options for the stylesheets loaded by Sass. Packages are entered
+/// through their `sass` and `style` conditions and fields.
+#[turbo_tasks::function]
+pub fn sass_resolve_options(root: Vc<FileSystemPath>) -> Vc<ResolveOptions> {
+    let conditions: ResolutionConditions = [
+        ("sass".to_string(), ConditionValue::Set),
+        ("style".to_string(), ConditionValue::Set),
+    ]
+    .into();
+    ResolveOptions {
+        extensions: vec![".scss".to_string(), ".sass".to_string(), ".css".to_string()],
+        modules: vec![ResolveModules::Nested(
+            root,
+            vec!["node_modules".to_string()],
+        )],
+        into_package: vec![
+            ResolveIntoPackage::ExportsField {
+                conditions,
+                unspecified_conditions: ConditionValue::Unset,
+            },
+            ResolveIntoPackage::MainField {
+                field: "sass".to_string(),
+                extensions: None,
+            },
+            ResolveIntoPackage::MainField {
+                field: "style".to_string(),
+                extensions: None,
+            },
+            ResolveIntoPackage::Default("_index".to_string()),
+            ResolveIntoPackage::Default("index".to_string()),
+        ],
+        ..Default::default()
+    }
+    .cell()
+}
+
+#[turbo_tasks::value]
+pub struct SassTransform {
+    resolve_options: Vc<ResolveOptions>,
+}
+
+#[turbo_tasks::value_impl]
+impl SassTransform {
+    #[turbo_tasks::function]
+    pub fn new(resolve_options: Vc<ResolveOptions>) -> Vc<Self> {
+        SassTransform { resolve_options }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for SassTransform {
+    #[turbo_tasks::function]
+    fn transform(&self, source: Vc<Box<dyn Source>>) -> Vc<Box<dyn Source>> {
+        Vc::upcast(
+            SassTransformedAsset {
+                resolve_options: self.resolve_options,
+                source,
+            }
+            .cell(),
+        )
+    }
+}
+
+#[turbo_tasks::value]
+struct SassTransformedAsset {
+    resolve_options: Vc<ResolveOptions>,
+    source: Vc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for SassTransformedAsset {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<Vc<AssetIdent>> {
+        let path = self.source.ident().path().await?;
+        // `style.module.scss` becomes `style.module.scss.module.css` so that it
+        // is still treated as a CSS module.
+        let pattern = if path.path.ends_with(".module.scss") || path.path.ends_with(".module.sass")
+        {
+            "*.module.css"
+        } else {
+            "*.css"
+        };
+        Ok(self.source.ident().rename_as(pattern.to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for SassTransformedAsset {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let this = self.await?;
+        Ok(self
+            .compile()
+            .issue_file_path(this.source.ident().path(), "Sass compilation")
+            .await?
+            .await?
+            .content)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for SassTransformedAsset {
+    #[turbo_tasks::function]
+    async fn generate_source_map(self: Vc<Self>) -> Result<Vc<OptionSourceMap>> {
+        Ok(Vc::cell(self.compile().await?.source_map))
+    }
+}
+
+#[turbo_tasks::value]
+struct SassCompileResult {
+    content: Vc<AssetContent>,
+    source_map: Option<Vc<SourceMap>>,
+}
+
+impl SassCompileResult {
+    fn not_found() -> Vc<Self> {
+        SassCompileResult {
+            content: AssetContent::File(FileContent::NotFound.cell()).cell(),
+            source_map: None,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SassTransformedAsset {
+    #[turbo_tasks::function]
+    async fn compile(self: Vc<Self>) -> Result<Vc<SassCompileResult>> {
+        let this = self.await?;
+        let path = this.source.ident().path();
+        let AssetContent::File(file) = *this.source.content().await? else {
+            bail!("Sass transform only supports transforming files");
+        };
+        let entry_source = match &*file.await? {
+            FileContent::Content(entry_file) => entry_file.content().to_str()?.into_owned(),
+            FileContent::NotFound => return Ok(SassCompileResult::not_found()),
+        };
+
+        // Collect the entry and every stylesheet it loads, keyed by the absolute
+        // paths that imports have been rewritten to, along with their original
+        // sources for the source map.
+        let entry = sass_path(&*path.await?);
+        let ident = this.source.ident().to_string().await?;
+        let mut originals = vec![(format!("/{SOURCE_MAP_ROOT_NAME}/{ident}"), entry_source)];
+        let mut stylesheets = HashMap::new();
+        let mut queue = vec![(path, file)];
+        while let Some((path, content)) = queue.pop() {
+            let key = sass_path(&*path.await?);
+            if stylesheets.contains_key(&key) {
+                continue;
+            }
+            let stylesheet = sass_stylesheet(path, content, this.resolve_options).await?;
+            queue.extend(
+                stylesheet
+                    .imports
+                    .iter()
+                    .map(|&import| (import, import.read())),
+            );
+            if key != entry {
+                if let FileContent::Content(file) = &*content.await? {
+                    originals.push((
+                        format!("/{SOURCE_MAP_ROOT_NAME}/{}", path.to_string().await?),
+                        file.content().to_str()?.into_owned(),
+                    ));
+                }
+            }
+            stylesheets.insert(key, stylesheet.source.clone());
+        }
+
+        let fs = SassFs(stylesheets);
+        let options = grass::Options::default().fs(&fs).quiet(true);
+        match grass::from_path(&entry, &options) {
+            Ok(css) => {
+                let source_map = exact_source_map(&originals, &css);
+                Ok(SassCompileResult {
+                    content: AssetContent::File(FileContent::Content(File::from(css)).cell())
+                        .cell(),
+                    source_map: Some(SourceMap::new_regular(source_map).cell()),
+                }
+                .cell())
+            }
+            Err(err) => {
+                SassTransformIssue {
+                    source: path,
+                    description: err.to_string(),
+                }
+                .cell()
+                .emit();
+                Ok(SassCompileResult::not_found())
+            }
+        }
+    }
+}
+
+/// grass doesn't generate source maps, so the compiled CSS is mapped back to
+/// the stylesheets it was compiled from line by line. Only lines which were
+/// copied verbatim from exactly one line of the loaded stylesheets are mapped.
+/// Everything Sass computes, e.g. flattened nested selectors or declarations
+/// using variables, is left unmapped rather than mapped to a guess.
+fn exact_source_map(
+    sources: &[(String, String)],
+    css: &str,
+) -> swc_core::base::sourcemap::SourceMap {
+    let mut builder = SourceMapBuilder::new(None);
+    let source_ids = sources
+        .iter()
+        .map(|(name, content)| {
+            let source_id = builder.add_source(name);
+            builder.set_source_contents(source_id, Some(content));
+            source_id
+        })
+        .collect::<Vec<_>>();
+    let contents = sources
+        .iter()
+        .map(|(_, content)| content.as_str())
+        .collect::<Vec<_>>();
+    for (generated_line, source, original_line) in line_mappings(&contents, css) {
+        builder.add_raw(
+            generated_line,
+            0,
+            original_line,
+            0,
+            Some(source_ids[source]),
+            None,
+        );
+    }
+    builder.into_sourcemap()
+}
+
+/// Generated lines with the index of the source and the line they were copied
+/// from, all zero based.
+fn line_mappings(sources: &[&str], css: &str) -> Vec<(u32, usize, u32)> {
+    // `None` marks lines which appear more than once, so can't be attributed
+    let mut lines = HashMap::new();
+    for (source, content) in sources.iter().enumerate() {
+        for (original_line, line) in content.lines().enumerate() {
+            lines
+                .entry(line.trim())
+                .and_modify(|location| *location = None)
+                .or_insert(Some((source, original_line as u32)));
+        }
+    }
+    css.lines()
+        .enumerate()
+        .filter_map(|(generated_line, line)| {
+            let line = line.trim();
+            // Closing braces and blank lines carry nothing to attribute
+            if line.is_empty() || line == "}" {
+                return None;
+            }
+            let (source, original_line) = (*lines.get(line)?)?;
+            Some((generated_line as u32, source, original_line))
+        })
+        .collect()
+}
+
+/// A stylesheet with its imports rewritten to the absolute paths of the
+/// stylesheets they resolved to.
+#[turbo_tasks::value]
+struct SassStylesheet {
+    source: String,
+    imports: Vec<Vc<FileSystemPath>>,
+}
+
+#[turbo_tasks::function]
+async fn sass_stylesheet(
+    path: Vc<FileSystemPath>,
+    content: Vc<FileContent>,
+    resolve_options: Vc<ResolveOptions>,
+) -> Result<Vc<SassStylesheet>> {
+    let FileContent::Content(file) = &*content.await? else {
+        bail!("Unable to read {}", path.await?.path);
+    };
+    let content = file.content().to_str()?;
+    let lookup_path = path.parent();
+
+    let mut source = String::with_capacity(content.len());
+    let mut imports = Vec::new();
+    let mut last = 0;
+    for import in find_imports(&content) {
+        // Unresolved imports are left for the compiler to report
+        let Some(resolved) = resolve_import(lookup_path, &import.url, resolve_options).await?
+        else {
+            continue;
+        };
+        source.push_str(&content[last..import.range.start]);
+        source.push_str(&format!("\"/{}\"", resolved.await?.path));
+        if let Some(namespace) = import.namespace {
+            source.push_str(&format!(" as {namespace}"));
+        }
+        last = import.range.end;
+        imports.push(resolved);
+    }
+    source.push_str(&content[last..]);
+
+    Ok(SassStylesheet { source, imports }.cell())
+}
+
+async fn resolve_import(
+    lookup_path: Vc<FileSystemPath>,
+    url: &str,
+    resolve_options: Vc<ResolveOptions>,
+) -> Result<Option<Vc<FileSystemPath>>> {
+    // Requests are relative to the stylesheet first, unless they are marked
+    // as packages with a webpack-style `~`.
+    let requests = if let Some(package) = url.strip_prefix('~') {
+        candidates(package)
+    } else if url.starts_with("./") || url.starts_with("../") {
+        candidates(url)
+    } else {
+        let candidates = candidates(url);
+        candidates
+            .iter()
+            .map(|candidate| format!("./{candidate}"))
+            .chain(candidates.iter().cloned())
+            .collect()
+    };
+
+    for request in requests {
+        let result = resolve(
+            lookup_path,
+            Value::new(ReferenceType::Css(CssReferenceSubType::AtImport(None))),
+            Request::parse(Value::new(request.into())),
+            resolve_options,
+        );
+        if let Some(source) = *result.first_source().await? {
+            return Ok(Some(source.ident().path()));
+        }
+    }
+    Ok(None)
+}
+
+// The requests Sass tries for a URL, in order: the file itself, its partial,
+// and the index files of a directory.
+fn candidates(url: &str) -> Vec<String> {
+    let partial = match url.rsplit_once('/') {
+        Some((dir, name)) => format!("{dir}/_{name}"),
+        None => format!("_{url}"),
+    };
+    let mut candidates = vec![url.to_string(), partial];
+    let name = url.rsplit('/').next().unwrap_or(url);
+    if !name.contains('.') {
+        candidates.push(format!("{url}/index"));
+        candidates.push(format!("{url}/_index"));
+    }
+    candidates
+}
+
+#[derive(Debug)]
+struct Import {
+    url: String,
+    /// The quoted URL in the stylesheet
+    range: Range<usize>,
+    /// The namespace of a `@use` without `as`, which would otherwise be
+    /// derived from the rewritten URL
+    namespace: Option<String>,
+}
+
+/// Blanks out the comments of a stylesheet while keeping every other byte at
+/// its offset, so that rules which are commented out aren't taken as imports.
+fn strip_comments(content: &str) -> String {
+    let bytes = content.as_bytes();
+    let mut stripped = bytes.to_vec();
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let comment_end = match (quote, bytes[i]) {
+            (Some(_), b'\\') => {
+                i += 2;
+                continue;
+            }
+            (Some(q), byte) if byte == q => {
+                quote = None;
+                None
+            }
+            (Some(_), _) => None,
+            (None, byte @ (b'"' | b'\'')) => {
+                quote = Some(byte);
+                None
+            }
+            // An unquoted url may contain `//`
+            (None, b'(') if content[..i].ends_with("url") => {
+                i = content[i..].find(')').map_or(bytes.len(), |end| i + end);
+                continue;
+            }
+            (None, b'/') if bytes.get(i + 1) == Some(&b'/') => {
+                Some(content[i..].find('\n').map_or(bytes.len(), |end| i + end))
+            }
+            (None, b'/') if bytes.get(i + 1) == Some(&b'*') => Some(
+                content[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 2),
+            ),
+            (None, _) => None,
+        };
+        match comment_end {
+            Some(end) => {
+                for byte in &mut stripped[i..end] {
+                    if *byte != b'\n' {
+                        *byte = b' ';
+                    }
+                }
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    // Only whole comments, which start and end at ascii characters, were
+    // replaced
+    String::from_utf8(stripped).expect("stripped stylesheet is valid utf-8")
+}
+
+fn find_imports(content: &str) -> Vec<Import> {
+    static USE_RE: OnceLock<Regex> = OnceLock::new();
+    static IMPORT_RE: OnceLock<Regex> = OnceLock::new();
+    static QUOTED_RE: OnceLock<Regex> = OnceLock::new();
+    let use_re = USE_RE.get_or_init(|| {
+        Regex::new(concat!(
+            r"@(?P<rule>use|forward)\s+",
+            r#"(?P<quoted>"(?P<dq>[^"]*)"|'(?P<sq>[^']*)')(?P<as>\s+as\b)?"#
+        ))
+        .expect("regex is valid")
+    });
+    let import_re = IMPORT_RE
+        .get_or_init(|| Regex::new(r"@import\s+(?P<urls>[^;\n]+)").expect("regex is valid"));
+    let quoted_re = QUOTED_RE
+        .get_or_init(|| Regex::new(r#""(?P<dq>[^"]*)"|'(?P<sq>[^']*)'"#).expect("regex is valid"));
+
+    let content = &strip_comments(content);
+    let mut imports = Vec::new();
+    for captures in use_re.captures_iter(content) {
+        let url = captures
+            .name("dq")
+            .or_else(|| captures.name("sq"))
+            .map_or("", |url| url.as_str());
+        if url.starts_with("sass:") || url.contains("#{") {
+            continue;
+        }
+        let namespace =
+            (&captures["rule"] == "use" && captures.name("as").is_none()).then(|| namespace(url));
+        imports.push(Import {
+            url: url.to_string(),
+            range: captures.name("quoted").expect("quoted url").range(),
+            namespace,
+        });
+    }
+    for captures in import_re.captures_iter(content) {
+        let urls = captures.name("urls").expect("urls");
+        // Imports of plain CSS are left to the CSS pipeline
+        if urls.as_str().contains("url(") {
+            continue;
+        }
+        for quoted in quoted_re.captures_iter(urls.as_str()) {
+            let url = quoted
+                .name("dq")
+                .or_else(|| quoted.name("sq"))
+                .map_or("", |url| url.as_str());
+            if url.ends_with(".css")
+                || url.starts_with("http://")
+                || url.starts_with("https://")
+                || url.starts_with("//")
+                || url.contains("#{")
+            {
+                continue;
+            }
+            let range = quoted.get(0).expect("match").range();
+            imports.push(Import {
+                url: url.to_string(),
+                range: urls.start() + range.start..urls.start() + range.end,
+                namespace: None,
+            });
+        }
+    }
+    imports.sort_by_key(|import| import.range.start);
+    imports
+}
+
+// The default namespace of `@use "url"`: the last path segment without
+// extension or partial underscore.
+fn namespace(url: &str) -> String {
+    let name = url.rsplit('/').next().unwrap_or(url);
+    let name = name.split('.').next().unwrap_or(name);
+    name.strip_prefix('_').unwrap_or(name).to_string()
+}
+
+fn sass_path(path: &FileSystemPath) -> PathBuf {
+    Path::new("/").join(&path.path)
+}
+
+/// The stylesheets available to the compiler, which doesn't touch the disk.
+#[derive(Debug)]
+struct SassFs(HashMap<PathBuf, String>);
+
+impl SassFs {
+    fn normalize(path: &Path) -> PathBuf {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                component => normalized.push(component),
+            }
+        }
+        normalized
+    }
+}
+
+impl grass::Fs for SassFs {
+    fn is_dir(&self, path: &Path) -> bool {
+        let path = Self::normalize(path);
+        self.0
+            .keys()
+            .any(|file| file != &path && file.starts_with(&path))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.0.contains_key(&Self::normalize(path))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.0
+            .get(&Self::normalize(path))
+            .map(|source| source.clone().into_bytes())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} could not be resolved", path.display()),
+                )
+            })
+    }
+}
+
+#[turbo_tasks::value]
+struct SassTransformIssue {
+    source: Vc<FileSystemPath>,
+    description: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for SassTransformIssue {
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.source
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Sass compilation failed".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(self.description.to_string()).cell(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("transform".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(content: &str) -> Vec<(&str, Option<String>)> {
+        find_imports(content)
+            .into_iter()
+            .map(|import| (&content[import.range], import.namespace))
+            .collect()
+    }
+
+    #[test]
+    fn finds_use_and_forward() {
+        assert_eq!(
+            urls(
+                "@use \"sass:math\";\n@use 'theme/colors';\n@use \"mixins\" as m;\n@forward \
+                 \"tokens\" show $gap;"
+            ),
+            vec![
+                ("'theme/colors'", Some("colors".to_string())),
+                ("\"mixins\"", None),
+                ("\"tokens\"", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_imports_but_not_plain_css() {
+        assert_eq!(
+            urls(
+                "@import \"a\", 'b/_c.scss';\n@import \"plain.css\";\n@import \
+                 url(\"x.scss\");\n@import \"https://example.com/font\";"
+            ),
+            vec![("\"a\"", None), ("'b/_c.scss'", None)]
+        );
+    }
+
+    #[test]
+    fn lists_candidates() {
+        assert_eq!(
+            candidates("theme/colors"),
+            vec![
+                "theme/colors",
+                "theme/_colors",
+                "theme/colors/index",
+                "theme/colors/_index"
+            ]
+        );
+        assert_eq!(
+            candidates("colors.scss"),
+            vec!["colors.scss", "_colors.scss"]
+        );
+    }
+
+    #[test]
+    fn derives_namespace() {
+        assert_eq!(namespace("@scope/pkg/_theme.scss"), "theme");
+        assert_eq!(namespace("colors"), "colors");
+    }
+
+    #[test]
+    fn ignores_commented_out_imports() {
+        assert_eq!(
+            urls(
+                "// @use \"a\";\n/* @import \"b\";\n@use \"c\"; */\n@use \"d\"; // @use \
+                 \"e\";\n@import \"//cdn/f\", \"g\";\n.x { background: url(//cdn/h.png); }\n@use \
+                 \"i\";"
+            ),
+            vec![
+                ("\"d\"", Some("d".to_string())),
+                ("\"g\"", None),
+                ("\"i\"", Some("i".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn maps_only_verbatim_lines() {
+        let source = "@use \"theme\";\n\n.card {\n  color: theme.$fg;\n\n  .title {\n    margin: \
+                      0;\n  }\n}\n";
+        let css = ".card {\n  color: red;\n}\n.card .title {\n  margin: 0;\n}";
+        assert_eq!(line_mappings(&[source], css), vec![(0, 0, 2), (4, 0, 6)]);
+    }
+
+    #[test]
+    fn maps_lines_from_mixins_in_other_stylesheets() {
+        let entry = ".a {\n  @include theme.box;\n}\n";
+        let theme = "@mixin box {\n  padding: 4px;\n}\n";
+        let css = ".a {\n  padding: 4px;\n}";
+        assert_eq!(
+            line_mappings(&[entry, theme], css),
+            vec![(0, 0, 0), (1, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn leaves_ambiguous_lines_unmapped() {
+        let source = ".a {\n  margin: 0;\n}\n.b {\n  margin: 0;\n}\n";
+        let css = ".a {\n  margin: 0;\n}\n.b {\n  margin: 0;\n}";
+        assert_eq!(line_mappings(&[source], css), vec![(0, 0, 0), (3, 0, 3)]);
+    }
+
+    #[test]
+    fn normalizes_paths() {
+        assert_eq!(
+            SassFs::normalize(Path::new("/src/./styles/../_a.scss")),
+            PathBuf::from("/src/_a.scss")
+        );
+    }
+}
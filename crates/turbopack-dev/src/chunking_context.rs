@@ -6,7 +6,8 @@ use turbopack_core::{
     chunk::{
         availability_info::AvailabilityInfo,
         chunk_group::{make_chunk_group, MakeChunkGroupResult},
-        Chunk, ChunkItem, ChunkableModule, ChunkingContext, EvaluatableAssets, ModuleId,
+        Chunk, ChunkItem, ChunkableModule, ChunkingConfig, ChunkingContext, EvaluatableAssets,
+        ModuleId,
     },
     environment::Environment,
     ident::AssetIdent,
@@ -60,6 +61,11 @@ impl DevChunkingContextBuilder {
         self
     }
 
+    pub fn chunking_config(mut self, chunking_config: Vc<ChunkingConfig>) -> Self {
+        self.chunking_context.chunking_config = chunking_config;
+        self
+    }
+
     pub fn build(self) -> Vc<DevChunkingContext> {
         DevChunkingContext::new(Value::new(self.chunking_context))
     }
@@ -98,6 +104,8 @@ pub struct DevChunkingContext {
     environment: Vc<Environment>,
     /// The kind of runtime to include in the output.
     runtime_type: RuntimeType,
+    /// How chunk items are split into chunks.
+    chunking_config: Vc<ChunkingConfig>,
 }
 
 impl DevChunkingContext {
@@ -121,6 +129,7 @@ impl DevChunkingContext {
                 enable_hot_module_replacement: false,
                 environment,
                 runtime_type: Default::default(),
+                chunking_config: Default::default(),
             },
         }
     }
@@ -311,6 +320,11 @@ impl ChunkingContext for DevChunkingContext {
         Vc::cell(self.enable_hot_module_replacement)
     }
 
+    #[turbo_tasks::function]
+    fn chunking_config(&self) -> Vc<ChunkingConfig> {
+        self.chunking_config
+    }
+
     #[turbo_tasks::function]
     async fn chunk_group(
         self: Vc<Self>,
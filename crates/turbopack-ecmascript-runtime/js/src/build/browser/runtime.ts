@@ -0,0 +1,357 @@
+/**
+ * This file contains the runtime code for production ECMAScript builds that
+ * run in the browser.
+ *
+ * Unlike the development DOM runtime, it doesn't support HMR: chunks are only
+ * ever loaded once, and module factories are never replaced.
+ */
+
+/* eslint-disable @next/next/no-assign-module-variable */
+
+/// <reference path="../../shared/runtime-utils.ts" />
+
+declare var CHUNK_BASE_PATH: string;
+
+type BuildRuntimeParams = {
+  otherChunks: ChunkData[];
+  runtimeModuleIds: ModuleId[];
+};
+
+type ChunkRegistration = [
+  chunkPath: ChunkPath,
+  chunkModules: ModuleFactories,
+  params: BuildRuntimeParams | undefined
+];
+
+type ChunkRegistry = {
+  push: (registration: ChunkRegistration) => void;
+};
+
+declare var TURBOPACK: ChunkRegistry | ChunkRegistration[] | undefined;
+
+type ModuleFactory = (
+  this: Module["exports"],
+  context: TurbopackBaseContext
+) => undefined;
+
+enum SourceType {
+  /**
+   * The module was instantiated because it was included in an evaluated chunk's
+   * runtime.
+   */
+  Runtime = 0,
+  /**
+   * The module was instantiated because a parent module imported it.
+   */
+  Parent = 1,
+}
+
+type SourceInfo =
+  | {
+      type: SourceType.Runtime;
+      chunkPath: ChunkPath;
+    }
+  | {
+      type: SourceType.Parent;
+      parentId: ModuleId;
+    };
+
+type ChunkResolver = {
+  resolved: boolean;
+  resolve: () => void;
+  reject: (error?: Error) => void;
+  promise: Promise<void>;
+};
+
+const moduleFactories: ModuleFactories = Object.create(null);
+const moduleCache: ModuleCache = Object.create(null);
+
+/**
+ * Maps chunk paths to the corresponding resolver.
+ */
+const chunkResolvers: Map<ChunkPath, ChunkResolver> = new Map();
+
+function getOrCreateResolver(chunkPath: ChunkPath): ChunkResolver {
+  let resolver = chunkResolvers.get(chunkPath);
+  if (!resolver) {
+    let resolve: () => void;
+    let reject: (error?: Error) => void;
+    const promise = new Promise<void>((innerResolve, innerReject) => {
+      resolve = innerResolve;
+      reject = innerReject;
+    });
+    resolver = {
+      resolved: false,
+      promise,
+      resolve: () => {
+        resolver!.resolved = true;
+        resolve();
+      },
+      reject: reject!,
+    };
+    chunkResolvers.set(chunkPath, resolver);
+  }
+  return resolver;
+}
+
+/**
+ * Returns the URL relative to the origin where a chunk can be fetched from.
+ */
+function getChunkRelativeUrl(chunkPath: ChunkPath): string {
+  return `${CHUNK_BASE_PATH}${chunkPath}`
+    .split("/")
+    .map((p) => encodeURIComponent(p))
+    .join("/");
+}
+
+/**
+ * Loads the given chunk, and returns a promise that resolves once the chunk
+ * has been loaded.
+ */
+function loadChunk(source: SourceInfo, chunkData: ChunkData): Promise<void> {
+  const chunkPath = getChunkPath(chunkData);
+  const resolver = getOrCreateResolver(chunkPath);
+  if (resolver.resolved) {
+    return resolver.promise;
+  }
+
+  const chunkUrl = getChunkRelativeUrl(chunkPath);
+
+  if (chunkPath.endsWith(".css")) {
+    const previousLinks = document.querySelectorAll(
+      `link[rel=stylesheet][href="${chunkUrl}"]`
+    );
+    if (previousLinks.length > 0) {
+      // CSS chunks do not register themselves, and as such must be marked as
+      // loaded instantly.
+      resolver.resolve();
+    } else {
+      const link = document.createElement("link");
+      link.rel = "stylesheet";
+      link.href = chunkUrl;
+      link.onerror = () => {
+        resolver.reject(new Error(`Failed to load chunk ${chunkPath}`));
+      };
+      link.onload = () => {
+        resolver.resolve();
+      };
+      document.head.appendChild(link);
+    }
+  } else if (chunkPath.endsWith(".js")) {
+    if (source.type === SourceType.Runtime) {
+      // Chunks of an evaluated chunk group are expected to be present in the
+      // HTML already. We need to wait for them to register themselves before
+      // instantiating runtime modules.
+      const previousScripts = document.querySelectorAll(
+        `script[src="${chunkUrl}"]`
+      );
+      if (previousScripts.length > 0) {
+        return resolver.promise;
+      }
+    }
+
+    const script = document.createElement("script");
+    script.src = chunkUrl;
+    // The chunk is only marked as loaded once it has been executed, which
+    // happens in `registerChunk`.
+    script.onerror = () => {
+      resolver.reject(new Error(`Failed to load chunk ${chunkPath}`));
+    };
+    document.head.appendChild(script);
+  } else {
+    throw new Error(`can't infer type of chunk from path ${chunkPath}`);
+  }
+
+  return resolver.promise;
+}
+
+function fetchWebAssembly(wasmChunkPath: ChunkPath) {
+  return fetch(getChunkRelativeUrl(wasmChunkPath));
+}
+
+async function loadWebAssembly(
+  _source: SourceInfo,
+  wasmChunkPath: ChunkPath,
+  importsObj: WebAssembly.Imports
+): Promise<Exports> {
+  const req = fetchWebAssembly(wasmChunkPath);
+
+  const { instance } = await WebAssembly.instantiateStreaming(req, importsObj);
+
+  return instance.exports;
+}
+
+async function loadWebAssemblyModule(
+  _source: SourceInfo,
+  wasmChunkPath: ChunkPath
+): Promise<WebAssembly.Module> {
+  const req = fetchWebAssembly(wasmChunkPath);
+
+  return await WebAssembly.compileStreaming(req);
+}
+
+function commonJsRequireContext(
+  entry: RequireContextEntry,
+  sourceModule: Module
+): Exports {
+  return commonJsRequire(sourceModule, entry.id());
+}
+
+function instantiateModule(id: ModuleId, source: SourceInfo): Module {
+  const moduleFactory = moduleFactories[id];
+  if (typeof moduleFactory !== "function") {
+    let instantiationReason;
+    switch (source.type) {
+      case SourceType.Runtime:
+        instantiationReason = `as a runtime entry of chunk ${source.chunkPath}`;
+        break;
+      case SourceType.Parent:
+        instantiationReason = `because it was required from module ${source.parentId}`;
+        break;
+    }
+    throw new Error(
+      `Module ${id} was instantiated ${instantiationReason}, but the module factory is not available.`
+    );
+  }
+
+  let parents: ModuleId[];
+  switch (source.type) {
+    case SourceType.Runtime:
+      parents = [];
+      break;
+    case SourceType.Parent:
+      // No need to add this module as a child of the parent module here, this
+      // has already been taken care of in `getOrInstantiateModuleFromParent`.
+      parents = [source.parentId];
+      break;
+  }
+
+  const module: Module = {
+    exports: {},
+    error: undefined,
+    loaded: false,
+    id,
+    parents,
+    children: [],
+    namespaceObject: undefined,
+  };
+  moduleCache[id] = module;
+
+  // NOTE(alexkirsz) This can fail when the module encounters a runtime error.
+  try {
+    const sourceInfo: SourceInfo = { type: SourceType.Parent, parentId: id };
+
+    moduleFactory.call(module.exports, {
+      a: asyncModule.bind(null, module),
+      e: module.exports,
+      r: commonJsRequire.bind(null, module),
+      t: runtimeRequire,
+      f: requireContext.bind(null, module),
+      i: esmImport.bind(null, module),
+      s: esmExport.bind(null, module, module.exports),
+      j: dynamicExport.bind(null, module, module.exports),
+      v: exportValue.bind(null, module),
+      n: exportNamespace.bind(null, module),
+      m: module,
+      c: moduleCache,
+      l: loadChunk.bind(null, sourceInfo),
+      w: loadWebAssembly.bind(null, sourceInfo),
+      u: loadWebAssemblyModule.bind(null, sourceInfo),
+      g: globalThis,
+      U: relativeURL,
+      __dirname: module.id.replace(/(^|\/)\/+$/, ""),
+    });
+  } catch (error) {
+    module.error = error as any;
+    throw error;
+  }
+
+  module.loaded = true;
+  if (module.namespaceObject && module.exports !== module.namespaceObject) {
+    // in case of a circular dependency: cjs1 -> esm2 -> cjs1
+    interopEsm(module.exports, module.namespaceObject);
+  }
+
+  return module;
+}
+
+/**
+ * Retrieves a module from the cache, or instantiate it if it is not cached.
+ */
+function getOrInstantiateModuleFromParent(
+  id: ModuleId,
+  sourceModule: Module
+): Module {
+  const module = moduleCache[id];
+
+  if (sourceModule.children.indexOf(id) === -1) {
+    sourceModule.children.push(id);
+  }
+
+  if (module) {
+    if (module.parents.indexOf(sourceModule.id) === -1) {
+      module.parents.push(sourceModule.id);
+    }
+
+    return module;
+  }
+
+  return instantiateModule(id, {
+    type: SourceType.Parent,
+    parentId: sourceModule.id,
+  });
+}
+
+/**
+ * Retrieves a module from the cache, or instantiate it as a runtime module if it is not cached.
+ */
+function getOrInstantiateRuntimeModule(
+  moduleId: ModuleId,
+  chunkPath: ChunkPath
+): Module {
+  const module = moduleCache[moduleId];
+  if (module) {
+    if (module.error) {
+      throw module.error;
+    }
+    return module;
+  }
+
+  return instantiateModule(moduleId, { type: SourceType.Runtime, chunkPath });
+}
+
+async function registerChunk([
+  chunkPath,
+  chunkModules,
+  params,
+]: ChunkRegistration) {
+  for (const [moduleId, moduleFactory] of Object.entries(chunkModules)) {
+    if (!moduleFactories[moduleId]) {
+      moduleFactories[moduleId] = moduleFactory;
+    }
+  }
+
+  getOrCreateResolver(chunkPath).resolve();
+
+  if (params == null) {
+    return;
+  }
+
+  // This waits for chunks to be loaded before instantiating the runtime
+  // modules, which may depend on modules from any of them.
+  await Promise.all(
+    params.otherChunks.map((otherChunkData) =>
+      loadChunk({ type: SourceType.Runtime, chunkPath }, otherChunkData)
+    )
+  );
+
+  for (const moduleId of params.runtimeModuleIds) {
+    getOrInstantiateRuntimeModule(moduleId, chunkPath);
+  }
+}
+
+const chunksToRegister = globalThis.TURBOPACK;
+globalThis.TURBOPACK = { push: registerChunk };
+if (Array.isArray(chunksToRegister)) {
+  chunksToRegister.forEach(registerChunk);
+}
@@ -1,9 +1,13 @@
+use std::io::Write;
+
 use anyhow::Result;
+use indoc::writedoc;
 use turbo_tasks::Vc;
 use turbopack_core::{
     code_builder::{Code, CodeBuilder},
     environment::Environment,
 };
+use turbopack_ecmascript::utils::StringifyJs;
 
 use crate::{asset_context::get_runtime_asset_context, embed_js::embed_static_code};
 
@@ -35,3 +39,47 @@ pub async fn get_build_runtime_code(environment: Vc<Environment>) -> Result<Vc<C
 
     Ok(Code::cell(code.build()))
 }
+
+/// Returns the code for the browser production ECMAScript runtime.
+#[turbo_tasks::function]
+pub async fn get_build_browser_runtime_code(
+    environment: Vc<Environment>,
+    chunk_base_path: Vc<Option<String>>,
+) -> Result<Vc<Code>> {
+    let asset_context = get_runtime_asset_context(environment);
+
+    let shared_runtime_utils_code =
+        embed_static_code(asset_context, "shared/runtime-utils.ts".to_string());
+    let runtime_code = embed_static_code(asset_context, "build/browser/runtime.ts".to_string());
+
+    let mut code = CodeBuilder::default();
+    let chunk_base_path = &*chunk_base_path.await?;
+    let chunk_base_path = chunk_base_path.as_deref().unwrap_or("/");
+
+    // Only the first runtime loaded in a page is used, later ones register
+    // their chunks with it.
+    writedoc!(
+        code,
+        r#"
+            (() => {{
+            if (!Array.isArray(globalThis.TURBOPACK)) {{
+                return;
+            }}
+
+            const CHUNK_BASE_PATH = {};
+        "#,
+        StringifyJs(chunk_base_path),
+    )?;
+
+    code.push_code(&*shared_runtime_utils_code.await?);
+    code.push_code(&*runtime_code.await?);
+
+    writedoc!(
+        code,
+        r#"
+            }})();
+        "#
+    )?;
+
+    Ok(Code::cell(code.build()))
+}
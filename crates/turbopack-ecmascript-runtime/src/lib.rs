@@ -8,7 +8,7 @@ pub(crate) mod dummy_runtime;
 pub(crate) mod embed_js;
 pub(crate) mod runtime_type;
 
-pub use build_runtime::get_build_runtime_code;
+pub use build_runtime::{get_build_browser_runtime_code, get_build_runtime_code};
 pub use dev_runtime::get_dev_runtime_code;
 #[cfg(feature = "test")]
 pub use dummy_runtime::get_dummy_runtime_code;
@@ -26,7 +26,7 @@ use turbopack::{
     resolve_options_context::ResolveOptionsContext,
     ModuleAssetContext,
 };
-use turbopack_build::{BuildChunkingContext, MinifyType};
+use turbopack_build::{BuildChunkingContext, BuildManifestAsset, BuildTarget, MinifyType};
 use turbopack_core::{
     asset::Asset,
     chunk::{
@@ -84,6 +84,8 @@ struct SnapshotOptions {
     runtime_type: RuntimeType,
     #[serde(default)]
     environment: SnapshotEnvironment,
+    #[serde(default)]
+    target: BuildTarget,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -109,6 +111,7 @@ impl Default for SnapshotOptions {
             runtime: Default::default(),
             runtime_type: default_runtime_type(),
             environment: Default::default(),
+            target: Default::default(),
         }
     }
 }
@@ -307,6 +310,7 @@ async fn run_test(resource: String) -> Result<Vc<FileSystemPath>> {
             )
             .minify_type(options.minify_type)
             .runtime_type(options.runtime_type)
+            .target(options.target)
             .build(),
         ),
     };
@@ -335,6 +339,29 @@ async fn run_test(resource: String) -> Result<Vc<FileSystemPath>> {
                     .unwrap_or_else(EvaluatableAssets::empty)
                     .with_entry(Vc::upcast(ecmascript)),
             ),
+            Runtime::Build if options.target == BuildTarget::Browser => {
+                // The manifest references the chunk group, so it's snapshotted
+                // along with the content-hashed chunks it lists.
+                let chunk_group = chunking_context.evaluated_chunk_group(
+                    ecmascript.ident(),
+                    runtime_entries
+                        .unwrap_or_else(EvaluatableAssets::empty)
+                        .with_entry(Vc::upcast(ecmascript)),
+                );
+                let name = entry_module
+                    .ident()
+                    .path()
+                    .file_stem()
+                    .await?
+                    .as_deref()
+                    .unwrap()
+                    .to_string();
+                Vc::cell(vec![Vc::upcast(BuildManifestAsset::new(
+                    chunk_root_path.join("manifest.json".to_string()),
+                    chunk_root_path,
+                    vec![(name, chunk_group)],
+                ))])
+            }
             Runtime::Build => {
                 Vc::cell(vec![Vc::try_resolve_downcast_type::<BuildChunkingContext>(
                     chunking_context,
@@ -0,0 +1,3 @@
+import "./styles.css";
+
+console.log("Hello, world!");
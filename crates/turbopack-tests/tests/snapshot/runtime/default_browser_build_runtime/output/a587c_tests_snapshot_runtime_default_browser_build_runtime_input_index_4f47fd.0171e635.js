@@ -0,0 +1,551 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/a587c_tests_snapshot_runtime_default_browser_build_runtime_input_index_4f47fd.0171e635.js",
+    {},
+    {"otherChunks":["output/a587c_tests_snapshot_runtime_default_browser_build_runtime_input_styles_ca3c1c.87115e41.css","output/a587c_tests_snapshot_runtime_default_browser_build_runtime_input_index_fa6a5c.f11bd40b.js"],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/runtime/default_browser_build_runtime/input/index.js [test] (ecmascript)"]}
+]);
+(() => {
+if (!Array.isArray(globalThis.TURBOPACK)) {
+    return;
+}
+
+const CHUNK_BASE_PATH = "/";
+/**
+ * This file contains runtime types and functions that are shared between all
+ * TurboPack ECMAScript runtimes.
+ *
+ * It will be prepended to the runtime code of each runtime.
+ */ /* eslint-disable @next/next/no-assign-module-variable */ /// <reference path="./runtime-types.d.ts" />
+const REEXPORTED_OBJECTS = Symbol("reexported objects");
+const hasOwnProperty = Object.prototype.hasOwnProperty;
+const toStringTag = typeof Symbol !== "undefined" && Symbol.toStringTag;
+function defineProp(obj, name, options) {
+    if (!hasOwnProperty.call(obj, name)) Object.defineProperty(obj, name, options);
+}
+/**
+ * Adds the getters to the exports object.
+ */ function esm(exports, getters) {
+    defineProp(exports, "__esModule", {
+        value: true
+    });
+    if (toStringTag) defineProp(exports, toStringTag, {
+        value: "Module"
+    });
+    for(const key in getters){
+        defineProp(exports, key, {
+            get: getters[key],
+            enumerable: true
+        });
+    }
+}
+/**
+ * Makes the module an ESM with exports
+ */ function esmExport(module, exports, getters) {
+    module.namespaceObject = module.exports;
+    esm(exports, getters);
+}
+function ensureDynamicExports(module, exports) {
+    let reexportedObjects = module[REEXPORTED_OBJECTS];
+    if (!reexportedObjects) {
+        reexportedObjects = module[REEXPORTED_OBJECTS] = [];
+        module.exports = module.namespaceObject = new Proxy(exports, {
+            get (target, prop) {
+                if (hasOwnProperty.call(target, prop) || prop === "default" || prop === "__esModule") {
+                    return Reflect.get(target, prop);
+                }
+                for (const obj of reexportedObjects){
+                    const value = Reflect.get(obj, prop);
+                    if (value !== undefined) return value;
+                }
+                return undefined;
+            },
+            ownKeys (target) {
+                const keys = Reflect.ownKeys(target);
+                for (const obj of reexportedObjects){
+                    for (const key of Reflect.ownKeys(obj)){
+                        if (key !== "default" && !keys.includes(key)) keys.push(key);
+                    }
+                }
+                return keys;
+            }
+        });
+    }
+}
+/**
+ * Dynamically exports properties from an object
+ */ function dynamicExport(module, exports, object) {
+    ensureDynamicExports(module, exports);
+    if (typeof object === "object" && object !== null) {
+        module[REEXPORTED_OBJECTS].push(object);
+    }
+}
+function exportValue(module, value) {
+    module.exports = value;
+}
+function exportNamespace(module, namespace) {
+    module.exports = module.namespaceObject = namespace;
+}
+function createGetter(obj, key) {
+    return ()=>obj[key];
+}
+/**
+ * @returns prototype of the object
+ */ const getProto = Object.getPrototypeOf ? (obj)=>Object.getPrototypeOf(obj) : (obj)=>obj.__proto__;
+/** Prototypes that are not expanded for exports */ const LEAF_PROTOTYPES = [
+    null,
+    getProto({}),
+    getProto([]),
+    getProto(getProto)
+];
+/**
+ * @param raw
+ * @param ns
+ * @param allowExportDefault
+ *   * `false`: will have the raw module as default export
+ *   * `true`: will have the default property as default export
+ */ function interopEsm(raw, ns, allowExportDefault) {
+    const getters = Object.create(null);
+    for(let current = raw; (typeof current === "object" || typeof current === "function") && !LEAF_PROTOTYPES.includes(current); current = getProto(current)){
+        for (const key of Object.getOwnPropertyNames(current)){
+            getters[key] = createGetter(raw, key);
+        }
+    }
+    // this is not really correct
+    // we should set the `default` getter if the imported module is a `.cjs file`
+    if (!(allowExportDefault && "default" in getters)) {
+        getters["default"] = ()=>raw;
+    }
+    esm(ns, getters);
+    return ns;
+}
+function esmImport(sourceModule, id) {
+    const module = getOrInstantiateModuleFromParent(id, sourceModule);
+    if (module.error) throw module.error;
+    // any ES module has to have `module.namespaceObject` defined.
+    if (module.namespaceObject) return module.namespaceObject;
+    // only ESM can be an async module, so we don't need to worry about exports being a promise here.
+    const raw = module.exports;
+    return module.namespaceObject = interopEsm(raw, {}, raw.__esModule);
+}
+// Add a simple runtime require so that environments without one can still pass
+// `typeof require` CommonJS checks so that exports are correctly registered.
+const runtimeRequire = typeof require === "function" ? require : function require1() {
+    throw new Error("Unexpected use of runtime require");
+};
+function commonJsRequire(sourceModule, id) {
+    const module = getOrInstantiateModuleFromParent(id, sourceModule);
+    if (module.error) throw module.error;
+    return module.exports;
+}
+function requireContext(sourceModule, map) {
+    function requireContext(id) {
+        const entry = map[id];
+        if (!entry) {
+            throw new Error(`module ${id} is required from a require.context, but is not in the context`);
+        }
+        return commonJsRequireContext(entry, sourceModule);
+    }
+    requireContext.keys = ()=>{
+        return Object.keys(map);
+    };
+    requireContext.resolve = (id)=>{
+        const entry = map[id];
+        if (!entry) {
+            throw new Error(`module ${id} is resolved from a require.context, but is not in the context`);
+        }
+        return entry.id();
+    };
+    return requireContext;
+}
+/**
+ * Returns the path of a chunk defined by its data.
+ */ function getChunkPath(chunkData) {
+    return typeof chunkData === "string" ? chunkData : chunkData.path;
+}
+function isPromise(maybePromise) {
+    return maybePromise != null && typeof maybePromise === "object" && "then" in maybePromise && typeof maybePromise.then === "function";
+}
+function isAsyncModuleExt(obj) {
+    return turbopackQueues in obj;
+}
+function createPromise() {
+    let resolve;
+    let reject;
+    const promise = new Promise((res, rej)=>{
+        reject = rej;
+        resolve = res;
+    });
+    return {
+        promise,
+        resolve: resolve,
+        reject: reject
+    };
+}
+// everything below is adapted from webpack
+// https://github.com/webpack/webpack/blob/6be4065ade1e252c1d8dcba4af0f43e32af1bdc1/lib/runtime/AsyncModuleRuntimeModule.js#L13
+const turbopackQueues = Symbol("turbopack queues");
+const turbopackExports = Symbol("turbopack exports");
+const turbopackError = Symbol("turbopack error");
+function resolveQueue(queue) {
+    if (queue && !queue.resolved) {
+        queue.resolved = true;
+        queue.forEach((fn)=>fn.queueCount--);
+        queue.forEach((fn)=>fn.queueCount-- ? fn.queueCount++ : fn());
+    }
+}
+function wrapDeps(deps) {
+    return deps.map((dep)=>{
+        if (dep !== null && typeof dep === "object") {
+            if (isAsyncModuleExt(dep)) return dep;
+            if (isPromise(dep)) {
+                const queue = Object.assign([], {
+                    resolved: false
+                });
+                const obj = {
+                    [turbopackExports]: {},
+                    [turbopackQueues]: (fn)=>fn(queue)
+                };
+                dep.then((res)=>{
+                    obj[turbopackExports] = res;
+                    resolveQueue(queue);
+                }, (err)=>{
+                    obj[turbopackError] = err;
+                    resolveQueue(queue);
+                });
+                return obj;
+            }
+        }
+        const ret = {
+            [turbopackExports]: dep,
+            [turbopackQueues]: ()=>{}
+        };
+        return ret;
+    });
+}
+function asyncModule(module, body, hasAwait) {
+    const queue = hasAwait ? Object.assign([], {
+        resolved: true
+    }) : undefined;
+    const depQueues = new Set();
+    ensureDynamicExports(module, module.exports);
+    const exports = module.exports;
+    const { resolve, reject, promise: rawPromise } = createPromise();
+    const promise = Object.assign(rawPromise, {
+        [turbopackExports]: exports,
+        [turbopackQueues]: (fn)=>{
+            queue && fn(queue);
+            depQueues.forEach(fn);
+            promise["catch"](()=>{});
+        }
+    });
+    module.exports = module.namespaceObject = promise;
+    function handleAsyncDependencies(deps) {
+        const currentDeps = wrapDeps(deps);
+        const getResult = ()=>currentDeps.map((d)=>{
+                if (d[turbopackError]) throw d[turbopackError];
+                return d[turbopackExports];
+            });
+        const { promise, resolve } = createPromise();
+        const fn = Object.assign(()=>resolve(getResult), {
+            queueCount: 0
+        });
+        function fnQueue(q) {
+            if (q !== queue && !depQueues.has(q)) {
+                depQueues.add(q);
+                if (q && !q.resolved) {
+                    fn.queueCount++;
+                    q.push(fn);
+                }
+            }
+        }
+        currentDeps.map((dep)=>dep[turbopackQueues](fnQueue));
+        return fn.queueCount ? promise : getResult();
+    }
+    function asyncResult(err) {
+        if (err) {
+            reject(promise[turbopackError] = err);
+        } else {
+            resolve(exports);
+        }
+        resolveQueue(queue);
+    }
+    body(handleAsyncDependencies, asyncResult);
+    if (queue) {
+        queue.resolved = false;
+    }
+}
+/**
+ * A pseudo, `fake` URL object to resolve to the its relative path.
+ * When urlrewritebehavior is set to relative, calls to the `new URL()` will construct url without base using this
+ * runtime function to generate context-agnostic urls between different rendering context, i.e ssr / client to avoid
+ * hydration mismatch.
+ *
+ * This is largely based on the webpack's existing implementation at
+ * https://github.com/webpack/webpack/blob/87660921808566ef3b8796f8df61bd79fc026108/lib/runtime/RelativeUrlRuntimeModule.js
+ */ var relativeURL = function(inputUrl) {
+    const realUrl = new URL(inputUrl, "x:/");
+    const values = {};
+    for(var key in realUrl)values[key] = realUrl[key];
+    values.href = inputUrl;
+    values.pathname = inputUrl.replace(/[?#].*/, "");
+    values.origin = values.protocol = "";
+    values.toString = values.toJSON = (..._args)=>inputUrl;
+    for(var key in values)Object.defineProperty(this, key, {
+        enumerable: true,
+        configurable: true,
+        value: values[key]
+    });
+};
+relativeURL.prototype = URL.prototype;
+/**
+ * This file contains the runtime code for production ECMAScript builds that
+ * run in the browser.
+ *
+ * Unlike the development DOM runtime, it doesn't support HMR: chunks are only
+ * ever loaded once, and module factories are never replaced.
+ */ /* eslint-disable @next/next/no-assign-module-variable */ /// <reference path="../../shared/runtime-utils.ts" />
+let SourceType;
+(function(SourceType) {
+    /**
+   * The module was instantiated because it was included in an evaluated chunk's
+   * runtime.
+   */ SourceType[SourceType["Runtime"] = 0] = "Runtime";
+    /**
+   * The module was instantiated because a parent module imported it.
+   */ SourceType[SourceType["Parent"] = 1] = "Parent";
+})(SourceType || (SourceType = {}));
+const moduleFactories = Object.create(null);
+const moduleCache = Object.create(null);
+/**
+ * Maps chunk paths to the corresponding resolver.
+ */ const chunkResolvers = new Map();
+function getOrCreateResolver(chunkPath) {
+    let resolver = chunkResolvers.get(chunkPath);
+    if (!resolver) {
+        let resolve;
+        let reject;
+        const promise = new Promise((innerResolve, innerReject)=>{
+            resolve = innerResolve;
+            reject = innerReject;
+        });
+        resolver = {
+            resolved: false,
+            promise,
+            resolve: ()=>{
+                resolver.resolved = true;
+                resolve();
+            },
+            reject: reject
+        };
+        chunkResolvers.set(chunkPath, resolver);
+    }
+    return resolver;
+}
+/**
+ * Returns the URL relative to the origin where a chunk can be fetched from.
+ */ function getChunkRelativeUrl(chunkPath) {
+    return `${CHUNK_BASE_PATH}${chunkPath}`.split("/").map((p)=>encodeURIComponent(p)).join("/");
+}
+/**
+ * Loads the given chunk, and returns a promise that resolves once the chunk
+ * has been loaded.
+ */ function loadChunk(source, chunkData) {
+    const chunkPath = getChunkPath(chunkData);
+    const resolver = getOrCreateResolver(chunkPath);
+    if (resolver.resolved) {
+        return resolver.promise;
+    }
+    const chunkUrl = getChunkRelativeUrl(chunkPath);
+    if (chunkPath.endsWith(".css")) {
+        const previousLinks = document.querySelectorAll(`link[rel=stylesheet][href="${chunkUrl}"]`);
+        if (previousLinks.length > 0) {
+            // CSS chunks do not register themselves, and as such must be marked as
+            // loaded instantly.
+            resolver.resolve();
+        } else {
+            const link = document.createElement("link");
+            link.rel = "stylesheet";
+            link.href = chunkUrl;
+            link.onerror = ()=>{
+                resolver.reject(new Error(`Failed to load chunk ${chunkPath}`));
+            };
+            link.onload = ()=>{
+                resolver.resolve();
+            };
+            document.head.appendChild(link);
+        }
+    } else if (chunkPath.endsWith(".js")) {
+        if (source.type === 0) {
+            // Chunks of an evaluated chunk group are expected to be present in the
+            // HTML already. We need to wait for them to register themselves before
+            // instantiating runtime modules.
+            const previousScripts = document.querySelectorAll(`script[src="${chunkUrl}"]`);
+            if (previousScripts.length > 0) {
+                return resolver.promise;
+            }
+        }
+        const script = document.createElement("script");
+        script.src = chunkUrl;
+        // The chunk is only marked as loaded once it has been executed, which
+        // happens in `registerChunk`.
+        script.onerror = ()=>{
+            resolver.reject(new Error(`Failed to load chunk ${chunkPath}`));
+        };
+        document.head.appendChild(script);
+    } else {
+        throw new Error(`can't infer type of chunk from path ${chunkPath}`);
+    }
+    return resolver.promise;
+}
+function fetchWebAssembly(wasmChunkPath) {
+    return fetch(getChunkRelativeUrl(wasmChunkPath));
+}
+async function loadWebAssembly(_source, wasmChunkPath, importsObj) {
+    const req = fetchWebAssembly(wasmChunkPath);
+    const { instance } = await WebAssembly.instantiateStreaming(req, importsObj);
+    return instance.exports;
+}
+async function loadWebAssemblyModule(_source, wasmChunkPath) {
+    const req = fetchWebAssembly(wasmChunkPath);
+    return await WebAssembly.compileStreaming(req);
+}
+function commonJsRequireContext(entry, sourceModule) {
+    return commonJsRequire(sourceModule, entry.id());
+}
+function instantiateModule(id, source) {
+    const moduleFactory = moduleFactories[id];
+    if (typeof moduleFactory !== "function") {
+        let instantiationReason;
+        switch(source.type){
+            case 0:
+                instantiationReason = `as a runtime entry of chunk ${source.chunkPath}`;
+                break;
+            case 1:
+                instantiationReason = `because it was required from module ${source.parentId}`;
+                break;
+        }
+        throw new Error(`Module ${id} was instantiated ${instantiationReason}, but the module factory is not available.`);
+    }
+    let parents;
+    switch(source.type){
+        case 0:
+            parents = [];
+            break;
+        case 1:
+            // No need to add this module as a child of the parent module here, this
+            // has already been taken care of in `getOrInstantiateModuleFromParent`.
+            parents = [
+                source.parentId
+            ];
+            break;
+    }
+    const module = {
+        exports: {},
+        error: undefined,
+        loaded: false,
+        id,
+        parents,
+        children: [],
+        namespaceObject: undefined
+    };
+    moduleCache[id] = module;
+    // NOTE(alexkirsz) This can fail when the module encounters a runtime error.
+    try {
+        const sourceInfo = {
+            type: 1,
+            parentId: id
+        };
+        moduleFactory.call(module.exports, {
+            a: asyncModule.bind(null, module),
+            e: module.exports,
+            r: commonJsRequire.bind(null, module),
+            t: runtimeRequire,
+            f: requireContext.bind(null, module),
+            i: esmImport.bind(null, module),
+            s: esmExport.bind(null, module, module.exports),
+            j: dynamicExport.bind(null, module, module.exports),
+            v: exportValue.bind(null, module),
+            n: exportNamespace.bind(null, module),
+            m: module,
+            c: moduleCache,
+            l: loadChunk.bind(null, sourceInfo),
+            w: loadWebAssembly.bind(null, sourceInfo),
+            u: loadWebAssemblyModule.bind(null, sourceInfo),
+            g: globalThis,
+            U: relativeURL,
+            __dirname: module.id.replace(/(^|\/)\/+$/, "")
+        });
+    } catch (error) {
+        module.error = error;
+        throw error;
+    }
+    module.loaded = true;
+    if (module.namespaceObject && module.exports !== module.namespaceObject) {
+        // in case of a circular dependency: cjs1 -> esm2 -> cjs1
+        interopEsm(module.exports, module.namespaceObject);
+    }
+    return module;
+}
+/**
+ * Retrieves a module from the cache, or instantiate it if it is not cached.
+ */ function getOrInstantiateModuleFromParent(id, sourceModule) {
+    const module = moduleCache[id];
+    if (sourceModule.children.indexOf(id) === -1) {
+        sourceModule.children.push(id);
+    }
+    if (module) {
+        if (module.parents.indexOf(sourceModule.id) === -1) {
+            module.parents.push(sourceModule.id);
+        }
+        return module;
+    }
+    return instantiateModule(id, {
+        type: 1,
+        parentId: sourceModule.id
+    });
+}
+/**
+ * Retrieves a module from the cache, or instantiate it as a runtime module if it is not cached.
+ */ function getOrInstantiateRuntimeModule(moduleId, chunkPath) {
+    const module = moduleCache[moduleId];
+    if (module) {
+        if (module.error) {
+            throw module.error;
+        }
+        return module;
+    }
+    return instantiateModule(moduleId, {
+        type: 0,
+        chunkPath
+    });
+}
+async function registerChunk([chunkPath, chunkModules, params]) {
+    for (const [moduleId, moduleFactory] of Object.entries(chunkModules)){
+        if (!moduleFactories[moduleId]) {
+            moduleFactories[moduleId] = moduleFactory;
+        }
+    }
+    getOrCreateResolver(chunkPath).resolve();
+    if (params == null) {
+        return;
+    }
+    // This waits for chunks to be loaded before instantiating the runtime
+    // modules, which may depend on modules from any of them.
+    await Promise.all(params.otherChunks.map((otherChunkData)=>loadChunk({
+            type: 0,
+            chunkPath
+        }, otherChunkData)));
+    for (const moduleId of params.runtimeModuleIds){
+        getOrInstantiateRuntimeModule(moduleId, chunkPath);
+    }
+}
+const chunksToRegister = globalThis.TURBOPACK;
+globalThis.TURBOPACK = {
+    push: registerChunk
+};
+if (Array.isArray(chunksToRegister)) {
+    chunksToRegister.forEach(registerChunk);
+}
+})();
+
+
+//# sourceMappingURL=a587c_tests_snapshot_runtime_default_browser_build_runtime_input_index_4f47fd.0171e635.js.map
\ No newline at end of file
@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/a587c_tests_snapshot_runtime_default_browser_build_runtime_input_index_fa6a5c.f11bd40b.js", {
+"[project]/crates/turbopack-tests/tests/snapshot/runtime/default_browser_build_runtime/input/index.js [test] (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, p: __turbopack_resolve_absolute_path__, U: __turbopack_relative_url__, R: __turbopack_resolve_module_id_path__, g: global, __dirname, m: module, e: exports, t: require }) { !function() {
+
+__turbopack_esm__({});
+;
+console.log("Hello, world!");
+
+}.call(this) }),
+}]);
+
+//# sourceMappingURL=a587c_tests_snapshot_runtime_default_browser_build_runtime_input_index_fa6a5c.f11bd40b.js.map
\ No newline at end of file
@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_sass_indented_input_index_300fdf.js",
+    {},
+    {"otherChunks":[{"path":"output/crates_turbopack-tests_tests_snapshot_sass_indented_input_index_sass_091dfb.css","included":["[project]/crates/turbopack-tests/tests/snapshot/sass/indented/input/index.sass.css [test] (css)"],"moduleChunks":["output/crates_turbopack-tests_tests_snapshot_sass_indented_input_index_sass_7d7e1c.css"]},"output/crates_turbopack-tests_tests_snapshot_sass_indented_input_index_b7baad.js"],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/sass/indented/input/index.js [test] (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
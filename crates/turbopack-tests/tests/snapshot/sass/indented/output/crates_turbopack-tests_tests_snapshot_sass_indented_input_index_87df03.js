@@ -0,0 +1,12 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_sass_indented_input_index_87df03.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/crates_turbopack-tests_tests_snapshot_sass_indented_input_index_87df03.js",
+  "chunks": [
+    "output/crates_turbopack-tests_tests_snapshot_sass_indented_input_index_sass_091dfb.css",
+    "output/crates_turbopack-tests_tests_snapshot_sass_indented_input_index_b7baad.js"
+  ],
+  "source": "entry"
+});
\ No newline at end of file
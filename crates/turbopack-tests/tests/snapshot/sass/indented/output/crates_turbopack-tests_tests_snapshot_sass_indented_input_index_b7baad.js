@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_sass_indented_input_index_b7baad.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/sass/indented/input/index.js [test] (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, p: __turbopack_resolve_absolute_path__, U: __turbopack_relative_url__, R: __turbopack_resolve_module_id_path__, g: global, __dirname, k: __turbopack_refresh__, m: module, e: exports, t: require }) { !function() {
+
+__turbopack_esm__({});
+;
+
+}.call(this) }),
+}]);
+
+//# sourceMappingURL=crates_turbopack-tests_tests_snapshot_sass_indented_input_index_b7baad.js.map
\ No newline at end of file
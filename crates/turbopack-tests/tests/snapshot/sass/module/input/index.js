@@ -0,0 +1,3 @@
+import { card } from "./index.module.scss";
+
+console.log(card);
@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_sass_module_input_index_189620.js",
+    {},
+    {"otherChunks":[{"path":"output/a587c_tests_snapshot_sass_module_input_index_module_scss_module_e63a5f.css","included":["[project]/crates/turbopack-tests/tests/snapshot/sass/module/input/index.module.scss.module.css [test] (css)"],"moduleChunks":["output/a587c_tests_snapshot_sass_module_input_index_module_scss_module_7d7e1c.css"]},"output/crates_turbopack-tests_tests_snapshot_sass_module_input_979ae9._.js"],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/sass/module/input/index.js [test] (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
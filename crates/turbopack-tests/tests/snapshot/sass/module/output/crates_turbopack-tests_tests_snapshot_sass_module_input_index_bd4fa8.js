@@ -0,0 +1,12 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_sass_module_input_index_bd4fa8.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/crates_turbopack-tests_tests_snapshot_sass_module_input_index_bd4fa8.js",
+  "chunks": [
+    "output/a587c_tests_snapshot_sass_module_input_index_module_scss_module_e63a5f.css",
+    "output/crates_turbopack-tests_tests_snapshot_sass_module_input_979ae9._.js"
+  ],
+  "source": "entry"
+});
\ No newline at end of file
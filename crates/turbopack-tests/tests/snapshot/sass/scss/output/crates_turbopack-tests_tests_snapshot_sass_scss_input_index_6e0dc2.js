@@ -0,0 +1,12 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_sass_scss_input_index_6e0dc2.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/crates_turbopack-tests_tests_snapshot_sass_scss_input_index_6e0dc2.js",
+  "chunks": [
+    "output/crates_turbopack-tests_tests_snapshot_sass_scss_input_index_scss_1acb43.css",
+    "output/crates_turbopack-tests_tests_snapshot_sass_scss_input_index_b1b2a9.js"
+  ],
+  "source": "entry"
+});
\ No newline at end of file
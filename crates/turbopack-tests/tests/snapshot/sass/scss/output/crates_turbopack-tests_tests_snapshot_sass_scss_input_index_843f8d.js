@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_sass_scss_input_index_843f8d.js",
+    {},
+    {"otherChunks":[{"path":"output/crates_turbopack-tests_tests_snapshot_sass_scss_input_index_scss_1acb43.css","included":["[project]/crates/turbopack-tests/tests/snapshot/sass/scss/input/index.scss.css [test] (css)"],"moduleChunks":["output/crates_turbopack-tests_tests_snapshot_sass_scss_input_index_scss_7d7e1c.css"]},"output/crates_turbopack-tests_tests_snapshot_sass_scss_input_index_b1b2a9.js"],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/sass/scss/input/index.js [test] (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_sass_use_input_index_080804.js",
+    {},
+    {"otherChunks":[{"path":"output/crates_turbopack-tests_tests_snapshot_sass_use_input_index_scss_bb4939.css","included":["[project]/crates/turbopack-tests/tests/snapshot/sass/use/input/index.scss.css [test] (css)"],"moduleChunks":["output/crates_turbopack-tests_tests_snapshot_sass_use_input_index_scss_7d7e1c.css"]},"output/crates_turbopack-tests_tests_snapshot_sass_use_input_index_2e5845.js"],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/sass/use/input/index.js [test] (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
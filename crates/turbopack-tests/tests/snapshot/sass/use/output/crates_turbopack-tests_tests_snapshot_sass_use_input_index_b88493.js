@@ -0,0 +1,12 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_sass_use_input_index_b88493.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/crates_turbopack-tests_tests_snapshot_sass_use_input_index_b88493.js",
+  "chunks": [
+    "output/crates_turbopack-tests_tests_snapshot_sass_use_input_index_scss_bb4939.css",
+    "output/crates_turbopack-tests_tests_snapshot_sass_use_input_index_2e5845.js"
+  ],
+  "source": "entry"
+});
\ No newline at end of file
@@ -15,7 +15,6 @@ pub mod rebase;
 pub mod resolve;
 pub mod resolve_options_context;
 pub mod transition;
-pub(crate) mod unsupported_sass;
 
 use std::{
     collections::{HashMap, HashSet},
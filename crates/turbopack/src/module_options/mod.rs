@@ -14,7 +14,10 @@ use turbopack_core::{
     reference_type::{CssReferenceSubType, ReferenceType, UrlReferenceSubType},
     resolve::options::{ImportMap, ImportMapping},
 };
-use turbopack_css::CssModuleAssetType;
+use turbopack_css::{
+    sass::{sass_resolve_options, SassTransform},
+    CssModuleAssetType,
+};
 use turbopack_ecmascript::{EcmascriptInputTransform, EcmascriptOptions, SpecifiedModuleType};
 use turbopack_mdx::MdxTransformOptions;
 use turbopack_node::transforms::{postcss::PostCssTransform, webpack::WebpackLoaders};
@@ -374,6 +377,18 @@ impl ModuleOptions {
             ),
         ];
 
+        // Sass is compiled to CSS under a `.css` ident, which the CSS rules below
+        // then pick up.
+        rules.push(ModuleRule::new(
+            ModuleRuleCondition::any(vec![
+                ModuleRuleCondition::ResourcePathEndsWith(".scss".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".sass".to_string()),
+            ]),
+            vec![ModuleRuleEffect::SourceTransforms(Vc::cell(vec![
+                Vc::upcast(SassTransform::new(sass_resolve_options(path.root()))),
+            ]))],
+        ));
+
         if enable_raw_css {
             rules.extend([
                 ModuleRule::new(
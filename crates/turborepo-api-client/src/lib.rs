@@ -62,6 +62,14 @@ pub trait Client {
         team_id: Option<&str>,
         team_slug: Option<&str>,
     ) -> Result<Option<Response>>;
+    async fn fetch_artifact_range(
+        &self,
+        hash: &str,
+        start: u64,
+        token: &str,
+        team_id: Option<&str>,
+        team_slug: Option<&str>,
+    ) -> Result<Option<Response>>;
     async fn artifact_exists(
         &self,
         hash: &str,
@@ -335,6 +343,18 @@ impl Client for APIClient {
             .await
     }
 
+    async fn fetch_artifact_range(
+        &self,
+        hash: &str,
+        start: u64,
+        token: &str,
+        team_id: Option<&str>,
+        team_slug: Option<&str>,
+    ) -> Result<Option<Response>> {
+        self.request_artifact(hash, token, team_id, team_slug, Method::GET, Some(start))
+            .await
+    }
+
     async fn artifact_exists(
         &self,
         hash: &str,
@@ -354,36 +374,8 @@ impl Client for APIClient {
         team_slug: Option<&str>,
         method: Method,
     ) -> Result<Option<Response>> {
-        let mut request_url = self.make_url(&format!("/v8/artifacts/{}", hash));
-        let mut allow_auth = true;
-
-        if self.use_preflight {
-            let preflight_response = self
-                .do_preflight(token, &request_url, "GET", "Authorization, User-Agent")
-                .await?;
-
-            allow_auth = preflight_response.allow_authorization_header;
-            request_url = preflight_response.location.to_string();
-        };
-
-        let mut request_builder = self
-            .client
-            .request(method, request_url)
-            .header("User-Agent", self.user_agent.clone());
-
-        if allow_auth {
-            request_builder = request_builder.header("Authorization", format!("Bearer {}", token));
-        }
-
-        request_builder = Self::add_team_params(request_builder, team_id, team_slug);
-
-        let response = retry::make_retryable_request(request_builder).await?;
-
-        match response.status() {
-            StatusCode::FORBIDDEN => Err(Self::handle_403(response).await),
-            StatusCode::NOT_FOUND => Ok(None),
-            _ => Ok(Some(response.error_for_status()?)),
-        }
+        self.request_artifact(hash, token, team_id, team_slug, method, None)
+            .await
     }
 
     async fn do_preflight(
@@ -512,6 +504,58 @@ impl APIClient {
         Ok(request_builder)
     }
 
+    // A `range_start` asks for the artifact from that byte onwards, e.g. to
+    // resume an interrupted download. Servers may ignore it and send the whole
+    // artifact.
+    async fn request_artifact(
+        &self,
+        hash: &str,
+        token: &str,
+        team_id: Option<&str>,
+        team_slug: Option<&str>,
+        method: Method,
+        range_start: Option<u64>,
+    ) -> Result<Option<Response>> {
+        let mut request_url = self.make_url(&format!("/v8/artifacts/{}", hash));
+        let mut allow_auth = true;
+
+        if self.use_preflight {
+            let request_headers = match range_start {
+                Some(_) => "Authorization, User-Agent, Range",
+                None => "Authorization, User-Agent",
+            };
+            let preflight_response = self
+                .do_preflight(token, &request_url, "GET", request_headers)
+                .await?;
+
+            allow_auth = preflight_response.allow_authorization_header;
+            request_url = preflight_response.location.to_string();
+        };
+
+        let mut request_builder = self
+            .client
+            .request(method, request_url)
+            .header("User-Agent", self.user_agent.clone());
+
+        if allow_auth {
+            request_builder = request_builder.header("Authorization", format!("Bearer {}", token));
+        }
+
+        if let Some(start) = range_start {
+            request_builder = request_builder.header("Range", format!("bytes={start}-"));
+        }
+
+        request_builder = Self::add_team_params(request_builder, team_id, team_slug);
+
+        let response = retry::make_retryable_request(request_builder).await?;
+
+        match response.status() {
+            StatusCode::FORBIDDEN => Err(Self::handle_403(response).await),
+            StatusCode::NOT_FOUND => Ok(None),
+            _ => Ok(Some(response.error_for_status()?)),
+        }
+    }
+
     fn add_team_params(
         mut request_builder: RequestBuilder,
         team_id: Option<&str>,
@@ -234,6 +234,16 @@ mod tests {
         ) -> turborepo_api_client::Result<Option<Response>> {
             unimplemented!("fetch_artifact")
         }
+        async fn fetch_artifact_range(
+            &self,
+            _hash: &str,
+            _start: u64,
+            _token: &str,
+            _team_id: Option<&str>,
+            _team_slug: Option<&str>,
+        ) -> turborepo_api_client::Result<Option<Response>> {
+            unimplemented!("fetch_artifact_range")
+        }
         async fn artifact_exists(
             &self,
             _hash: &str,
@@ -0,0 +1,101 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use tempfile::TempDir;
+use turbopath::AbsoluteSystemPathBuf;
+use turborepo_cache::cache_archive::CacheReader;
+
+// Roughly the shape of a framework's build output: a few large bundles
+// alongside many small chunks spread over nested directories
+const DIRECTORIES: usize = 50;
+const SMALL_FILES_PER_DIRECTORY: usize = 40;
+const SMALL_FILE_SIZE: usize = 16 * 1024;
+const LARGE_FILES: usize = 8;
+const LARGE_FILE_SIZE: usize = 8 * 1024 * 1024;
+
+fn create_artifact() -> (Vec<u8>, u64) {
+    let mut artifact = Vec::new();
+    let mut total_size = 0;
+    {
+        let encoder = zstd::Encoder::new(&mut artifact, 0).unwrap().auto_finish();
+        let mut builder = tar::Builder::new(encoder);
+        let mut append = |path: String, entry_type: tar::EntryType, contents: &[u8]| {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(entry_type);
+            header.set_size(contents.len() as u64);
+            header.set_mode(match entry_type {
+                tar::EntryType::Directory => 0o755,
+                _ => 0o644,
+            });
+            builder.append_data(&mut header, path, contents).unwrap();
+        };
+
+        append("dist".to_string(), tar::EntryType::Directory, &[]);
+        for dir in 0..DIRECTORIES {
+            append(format!("dist/{dir}"), tar::EntryType::Directory, &[]);
+            for file in 0..SMALL_FILES_PER_DIRECTORY {
+                let contents = contents(dir * SMALL_FILES_PER_DIRECTORY + file, SMALL_FILE_SIZE);
+                append(
+                    format!("dist/{dir}/{file}.js"),
+                    tar::EntryType::Regular,
+                    &contents,
+                );
+                total_size += contents.len() as u64;
+            }
+        }
+        for file in 0..LARGE_FILES {
+            let contents = contents(file, LARGE_FILE_SIZE);
+            append(
+                format!("dist/bundle-{file}.js"),
+                tar::EntryType::Regular,
+                &contents,
+            );
+            total_size += contents.len() as u64;
+        }
+
+        builder.finish().unwrap();
+    }
+
+    (artifact, total_size)
+}
+
+// Compressible but not trivially so, like minified JavaScript
+fn contents(seed: usize, size: usize) -> Vec<u8> {
+    (0..size)
+        .map(|i| b"abcdefghijklmnopqrstuvwxyz{}();=,."[(i * 31 + seed * 7 + i / 97) % 34])
+        .collect()
+}
+
+fn bench_restore(c: &mut Criterion) {
+    let (artifact, total_size) = create_artifact();
+
+    let mut group = c.benchmark_group("cache_restore");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(total_size));
+
+    let parallel = std::thread::available_parallelism().map_or(1, |n| n.get().min(8));
+    // No writers is the sequential restore, writing each file as it's read
+    for writers in [0, parallel] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{writers} writers")),
+            &writers,
+            |b, &writers| {
+                b.iter_batched(
+                    || TempDir::new().unwrap(),
+                    |dir| {
+                        let anchor = AbsoluteSystemPathBuf::try_from(dir.path()).unwrap();
+                        CacheReader::from_reader(artifact.as_slice(), true)
+                            .unwrap()
+                            .restore_with_writers(&anchor, writers)
+                            .unwrap();
+                        dir
+                    },
+                    BatchSize::PerIteration,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_restore);
+criterion_main!(benches);
@@ -174,7 +174,7 @@ mod tests {
 
     use crate::{
         test_cases::{get_test_cases, TestCase},
-        AsyncCache, CacheHitMetadata, CacheOpts, CacheSource, RemoteCacheOpts,
+        AsyncCache, CacheHitMetadata, CacheOpts, CacheSource, RemoteCacheBackend, RemoteCacheOpts,
     };
 
     #[tokio::test]
@@ -185,7 +185,8 @@ mod tests {
         try_join_all(get_test_cases().into_iter().map(|test_case| async move {
             round_trip_test_with_both_caches(&test_case, port).await?;
             round_trip_test_without_remote_cache(&test_case).await?;
-            round_trip_test_without_fs(&test_case, port).await
+            round_trip_test_without_fs(&test_case, port).await?;
+            round_trip_test_with_shared_dir(&test_case, port).await
         }))
         .await?;
 
@@ -193,6 +194,64 @@ mod tests {
         Ok(())
     }
 
+    async fn round_trip_test_with_shared_dir(test_case: &TestCase, port: u16) -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        test_case.initialize(&repo_root_path)?;
+        let shared_dir = tempdir()?;
+
+        let hash = format!("{}-shared-dir", test_case.hash);
+
+        let opts = CacheOpts {
+            override_dir: None,
+            remote_cache_read_only: false,
+            skip_remote: false,
+            skip_filesystem: true,
+            workers: 10,
+            remote_cache_opts: None,
+            remote_cache_backend: RemoteCacheBackend::Directory {
+                path: shared_dir.path().to_str().unwrap().to_string(),
+            },
+            ..Default::default()
+        };
+
+        // The shared directory doesn't need Vercel credentials
+        let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
+        let async_cache = AsyncCache::new(&opts, &repo_root_path, api_client, None, None)?;
+
+        let response = async_cache.exists(&hash).await;
+        assert_matches!(response, Ok(None));
+
+        async_cache
+            .put(
+                repo_root_path.clone(),
+                hash.clone(),
+                test_case
+                    .files
+                    .iter()
+                    .map(|f| f.path().to_owned())
+                    .collect(),
+                test_case.duration,
+            )
+            .await
+            .unwrap();
+
+        async_cache.wait().await;
+
+        assert!(shared_dir.path().join(format!("{}.tar.zst", hash)).exists());
+
+        let response = async_cache.exists(&hash).await?;
+        assert_eq!(
+            response,
+            Some(CacheHitMetadata {
+                source: CacheSource::Remote,
+                time_saved: test_case.duration
+            })
+        );
+
+        Ok(())
+    }
+
     async fn round_trip_test_without_fs(test_case: &TestCase, port: u16) -> Result<()> {
         let repo_root = tempdir()?;
         let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
@@ -210,6 +269,8 @@ mod tests {
                 team_id: "my-team".to_string(),
                 signature: false,
             }),
+            remote_cache_backend: RemoteCacheBackend::Vercel,
+            ..Default::default()
         };
 
         let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
@@ -284,6 +345,8 @@ mod tests {
                 team_id: "my-team".to_string(),
                 signature: false,
             }),
+            remote_cache_backend: RemoteCacheBackend::Vercel,
+            ..Default::default()
         };
 
         // Initialize client with invalid API url to ensure that we don't hit the
@@ -368,6 +431,8 @@ mod tests {
                 team_id: "my-team".to_string(),
                 signature: false,
             }),
+            remote_cache_backend: RemoteCacheBackend::Vercel,
+            ..Default::default()
         };
 
         let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use turbopath::{AbsoluteSystemPath, AnchoredSystemPathBuf};
+
+use crate::{
+    cache_archive::{CacheReader, CacheWriter},
+    http::HTTPCache,
+    CacheError, CacheHitMetadata,
+};
+
+/// A remote cache that the multiplexer consults after the file system cache.
+///
+/// Artifacts are identified by their task hash. Implementations restore
+/// fetched artifacts relative to `anchor` and report them as
+/// `CacheSource::Remote` hits.
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    async fn put(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+        files: &[AnchoredSystemPathBuf],
+        duration: u64,
+    ) -> Result<(), CacheError>;
+
+    async fn fetch(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+    ) -> Result<Option<(CacheHitMetadata, Vec<AnchoredSystemPathBuf>)>, CacheError>;
+
+    async fn exists(&self, hash: &str) -> Result<Option<CacheHitMetadata>, CacheError>;
+}
+
+/// Which remote cache to use, as configured by `remoteCache.backend` in
+/// turbo.json.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RemoteCacheBackend {
+    /// The Vercel Remote Cache API. Requires `turbo login` and `turbo link`.
+    #[default]
+    Vercel,
+    /// An S3 compatible object store, e.g. AWS S3 or MinIO.
+    S3(S3CacheOpts),
+    /// A directory shared between machines, e.g. an NFS mount.
+    #[serde(rename_all = "camelCase")]
+    Directory { path: String },
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct S3CacheOpts {
+    pub bucket: String,
+    /// Defaults to `us-east-1`.
+    pub region: Option<String>,
+    /// Endpoint of an S3 compatible store. When set, objects are addressed
+    /// path-style (`<endpoint>/<bucket>/<key>`), otherwise the AWS
+    /// virtual-hosted endpoint of the bucket is used.
+    pub endpoint: Option<String>,
+    /// Prepended to every object key, e.g. `turbo/`.
+    pub prefix: Option<String>,
+}
+
+/// Serializes `files` into the compressed tar format that is shared by all
+/// remote backends.
+pub(crate) fn create_artifact(
+    anchor: &AbsoluteSystemPath,
+    files: &[AnchoredSystemPathBuf],
+) -> Result<Vec<u8>, CacheError> {
+    let mut artifact_body = Vec::new();
+    {
+        let mut cache_archive = CacheWriter::from_writer(&mut artifact_body, true)?;
+        for file in files {
+            cache_archive.add_file(anchor, file)?;
+        }
+        cache_archive.finish()?;
+    }
+
+    Ok(artifact_body)
+}
+
+pub(crate) fn restore_artifact(
+    anchor: &AbsoluteSystemPath,
+    body: &[u8],
+) -> Result<Vec<AnchoredSystemPathBuf>, CacheError> {
+    let mut cache_reader = CacheReader::from_reader(body, true)?;
+    cache_reader.restore(anchor)
+}
+
+#[async_trait]
+impl CacheBackend for HTTPCache {
+    async fn put(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+        files: &[AnchoredSystemPathBuf],
+        duration: u64,
+    ) -> Result<(), CacheError> {
+        HTTPCache::put(self, anchor, hash, files, duration).await
+    }
+
+    // The HTTP cache always restores into the repo root it was created with.
+    async fn fetch(
+        &self,
+        _anchor: &AbsoluteSystemPath,
+        hash: &str,
+    ) -> Result<Option<(CacheHitMetadata, Vec<AnchoredSystemPathBuf>)>, CacheError> {
+        HTTPCache::fetch(self, hash).await
+    }
+
+    async fn exists(&self, hash: &str) -> Result<Option<CacheHitMetadata>, CacheError> {
+        HTTPCache::exists(self, hash).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(r#"{ "type": "vercel" }"#, RemoteCacheBackend::Vercel ; "vercel")]
+    #[test_case(
+        r#"{ "type": "directory", "path": "/mnt/turbo-cache" }"#,
+        RemoteCacheBackend::Directory { path: "/mnt/turbo-cache".to_string() }
+        ; "directory"
+    )]
+    #[test_case(
+        r#"{ "type": "s3", "bucket": "cache", "endpoint": "http://localhost:9000" }"#,
+        RemoteCacheBackend::S3(S3CacheOpts {
+            bucket: "cache".to_string(),
+            endpoint: Some("http://localhost:9000".to_string()),
+            ..Default::default()
+        })
+        ; "s3"
+    )]
+    fn test_deserialize_backend(json: &str, expected: RemoteCacheBackend) {
+        let backend: RemoteCacheBackend = serde_json::from_str(json).unwrap();
+        assert_eq!(backend, expected);
+    }
+}
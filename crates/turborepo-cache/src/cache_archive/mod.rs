@@ -2,6 +2,7 @@
 mod create;
 mod restore;
 mod restore_directory;
+mod restore_parallel;
 mod restore_regular;
 mod restore_symlink;
 
@@ -8,6 +8,7 @@ use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf
 use crate::{
     cache_archive::{
         restore_directory::{restore_directory, CachedDirTree},
+        restore_parallel::FileWriters,
         restore_regular::restore_regular,
         restore_symlink::{
             canonicalize_linkname, restore_symlink, restore_symlink_allow_missing_target,
@@ -61,6 +62,17 @@ impl<'a> CacheReader<'a> {
     pub fn restore(
         &mut self,
         anchor: &AbsoluteSystemPath,
+    ) -> Result<Vec<AnchoredSystemPathBuf>, CacheError> {
+        self.restore_with_writers(anchor, FileWriters::default_writers())
+    }
+
+    /// Restores the archive, writing the contents of regular files on
+    /// `writers` threads while the rest of the archive is read. With no
+    /// writers, every file is written in order as it is read.
+    pub fn restore_with_writers(
+        &mut self,
+        anchor: &AbsoluteSystemPath,
+        writers: usize,
     ) -> Result<Vec<AnchoredSystemPathBuf>, CacheError> {
         let mut restored = Vec::new();
         anchor.create_dir_all()?;
@@ -82,7 +94,13 @@ impl<'a> CacheReader<'a> {
         let dir_cache = CachedDirTree::new(anchor.to_owned());
         let mut tr = tar::Archive::new(&mut self.reader);
 
-        Self::restore_entries(&mut tr, &mut restored, dir_cache, anchor)?;
+        Self::restore_entries(
+            &mut tr,
+            &mut restored,
+            dir_cache,
+            anchor,
+            &mut FileWriters::new(writers),
+        )?;
         Ok(restored)
     }
 
@@ -91,6 +109,7 @@ impl<'a> CacheReader<'a> {
         restored: &mut Vec<AnchoredSystemPathBuf>,
         mut dir_cache: CachedDirTree,
         anchor: &AbsoluteSystemPath,
+        writers: &mut FileWriters,
     ) -> Result<(), CacheError> {
         // On first attempt to restore it's possible that a link target doesn't exist.
         // Save them and topologically sort them.
@@ -98,7 +117,7 @@ impl<'a> CacheReader<'a> {
 
         for entry in tr.entries()? {
             let mut entry = entry?;
-            match restore_entry(&mut dir_cache, anchor, &mut entry) {
+            match restore_entry(&mut dir_cache, anchor, &mut entry, writers) {
                 Err(CacheError::LinkTargetDoesNotExist(_, _)) => {
                     symlinks.push(entry);
                 }
@@ -107,6 +126,7 @@ impl<'a> CacheReader<'a> {
             }
         }
 
+        writers.finish()?;
         let mut restored_symlinks =
             Self::topologically_restore_symlinks(&mut dir_cache, anchor, &symlinks)?;
         restored.append(&mut restored_symlinks);
@@ -168,13 +188,19 @@ fn restore_entry<T: Read>(
     dir_cache: &mut CachedDirTree,
     anchor: &AbsoluteSystemPath,
     entry: &mut Entry<T>,
+    writers: &mut FileWriters,
 ) -> Result<AnchoredSystemPathBuf, CacheError> {
     let header = entry.header();
 
     match header.entry_type() {
         tar::EntryType::Directory => restore_directory(dir_cache, anchor, entry.header()),
-        tar::EntryType::Regular => restore_regular(dir_cache, anchor, entry),
-        tar::EntryType::Symlink => restore_symlink(dir_cache, anchor, entry.header()),
+        tar::EntryType::Regular => restore_regular(dir_cache, anchor, entry, writers),
+        tar::EntryType::Symlink => {
+            // Queued files may sit beneath the directory this link replaces or
+            // be the link's target, so they have to land first
+            writers.finish()?;
+            restore_symlink(dir_cache, anchor, entry.header())
+        }
         ty => Err(CacheError::RestoreUnsupportedFileType(
             ty,
             Backtrace::capture(),
@@ -868,45 +894,52 @@ mod tests {
         ];
 
         for is_compressed in [true, false] {
-            for test in &tests {
-                debug!("test: {}", test.name);
-                let input_dir = tempdir()?;
-                let archive_path = generate_tar(&input_dir, &test.input_files)?;
-                let output_dir = tempdir()?;
-                let output_dir_path = output_dir.path().to_string_lossy();
-                let anchor = AbsoluteSystemPath::new(&output_dir_path)?;
-
-                let archive_path = if is_compressed {
-                    compress_tar(&archive_path)?
-                } else {
-                    archive_path
-                };
-
-                let mut cache_reader = CacheReader::open(&archive_path)?;
-
-                match (cache_reader.restore(anchor), &test.expected_output) {
-                    (Ok(restored_files), Err(expected_error)) => {
-                        panic!(
-                            "expected error: {:?}, received {:?}",
-                            expected_error, restored_files
-                        );
+            // Files are written in parallel by default, check that it matches
+            // writing them one at a time
+            for writers in [0, 4] {
+                for test in &tests {
+                    debug!("test: {}", test.name);
+                    let input_dir = tempdir()?;
+                    let archive_path = generate_tar(&input_dir, &test.input_files)?;
+                    let output_dir = tempdir()?;
+                    let output_dir_path = output_dir.path().to_string_lossy();
+                    let anchor = AbsoluteSystemPath::new(&output_dir_path)?;
+
+                    let archive_path = if is_compressed {
+                        compress_tar(&archive_path)?
+                    } else {
+                        archive_path
+                    };
+
+                    let mut cache_reader = CacheReader::open(&archive_path)?;
+
+                    match (
+                        cache_reader.restore_with_writers(anchor, writers),
+                        &test.expected_output,
+                    ) {
+                        (Ok(restored_files), Err(expected_error)) => {
+                            panic!(
+                                "expected error: {:?}, received {:?}",
+                                expected_error, restored_files
+                            );
+                        }
+                        (Ok(restored_files), Ok(expected_files)) => {
+                            assert_eq!(&restored_files, expected_files);
+                        }
+                        (Err(err), Err(expected_error)) => {
+                            assert_eq!(&err.to_string(), expected_error);
+                            continue;
+                        }
+                        (Err(err), Ok(_)) => {
+                            panic!("unexpected error: {:?}", err);
+                        }
+                    };
+
+                    let expected_files = &test.expected_files;
+
+                    for expected_file in expected_files {
+                        assert_file_exists(anchor, expected_file)?;
                     }
-                    (Ok(restored_files), Ok(expected_files)) => {
-                        assert_eq!(&restored_files, expected_files);
-                    }
-                    (Err(err), Err(expected_error)) => {
-                        assert_eq!(&err.to_string(), expected_error);
-                        continue;
-                    }
-                    (Err(err), Ok(_)) => {
-                        panic!("unexpected error: {:?}", err);
-                    }
-                };
-
-                let expected_files = &test.expected_files;
-
-                for expected_file in expected_files {
-                    assert_file_exists(anchor, expected_file)?;
                 }
             }
         }
@@ -0,0 +1,204 @@
+use std::{
+    fs::OpenOptions,
+    io,
+    io::Read,
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+};
+
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use crate::CacheError;
+
+// Larger files are written by the thread reading the archive, so that the
+// memory held by queued files stays bounded
+const MAX_QUEUED_FILE_SIZE: u64 = 1024 * 1024;
+const QUEUED_FILES_PER_WRITER: usize = 4;
+
+struct QueuedFile {
+    path: AbsoluteSystemPathBuf,
+    mode: u32,
+    contents: Vec<u8>,
+}
+
+/// Writes the contents of regular files on a pool of threads while the
+/// archive continues to be read. Directories are created by the reading
+/// thread before a file is queued, so writers never race on them.
+pub struct FileWriters {
+    writers: usize,
+    sender: Option<SyncSender<QueuedFile>>,
+    handles: Vec<JoinHandle<io::Result<()>>>,
+}
+
+impl FileWriters {
+    /// With no writers, every file is written as soon as it's read
+    pub fn new(writers: usize) -> Self {
+        Self {
+            writers,
+            sender: None,
+            handles: Vec::new(),
+        }
+    }
+
+    pub fn default_writers() -> usize {
+        std::thread::available_parallelism().map_or(1, |n| n.get().min(8))
+    }
+
+    pub fn write(
+        &mut self,
+        path: AbsoluteSystemPathBuf,
+        mode: u32,
+        size: u64,
+        mut contents: impl Read,
+    ) -> Result<(), CacheError> {
+        if self.writers == 0 || size > MAX_QUEUED_FILE_SIZE {
+            write_file(&path, mode, contents)?;
+            return Ok(());
+        }
+
+        let mut buffer = Vec::with_capacity(size as usize);
+        contents.read_to_end(&mut buffer)?;
+        let file = QueuedFile {
+            path,
+            mode,
+            contents: buffer,
+        };
+        if self.sender().send(file).is_err() {
+            // Every writer has stopped, which only happens once one has failed
+            self.finish()?;
+            return Err(io::Error::new(io::ErrorKind::Other, "file writers stopped").into());
+        }
+
+        Ok(())
+    }
+
+    /// Waits for every queued file to be written. Writers are started again
+    /// for any files written afterwards.
+    pub fn finish(&mut self) -> Result<(), CacheError> {
+        self.sender = None;
+        let mut result = Ok(());
+        for handle in self.handles.drain(..) {
+            let written = handle.join().unwrap_or_else(|_| {
+                Err(io::Error::new(io::ErrorKind::Other, "file writer panicked"))
+            });
+            if result.is_ok() {
+                result = written;
+            }
+        }
+
+        Ok(result?)
+    }
+
+    fn sender(&mut self) -> &SyncSender<QueuedFile> {
+        let writers = self.writers;
+        let handles = &mut self.handles;
+        self.sender.get_or_insert_with(|| {
+            let (sender, receiver) = sync_channel(writers * QUEUED_FILES_PER_WRITER);
+            let receiver = Arc::new(Mutex::new(receiver));
+            handles.extend((0..writers).map(|_| {
+                let receiver = receiver.clone();
+                std::thread::spawn(move || write_queued_files(&receiver))
+            }));
+            sender
+        })
+    }
+}
+
+impl Drop for FileWriters {
+    fn drop(&mut self) {
+        // Errors have either been reported already or are superseded by the
+        // error that stopped the restore
+        let _ = self.finish();
+    }
+}
+
+fn write_queued_files(receiver: &Mutex<Receiver<QueuedFile>>) -> io::Result<()> {
+    loop {
+        let next = receiver.lock().expect("file queue poisoned").recv();
+        let Ok(file) = next else {
+            return Ok(());
+        };
+        write_file(&file.path, file.mode, file.contents.as_slice())?;
+    }
+}
+
+fn write_file(path: &AbsoluteSystemPath, mode: u32, mut contents: impl Read) -> io::Result<()> {
+    let mut open_options = OpenOptions::new();
+    open_options.write(true).truncate(true).create(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        open_options.mode(mode);
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    let mut file = open_options.open(path.as_path())?;
+    io::copy(&mut contents, &mut file)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::FileWriters;
+
+    #[test]
+    fn test_writes_queued_and_large_files() -> Result<()> {
+        let dir = tempdir()?;
+        let dir = AbsoluteSystemPathBuf::try_from(dir.path())?;
+        let large = vec![b'a'; super::MAX_QUEUED_FILE_SIZE as usize + 1];
+
+        let mut writers = FileWriters::new(2);
+        for i in 0..20 {
+            let contents = format!("file {i}");
+            writers.write(
+                dir.join_component(&format!("{i}.txt")),
+                0o644,
+                contents.len() as u64,
+                contents.as_bytes(),
+            )?;
+        }
+        writers.write(
+            dir.join_component("large.txt"),
+            0o644,
+            large.len() as u64,
+            large.as_slice(),
+        )?;
+        writers.finish()?;
+
+        for i in 0..20 {
+            assert_eq!(
+                dir.join_component(&format!("{i}.txt")).read_to_string()?,
+                format!("file {i}")
+            );
+        }
+        assert_eq!(dir.join_component("large.txt").read()?, large);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reports_failed_writes() -> Result<()> {
+        let dir = tempdir()?;
+        let dir = AbsoluteSystemPathBuf::try_from(dir.path())?;
+
+        let mut writers = FileWriters::new(2);
+        // The parent directory doesn't exist, so the write fails
+        writers.write(
+            dir.join_components(&["missing", "file.txt"]),
+            0o644,
+            4,
+            b"file".as_slice(),
+        )?;
+        assert!(writers.finish().is_err());
+        Ok(())
+    }
+}
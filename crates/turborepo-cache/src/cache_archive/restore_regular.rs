@@ -1,16 +1,22 @@
-use std::{fs::OpenOptions, io, io::Read, path::Path};
+use std::{io::Read, path::Path};
 
 use tar::Entry;
 use turbopath::{AbsoluteSystemPath, AnchoredSystemPath, AnchoredSystemPathBuf};
 
-use crate::{cache_archive::restore_directory::CachedDirTree, CacheError};
+use crate::{
+    cache_archive::{restore_directory::CachedDirTree, restore_parallel::FileWriters},
+    CacheError,
+};
 
 pub fn restore_regular(
     dir_cache: &mut CachedDirTree,
     anchor: &AbsoluteSystemPath,
     entry: &mut Entry<impl Read>,
+    writers: &mut FileWriters,
 ) -> Result<AnchoredSystemPathBuf, CacheError> {
     let header = entry.header();
+    let mode = header.mode()?;
+    let size = header.size()?;
     // Assuming this was a `turbo`-created input, we currently have an
     // RelativeUnixPath. Assuming this is malicious input we don't really care
     // if we do the wrong thing.
@@ -26,17 +32,7 @@ pub fn restore_regular(
     dir_cache.safe_mkdir_file(anchor, &processed_name)?;
 
     let resolved_path = anchor.resolve(&processed_name);
-    let mut open_options = OpenOptions::new();
-    open_options.write(true).truncate(true).create(true);
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::OpenOptionsExt;
-        open_options.mode(header.mode()?);
-    }
-
-    let mut file = open_options.open(resolved_path.as_path())?;
-    io::copy(entry, &mut file)?;
+    writers.write(resolved_path, mode, size, entry)?;
 
     Ok(processed_name)
 }
@@ -0,0 +1,443 @@
+use std::{
+    backtrace::Backtrace,
+    collections::HashSet,
+    fs::Metadata,
+    io,
+    io::{Read, Write},
+    sync::mpsc::{sync_channel, Receiver, SyncSender},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tar::{EntryType, Header};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
+
+use crate::{cache_archive::CacheReader, CacheError};
+
+const MANIFEST_VERSION: u32 = 1;
+// Chunks of the generated tar that can be buffered ahead of the restore
+const PIPE_BUFFER: usize = 16;
+const PIPE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Lists the files of an artifact. Regular files refer to the SHA-256 of
+/// their contents, which are stored as blobs, so that identical files are
+/// stored once however many artifacts contain them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    version: u32,
+    entries: Vec<ManifestEntry>,
+}
+
+// Paths are unix paths, with a trailing slash for directories, as they are
+// in tarballs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum ManifestEntry {
+    Directory {
+        path: String,
+        mode: u32,
+    },
+    File {
+        path: String,
+        mode: u32,
+        size: u64,
+        blob: String,
+    },
+    Symlink {
+        path: String,
+        target: String,
+    },
+}
+
+/// A file whose contents need to be stored as the blob `hash`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobSource {
+    pub hash: String,
+    pub path: AbsoluteSystemPathBuf,
+}
+
+impl Manifest {
+    /// Describes `files`, returning the manifest along with the files whose
+    /// contents make up its blobs. Each blob is listed once.
+    pub fn create(
+        anchor: &AbsoluteSystemPath,
+        files: &[AnchoredSystemPathBuf],
+    ) -> Result<(Self, Vec<BlobSource>), CacheError> {
+        let mut entries = Vec::with_capacity(files.len());
+        let mut blobs = Vec::new();
+        let mut seen_blobs = HashSet::new();
+
+        for file in files {
+            let source_path = anchor.resolve(file);
+            let file_info = source_path.symlink_metadata()?;
+            let mut path = file.to_unix();
+            path.make_canonical_for_tar(file_info.is_dir());
+            let path = path.to_string();
+
+            let entry = if file_info.is_symlink() {
+                ManifestEntry::Symlink {
+                    path,
+                    target: source_path.read_link()?.to_string(),
+                }
+            } else if file_info.is_dir() {
+                ManifestEntry::Directory {
+                    path,
+                    mode: mode(&file_info),
+                }
+            } else if file_info.is_file() {
+                let hash = hash_file(&source_path)?;
+                if seen_blobs.insert(hash.clone()) {
+                    blobs.push(BlobSource {
+                        hash: hash.clone(),
+                        path: source_path,
+                    });
+                }
+                ManifestEntry::File {
+                    path,
+                    mode: mode(&file_info),
+                    size: file_info.len(),
+                    blob: hash,
+                }
+            } else {
+                return Err(CacheError::CreateUnsupportedFileType(Backtrace::capture()));
+            };
+            entries.push(entry);
+        }
+
+        Ok((
+            Manifest {
+                version: MANIFEST_VERSION,
+                entries,
+            },
+            blobs,
+        ))
+    }
+
+    pub fn from_slice(body: &[u8]) -> Result<Self, CacheError> {
+        let manifest: Manifest = serde_json::from_slice(body)
+            .map_err(|e| CacheError::InvalidManifest(e.to_string(), Backtrace::capture()))?;
+        if manifest.version != MANIFEST_VERSION {
+            return Err(CacheError::InvalidManifest(
+                format!("unsupported version {}", manifest.version),
+                Backtrace::capture(),
+            ));
+        }
+        // Blob hashes become file names and URLs
+        if let Some(blob) = manifest
+            .blobs()
+            .into_iter()
+            .find(|blob| !is_blob_hash(blob))
+        {
+            return Err(CacheError::InvalidManifest(
+                format!("invalid blob hash {blob}"),
+                Backtrace::capture(),
+            ));
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("manifest is serializable")
+    }
+
+    /// The hashes of every blob the manifest refers to, without duplicates
+    pub fn blobs(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                ManifestEntry::File { blob, .. } => Some(blob.as_str()),
+                _ => None,
+            })
+            .filter(|blob| seen.insert(*blob))
+            .collect()
+    }
+
+    /// Restores the files of the manifest into `anchor`, reading the contents
+    /// of each blob from `open_blob`.
+    ///
+    /// The files are written as a tarball that is restored as it's written,
+    /// so manifests get the same protections against escaping `anchor` as
+    /// tarballs do.
+    pub fn restore<R: Read>(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        open_blob: impl FnMut(&str) -> Result<R, CacheError> + Send,
+    ) -> Result<Vec<AnchoredSystemPathBuf>, CacheError> {
+        std::thread::scope(|scope| {
+            let (sender, receiver) = sync_channel(PIPE_BUFFER);
+            let writer = scope.spawn(move || {
+                match self.write_tar(PipeWriter::new(sender), open_blob) {
+                    // The restore stopped early and will report why
+                    Err(CacheError::IO(e, _)) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+                    result => result,
+                }
+            });
+
+            let restored = CacheReader::from_reader(PipeReader::new(receiver), false)
+                .and_then(|mut cache_reader| cache_reader.restore(anchor));
+            // A tarball that ends early looks complete, so errors writing it
+            // take precedence
+            writer.join().expect("manifest writer panicked")?;
+            restored
+        })
+    }
+
+    fn write_tar<R: Read>(
+        &self,
+        writer: impl Write,
+        mut open_blob: impl FnMut(&str) -> Result<R, CacheError>,
+    ) -> Result<(), CacheError> {
+        let mut builder = tar::Builder::new(writer);
+        for entry in &self.entries {
+            let mut header = Header::new_gnu();
+            header.set_uid(0);
+            header.set_gid(0);
+            header.set_mtime(0);
+            match entry {
+                ManifestEntry::Directory { path, mode } => {
+                    header.set_entry_type(EntryType::Directory);
+                    header.set_mode(*mode);
+                    header.set_size(0);
+                    builder.append_data(&mut header, path, io::empty())?;
+                }
+                ManifestEntry::File {
+                    path,
+                    mode,
+                    size,
+                    blob,
+                } => {
+                    header.set_entry_type(EntryType::Regular);
+                    header.set_mode(*mode);
+                    header.set_size(*size);
+                    builder.append_data(&mut header, path, open_blob(blob)?)?;
+                }
+                ManifestEntry::Symlink { path, target } => {
+                    header.set_entry_type(EntryType::Symlink);
+                    header.set_mode(0o777);
+                    header.set_size(0);
+                    builder.append_link(&mut header, path, target)?;
+                }
+            }
+        }
+        builder.into_inner()?.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Manifests are JSON, while tarballs are always compressed, so the first
+/// byte tells them apart
+pub fn is_manifest(body: &[u8]) -> bool {
+    body.first() == Some(&b'{')
+}
+
+fn is_blob_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+pub fn hash_file(path: &AbsoluteSystemPath) -> Result<String, CacheError> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut path.open()?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Checks that a compressed blob has the contents its hash says it has
+pub fn verify_blob(hash: &str, compressed: &[u8]) -> Result<(), CacheError> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut zstd::Decoder::new(compressed)?, &mut hasher)?;
+    if hex::encode(hasher.finalize()) != hash {
+        return Err(CacheError::InvalidBlob(
+            hash.to_string(),
+            Backtrace::capture(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn mode(file_info: &Metadata) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        file_info.mode()
+    }
+    #[cfg(windows)]
+    {
+        // Matches the mode tarballs record for files created on Windows
+        let _ = file_info;
+        0o755
+    }
+}
+
+struct PipeWriter {
+    sender: SyncSender<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl PipeWriter {
+    fn new(sender: SyncSender<Vec<u8>>) -> Self {
+        Self {
+            sender,
+            buffer: Vec::with_capacity(PIPE_CHUNK_SIZE),
+        }
+    }
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= PIPE_CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(PIPE_CHUNK_SIZE));
+        self.sender
+            .send(chunk)
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+}
+
+struct PipeReader {
+    receiver: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl PipeReader {
+    fn new(receiver: Receiver<Vec<u8>>) -> Self {
+        Self {
+            receiver,
+            chunk: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                // The writer is done
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn create_files(anchor: &AbsoluteSystemPath) -> Result<Vec<AnchoredSystemPathBuf>> {
+        anchor.join_component("dist").create_dir_all()?;
+        anchor
+            .join_components(&["dist", "a.js"])
+            .create_with_contents("same")?;
+        anchor
+            .join_components(&["dist", "b.js"])
+            .create_with_contents("same")?;
+        anchor
+            .join_components(&["dist", "c.js"])
+            .create_with_contents("different")?;
+
+        Ok(["dist", "dist/a.js", "dist/b.js", "dist/c.js"]
+            .into_iter()
+            .map(AnchoredSystemPathBuf::from_raw)
+            .collect::<Result<_, _>>()?)
+    }
+
+    #[test]
+    fn test_identical_files_share_a_blob() -> Result<()> {
+        let dir = tempdir()?;
+        let anchor = AbsoluteSystemPath::from_std_path(dir.path())?;
+        let files = create_files(anchor)?;
+
+        let (manifest, blobs) = Manifest::create(anchor, &files)?;
+
+        assert_eq!(blobs.len(), 2);
+        assert_eq!(manifest.blobs().len(), 2);
+        assert_eq!(Manifest::from_slice(&manifest.to_vec())?, manifest);
+        assert!(is_manifest(&manifest.to_vec()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore() -> Result<()> {
+        let source = tempdir()?;
+        let source = AbsoluteSystemPath::from_std_path(source.path())?;
+        let files = create_files(source)?;
+        let (manifest, blobs) = Manifest::create(source, &files)?;
+        let contents = blobs
+            .iter()
+            .map(|blob| Ok((blob.hash.clone(), blob.path.read()?)))
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let output = tempdir()?;
+        let output = AbsoluteSystemPath::from_std_path(output.path())?;
+        let restored = manifest.restore(output, |blob| Ok(contents[blob].as_slice()))?;
+
+        assert_eq!(restored, files);
+        assert_eq!(
+            output.join_components(&["dist", "b.js"]).read_to_string()?,
+            "same"
+        );
+        assert_eq!(
+            output.join_components(&["dist", "c.js"]).read_to_string()?,
+            "different"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_missing_blob() -> Result<()> {
+        let source = tempdir()?;
+        let source = AbsoluteSystemPath::from_std_path(source.path())?;
+        let files = create_files(source)?;
+        let (manifest, _) = Manifest::create(source, &files)?;
+
+        let output = tempdir()?;
+        let output = AbsoluteSystemPath::from_std_path(output.path())?;
+        let result = manifest.restore(output, |blob| -> Result<&[u8], CacheError> {
+            Err(CacheError::BlobMissing(
+                blob.to_string(),
+                Backtrace::capture(),
+            ))
+        });
+
+        assert!(matches!(result, Err(CacheError::BlobMissing(..))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_blob() -> Result<()> {
+        let contents = b"hello world";
+        let hash = hex::encode(Sha256::digest(contents));
+        let compressed = zstd::encode_all(contents.as_slice(), 0)?;
+
+        assert!(verify_blob(&hash, &compressed).is_ok());
+        assert!(verify_blob(&"0".repeat(64), &compressed).is_err());
+        Ok(())
+    }
+}
@@ -1,32 +1,147 @@
-use std::{backtrace::Backtrace, fs::OpenOptions};
+use std::{
+    backtrace::Backtrace,
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, ErrorKind},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use camino::Utf8Path;
 use serde::{Deserialize, Serialize};
+use tracing::debug;
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
 use turborepo_analytics::AnalyticsSender;
 use turborepo_api_client::{analytics, analytics::AnalyticsEvent};
 
 use crate::{
     cache_archive::{CacheReader, CacheWriter},
-    CacheError, CacheHitMetadata, CacheSource,
+    content_addressed::{BlobSource, Manifest},
+    ArtifactFormat, CacheError, CacheHitMetadata, CacheSource,
 };
 
+// Distinguishes the temporary files of blobs written concurrently
+static BLOB_WRITES: AtomicU64 = AtomicU64::new(0);
+
+// Blobs are written before the manifest that refers to them, so a blob that
+// no manifest refers to yet may belong to an artifact that's still being put.
+// Eviction leaves blobs alone until they're this old.
+const BLOB_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
 pub struct FSCache {
     cache_directory: AbsoluteSystemPathBuf,
     analytics_recorder: Option<AnalyticsSender>,
+    artifact_format: ArtifactFormat,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-struct CacheMetadata {
-    hash: String,
-    duration: u64,
+pub(crate) struct CacheMetadata {
+    pub(crate) hash: String,
+    pub(crate) duration: u64,
 }
 
 impl CacheMetadata {
-    fn read(path: &AbsoluteSystemPath) -> Result<CacheMetadata, CacheError> {
+    pub(crate) fn new(hash: &str, duration: u64) -> Self {
+        CacheMetadata {
+            hash: hash.to_string(),
+            duration,
+        }
+    }
+
+    pub(crate) fn read(path: &AbsoluteSystemPath) -> Result<CacheMetadata, CacheError> {
         serde_json::from_str(&path.read_to_string()?)
             .map_err(|e| CacheError::InvalidMetadata(e, Backtrace::capture()))
     }
+
+    fn write(&self, path: &AbsoluteSystemPath) -> Result<(), CacheError> {
+        let contents = serde_json::to_vec(self)
+            .map_err(|e| CacheError::MetadataWriteFailure(e, Backtrace::capture()))?;
+        path.create_with_contents(contents)?;
+        Ok(())
+    }
+}
+
+// The last access of an entry is the newest modification time of its files,
+// so restoring an entry only needs to bump that instead of rewriting a file
+fn touch(path: &AbsoluteSystemPath, time: SystemTime) -> std::io::Result<()> {
+    File::options()
+        .write(true)
+        .open(path.as_std_path())?
+        .set_modified(time)
+}
+
+fn written_recently(path: &AbsoluteSystemPath) -> bool {
+    path.symlink_metadata()
+        .ok()
+        .and_then(|metadata| metadata.modified().ok())
+        .map_or(false, |modified| {
+            modified
+                .elapsed()
+                .map_or(true, |age| age < BLOB_GRACE_PERIOD)
+        })
+}
+
+// Blobs are spread over subdirectories by the first byte of their hash to
+// keep directories small
+fn blob_path(cache_directory: &AbsoluteSystemPath, hash: &str) -> AbsoluteSystemPathBuf {
+    cache_directory.join_components(&["blobs", &hash[..2], &format!("{hash}.zst")])
+}
+
+fn open_blob(
+    cache_directory: &AbsoluteSystemPath,
+    hash: &str,
+) -> Result<zstd::Decoder<'static, BufReader<File>>, CacheError> {
+    let file = match blob_path(cache_directory, hash).open() {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            return Err(CacheError::BlobMissing(
+                hash.to_string(),
+                Backtrace::capture(),
+            ))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(zstd::Decoder::new(file)?)
+}
+
+/// Limits for the file system cache. Entries that exceed them are evicted,
+/// least recently used first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EvictionPolicy {
+    /// The maximum total size of all cache entries in bytes.
+    pub max_size: Option<u64>,
+    /// The maximum time since an entry was last written or restored.
+    pub max_age: Option<Duration>,
+}
+
+impl EvictionPolicy {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_size.is_none() && self.max_age.is_none()
+    }
+}
+
+/// An artifact in the file system cache together with its metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub hash: String,
+    pub size: u64,
+    pub last_accessed: SystemTime,
+    files: Vec<AbsoluteSystemPathBuf>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub entries: usize,
+    pub total_size: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvictionSummary {
+    pub evicted: CacheStats,
+    pub remaining: CacheStats,
 }
 
 impl FSCache {
@@ -52,9 +167,23 @@ impl FSCache {
         Ok(FSCache {
             cache_directory,
             analytics_recorder,
+            artifact_format: ArtifactFormat::default(),
         })
     }
 
+    /// Sets the format new artifacts are written in. Artifacts in either
+    /// format can always be restored, and in the content addressed format
+    /// tarballs are converted as they're restored.
+    pub fn with_artifact_format(mut self, artifact_format: ArtifactFormat) -> Self {
+        self.artifact_format = artifact_format;
+        self
+    }
+
+    fn manifest_path(&self, hash: &str) -> AbsoluteSystemPathBuf {
+        self.cache_directory
+            .join_component(&format!("{}-manifest.json", hash))
+    }
+
     fn log_fetch(&self, event: analytics::CacheEvent, hash: &str, duration: u64) {
         // If analytics fails to record, it's not worth failing the cache
         if let Some(analytics_recorder) = &self.analytics_recorder {
@@ -81,25 +210,49 @@ impl FSCache {
         let compressed_cache_path = self
             .cache_directory
             .join_component(&format!("{}.tar.zst", hash));
-
-        let cache_path = if uncompressed_cache_path.exists() {
-            uncompressed_cache_path
-        } else if compressed_cache_path.exists() {
-            compressed_cache_path
+        let manifest_path = self.manifest_path(hash);
+
+        let restored_files = if manifest_path.exists() {
+            let manifest = Manifest::from_slice(&manifest_path.read()?)?;
+            // Blobs can be evicted with another artifact, which leaves this
+            // one incomplete
+            if manifest
+                .blobs()
+                .iter()
+                .any(|blob| !blob_path(&self.cache_directory, blob).exists())
+            {
+                self.log_fetch(analytics::CacheEvent::Miss, hash, 0);
+                return Ok(None);
+            }
+            manifest.restore(anchor, |blob| open_blob(&self.cache_directory, blob))?
         } else {
-            self.log_fetch(analytics::CacheEvent::Miss, hash, 0);
-            return Ok(None);
-        };
+            let cache_path = if uncompressed_cache_path.exists() {
+                uncompressed_cache_path
+            } else if compressed_cache_path.exists() {
+                compressed_cache_path
+            } else {
+                self.log_fetch(analytics::CacheEvent::Miss, hash, 0);
+                return Ok(None);
+            };
 
-        let mut cache_reader = CacheReader::open(&cache_path)?;
+            let restored_files = CacheReader::open(&cache_path)?.restore(anchor)?;
 
-        let restored_files = cache_reader.restore(anchor)?;
+            if self.artifact_format == ArtifactFormat::ContentAddressed {
+                if let Err(err) = self.migrate(anchor, hash, &restored_files, &cache_path) {
+                    debug!("failed to convert {hash} to a content addressed artifact: {err}");
+                }
+            }
+            restored_files
+        };
 
-        let meta = CacheMetadata::read(
-            &self
-                .cache_directory
-                .join_component(&format!("{}-meta.json", hash)),
-        )?;
+        let metadata_path = self
+            .cache_directory
+            .join_component(&format!("{}-meta.json", hash));
+        let meta = CacheMetadata::read(&metadata_path)?;
+
+        // Recording the access is only needed for eviction, a failure here
+        // shouldn't fail the restore.
+        let _ = touch(&metadata_path, SystemTime::now());
 
         self.log_fetch(analytics::CacheEvent::Hit, hash, meta.duration);
 
@@ -120,7 +273,10 @@ impl FSCache {
             .cache_directory
             .join_component(&format!("{}.tar.zst", hash));
 
-        if !uncompressed_cache_path.exists() && !compressed_cache_path.exists() {
+        if !uncompressed_cache_path.exists()
+            && !compressed_cache_path.exists()
+            && !self.manifest_path(hash).exists()
+        {
             return Ok(None);
         }
 
@@ -145,35 +301,285 @@ impl FSCache {
         files: &[AnchoredSystemPathBuf],
         duration: u64,
     ) -> Result<(), CacheError> {
-        let cache_path = self
-            .cache_directory
-            .join_component(&format!("{}.tar.zst", hash));
+        match self.artifact_format {
+            ArtifactFormat::Tarball => {
+                let cache_path = self
+                    .cache_directory
+                    .join_component(&format!("{}.tar.zst", hash));
 
-        let mut cache_item = CacheWriter::create(&cache_path)?;
+                let mut cache_item = CacheWriter::create(&cache_path)?;
 
-        for file in files {
-            cache_item.add_file(anchor, file)?;
+                for file in files {
+                    cache_item.add_file(anchor, file)?;
+                }
+            }
+            ArtifactFormat::ContentAddressed => {
+                let (manifest, blobs) = Manifest::create(anchor, files)?;
+                for blob in &blobs {
+                    self.put_blob(blob)?;
+                }
+                // Written last, so that a manifest is never missing its blobs
+                self.manifest_path(hash)
+                    .create_with_contents(manifest.to_vec())?;
+            }
         }
 
         let metadata_path = self
             .cache_directory
             .join_component(&format!("{}-meta.json", hash));
 
-        let meta = CacheMetadata {
-            hash: hash.to_string(),
-            duration,
-        };
+        CacheMetadata::new(hash, duration).write(&metadata_path)?;
 
-        let mut metadata_options = OpenOptions::new();
-        metadata_options.create(true).write(true);
+        Ok(())
+    }
 
-        let metadata_file = metadata_path.open_with_options(metadata_options)?;
+    fn put_blob(&self, blob: &BlobSource) -> Result<(), CacheError> {
+        let path = blob_path(&self.cache_directory, &blob.hash);
+        match touch(&path, SystemTime::now()) {
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            // The blob already exists. Touching it keeps eviction from
+            // removing it before the manifest that reuses it is written.
+            _ => return Ok(()),
+        }
+        path.ensure_dir()?;
+
+        // Blobs are written to a temporary file first so that a blob is never
+        // seen half written, even by other processes
+        let tmp_path = path
+            .parent()
+            .expect("blob has a parent")
+            .join_component(&format!(
+                "{}.{}-{}.tmp",
+                blob.hash,
+                std::process::id(),
+                BLOB_WRITES.fetch_add(1, Ordering::Relaxed)
+            ));
+        let written = (|| {
+            let source = blob.path.open()?;
+            let tmp_file = File::create(tmp_path.as_std_path())?;
+            zstd::stream::copy_encode(source, tmp_file, 0)?;
+            tmp_path.rename(&path)
+        })();
+        if written.is_err() {
+            let _ = tmp_path.remove_file();
+        }
 
-        serde_json::to_writer(metadata_file, &meta)
-            .map_err(|e| CacheError::InvalidMetadata(e, Backtrace::capture()))?;
+        Ok(written?)
+    }
+
+    // Rewrites a tarball as a manifest, using the files that were just
+    // restored from it
+    fn migrate(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+        files: &[AnchoredSystemPathBuf],
+        tarball_path: &AbsoluteSystemPath,
+    ) -> Result<(), CacheError> {
+        let (manifest, blobs) = Manifest::create(anchor, files)?;
+        for blob in &blobs {
+            self.put_blob(blob)?;
+        }
+        self.manifest_path(hash)
+            .create_with_contents(manifest.to_vec())?;
+        tarball_path.remove_file()?;
 
         Ok(())
     }
+
+    /// Lists all entries in the cache, least recently used first.
+    ///
+    /// Blobs shared between entries are counted towards the most recently
+    /// used of them, as they're only removed along with it.
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, CacheError> {
+        Ok(self.entries_and_orphaned_blobs()?.0)
+    }
+
+    fn entries_and_orphaned_blobs(
+        &self,
+    ) -> Result<(Vec<CacheEntry>, Vec<AbsoluteSystemPathBuf>), CacheError> {
+        let mut entries: HashMap<String, CacheEntry> = HashMap::new();
+        let read_dir = match std::fs::read_dir(self.cache_directory.as_std_path()) {
+            Ok(read_dir) => read_dir,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok((Vec::new(), Vec::new())),
+            Err(err) => return Err(err.into()),
+        };
+
+        for dir_entry in read_dir {
+            let dir_entry = dir_entry?;
+            let file_name = dir_entry.file_name();
+            let Some(file_name) = file_name.to_str() else {
+                continue;
+            };
+            let Some(hash) = [".tar.zst", ".tar", "-meta.json", "-manifest.json"]
+                .iter()
+                .find_map(|suffix| file_name.strip_suffix(suffix))
+            else {
+                continue;
+            };
+            let metadata = dir_entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+
+            let entry = entries
+                .entry(hash.to_string())
+                .or_insert_with(|| CacheEntry {
+                    hash: hash.to_string(),
+                    size: 0,
+                    last_accessed: UNIX_EPOCH,
+                    files: Vec::new(),
+                });
+            entry.size += metadata.len();
+            if let Ok(modified) = metadata.modified() {
+                entry.last_accessed = entry.last_accessed.max(modified);
+            }
+            entry
+                .files
+                .push(self.cache_directory.join_component(file_name));
+        }
+
+        let mut entries = entries.into_values().collect::<Vec<_>>();
+        entries.sort_by(|a, b| {
+            a.last_accessed
+                .cmp(&b.last_accessed)
+                .then_with(|| a.hash.cmp(&b.hash))
+        });
+
+        let mut blobs = self.blobs()?;
+        if !blobs.is_empty() {
+            for entry in entries.iter_mut().rev() {
+                let Ok(manifest) = self
+                    .manifest_path(&entry.hash)
+                    .read()
+                    .map_err(CacheError::from)
+                    .and_then(|manifest| Manifest::from_slice(&manifest))
+                else {
+                    continue;
+                };
+                for blob in manifest.blobs() {
+                    if let Some((path, size)) = blobs.remove(blob) {
+                        entry.size += size;
+                        entry.files.push(path);
+                    }
+                }
+            }
+        }
+        let orphaned_blobs = blobs.into_values().map(|(path, _)| path).collect();
+
+        Ok((entries, orphaned_blobs))
+    }
+
+    // The path and size of every blob, by hash
+    fn blobs(&self) -> Result<HashMap<String, (AbsoluteSystemPathBuf, u64)>, CacheError> {
+        let mut blobs = HashMap::new();
+        let blobs_dir = self.cache_directory.join_component("blobs");
+        let prefixes = match std::fs::read_dir(blobs_dir.as_std_path()) {
+            Ok(prefixes) => prefixes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(blobs),
+            Err(err) => return Err(err.into()),
+        };
+
+        for prefix in prefixes {
+            let prefix = prefix?;
+            let Some(prefix_name) = prefix.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if !prefix.file_type()?.is_dir() {
+                continue;
+            }
+            for blob in std::fs::read_dir(prefix.path())? {
+                let blob = blob?;
+                let file_name = blob.file_name();
+                let Some(hash) = file_name
+                    .to_str()
+                    .and_then(|name| name.strip_suffix(".zst"))
+                else {
+                    continue;
+                };
+                let metadata = blob.metadata()?;
+                if !metadata.is_file() {
+                    continue;
+                }
+                let path = blobs_dir.join_components(&[&prefix_name, &format!("{hash}.zst")]);
+                blobs.insert(hash.to_string(), (path, metadata.len()));
+            }
+        }
+
+        Ok(blobs)
+    }
+
+    pub fn stats(&self) -> Result<CacheStats, CacheError> {
+        let entries = self.entries()?;
+        Ok(CacheStats {
+            entries: entries.len(),
+            total_size: entries.iter().map(|entry| entry.size).sum(),
+        })
+    }
+
+    /// Removes entries that are older than `policy.max_age` and then the
+    /// least recently used entries until the cache fits in `policy.max_size`.
+    pub fn evict(
+        &self,
+        policy: &EvictionPolicy,
+        now: SystemTime,
+    ) -> Result<EvictionSummary, CacheError> {
+        let (entries, orphaned_blobs) = self.entries_and_orphaned_blobs()?;
+        // Left behind by interrupted writes or migrations, unless they were
+        // written by a put that hasn't written its manifest yet
+        for blob in orphaned_blobs {
+            if !written_recently(&blob) {
+                let _ = blob.remove_file();
+            }
+        }
+        let blobs_dir = self.cache_directory.join_component("blobs");
+
+        let mut remaining_size: u64 = entries.iter().map(|entry| entry.size).sum();
+        let mut remaining_entries = entries.len();
+        let mut evicted = CacheStats::default();
+
+        for entry in entries {
+            let too_old = policy.max_age.map_or(false, |max_age| {
+                now.duration_since(entry.last_accessed)
+                    .map_or(false, |age| age > max_age)
+            });
+            let too_large = policy
+                .max_size
+                .map_or(false, |max_size| remaining_size > max_size);
+            // Entries are sorted by last access, so once an entry fits both
+            // limits all newer ones do too.
+            if !too_old && !too_large {
+                break;
+            }
+
+            // Manifests go first, so that an interrupted eviction can't leave
+            // one referring to blobs that were removed
+            for file in &entry.files {
+                // A concurrent put may be reusing the blob
+                if file.as_std_path().starts_with(blobs_dir.as_std_path()) && written_recently(file)
+                {
+                    continue;
+                }
+                match file.remove_file() {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == ErrorKind::NotFound => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            remaining_size -= entry.size;
+            remaining_entries -= 1;
+            evicted.entries += 1;
+            evicted.total_size += entry.size;
+        }
+
+        Ok(EvictionSummary {
+            evicted,
+            remaining: CacheStats {
+                entries: remaining_entries,
+                total_size: remaining_size,
+            },
+        })
+    }
 }
 
 #[cfg(test)]
@@ -258,4 +664,297 @@ mod test {
         analytics_handle.close_with_timeout().await;
         Ok(())
     }
+
+    fn put_entry(
+        cache: &FSCache,
+        repo_root: &AbsoluteSystemPath,
+        hash: &str,
+        last_accessed_secs: u64,
+    ) -> Result<()> {
+        let file = AnchoredSystemPathBuf::from_raw(format!("{hash}.txt"))?;
+        repo_root.resolve(&file).create_with_contents(hash)?;
+        cache.put(repo_root, hash, &[file], 0)?;
+        set_last_accessed(cache, hash, last_accessed_secs)
+    }
+
+    fn set_last_accessed(cache: &FSCache, hash: &str, last_accessed_secs: u64) -> Result<()> {
+        let time = UNIX_EPOCH + Duration::from_secs(last_accessed_secs);
+        for suffix in [".tar.zst", "-meta.json", "-manifest.json"] {
+            let path = cache
+                .cache_directory
+                .join_component(&format!("{hash}{suffix}"));
+            if path.exists() {
+                touch(&path, time)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn remaining_hashes(cache: &FSCache) -> Result<Vec<String>> {
+        Ok(cache
+            .entries()?
+            .into_iter()
+            .map(|entry| entry.hash)
+            .collect())
+    }
+
+    #[test]
+    fn test_evict_max_age() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(None, repo_root_path, None)?;
+        put_entry(&cache, repo_root_path, "a", 1000)?;
+        put_entry(&cache, repo_root_path, "b", 2000)?;
+        put_entry(&cache, repo_root_path, "c", 3000)?;
+
+        let policy = EvictionPolicy {
+            max_age: Some(Duration::from_secs(1000)),
+            ..Default::default()
+        };
+        let summary = cache.evict(&policy, UNIX_EPOCH + Duration::from_secs(3500))?;
+
+        assert_eq!(summary.evicted.entries, 2);
+        assert_eq!(summary.remaining.entries, 1);
+        assert_eq!(remaining_hashes(&cache)?, vec!["c"]);
+        assert_eq!(cache.stats()?, summary.remaining);
+        Ok(())
+    }
+
+    #[test]
+    fn test_evict_max_size_least_recently_used() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(None, repo_root_path, None)?;
+        put_entry(&cache, repo_root_path, "a", 1000)?;
+        put_entry(&cache, repo_root_path, "b", 2000)?;
+        put_entry(&cache, repo_root_path, "c", 3000)?;
+        assert_eq!(remaining_hashes(&cache)?, vec!["a", "b", "c"]);
+
+        // Restoring an entry marks it as recently used, without rewriting its
+        // metadata
+        let metadata_path = cache.cache_directory.join_component("a-meta.json");
+        let metadata = metadata_path.read()?;
+        cache.fetch(repo_root_path, "a")?.unwrap();
+        assert_eq!(remaining_hashes(&cache)?, vec!["b", "c", "a"]);
+        assert_eq!(metadata_path.read()?, metadata);
+
+        let stats = cache.stats()?;
+        assert_eq!(stats.entries, 3);
+        let policy = EvictionPolicy {
+            max_size: Some(stats.total_size - 1),
+            ..Default::default()
+        };
+        let summary = cache.evict(&policy, SystemTime::now())?;
+
+        assert_eq!(summary.evicted.entries, 1);
+        assert_eq!(remaining_hashes(&cache)?, vec!["c", "a"]);
+        assert!(!cache.cache_directory.join_component("b-meta.json").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_evict_unlimited_keeps_everything() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(None, repo_root_path, None)?;
+        put_entry(&cache, repo_root_path, "a", 1000)?;
+
+        let summary = cache.evict(&EvictionPolicy::default(), SystemTime::now())?;
+
+        assert_eq!(summary.evicted, CacheStats::default());
+        assert_eq!(remaining_hashes(&cache)?, vec!["a"]);
+        Ok(())
+    }
+
+    fn put_files(
+        cache: &FSCache,
+        repo_root: &AbsoluteSystemPath,
+        hash: &str,
+        files: &[(&str, &str)],
+    ) -> Result<Vec<AnchoredSystemPathBuf>> {
+        let files = files
+            .iter()
+            .map(|(path, contents)| {
+                let file = AnchoredSystemPathBuf::from_raw(path)?;
+                repo_root.resolve(&file).create_with_contents(contents)?;
+                Ok(file)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        cache.put(repo_root, hash, &files, 0)?;
+        Ok(files)
+    }
+
+    fn blob_count(cache: &FSCache) -> Result<usize> {
+        Ok(cache.blobs()?.len())
+    }
+
+    #[test]
+    fn test_content_addressed_round_trip_shares_blobs() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(None, repo_root_path, None)?
+            .with_artifact_format(ArtifactFormat::ContentAddressed);
+
+        let files = put_files(
+            &cache,
+            repo_root_path,
+            "a",
+            &[("one.txt", "shared"), ("two.txt", "only a")],
+        )?;
+        put_files(
+            &cache,
+            repo_root_path,
+            "b",
+            &[("one.txt", "shared"), ("three.txt", "shared")],
+        )?;
+        assert_eq!(blob_count(&cache)?, 2);
+        assert!(!cache.cache_directory.join_component("a.tar.zst").exists());
+
+        let output = tempdir()?;
+        let output_path = AbsoluteSystemPath::from_std_path(output.path())?;
+        let (_, restored) = cache.fetch(output_path, "a")?.unwrap();
+        assert_eq!(restored, files);
+        assert_eq!(
+            output_path.join_component("two.txt").read_to_string()?,
+            "only a"
+        );
+        assert!(cache.exists("b")?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_addressed_migrates_tarballs() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let tarball_cache = FSCache::new(None, repo_root_path, None)?;
+        put_files(&tarball_cache, repo_root_path, "a", &[("one.txt", "one")])?;
+
+        let cache = FSCache::new(None, repo_root_path, None)?
+            .with_artifact_format(ArtifactFormat::ContentAddressed);
+        cache.fetch(repo_root_path, "a")?.unwrap();
+
+        assert!(!cache.cache_directory.join_component("a.tar.zst").exists());
+        assert!(cache.manifest_path("a").exists());
+        assert_eq!(blob_count(&cache)?, 1);
+
+        // The migrated artifact restores like the original
+        let output = tempdir()?;
+        let output_path = AbsoluteSystemPath::from_std_path(output.path())?;
+        cache.fetch(output_path, "a")?.unwrap();
+        assert_eq!(
+            output_path.join_component("one.txt").read_to_string()?,
+            "one"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_evict_keeps_blobs_of_remaining_entries() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(None, repo_root_path, None)?
+            .with_artifact_format(ArtifactFormat::ContentAddressed);
+        put_entry(&cache, repo_root_path, "a", 1000)?;
+        put_files(
+            &cache,
+            repo_root_path,
+            "b",
+            &[("shared.txt", "a"), ("b.txt", "b")],
+        )?;
+        set_last_accessed(&cache, "b", 2000)?;
+        assert_eq!(blob_count(&cache)?, 2);
+
+        // "a" only contains a blob shared with "b", so it's counted towards "b"
+        let entries = cache.entries()?;
+        let total_size: u64 = entries.iter().map(|entry| entry.size).sum();
+        let policy = EvictionPolicy {
+            max_age: Some(Duration::from_secs(1000)),
+            ..Default::default()
+        };
+        let summary = cache.evict(&policy, UNIX_EPOCH + Duration::from_secs(2500))?;
+
+        assert_eq!(summary.evicted.entries, 1);
+        assert_eq!(summary.evicted.total_size, entries[0].size);
+        assert_eq!(summary.remaining.total_size, total_size - entries[0].size);
+        assert_eq!(remaining_hashes(&cache)?, vec!["b"]);
+        assert_eq!(blob_count(&cache)?, 2);
+
+        let output = tempdir()?;
+        let output_path = AbsoluteSystemPath::from_std_path(output.path())?;
+        cache.fetch(output_path, "b")?.unwrap();
+        assert_eq!(
+            output_path.join_component("shared.txt").read_to_string()?,
+            "a"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_blob_is_a_miss() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(None, repo_root_path, None)?
+            .with_artifact_format(ArtifactFormat::ContentAddressed);
+        put_files(&cache, repo_root_path, "a", &[("one.txt", "one")])?;
+        for (path, _) in cache.blobs()?.into_values() {
+            path.remove_file()?;
+        }
+
+        assert!(cache.fetch(repo_root_path, "a")?.is_none());
+        Ok(())
+    }
+
+    fn backdate_blobs(cache: &FSCache) -> Result<()> {
+        let time = SystemTime::now() - BLOB_GRACE_PERIOD * 2;
+        for (path, _) in cache.blobs()?.into_values() {
+            touch(&path, time)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_evict_keeps_blobs_of_puts_in_progress() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(None, repo_root_path, None)?
+            .with_artifact_format(ArtifactFormat::ContentAddressed);
+        let file = AnchoredSystemPathBuf::from_raw("one.txt")?;
+        repo_root_path.resolve(&file).create_with_contents("one")?;
+        // A put that has written its blobs but not its manifest yet
+        let (_, blobs) = Manifest::create(repo_root_path, &[file])?;
+        for blob in &blobs {
+            cache.put_blob(blob)?;
+        }
+
+        cache.evict(&EvictionPolicy::default(), SystemTime::now())?;
+        assert_eq!(blob_count(&cache)?, 1);
+
+        backdate_blobs(&cache)?;
+        cache.evict(&EvictionPolicy::default(), SystemTime::now())?;
+        assert_eq!(blob_count(&cache)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_evict_keeps_recently_reused_blobs() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(None, repo_root_path, None)?
+            .with_artifact_format(ArtifactFormat::ContentAddressed);
+        put_entry(&cache, repo_root_path, "a", 1000)?;
+        backdate_blobs(&cache)?;
+        // Another put reuses the blob of "a" while "a" is being evicted
+        let file = AnchoredSystemPathBuf::from_raw("a.txt")?;
+        let (_, blobs) = Manifest::create(repo_root_path, &[file])?;
+        cache.put_blob(&blobs[0])?;
+
+        let policy = EvictionPolicy {
+            max_age: Some(Duration::from_secs(1000)),
+            ..Default::default()
+        };
+        let summary = cache.evict(&policy, UNIX_EPOCH + Duration::from_secs(2500))?;
+        assert_eq!(summary.evicted.entries, 1);
+        assert_eq!(blob_count(&cache)?, 1);
+        Ok(())
+    }
 }
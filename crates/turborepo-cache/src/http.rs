@@ -1,5 +1,13 @@
-use std::{backtrace::Backtrace, io::Write};
+use std::{
+    backtrace::Backtrace,
+    collections::HashMap,
+    io::{Read, Write},
+};
 
+use bytes::{Buf, Bytes, BytesMut};
+use futures::{stream, StreamExt, TryStreamExt};
+use reqwest::StatusCode;
+use tokio::sync::mpsc;
 use tracing::debug;
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
 use turborepo_analytics::AnalyticsSender;
@@ -9,16 +17,24 @@ use turborepo_api_client::{
 
 use crate::{
     cache_archive::{CacheReader, CacheWriter},
+    content_addressed::{is_manifest, verify_blob, BlobSource, Manifest},
     signature_authentication::ArtifactSignatureAuthenticator,
-    CacheError, CacheHitMetadata, CacheOpts, CacheSource,
+    ArtifactFormat, CacheError, CacheHitMetadata, CacheOpts, CacheSource,
 };
 
+// Downloaded chunks that can be buffered ahead of the restore
+const DOWNLOAD_BUFFER: usize = 64;
+const MAX_DOWNLOAD_RESUMES: u32 = 3;
+// Blobs uploaded or downloaded at once for a single artifact
+const BLOB_TRANSFERS: usize = 8;
+
 pub struct HTTPCache {
     client: APIClient,
     signer_verifier: Option<ArtifactSignatureAuthenticator>,
     repo_root: AbsoluteSystemPathBuf,
     api_auth: APIAuth,
     analytics_recorder: Option<AnalyticsSender>,
+    artifact_format: ArtifactFormat,
 }
 
 impl HTTPCache {
@@ -53,6 +69,7 @@ impl HTTPCache {
             repo_root,
             api_auth,
             analytics_recorder,
+            artifact_format: opts.artifact_format,
         }
     }
 
@@ -63,8 +80,14 @@ impl HTTPCache {
         files: &[AnchoredSystemPathBuf],
         duration: u64,
     ) -> Result<(), CacheError> {
-        let mut artifact_body = Vec::new();
-        self.write(&mut artifact_body, anchor, files).await?;
+        let artifact_body = match self.artifact_format {
+            ArtifactFormat::Tarball => {
+                let mut artifact_body = Vec::new();
+                self.write(&mut artifact_body, anchor, files).await?;
+                artifact_body
+            }
+            ArtifactFormat::ContentAddressed => self.put_blobs(anchor, files).await?,
+        };
 
         let tag = self
             .signer_verifier
@@ -87,6 +110,67 @@ impl HTTPCache {
         Ok(())
     }
 
+    // Uploads the blobs of `files` that the remote cache doesn't have yet and
+    // returns the manifest to upload in place of a tarball
+    async fn put_blobs(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        files: &[AnchoredSystemPathBuf],
+    ) -> Result<Vec<u8>, CacheError> {
+        let (manifest, blobs) = Manifest::create(anchor, files)?;
+        // Collected first, as a closure in the stream keeps the future from
+        // being `Send`
+        let uploads = blobs
+            .iter()
+            .map(|blob| self.put_blob(blob))
+            .collect::<Vec<_>>();
+        stream::iter(uploads)
+            .buffer_unordered(BLOB_TRANSFERS)
+            .try_collect::<()>()
+            .await?;
+
+        Ok(manifest.to_vec())
+    }
+
+    async fn put_blob(&self, blob: &BlobSource) -> Result<(), CacheError> {
+        let key = blob_key(&blob.hash);
+        if self
+            .client
+            .artifact_exists(
+                &key,
+                &self.api_auth.token,
+                self.api_auth.team_id.as_deref(),
+                self.api_auth.team_slug.as_deref(),
+            )
+            .await?
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        let path = blob.path.clone();
+        let body = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, CacheError> {
+            Ok(zstd::encode_all(path.open()?, 0)?)
+        })
+        .await??;
+
+        // Blobs are checked against their hash when they're downloaded, so
+        // they don't need a tag of their own
+        self.client
+            .put_artifact(
+                &key,
+                &body,
+                0,
+                None,
+                &self.api_auth.token,
+                self.api_auth.team_id.as_deref(),
+                self.api_auth.team_slug.as_deref(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
     async fn write(
         &self,
         writer: impl Write,
@@ -172,7 +256,12 @@ impl HTTPCache {
 
         let duration = Self::get_duration_from_response(&response)?;
 
-        let body = if let Some(signer_verifier) = &self.signer_verifier {
+        // Signed artifacts aren't streamed. The tag covers the whole artifact
+        // and can only be checked once the last byte has arrived, and
+        // restoring while downloading would write files from an artifact that
+        // may turn out to have been tampered with. They're buffered in memory,
+        // verified, and only then restored.
+        let files = if let Some(signer_verifier) = &self.signer_verifier {
             let expected_tag = response
                 .headers()
                 .get("x-artifact-tag")
@@ -183,29 +272,38 @@ impl HTTPCache {
                 .map_err(|_| CacheError::InvalidTag(Backtrace::capture()))?
                 .to_string();
 
-            let body = response.bytes().await.map_err(|e| {
-                CacheError::ApiClientError(
-                    Box::new(turborepo_api_client::Error::ReqwestError(e)),
-                    Backtrace::capture(),
-                )
-            })?;
+            let body = response.bytes().await.map_err(download_error)?;
             let is_valid = signer_verifier.validate(hash.as_bytes(), &body, &expected_tag)?;
 
             if !is_valid {
                 return Err(CacheError::InvalidTag(Backtrace::capture()));
             }
 
-            body
+            self.restore_body(&body).await?
         } else {
-            response.bytes().await.map_err(|e| {
-                CacheError::ApiClientError(
-                    Box::new(turborepo_api_client::Error::ReqwestError(e)),
-                    Backtrace::capture(),
-                )
-            })?
+            let mut response = response;
+            let first_chunk = response
+                .chunk()
+                .await
+                .map_err(download_error)?
+                .unwrap_or_default();
+            // Manifests are small, and their blobs can't be restored until
+            // they've all been read
+            if is_manifest(&first_chunk) {
+                let mut body = BytesMut::from(first_chunk.as_ref());
+                while let Some(chunk) = response.chunk().await.map_err(download_error)? {
+                    body.extend_from_slice(&chunk);
+                }
+                self.restore_body(&body).await?
+            } else {
+                Some(self.restore_streaming(hash, response, first_chunk).await?)
+            }
+        };
+        let Some(files) = files else {
+            // Some of the artifact's blobs have expired
+            self.log_fetch(analytics::CacheEvent::Miss, hash, 0);
+            return Ok(None);
         };
-
-        let files = Self::restore_tar(&self.repo_root, &body)?;
 
         self.log_fetch(analytics::CacheEvent::Hit, hash, duration);
         Ok(Some((
@@ -217,6 +315,145 @@ impl HTTPCache {
         )))
     }
 
+    // Restores the artifact on a blocking thread while it's still downloading
+    async fn restore_streaming(
+        &self,
+        hash: &str,
+        response: Response,
+        first_chunk: Bytes,
+    ) -> Result<Vec<AnchoredSystemPathBuf>, CacheError> {
+        let (chunks, receiver) = mpsc::channel(DOWNLOAD_BUFFER);
+        let repo_root = self.repo_root.clone();
+        let restore = tokio::task::spawn_blocking(move || {
+            let mut cache_reader = CacheReader::from_reader(ChunkReader::new(receiver), true)?;
+            cache_reader.restore(&repo_root)
+        });
+
+        let downloaded = self.download(hash, response, first_chunk, chunks).await;
+        let restored = restore.await;
+        // A failed download leaves the restore with a truncated archive, so
+        // the download error says more about what went wrong
+        downloaded?;
+        restored?
+    }
+
+    // Sends the artifact body to `chunks` as it arrives. If the connection
+    // drops, the download resumes from the last byte received.
+    async fn download(
+        &self,
+        hash: &str,
+        mut response: Response,
+        first_chunk: Bytes,
+        chunks: mpsc::Sender<Bytes>,
+    ) -> Result<(), CacheError> {
+        let mut received = first_chunk.len() as u64;
+        if !first_chunk.is_empty() && chunks.send(first_chunk).await.is_err() {
+            return Ok(());
+        }
+        let mut resumes = 0;
+        // Servers that don't support ranges send the whole artifact again
+        let mut skip = 0;
+
+        loop {
+            let mut chunk = match response.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => return Ok(()),
+                Err(e) if resumes < MAX_DOWNLOAD_RESUMES => {
+                    resumes += 1;
+                    debug!("download of {hash} interrupted after {received} bytes, resuming: {e}");
+                    response = self
+                        .client
+                        .fetch_artifact_range(
+                            hash,
+                            received,
+                            &self.api_auth.token,
+                            self.api_auth.team_id.as_deref(),
+                            self.api_auth.team_slug.as_deref(),
+                        )
+                        .await?
+                        .ok_or_else(|| {
+                            CacheError::DownloadInterrupted(hash.to_string(), Backtrace::capture())
+                        })?;
+                    skip = match response.status() {
+                        StatusCode::PARTIAL_CONTENT => 0,
+                        _ => received,
+                    };
+                    continue;
+                }
+                Err(e) => return Err(download_error(e)),
+            };
+
+            let skipped = skip.min(chunk.len() as u64);
+            chunk.advance(skipped as usize);
+            skip -= skipped;
+            if chunk.is_empty() {
+                continue;
+            }
+
+            received += chunk.len() as u64;
+            if chunks.send(chunk).await.is_err() {
+                // The restore stopped early and will report why
+                return Ok(());
+            }
+        }
+    }
+
+    // Restores an artifact that has been downloaded in full, returning `None`
+    // if it's a manifest with blobs the remote cache no longer has
+    async fn restore_body(
+        &self,
+        body: &[u8],
+    ) -> Result<Option<Vec<AnchoredSystemPathBuf>>, CacheError> {
+        if !is_manifest(body) {
+            return Self::restore_tar(&self.repo_root, body).map(Some);
+        }
+
+        let manifest = Manifest::from_slice(body)?;
+        let fetches = manifest
+            .blobs()
+            .into_iter()
+            .map(|hash| self.fetch_blob(hash))
+            .collect::<Vec<_>>();
+        let blobs = stream::iter(fetches)
+            .buffer_unordered(BLOB_TRANSFERS)
+            .try_collect::<Vec<_>>()
+            .await?;
+        let Some(blobs) = blobs.into_iter().collect::<Option<HashMap<_, _>>>() else {
+            return Ok(None);
+        };
+
+        let repo_root = self.repo_root.clone();
+        tokio::task::spawn_blocking(move || {
+            manifest.restore(&repo_root, |hash| {
+                let blob = blobs.get(hash).cloned().ok_or_else(|| {
+                    CacheError::BlobMissing(hash.to_string(), Backtrace::capture())
+                })?;
+                Ok(zstd::Decoder::new(blob.reader())?)
+            })
+        })
+        .await?
+        .map(Some)
+    }
+
+    async fn fetch_blob(&self, hash: &str) -> Result<Option<(String, Bytes)>, CacheError> {
+        let Some(response) = self
+            .client
+            .fetch_artifact(
+                &blob_key(hash),
+                &self.api_auth.token,
+                self.api_auth.team_id.as_deref(),
+                self.api_auth.team_slug.as_deref(),
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let body = response.bytes().await.map_err(download_error)?;
+        verify_blob(hash, &body)?;
+
+        Ok(Some((hash.to_string(), body)))
+    }
+
     pub(crate) fn restore_tar(
         root: &AbsoluteSystemPath,
         body: &[u8],
@@ -226,20 +463,83 @@ impl HTTPCache {
     }
 }
 
+// Blobs are stored alongside artifacts, under keys that can't be mistaken
+// for a task hash
+fn blob_key(hash: &str) -> String {
+    format!("blob-{hash}")
+}
+
+fn download_error(e: reqwest::Error) -> CacheError {
+    CacheError::ApiClientError(
+        Box::new(turborepo_api_client::Error::ReqwestError(e)),
+        Backtrace::capture(),
+    )
+}
+
+// Reads the chunks of a download as they arrive
+struct ChunkReader {
+    chunks: mpsc::Receiver<Bytes>,
+    current: Bytes,
+}
+
+impl ChunkReader {
+    fn new(chunks: mpsc::Receiver<Bytes>) -> Self {
+        Self {
+            chunks,
+            current: Bytes::new(),
+        }
+    }
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.current.is_empty() {
+            match self.chunks.blocking_recv() {
+                Some(chunk) => self.current = chunk,
+                // The download has finished
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.current.len());
+        buf[..len].copy_from_slice(&self.current[..len]);
+        self.current.advance(len);
+        Ok(len)
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::{
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
     use anyhow::Result;
-    use futures::future::try_join_all;
+    use axum::{
+        body::StreamBody,
+        http::{HeaderMap, StatusCode},
+        routing::get,
+        Router,
+    };
+    use bytes::Bytes;
+    use futures::{future::try_join_all, StreamExt};
     use tempfile::tempdir;
+    use test_case::test_case;
     use turbopath::AbsoluteSystemPathBuf;
     use turborepo_analytics::start_analytics;
     use turborepo_api_client::{analytics, APIClient};
     use turborepo_vercel_api_mock::start_test_server;
 
     use crate::{
+        cache_archive::CacheWriter,
         http::{APIAuth, HTTPCache},
         test_cases::{get_test_cases, validate_analytics, TestCase},
-        CacheOpts, CacheSource,
+        ArtifactFormat, CacheOpts, CacheSource,
     };
 
     #[tokio::test]
@@ -319,4 +619,158 @@ mod test {
 
         Ok(())
     }
+
+    // Serves `artifact`, but drops the connection halfway through the first
+    // download
+    async fn start_flaky_server(artifact: Bytes, supports_ranges: bool) -> Result<u16> {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let app = Router::new().route(
+            "/v8/artifacts/:hash",
+            get(move |headers: HeaderMap| {
+                let artifact = artifact.clone();
+                let is_first = requests.fetch_add(1, Ordering::SeqCst) == 0;
+                async move {
+                    let start = headers
+                        .get("range")
+                        .and_then(|range| range.to_str().ok())
+                        .and_then(|range| range.strip_prefix("bytes="))
+                        .and_then(|range| range.trim_end_matches('-').parse().ok())
+                        .filter(|_| supports_ranges)
+                        .unwrap_or(0);
+                    let status = match start {
+                        0 => StatusCode::OK,
+                        _ => StatusCode::PARTIAL_CONTENT,
+                    };
+                    let body = artifact.slice(start..);
+                    let chunks = if is_first {
+                        let head = body.slice(..body.len() / 2);
+                        // Give the headers and the first half time to reach
+                        // the client before the connection is dropped
+                        futures::stream::once(async move { Ok(head) })
+                            .chain(futures::stream::once(async {
+                                tokio::time::sleep(Duration::from_millis(100)).await;
+                                Err(std::io::Error::new(
+                                    std::io::ErrorKind::ConnectionReset,
+                                    "connection reset",
+                                ))
+                            }))
+                            .boxed()
+                    } else {
+                        futures::stream::once(async move { Ok(body) }).boxed()
+                    };
+                    (status, StreamBody::new(chunks))
+                }
+            }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let server = axum::Server::from_tcp(listener)?.serve(app.into_make_service());
+        tokio::spawn(server);
+
+        Ok(port)
+    }
+
+    #[test_case(true ; "with range support")]
+    #[test_case(false ; "without range support")]
+    #[tokio::test]
+    async fn test_resumes_interrupted_download(supports_ranges: bool) -> Result<()> {
+        let test_case = &get_test_cases()[2];
+        let source_dir = tempdir()?;
+        let source_path = AbsoluteSystemPathBuf::try_from(source_dir.path())?;
+        test_case.initialize(&source_path)?;
+
+        let mut artifact = Vec::new();
+        let mut cache_writer = CacheWriter::from_writer(&mut artifact, true)?;
+        for file in &test_case.files {
+            cache_writer.add_file(&source_path, file.path())?;
+        }
+        cache_writer.finish()?;
+
+        let port = start_flaky_server(Bytes::from(artifact), supports_ranges).await?;
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let cache = HTTPCache::new(
+            APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", false)?,
+            &CacheOpts::default(),
+            repo_root_path.clone(),
+            APIAuth {
+                team_id: Some("my-team".to_string()),
+                token: "my-token".to_string(),
+                team_slug: None,
+            },
+            None,
+        );
+
+        let (_, received_files) = cache.fetch(test_case.hash).await?.unwrap();
+        assert_eq!(received_files.len(), test_case.files.len());
+        for file in &test_case.files {
+            let file_path = repo_root_path.resolve(file.path());
+            match file.contents() {
+                Some(contents) => assert_eq!(file_path.read_to_string()?, contents),
+                None => assert!(file_path.exists()),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_content_addressed_round_trip() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+        let test_case = &get_test_cases()[2];
+        let source_dir = tempdir()?;
+        let source_path = AbsoluteSystemPathBuf::try_from(source_dir.path())?;
+        test_case.initialize(&source_path)?;
+        let files: Vec<_> = test_case
+            .files
+            .iter()
+            .map(|f| f.path().to_owned())
+            .collect();
+
+        let api_auth = APIAuth {
+            team_id: Some("my-team".to_string()),
+            token: "my-token".to_string(),
+            team_slug: None,
+        };
+        let opts = CacheOpts {
+            artifact_format: ArtifactFormat::ContentAddressed,
+            ..Default::default()
+        };
+        let cache = HTTPCache::new(
+            APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?,
+            &opts,
+            source_path.clone(),
+            api_auth.clone(),
+            None,
+        );
+        // The mock server appends to artifacts that are uploaded again, so a
+        // blob uploaded twice would fail verification
+        cache.put(&source_path, "content-a", &files, 10).await?;
+        cache.put(&source_path, "content-b", &files, 10).await?;
+
+        // Manifests can be restored whichever format the cache writes
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let cache = HTTPCache::new(
+            APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?,
+            &CacheOpts::default(),
+            repo_root_path.clone(),
+            api_auth,
+            None,
+        );
+        let (_, received_files) = cache.fetch("content-b").await?.unwrap();
+        assert_eq!(received_files, files);
+        for file in &test_case.files {
+            let file_path = repo_root_path.resolve(file.path());
+            match file.contents() {
+                Some(contents) => assert_eq!(file_path.read_to_string()?, contents),
+                None => assert!(file_path.exists()),
+            }
+        }
+
+        handle.abort();
+        Ok(())
+    }
 }
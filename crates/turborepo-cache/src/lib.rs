@@ -5,8 +5,13 @@
 
 /// A wrapper for the cache that uses a worker pool to perform cache operations
 mod async_cache;
+/// The interface shared by remote caches and their configuration.
+mod backend;
 /// The core cache creation and restoration logic.
 pub mod cache_archive;
+/// An artifact format that stores each distinct file once, shared between
+/// artifacts.
+pub mod content_addressed;
 /// File system cache
 pub mod fs;
 /// Remote cache
@@ -14,6 +19,10 @@ pub mod http;
 /// A wrapper that allows reads and writes from the file system and remote
 /// cache.
 mod multiplexer;
+/// Remote cache stored in an S3 compatible object store
+pub mod s3;
+/// Remote cache stored in a directory shared between machines
+pub mod shared_dir;
 /// Cache signature authentication lets users provide a private key to sign
 /// their cache payloads.
 pub mod signature_authentication;
@@ -23,11 +32,12 @@ mod test_cases;
 use std::{backtrace, backtrace::Backtrace};
 
 pub use async_cache::AsyncCache;
+pub use backend::{CacheBackend, RemoteCacheBackend, S3CacheOpts};
 use camino::Utf8Path;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::signature_authentication::SignatureError;
+use crate::{fs::EvictionPolicy, signature_authentication::SignatureError};
 
 #[derive(Debug, Error)]
 pub enum CacheError {
@@ -42,6 +52,14 @@ pub enum CacheError {
     InvalidTag(#[backtrace] Backtrace),
     #[error("cannot untar file to {0}")]
     InvalidFilePath(String, #[backtrace] Backtrace),
+    #[error("download of artifact {0} was interrupted and could not be resumed")]
+    DownloadInterrupted(String, #[backtrace] Backtrace),
+    #[error("invalid artifact manifest: {0}")]
+    InvalidManifest(String, #[backtrace] Backtrace),
+    #[error("contents of cached blob {0} do not match its hash")]
+    InvalidBlob(String, #[backtrace] Backtrace),
+    #[error("cached blob {0} is missing")]
+    BlobMissing(String, #[backtrace] Backtrace),
     #[error("artifact verification failed: {0}")]
     ApiClientError(Box<turborepo_api_client::Error>, #[backtrace] Backtrace),
     #[error("signing artifact failed: {0}")]
@@ -74,6 +92,14 @@ pub enum CacheError {
     MetadataWriteFailure(serde_json::Error, #[backtrace] Backtrace),
     #[error("Unable to perform write as cache is shutting down")]
     CacheShuttingDown,
+    #[error("invalid S3 endpoint: {0}")]
+    InvalidS3Endpoint(String, #[backtrace] Backtrace),
+    #[error("S3 request failed: {0}")]
+    S3RequestError(reqwest::Error, #[backtrace] Backtrace),
+    #[error("S3 request failed with status {0}")]
+    S3UnexpectedStatus(reqwest::StatusCode, #[backtrace] Backtrace),
+    #[error("cache task failed: {0}")]
+    TaskFailed(#[from] tokio::task::JoinError, #[backtrace] Backtrace),
 }
 
 impl From<turborepo_api_client::Error> for CacheError {
@@ -94,6 +120,18 @@ pub struct CacheHitMetadata {
     pub time_saved: u64,
 }
 
+/// How artifacts are stored in the file system cache and uploaded to the
+/// remote cache.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactFormat {
+    /// A compressed tarball per artifact
+    #[default]
+    Tarball,
+    /// A manifest per artifact referring to blobs by the hash of their
+    /// contents. Blobs are only stored and uploaded once.
+    ContentAddressed,
+}
+
 #[derive(Debug, Default)]
 pub struct CacheOpts<'a> {
     pub override_dir: Option<&'a Utf8Path>,
@@ -102,6 +140,13 @@ pub struct CacheOpts<'a> {
     pub skip_filesystem: bool,
     pub workers: u32,
     pub remote_cache_opts: Option<RemoteCacheOpts>,
+    pub remote_cache_backend: RemoteCacheBackend,
+    /// Timeout of requests to a self-hosted remote cache in seconds, 0 for
+    /// none. Requests to the Vercel remote cache use the timeout of the
+    /// `APIClient`.
+    pub remote_cache_timeout: u64,
+    pub eviction_policy: EvictionPolicy,
+    pub artifact_format: ArtifactFormat,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -1,24 +1,38 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
+};
 
-use tracing::{debug, warn};
+use tracing::{debug, field, warn, Instrument};
 use turbopath::{AbsoluteSystemPath, AnchoredSystemPathBuf};
 use turborepo_analytics::AnalyticsSender;
 use turborepo_api_client::{APIAuth, APIClient};
 
-use crate::{fs::FSCache, http::HTTPCache, CacheError, CacheHitMetadata, CacheOpts};
+use crate::{
+    backend::{CacheBackend, RemoteCacheBackend},
+    fs::FSCache,
+    http::HTTPCache,
+    s3::{S3Cache, S3Credentials},
+    shared_dir::SharedDirCache,
+    CacheError, CacheHitMetadata, CacheOpts,
+};
+
+// turbo exports spans with this target, along with its run and task spans,
+// when tracing to an OpenTelemetry collector
+const SPAN_TARGET: &str = "turbo::cache";
 
 pub struct CacheMultiplexer {
     // We use an `AtomicBool` instead of removing the cache because that would require
     // wrapping the cache in a `Mutex` which would cause a lot of contention.
     // This does create a mild race condition where we might use the cache
     // even though another thread might be removing it, but that's fine.
-    should_use_http_cache: AtomicBool,
+    should_use_remote_cache: AtomicBool,
     // Just for keeping track of whether we've already printed a warning about the remote cache
     // being read-only
     should_print_skipping_remote_put: AtomicBool,
     remote_cache_read_only: bool,
     fs: Option<FSCache>,
-    http: Option<HTTPCache>,
+    remote: Option<Box<dyn CacheBackend>>,
 }
 
 impl CacheMultiplexer {
@@ -30,46 +44,72 @@ impl CacheMultiplexer {
         analytics_recorder: Option<AnalyticsSender>,
     ) -> Result<Self, CacheError> {
         let use_fs_cache = !opts.skip_filesystem;
-        let use_http_cache = !opts.skip_remote;
+        let use_remote_cache = !opts.skip_remote;
 
         // Since the above two flags are not mutually exclusive it is possible to
         // configure yourself out of having a cache. We should tell you about it
         // but we shouldn't fail your build for that reason.
-        if !use_fs_cache && !use_http_cache {
+        if !use_fs_cache && !use_remote_cache {
             warn!("no caches are enabled");
         }
 
         let fs_cache = use_fs_cache
-            .then(|| FSCache::new(opts.override_dir, repo_root, analytics_recorder.clone()))
+            .then(|| {
+                FSCache::new(opts.override_dir, repo_root, analytics_recorder.clone())
+                    .map(|fs_cache| fs_cache.with_artifact_format(opts.artifact_format))
+            })
             .transpose()?;
 
-        let http_cache = use_http_cache
-            .then_some(api_auth)
-            .flatten()
-            .map(|api_auth| {
-                HTTPCache::new(
-                    api_client,
-                    opts,
-                    repo_root.to_owned(),
-                    api_auth,
-                    analytics_recorder.clone(),
-                )
-            });
+        if let Some(fs_cache) = fs_cache.as_ref() {
+            if !opts.eviction_policy.is_unlimited() {
+                match fs_cache.evict(&opts.eviction_policy, SystemTime::now()) {
+                    Ok(summary) => debug!(
+                        "evicted {} entries ({} bytes) from fs cache",
+                        summary.evicted.entries, summary.evicted.total_size
+                    ),
+                    Err(err) => warn!("failed to evict entries from fs cache: {err}"),
+                }
+            }
+        }
+
+        let remote_cache: Option<Box<dyn CacheBackend>> = if use_remote_cache {
+            match &opts.remote_cache_backend {
+                RemoteCacheBackend::Vercel => api_auth.map(|api_auth| {
+                    Box::new(HTTPCache::new(
+                        api_client,
+                        opts,
+                        repo_root.to_owned(),
+                        api_auth,
+                        analytics_recorder.clone(),
+                    )) as Box<dyn CacheBackend>
+                }),
+                RemoteCacheBackend::S3(s3_opts) => Some(Box::new(S3Cache::new(
+                    s3_opts,
+                    S3Credentials::from_env(),
+                    opts.remote_cache_timeout,
+                )?)),
+                RemoteCacheBackend::Directory { path } => {
+                    Some(Box::new(SharedDirCache::new(path, repo_root)?))
+                }
+            }
+        } else {
+            None
+        };
 
         Ok(CacheMultiplexer {
             should_print_skipping_remote_put: AtomicBool::new(true),
-            should_use_http_cache: AtomicBool::new(http_cache.is_some()),
+            should_use_remote_cache: AtomicBool::new(remote_cache.is_some()),
             remote_cache_read_only: opts.remote_cache_read_only,
             fs: fs_cache,
-            http: http_cache,
+            remote: remote_cache,
         })
     }
 
     // This is technically a TOCTOU bug, but at worst it'll cause
     // a few extra cache requests.
-    fn get_http_cache(&self) -> Option<&HTTPCache> {
-        if self.should_use_http_cache.load(Ordering::Relaxed) {
-            self.http.as_ref()
+    fn get_remote_cache(&self) -> Option<&dyn CacheBackend> {
+        if self.should_use_remote_cache.load(Ordering::Relaxed) {
+            self.remote.as_deref()
         } else {
             None
         }
@@ -87,8 +127,8 @@ impl CacheMultiplexer {
             .map(|fs| fs.put(anchor, key, files, duration))
             .transpose()?;
 
-        let http_result = match self.get_http_cache() {
-            Some(http) => {
+        let remote_result = match self.get_remote_cache() {
+            Some(remote) => {
                 if self.remote_cache_read_only {
                     if self
                         .should_print_skipping_remote_put
@@ -103,21 +143,21 @@ impl CacheMultiplexer {
                     // write to it
                     None
                 } else {
-                    let http_result = http.put(anchor, key, files, duration).await;
+                    let remote_result = remote.put(anchor, key, files, duration).await;
 
-                    Some(http_result)
+                    Some(remote_result)
                 }
             }
             _ => None,
         };
 
-        match http_result {
+        match remote_result {
             Some(Err(CacheError::ApiClientError(
                 box turborepo_api_client::Error::CacheDisabled { .. },
                 ..,
             ))) => {
                 warn!("failed to put to http cache: cache disabled");
-                self.should_use_http_cache.store(false, Ordering::Relaxed);
+                self.should_use_remote_cache.store(false, Ordering::Relaxed);
                 Ok(())
             }
             Some(Err(e)) => Err(e),
@@ -131,17 +171,21 @@ impl CacheMultiplexer {
         key: &str,
     ) -> Result<Option<(CacheHitMetadata, Vec<AnchoredSystemPathBuf>)>, CacheError> {
         if let Some(fs) = &self.fs {
-            if let response @ Ok(Some(_)) = fs.fetch(anchor, key) {
+            let span = fetch_span("LOCAL");
+            let response = span.in_scope(|| fs.fetch(anchor, key));
+            span.record("turbo.cache.hit", matches!(response, Ok(Some(_))));
+            if let response @ Ok(Some(_)) = response {
                 return response;
             }
         }
 
-        if let Some(http) = self.get_http_cache() {
-            if let Ok(Some((CacheHitMetadata { source, time_saved }, files))) =
-                http.fetch(key).await
-            {
+        if let Some(remote) = self.get_remote_cache() {
+            let span = fetch_span("REMOTE");
+            let response = remote.fetch(anchor, key).instrument(span.clone()).await;
+            span.record("turbo.cache.hit", matches!(response, Ok(Some(_))));
+            if let Ok(Some((CacheHitMetadata { source, time_saved }, files))) = response {
                 // Store this into fs cache. We can ignore errors here because we know
-                // we have previously successfully stored in HTTP cache, and so the overall
+                // we have previously successfully stored in remote cache, and so the overall
                 // result is a success at fetching. Storing in lower-priority caches is an
                 // optimization.
                 if let Some(fs) = &self.fs {
@@ -166,16 +210,25 @@ impl CacheMultiplexer {
             }
         }
 
-        if let Some(http) = self.get_http_cache() {
-            match http.exists(key).await {
+        if let Some(remote) = self.get_remote_cache() {
+            match remote.exists(key).await {
                 cache_hit @ Ok(Some(_)) => {
                     return cache_hit;
                 }
                 Ok(None) => {}
-                Err(err) => debug!("failed to check http cache: {:?}", err),
+                Err(err) => debug!("failed to check remote cache: {:?}", err),
             }
         }
 
         Ok(None)
     }
 }
+
+fn fetch_span(source: &'static str) -> tracing::Span {
+    tracing::info_span!(
+        target: SPAN_TARGET,
+        "cache_fetch",
+        "turbo.cache.source" = source,
+        "turbo.cache.hit" = field::Empty,
+    )
+}
@@ -0,0 +1,199 @@
+use std::{
+    backtrace::Backtrace,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
+
+use crate::{
+    backend::{create_artifact, restore_artifact, CacheBackend},
+    fs::CacheMetadata,
+    CacheError, CacheHitMetadata, CacheSource,
+};
+
+/// A remote cache backed by a directory that several machines can access,
+/// e.g. an NFS mount. It uses the same layout as the file system cache, but
+/// writes are made visible atomically so that concurrent readers on other
+/// machines never observe a partially written artifact.
+pub struct SharedDirCache {
+    cache_directory: AbsoluteSystemPathBuf,
+}
+
+impl SharedDirCache {
+    pub fn new(path: &str, repo_root: &AbsoluteSystemPath) -> Result<Self, CacheError> {
+        let cache_directory = AbsoluteSystemPathBuf::from_unknown(repo_root, path);
+        cache_directory.create_dir_all()?;
+
+        Ok(SharedDirCache { cache_directory })
+    }
+
+    fn artifact_path(&self, hash: &str) -> AbsoluteSystemPathBuf {
+        self.cache_directory
+            .join_component(&format!("{}.tar.zst", hash))
+    }
+
+    fn metadata_path(&self, hash: &str) -> AbsoluteSystemPathBuf {
+        self.cache_directory
+            .join_component(&format!("{}-meta.json", hash))
+    }
+
+    // Writes `contents` next to `path` and renames it into place. A rename
+    // within a directory is atomic, including on NFS.
+    fn write_atomic(&self, path: &AbsoluteSystemPath, contents: &[u8]) -> Result<(), CacheError> {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let temp_path = self.cache_directory.join_component(&format!(
+            ".{}.{}-{}.tmp",
+            path.as_path().file_name().unwrap_or_default(),
+            std::process::id(),
+            nonce
+        ));
+        temp_path.create_with_contents(contents)?;
+        if let Err(err) = temp_path.rename(path) {
+            let _ = temp_path.remove_file();
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CacheBackend for SharedDirCache {
+    async fn put(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+        files: &[AnchoredSystemPathBuf],
+        duration: u64,
+    ) -> Result<(), CacheError> {
+        let artifact_body = create_artifact(anchor, files)?;
+        let meta = serde_json::to_vec(&CacheMetadata::new(hash, duration))
+            .map_err(|e| CacheError::MetadataWriteFailure(e, Backtrace::capture()))?;
+
+        // The metadata goes first so that it's present by the time the artifact
+        // can be found.
+        self.write_atomic(&self.metadata_path(hash), &meta)?;
+        self.write_atomic(&self.artifact_path(hash), &artifact_body)?;
+
+        Ok(())
+    }
+
+    async fn fetch(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+    ) -> Result<Option<(CacheHitMetadata, Vec<AnchoredSystemPathBuf>)>, CacheError> {
+        let Some(cache_hit) = self.exists(hash).await? else {
+            return Ok(None);
+        };
+
+        let artifact_body = self.artifact_path(hash).read()?;
+        let files = restore_artifact(anchor, &artifact_body)?;
+
+        Ok(Some((cache_hit, files)))
+    }
+
+    async fn exists(&self, hash: &str) -> Result<Option<CacheHitMetadata>, CacheError> {
+        if !self.artifact_path(hash).exists() {
+            return Ok(None);
+        }
+
+        let duration = CacheMetadata::read(&self.metadata_path(hash))
+            .map(|meta| meta.duration)
+            .unwrap_or(0);
+
+        Ok(Some(CacheHitMetadata {
+            time_saved: duration,
+            source: CacheSource::Remote,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use futures::future::try_join_all;
+    use tempfile::tempdir;
+    use turbopath::AnchoredSystemPath;
+
+    use super::*;
+    use crate::test_cases::{get_test_cases, TestCase};
+
+    #[tokio::test]
+    async fn test_shared_dir_cache() -> Result<()> {
+        let shared_dir = tempdir()?;
+        let shared_dir_path = shared_dir.path().to_str().unwrap();
+
+        try_join_all(
+            get_test_cases()
+                .iter()
+                .map(|test_case| round_trip_test(test_case, shared_dir_path)),
+        )
+        .await?;
+
+        let leftover_temp_files = std::fs::read_dir(shared_dir.path())?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftover_temp_files, 0);
+
+        Ok(())
+    }
+
+    async fn round_trip_test(test_case: &TestCase, shared_dir: &str) -> Result<()> {
+        // Two checkouts of the same repo share a cache directory.
+        let writer_root = tempdir()?;
+        let writer_root_path = AbsoluteSystemPath::from_std_path(writer_root.path())?;
+        test_case.initialize(writer_root_path)?;
+        let reader_root = tempdir()?;
+        let reader_root_path = AbsoluteSystemPath::from_std_path(reader_root.path())?;
+
+        let writer = SharedDirCache::new(shared_dir, writer_root_path)?;
+        let reader = SharedDirCache::new(shared_dir, reader_root_path)?;
+
+        assert!(reader
+            .fetch(reader_root_path, test_case.hash)
+            .await?
+            .is_none());
+
+        let files: Vec<_> = test_case
+            .files
+            .iter()
+            .map(|f| f.path().to_owned())
+            .collect();
+        writer
+            .put(writer_root_path, test_case.hash, &files, test_case.duration)
+            .await?;
+
+        let cache_response = reader.exists(test_case.hash).await?.unwrap();
+        assert_eq!(
+            cache_response,
+            CacheHitMetadata {
+                time_saved: test_case.duration,
+                source: CacheSource::Remote
+            }
+        );
+
+        let (_, restored_files) = reader
+            .fetch(reader_root_path, test_case.hash)
+            .await?
+            .unwrap();
+        assert_eq!(restored_files.len(), test_case.files.len());
+        for (expected, actual) in test_case.files.iter().zip(restored_files.iter()) {
+            let actual: &AnchoredSystemPath = actual;
+            assert_eq!(expected.path(), actual);
+            let actual_file = reader_root_path.resolve(actual);
+            if let Some(contents) = expected.contents() {
+                assert_eq!(contents, actual_file.read_to_string()?);
+            } else {
+                assert!(actual_file.exists());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::Vendor;
+
+/// How a vendor's log viewer marks a collapsible group of lines
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupStyle {
+    GitHubActions,
+    GitLab,
+    Buildkite,
+    AzurePipelines,
+}
+
+/// How a vendor surfaces an error logged by a build in its UI
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnnotationStyle {
+    GitHubActions,
+    AzurePipelines,
+}
+
+/// What turbo can do with a vendor's log viewer beyond printing plain text.
+/// Vendors that aren't in the table can't do anything more.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub group: Option<GroupStyle>,
+    pub annotation: Option<AnnotationStyle>,
+}
+
+// Keyed by the vendor's constant
+const CAPABILITIES: &[(&str, Capabilities)] = &[
+    (
+        "AZURE_PIPELINES",
+        Capabilities {
+            group: Some(GroupStyle::AzurePipelines),
+            annotation: Some(AnnotationStyle::AzurePipelines),
+        },
+    ),
+    (
+        "BUILDKITE",
+        Capabilities {
+            group: Some(GroupStyle::Buildkite),
+            annotation: None,
+        },
+    ),
+    (
+        "GITHUB_ACTIONS",
+        Capabilities {
+            group: Some(GroupStyle::GitHubActions),
+            annotation: Some(AnnotationStyle::GitHubActions),
+        },
+    ),
+    (
+        "GITLAB",
+        Capabilities {
+            group: Some(GroupStyle::GitLab),
+            annotation: None,
+        },
+    ),
+];
+
+/// A task's logs, written as a collapsible group once the task finishes
+#[derive(Clone, Copy, Debug)]
+pub struct LogGroup<'a> {
+    pub name: &'a str,
+    pub started_at: SystemTime,
+    pub ended_at: SystemTime,
+    pub failed: bool,
+}
+
+impl Vendor {
+    pub fn capabilities(&self) -> Capabilities {
+        CAPABILITIES
+            .iter()
+            .find(|(constant, _)| *constant == self.constant)
+            .map(|(_, capabilities)| *capabilities)
+            .unwrap_or_default()
+    }
+}
+
+impl Capabilities {
+    /// The lines written before and after a group's logs. Vendors that can't
+    /// collapse a group when it's opened show the failure as an annotation
+    /// instead, the rest open the groups of failed tasks.
+    pub fn header_footer(&self, group: &LogGroup) -> Option<(String, String)> {
+        let LogGroup {
+            name,
+            started_at,
+            ended_at,
+            failed,
+        } = *group;
+        Some(match self.group? {
+            GroupStyle::GitHubActions => (format!("::group::{name}\n"), "::endgroup::\n".into()),
+            GroupStyle::AzurePipelines => (format!("##[group]{name}\n"), "##[endgroup]\n".into()),
+            // Sections are matched up by their id, and the duration GitLab
+            // shows for them comes from the timestamps
+            GroupStyle::GitLab => {
+                let id = gitlab_section_id(name);
+                let collapsed = if failed { "" } else { "[collapsed=true]" };
+                (
+                    format!(
+                        "\x1b[0Ksection_start:{}:{id}{collapsed}\r\x1b[0K{name}\n",
+                        unix_seconds(started_at)
+                    ),
+                    format!(
+                        "\x1b[0Ksection_end:{}:{id}\r\x1b[0K\n",
+                        unix_seconds(ended_at)
+                    ),
+                )
+            }
+            // A group lasts until the next one starts, so there's no footer
+            GroupStyle::Buildkite => {
+                let marker = if failed { "+++" } else { "---" };
+                (format!("{marker} {name}\n"), String::new())
+            }
+        })
+    }
+
+    /// A line that the vendor shows as an error annotation on the build
+    pub fn error_annotation(&self, message: &str) -> Option<String> {
+        Some(match self.annotation? {
+            AnnotationStyle::GitHubActions => format!(
+                "::error::{}",
+                escape(message, &[('%', "%25"), ('\r', "%0D"), ('\n', "%0A")])
+            ),
+            AnnotationStyle::AzurePipelines => format!(
+                "##vso[task.logissue type=error]{}",
+                escape(message, &[('%', "%AZP25"), ('\r', "%0D"), ('\n', "%0A")])
+            ),
+        })
+    }
+}
+
+// Annotations have to fit on a single line
+fn escape(message: &str, escapes: &[(char, &str)]) -> String {
+    let mut escaped = String::with_capacity(message.len());
+    for c in message.chars() {
+        match escapes.iter().find(|(from, _)| *from == c) {
+            Some((_, to)) => escaped.push_str(to),
+            None => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn gitlab_section_id(name: &str) -> String {
+    let id = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '.' | '-' => c,
+            _ => '_',
+        })
+        .collect::<String>();
+    format!("turbo_{id}")
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use test_case::test_case;
+
+    use super::*;
+    use crate::vendors::get_vendors;
+
+    fn capabilities(constant: &str) -> Capabilities {
+        get_vendors()
+            .iter()
+            .find(|vendor| vendor.constant == constant)
+            .expect("vendor exists")
+            .capabilities()
+    }
+
+    fn group(failed: bool) -> LogGroup<'static> {
+        LogGroup {
+            name: "web:build",
+            started_at: UNIX_EPOCH + Duration::from_secs(1700000000),
+            ended_at: UNIX_EPOCH + Duration::from_secs(1700000042),
+            failed,
+        }
+    }
+
+    #[test]
+    fn test_table_matches_vendors() {
+        for (constant, _) in CAPABILITIES {
+            assert!(
+                get_vendors()
+                    .iter()
+                    .any(|vendor| vendor.constant == *constant),
+                "{constant} is not a vendor"
+            );
+        }
+        assert_eq!(capabilities("JENKINS"), Capabilities::default());
+    }
+
+    #[test_case("GITHUB_ACTIONS", false, "::group::web:build\n", "::endgroup::\n" ; "github")]
+    #[test_case("AZURE_PIPELINES", false, "##[group]web:build\n", "##[endgroup]\n" ; "azure")]
+    #[test_case(
+        "GITLAB",
+        false,
+        "\x1b[0Ksection_start:1700000000:turbo_web_build[collapsed=true]\r\x1b[0Kweb:build\n",
+        "\x1b[0Ksection_end:1700000042:turbo_web_build\r\x1b[0K\n"
+        ; "gitlab"
+    )]
+    #[test_case(
+        "GITLAB",
+        true,
+        "\x1b[0Ksection_start:1700000000:turbo_web_build\r\x1b[0Kweb:build\n",
+        "\x1b[0Ksection_end:1700000042:turbo_web_build\r\x1b[0K\n"
+        ; "gitlab failed"
+    )]
+    #[test_case("BUILDKITE", false, "--- web:build\n", "" ; "buildkite")]
+    #[test_case("BUILDKITE", true, "+++ web:build\n", "" ; "buildkite failed")]
+    fn test_header_footer(constant: &str, failed: bool, header: &str, footer: &str) {
+        assert_eq!(
+            capabilities(constant).header_footer(&group(failed)),
+            Some((header.to_string(), footer.to_string()))
+        );
+    }
+
+    #[test_case("GITHUB_ACTIONS", Some("::error::web#build: 100%25 broken%0Asee logs") ; "github")]
+    #[test_case(
+        "AZURE_PIPELINES",
+        Some("##vso[task.logissue type=error]web#build: 100%AZP25 broken%0Asee logs")
+        ; "azure"
+    )]
+    #[test_case("GITLAB", None ; "gitlab")]
+    fn test_error_annotation(constant: &str, expected: Option<&str>) {
+        assert_eq!(
+            capabilities(constant).error_annotation("web#build: 100% broken\nsee logs"),
+            expected.map(str::to_string)
+        );
+    }
+}
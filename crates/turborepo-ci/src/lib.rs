@@ -1,11 +1,15 @@
 #![deny(clippy::all)]
 
+mod capabilities;
 mod vendors;
 
 use std::{env, sync::OnceLock};
 
 use crate::vendors::get_vendors;
-pub use crate::vendors::Vendor;
+pub use crate::{
+    capabilities::{AnnotationStyle, Capabilities, GroupStyle, LogGroup},
+    vendors::Vendor,
+};
 
 static IS_CI: OnceLock<bool> = OnceLock::new();
 static VENDOR: OnceLock<Option<&'static Vendor>> = OnceLock::new();
@@ -93,15 +97,6 @@ impl Vendor {
     }
 }
 
-pub fn github_header_footer(package: Option<&str>, task: &str) -> (String, String) {
-    let header = if let Some(package) = package {
-        format!("::group::{package}:{task}\n")
-    } else {
-        format!("::group::{task}\n")
-    };
-    (header, "::endgroup::\n".to_string())
-}
-
 #[cfg(test)]
 mod tests {
     use tracing::info;
@@ -0,0 +1,304 @@
+//! Loading of `.env` files into the environment of a task.
+//!
+//! Files are given from highest to lowest precedence and never override a
+//! variable that is already set. Values can reference other variables as
+//! `${VAR}`, which are looked up in the environment first and then in the
+//! files. Only variables that are part of a task's hash can be referenced
+//! from the environment, otherwise changing one would change the task's
+//! environment without changing its hash. Other variables that are set expand
+//! to nothing.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use regex::{Captures, Regex};
+
+use crate::{EnvironmentVariableMap, Error};
+
+#[derive(Debug, PartialEq, Eq)]
+struct Entry {
+    value: String,
+    // Values in single quotes are taken literally
+    interpolate: bool,
+}
+
+/// Resolves the variables declared by `.env` files that aren't already set
+/// in `env`. References can only read the variables of `env` that are also in
+/// `hashed_env`. Each file is given as its path, used in errors, and its
+/// contents.
+pub fn resolve<P: AsRef<str>, C: AsRef<str>>(
+    env: &EnvironmentVariableMap,
+    hashed_env: &EnvironmentVariableMap,
+    files: &[(P, C)],
+) -> Result<EnvironmentVariableMap, Error> {
+    let mut entries = HashMap::new();
+    // Walk from lowest to highest precedence so that the winning file is
+    // the last to write each variable
+    for (path, contents) in files.iter().rev() {
+        let parsed = parse(contents.as_ref()).map_err(|line| Error::DotEnvSyntax {
+            file: path.as_ref().to_string(),
+            line,
+        })?;
+        entries.extend(parsed);
+    }
+
+    let mut resolved = HashMap::new();
+    for name in entries.keys() {
+        if !env.contains_key(name) {
+            let value = expand(
+                name,
+                env,
+                hashed_env,
+                &entries,
+                &mut resolved,
+                &mut Vec::new(),
+            )?;
+            resolved.insert(name.clone(), value);
+        }
+    }
+
+    Ok(EnvironmentVariableMap(resolved))
+}
+
+fn expand(
+    name: &str,
+    env: &EnvironmentVariableMap,
+    hashed_env: &EnvironmentVariableMap,
+    entries: &HashMap<String, Entry>,
+    resolved: &mut HashMap<String, String>,
+    stack: &mut Vec<String>,
+) -> Result<String, Error> {
+    if env.contains_key(name) {
+        return Ok(hashed_env.get(name).cloned().unwrap_or_default());
+    }
+    if let Some(value) = resolved.get(name) {
+        return Ok(value.clone());
+    }
+    let Some(entry) = entries.get(name) else {
+        return Ok(String::new());
+    };
+    if !entry.interpolate {
+        return Ok(entry.value.clone());
+    }
+    if stack.iter().any(|ancestor| ancestor == name) {
+        stack.push(name.to_string());
+        return Err(Error::DotEnvCycle(stack.join(" -> ")));
+    }
+
+    stack.push(name.to_string());
+    let mut error = None;
+    let value = reference_regex()
+        .replace_all(&entry.value, |captures: &Captures| {
+            if let Some(escaped) = captures.name("escaped") {
+                return escaped.as_str().to_string();
+            }
+            match expand(&captures["name"], env, hashed_env, entries, resolved, stack) {
+                Ok(value) => value,
+                Err(e) => {
+                    error.get_or_insert(e);
+                    String::new()
+                }
+            }
+        })
+        .into_owned();
+    if let Some(error) = error {
+        return Err(error);
+    }
+    stack.pop();
+
+    resolved.insert(name.to_string(), value.clone());
+    Ok(value)
+}
+
+fn reference_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"\\(?P<escaped>\$)|\$\{(?P<name>[A-Za-z_][A-Za-z0-9_]*)\}")
+            .expect("regex is valid")
+    })
+}
+
+// Errors with the line number of invalid syntax
+fn parse(contents: &str) -> Result<HashMap<String, Entry>, usize> {
+    let mut entries = HashMap::new();
+    let mut lines = contents.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let invalid = || index + 1;
+
+        let (name, value) = line.split_once('=').ok_or_else(invalid)?;
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(invalid());
+        }
+        let value = value.trim_start();
+
+        let entry = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                // Quoted values may span several lines
+                let mut value = value[1..].to_string();
+                let end = loop {
+                    if let Some(end) = closing_quote(&value, quote) {
+                        break end;
+                    }
+                    let (_, next) = lines.next().ok_or_else(invalid)?;
+                    value.push('\n');
+                    value.push_str(next);
+                };
+                let value = &value[..end];
+                match quote {
+                    '"' => Entry {
+                        value: unescape(value),
+                        interpolate: true,
+                    },
+                    _ => Entry {
+                        value: value.to_string(),
+                        interpolate: false,
+                    },
+                }
+            }
+            _ => Entry {
+                value: value
+                    .split_once(" #")
+                    .map_or(value, |(value, _comment)| value)
+                    .trim_end()
+                    .to_string(),
+                interpolate: true,
+            },
+        };
+        entries.insert(name.to_string(), entry);
+    }
+
+    Ok(entries)
+}
+
+// Anything after the closing quote is ignored
+fn closing_quote(value: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in value.char_indices() {
+        match c {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            c if c == quote && !escaped => return Some(index),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('"') => unescaped.push('"'),
+            Some('\\') => unescaped.push('\\'),
+            // Left for interpolation to handle
+            Some('$') => unescaped.push_str("\\$"),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> EnvironmentVariableMap {
+        EnvironmentVariableMap(
+            vars.iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test_case("FOO=bar", "bar" ; "unquoted")]
+    #[test_case("export FOO = bar  # comment", "bar" ; "export and comment")]
+    #[test_case("FOO=bar#baz", "bar#baz" ; "hash without space")]
+    #[test_case("FOO=\"a \\\"b\\\"\\nc\"", "a \"b\"\nc" ; "double quoted")]
+    #[test_case("FOO='${HOME} \\n'", "${HOME} \\n" ; "single quoted")]
+    #[test_case("FOO=\"first\nsecond\"", "first\nsecond" ; "multiline")]
+    #[test_case("FOO=${HOME}/bin", "/home/turbo/bin" ; "environment reference")]
+    #[test_case("FOO=\\${HOME}", "${HOME}" ; "escaped reference")]
+    #[test_case("FOO=${MISSING}", "" ; "missing reference")]
+    #[test_case("FOO=${SECRET}", "" ; "unhashed reference")]
+    #[test_case("BAR=baz\nFOO=\"${BAR}-${BAR}\"", "baz-baz" ; "file reference")]
+    #[test_case("FOO=${BAR}\nBAR=${BAZ}\nBAZ=qux", "qux" ; "nested reference")]
+    fn test_resolve(contents: &str, expected: &str) {
+        let resolved = resolve(
+            &env(&[("HOME", "/home/turbo"), ("SECRET", "hunter2")]),
+            &env(&[("HOME", "/home/turbo")]),
+            &[(".env", contents)],
+        )
+        .unwrap();
+        assert_eq!(resolved.get("FOO").map(String::as_str), Some(expected));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let resolved = resolve(
+            &env(&[("SET", "environment")]),
+            &env(&[("SET", "environment")]),
+            &[
+                (".env.local", "LOCAL=local\nSET=local"),
+                (
+                    ".env",
+                    "LOCAL=shared\nSHARED=shared\nSET=shared\nREF=${LOCAL}",
+                ),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            resolved.into_inner(),
+            [("LOCAL", "local"), ("SHARED", "shared"), ("REF", "local")]
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        );
+    }
+
+    #[test_case("FOO" ; "missing equals")]
+    #[test_case("FOO BAR=baz" ; "whitespace in name")]
+    #[test_case("FOO=\"unterminated" ; "unterminated quote")]
+    fn test_syntax_error(contents: &str) {
+        assert_eq!(
+            resolve(
+                &env(&[]),
+                &env(&[]),
+                &[(".env", format!("# comment\n{contents}"))]
+            )
+            .unwrap_err()
+            .to_string(),
+            "invalid syntax on line 2 of .env"
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let error =
+            resolve(&env(&[]), &env(&[]), &[(".env", "FOO=${BAR}\nBAR=${FOO}")]).unwrap_err();
+        assert!(
+            matches!(
+                error,
+                Error::DotEnvCycle(ref cycle)
+                    if cycle == "FOO -> BAR -> FOO" || cycle == "BAR -> FOO -> BAR"
+            ),
+            "unexpected error {error}"
+        );
+    }
+}
@@ -12,6 +12,8 @@ use serde::Serialize;
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 
+pub mod dot_env;
+
 const DEFAULT_ENV_VARS: [&str; 1] = ["VERCEL_ANALYTICS_ID"];
 
 /// Environment mode after we've resolved the `Infer` variant
@@ -25,6 +27,10 @@ pub enum ResolvedEnvMode {
 pub enum Error {
     #[error("Failed to parse regex: {0}")]
     Regex(#[from] regex::Error),
+    #[error("invalid syntax on line {line} of {file}")]
+    DotEnvSyntax { file: String, line: usize },
+    #[error("cyclic reference between .env variables: {0}")]
+    DotEnvCycle(String),
 }
 
 // TODO: Consider using immutable data structures here
@@ -12,6 +12,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build_server(true)
         .file_descriptor_set_path("src/daemon/file_descriptor_set.bin")
         .compile(&["turbod.proto"], &["../../cli/internal/turbodprotocol"]);
+    let worker_build_result = tonic_build::configure()
+        .build_server(true)
+        .compile(&["worker.proto"], &["./src/distributed"]);
     let capnpc_result = capnpc::CompilerCommand::new()
         .file("./src/hash/proto.capnp")
         .import_path("./src/hash/std") // we need to include the 'stdlib' for capnp-go
@@ -20,7 +23,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let invocation = std::env::var("RUSTC_WRAPPER").unwrap_or_default();
     if invocation.ends_with("rust-analyzer") {
-        if tonic_build_result.is_err() {
+        if tonic_build_result.is_err() || worker_build_result.is_err() {
             println!("cargo:warning=tonic_build failed, but continuing with rust-analyzer");
         }
 
@@ -31,6 +34,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     } else {
         tonic_build_result.expect("tonic_build command");
+        worker_build_result.expect("tonic_build command");
         capnpc_result.expect("schema compiler command");
     }
 
@@ -0,0 +1,490 @@
+//! Boundaries keep workspaces from depending on what they shouldn't. Tagged
+//! workspaces are checked against the tag rules in the root turbo.json, and
+//! every package a workspace imports has to be declared in its package.json.
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::OnceLock,
+};
+
+use regex::Regex;
+use turbopath::{AbsoluteSystemPath, RelativeUnixPathBuf};
+use turborepo_repository::{
+    package_graph::{PackageGraph, WorkspaceInfo, WorkspaceName, WorkspaceNode},
+    package_json::PackageJson,
+};
+use turborepo_scm::SCM;
+
+use crate::config::{self, BoundariesJson, TagRulesJson, TurboJson};
+
+const SOURCE_EXTENSIONS: &[&str] = &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"];
+
+// Modules that come with Node, which don't need to be declared. They can also
+// be imported with a `node:` prefix.
+const NODE_BUILTINS: &[&str] = &[
+    "assert",
+    "async_hooks",
+    "buffer",
+    "child_process",
+    "cluster",
+    "console",
+    "constants",
+    "crypto",
+    "dgram",
+    "diagnostics_channel",
+    "dns",
+    "domain",
+    "events",
+    "fs",
+    "http",
+    "http2",
+    "https",
+    "inspector",
+    "module",
+    "net",
+    "os",
+    "path",
+    "perf_hooks",
+    "process",
+    "punycode",
+    "querystring",
+    "readline",
+    "repl",
+    "stream",
+    "string_decoder",
+    "sys",
+    "timers",
+    "tls",
+    "trace_events",
+    "tty",
+    "url",
+    "util",
+    "v8",
+    "vm",
+    "wasi",
+    "worker_threads",
+    "zlib",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error("unable to list the files of {package}: {source}")]
+    ListFiles {
+        package: WorkspaceName,
+        #[source]
+        source: turborepo_scm::Error,
+    },
+    #[error(transparent)]
+    Path(#[from] turbopath::PathError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Violation {
+    /// The file with the offending dependency or import
+    pub path: RelativeUnixPathBuf,
+    pub line: Option<usize>,
+    pub kind: ViolationKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ViolationKind {
+    /// The dependency has none of the tags the dependent's tag allows
+    NotAllowed {
+        dependency: WorkspaceName,
+        tag: String,
+        allowed: Vec<String>,
+    },
+    /// The dependency has a tag that the dependent's tag denies
+    Denied {
+        dependency: WorkspaceName,
+        tag: String,
+        denied: String,
+    },
+    /// A package is imported without being declared in package.json
+    Undeclared { import: String, package: String },
+    /// A relative import reaches into another directory of the repo
+    OutsidePackage { import: String },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{line}: ", self.path)?,
+            None => write!(f, "{}: ", self.path)?,
+        }
+        match &self.kind {
+            ViolationKind::NotAllowed {
+                dependency,
+                tag,
+                allowed,
+            } => write!(
+                f,
+                "depends on {dependency}, but packages tagged \"{tag}\" can only depend on \
+                 packages tagged {}",
+                allowed
+                    .iter()
+                    .map(|tag| format!("\"{tag}\""))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ViolationKind::Denied {
+                dependency,
+                tag,
+                denied,
+            } => write!(
+                f,
+                "depends on {dependency}, but packages tagged \"{tag}\" can't depend on packages \
+                 tagged \"{denied}\""
+            ),
+            ViolationKind::Undeclared { import, package } => write!(
+                f,
+                "imports \"{import}\", but {package} isn't a dependency in package.json"
+            ),
+            ViolationKind::OutsidePackage { import } => {
+                write!(f, "imports \"{import}\", which is outside of the package")
+            }
+        }
+    }
+}
+
+/// Finds every violation of the repository's boundaries, sorted by path
+pub fn check(
+    repo_root: &AbsoluteSystemPath,
+    pkg_graph: &PackageGraph,
+    root_turbo_json: &TurboJson,
+    scm: &SCM,
+) -> Result<Vec<Violation>, Error> {
+    let mut violations = Vec::new();
+
+    if let Some(boundaries) = &root_turbo_json.boundaries {
+        let tags = workspace_tags(repo_root, pkg_graph)?;
+        violations.extend(check_tags(pkg_graph, boundaries, &tags));
+    }
+
+    for (name, info) in pkg_graph.workspaces() {
+        // The root contains every other workspace, so its files aren't its own
+        if name == &WorkspaceName::Root {
+            continue;
+        }
+        violations.extend(check_imports(repo_root, scm, name, info)?);
+    }
+
+    violations.sort();
+    Ok(violations)
+}
+
+fn workspace_tags<'a>(
+    repo_root: &AbsoluteSystemPath,
+    pkg_graph: &'a PackageGraph,
+) -> Result<HashMap<&'a WorkspaceName, Vec<String>>, Error> {
+    let mut tags = HashMap::new();
+    for (name, info) in pkg_graph.workspaces() {
+        if name == &WorkspaceName::Root {
+            continue;
+        }
+        let workspace_dir = repo_root.resolve(info.package_path());
+        match TurboJson::load(&workspace_dir, &PackageJson::default(), false) {
+            Ok(turbo_json) => {
+                tags.insert(name, turbo_json.tags);
+            }
+            // Workspaces without a turbo.json are untagged
+            Err(config::Error::NoTurboJSON) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(tags)
+}
+
+fn check_tags(
+    pkg_graph: &PackageGraph,
+    boundaries: &BoundariesJson,
+    tags: &HashMap<&WorkspaceName, Vec<String>>,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (name, info) in pkg_graph.workspaces() {
+        let Some(dependent_tags) = tags.get(name) else {
+            continue;
+        };
+        let dependencies = pkg_graph
+            .immediate_dependencies(&WorkspaceNode::Workspace(name.clone()))
+            .unwrap_or_default();
+        for dependency in dependencies {
+            let WorkspaceNode::Workspace(dependency) = dependency else {
+                continue;
+            };
+            if dependency == &WorkspaceName::Root {
+                continue;
+            }
+            let dependency_tags = tags.get(dependency).map_or(&[][..], Vec::as_slice);
+            for tag in dependent_tags {
+                let Some(rules) = boundaries.tags.get(tag) else {
+                    continue;
+                };
+                violations.extend(tag_violations(tag, rules, dependency, dependency_tags).map(
+                    |kind| Violation {
+                        path: info.package_json_path().to_unix(),
+                        line: None,
+                        kind,
+                    },
+                ));
+            }
+        }
+    }
+    violations
+}
+
+fn tag_violations<'a>(
+    tag: &'a str,
+    rules: &'a TagRulesJson,
+    dependency: &'a WorkspaceName,
+    dependency_tags: &'a [String],
+) -> impl Iterator<Item = ViolationKind> + 'a {
+    let not_allowed = rules
+        .allow
+        .as_ref()
+        .filter(|allowed| !dependency_tags.iter().any(|tag| allowed.contains(tag)))
+        .map(|allowed| ViolationKind::NotAllowed {
+            dependency: dependency.clone(),
+            tag: tag.to_string(),
+            allowed: allowed.clone(),
+        });
+    let denied = rules
+        .deny
+        .iter()
+        .flatten()
+        .filter(move |denied| dependency_tags.contains(denied))
+        .map(move |denied| ViolationKind::Denied {
+            dependency: dependency.clone(),
+            tag: tag.to_string(),
+            denied: denied.clone(),
+        });
+    not_allowed.into_iter().chain(denied)
+}
+
+fn check_imports(
+    repo_root: &AbsoluteSystemPath,
+    scm: &SCM,
+    name: &WorkspaceName,
+    info: &WorkspaceInfo,
+) -> Result<Vec<Violation>, Error> {
+    let package_json = &info.package_json;
+    let declared = package_json
+        .all_dependencies()
+        .chain(package_json.peer_dependencies.iter().flatten())
+        .map(|(dependency, _)| dependency.as_str())
+        // Packages can import themselves by name
+        .chain(package_json.name.as_deref())
+        .collect::<HashSet<_>>();
+
+    let package_path = info.package_path();
+    let package_dir = repo_root.resolve(package_path);
+    // Hashing without inputs lists the package's files, leaving out ignored
+    // ones such as build outputs and node_modules
+    let files = scm
+        .get_package_file_hashes::<&str>(repo_root, package_path, &[])
+        .map_err(|source| Error::ListFiles {
+            package: name.clone(),
+            source,
+        })?;
+
+    let mut violations = Vec::new();
+    for file in files.keys() {
+        if !file
+            .extension()
+            .map_or(false, |extension| SOURCE_EXTENSIONS.contains(&extension))
+        {
+            continue;
+        }
+        // Files that can't be read as text have nothing for us to check
+        let Ok(contents) = package_dir.join_unix_path(file)?.read_to_string() else {
+            continue;
+        };
+        for (line, import) in imports(&contents) {
+            let kind = if import.starts_with('.') {
+                if !escapes_package(file.as_str(), import) {
+                    continue;
+                }
+                ViolationKind::OutsidePackage {
+                    import: import.to_string(),
+                }
+            } else {
+                match package_name(import) {
+                    Some(package)
+                        if !declared.contains(package) && !NODE_BUILTINS.contains(&package) =>
+                    {
+                        ViolationKind::Undeclared {
+                            import: import.to_string(),
+                            package: package.to_string(),
+                        }
+                    }
+                    _ => continue,
+                }
+            };
+            violations.push(Violation {
+                path: RelativeUnixPathBuf::new(format!("{}/{file}", package_path.to_unix()))?,
+                line: Some(line),
+                kind,
+            });
+        }
+    }
+    Ok(violations)
+}
+
+fn import_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    // Covers `import ... from`, `export ... from`, side effect imports,
+    // `require()` and dynamic `import()`
+    RE.get_or_init(|| {
+        Regex::new(r#"\b(?:from|import|require)\s*\(?\s*["']([^"'\n]+)["']"#).unwrap()
+    })
+}
+
+/// The specifiers imported by a source file, along with the line they're on
+fn imports(contents: &str) -> impl Iterator<Item = (usize, &str)> {
+    import_regex().captures_iter(contents).map(|captures| {
+        let specifier = captures.get(1).expect("regex has a capture group");
+        let line = contents[..specifier.start()].matches('\n').count() + 1;
+        (line, specifier.as_str())
+    })
+}
+
+/// The package a bare specifier imports from, e.g. `@scope/pkg` for
+/// `@scope/pkg/utils`. Absolute paths, subpath imports (`#internal`),
+/// protocols (`node:fs`) and aliases such as `@/` or `~/` aren't packages.
+fn package_name(specifier: &str) -> Option<&str> {
+    if specifier.starts_with(['/', '#', '~']) {
+        return None;
+    }
+    let mut segments = specifier.splitn(3, '/');
+    let first = segments.next()?;
+    if first.contains(':') {
+        return None;
+    }
+    match first.strip_prefix('@') {
+        Some("") => None,
+        Some(_) => {
+            let name = segments.next()?;
+            Some(&specifier[..first.len() + 1 + name.len()])
+        }
+        None => Some(first),
+    }
+}
+
+/// Whether a relative import from `file`, which is relative to its package,
+/// leaves the package's directory
+fn escapes_package(file: &str, import: &str) -> bool {
+    let mut depth = file.split('/').count() - 1;
+    for component in import.split('/') {
+        match component {
+            "" | "." => {}
+            ".." if depth == 0 => return true,
+            ".." => depth -= 1,
+            _ => depth += 1,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test]
+    fn test_imports() {
+        let contents = r#"import React from "react";
+import { Button } from '@acme/ui/button';
+import "./styles.css";
+export * from "../shared";
+const fs = require("node:fs");
+const lazy = await import("lodash/debounce");
+// this is just text that mentions importing
+"#;
+        assert_eq!(
+            imports(contents).collect::<Vec<_>>(),
+            vec![
+                (1, "react"),
+                (2, "@acme/ui/button"),
+                (3, "./styles.css"),
+                (4, "../shared"),
+                (5, "node:fs"),
+                (6, "lodash/debounce"),
+            ]
+        );
+    }
+
+    #[test_case("react", Some("react") ; "package")]
+    #[test_case("lodash/debounce", Some("lodash") ; "subpath")]
+    #[test_case("@acme/ui", Some("@acme/ui") ; "scoped")]
+    #[test_case("@acme/ui/button", Some("@acme/ui") ; "scoped subpath")]
+    #[test_case("@acme", None ; "scope without name")]
+    #[test_case("@/components/button", None ; "at alias")]
+    #[test_case("~/components/button", None ; "tilde alias")]
+    #[test_case("#internal/utils", None ; "subpath import")]
+    #[test_case("node:fs", None ; "node protocol")]
+    #[test_case("/abs/path", None ; "absolute")]
+    fn test_package_name(specifier: &str, expected: Option<&str>) {
+        assert_eq!(package_name(specifier), expected);
+    }
+
+    #[test_case("index.ts", "./utils", false ; "sibling")]
+    #[test_case("src/index.ts", "../package.json", false ; "package root")]
+    #[test_case("index.ts", "../ui/button", true ; "sibling package")]
+    #[test_case("src/a/index.ts", "../../../../apps/web", true ; "far outside")]
+    #[test_case("src/index.ts", "./../../ui", true ; "leading dot")]
+    #[test_case("index.ts", "./a/../../b", true ; "back out")]
+    fn test_escapes_package(file: &str, import: &str, expected: bool) {
+        assert_eq!(escapes_package(file, import), expected);
+    }
+
+    #[test]
+    fn test_tag_violations() {
+        let rules = TagRulesJson {
+            allow: Some(vec!["ui".to_string(), "lib".to_string()]),
+            deny: Some(vec!["internal".to_string()]),
+        };
+        let dependency = WorkspaceName::from("docs");
+        let violations = |tags: &[&str]| {
+            let tags = tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+            tag_violations("app", &rules, &dependency, &tags).collect::<Vec<_>>()
+        };
+
+        assert!(violations(&["ui"]).is_empty());
+        assert_eq!(
+            violations(&[]),
+            vec![ViolationKind::NotAllowed {
+                dependency: dependency.clone(),
+                tag: "app".to_string(),
+                allowed: vec!["ui".to_string(), "lib".to_string()],
+            }]
+        );
+        assert_eq!(
+            violations(&["lib", "internal"]),
+            vec![ViolationKind::Denied {
+                dependency: dependency.clone(),
+                tag: "app".to_string(),
+                denied: "internal".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let violation = Violation {
+            path: RelativeUnixPathBuf::new("apps/web/src/index.ts").unwrap(),
+            line: Some(3),
+            kind: ViolationKind::Undeclared {
+                import: "lodash/debounce".to_string(),
+                package: "lodash".to_string(),
+            },
+        };
+        assert_eq!(
+            violation.to_string(),
+            "apps/web/src/index.ts:3: imports \"lodash/debounce\", but lodash isn't a dependency \
+             in package.json"
+        );
+    }
+}
@@ -5,8 +5,9 @@ use thiserror::Error;
 use turborepo_repository::package_graph;
 
 use crate::{
-    commands::{bin, generate, prune},
+    commands::{bin, boundaries, deps, generate, prune, query},
     daemon::DaemonError,
+    distributed,
     rewrite_json::RewriteError,
     run,
 };
@@ -18,10 +19,14 @@ pub enum Error {
     #[error("{0}")]
     Bin(#[from] bin::Error, #[backtrace] backtrace::Backtrace),
     #[error(transparent)]
+    Boundaries(#[from] boundaries::Error),
+    #[error(transparent)]
     Path(#[from] turbopath::PathError),
     #[error("at least one task must be specified")]
     NoTasks(#[backtrace] backtrace::Backtrace),
     #[error(transparent)]
+    Cache(#[from] turborepo_cache::CacheError),
+    #[error(transparent)]
     Config(#[from] crate::config::Error),
     #[error(transparent)]
     ChromeTracing(#[from] crate::tracing::Error),
@@ -34,10 +39,14 @@ pub enum Error {
     #[error(transparent)]
     Daemon(#[from] DaemonError),
     #[error(transparent)]
+    Deps(#[from] deps::Error),
+    #[error(transparent)]
     Generate(#[from] generate::Error),
     #[error(transparent)]
     Prune(#[from] prune::Error),
     #[error(transparent)]
+    Query(#[from] query::Error),
+    #[error(transparent)]
     PackageJson(#[from] turborepo_repository::package_json::Error),
     #[error(transparent)]
     PackageManager(#[from] turborepo_repository::package_manager::Error),
@@ -46,4 +55,6 @@ pub enum Error {
     Run(#[from] run::Error),
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
+    #[error(transparent)]
+    Worker(#[from] distributed::Error),
 }
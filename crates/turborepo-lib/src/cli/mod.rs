@@ -1,4 +1,11 @@
-use std::{backtrace, backtrace::Backtrace, env, io, mem, process};
+use std::{
+    backtrace,
+    backtrace::Backtrace,
+    env, io, mem,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    process,
+    time::Duration,
+};
 
 use camino::{Utf8Path, Utf8PathBuf};
 use clap::{
@@ -8,13 +15,16 @@ use clap::{
 use clap_complete::{generate, Shell};
 pub use error::Error;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 use turbopath::AbsoluteSystemPathBuf;
 use turborepo_repository::inference::{RepoMode, RepoState};
 use turborepo_ui::UI;
 
 use crate::{
-    commands::{bin, daemon, generate, info, link, login, logout, prune, unlink, CommandBase},
+    commands::{
+        bin, boundaries, cache, daemon, deps, generate, info, link, login, logout, prune, query,
+        unlink, worker, CommandBase,
+    },
     get_version,
     tracing::TurboSubscriber,
     Payload,
@@ -62,6 +72,23 @@ impl Default for LogOrder {
     }
 }
 
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, ValueEnum)]
+pub enum UIMode {
+    #[default]
+    #[serde(rename = "stream")]
+    Stream,
+    #[serde(rename = "tui")]
+    Tui,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, ValueEnum)]
+pub enum CacheFormat {
+    #[serde(rename = "tarball")]
+    Tarball,
+    #[serde(rename = "content-addressed")]
+    ContentAddressed,
+}
+
 // NOTE: These *must* be kept in sync with the `_dryRunJSONValue`
 // and `_dryRunTextValue` constants in run.go.
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, ValueEnum)]
@@ -200,6 +227,115 @@ pub enum DaemonCommand {
     Clean,
 }
 
+#[derive(Subcommand, Copy, Clone, Debug, Serialize, PartialEq)]
+#[serde(tag = "command")]
+pub enum CacheCommand {
+    /// Removes entries from the filesystem cache, least recently used first,
+    /// until it fits within the given limits
+    #[command(group = ArgGroup::new("limits").multiple(true).required(true))]
+    Prune {
+        /// Maximum total size of the cache, e.g. 10GB or 500MB
+        #[clap(long, group = "limits", value_parser = parse_size)]
+        max_size: Option<u64>,
+        /// Maximum time since an entry was last used, e.g. 7d or 12h
+        #[clap(long, group = "limits", value_parser = humantime::parse_duration)]
+        max_age: Option<Duration>,
+    },
+    /// Reports the number of entries in the filesystem cache and their size
+    Stats {
+        /// Pass --json to report stats in JSON format
+        #[clap(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug, Serialize, PartialEq)]
+#[serde(tag = "command")]
+pub enum QueryCommand {
+    /// Reports the files, packages and tasks affected by the changes between
+    /// two git refs
+    Affected {
+        /// The git ref to compare against
+        #[clap(long)]
+        base: String,
+        /// The git ref containing the changes
+        #[clap(long, default_value = "HEAD")]
+        head: String,
+        /// Only report these tasks. Defaults to every task in turbo.json
+        #[clap(long, value_delimiter = ',')]
+        tasks: Vec<String>,
+        /// Pass --json to report in JSON format
+        #[clap(long)]
+        json: bool,
+    },
+}
+
+// Parses sizes like `500MB` or `10GB`. Units are powers of 1024.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{value}'"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        "T" | "TB" => 1 << 40,
+        _ => {
+            return Err(format!(
+                "invalid size unit '{unit}', expected one of B, KB, MB, GB or TB"
+            ))
+        }
+    };
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub enum ReportFormat {
+    #[serde(rename = "junit")]
+    Junit,
+    #[serde(rename = "tap")]
+    Tap,
+}
+
+/// A report of the run's task results, written in a format that CI systems
+/// can display natively
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Report {
+    pub format: ReportFormat,
+    pub path: Utf8PathBuf,
+}
+
+// Parses reports like `junit:reports/turbo.xml`
+fn parse_report(value: &str) -> Result<Report, String> {
+    let (format, path) = value
+        .split_once(':')
+        .ok_or_else(|| format!("invalid report '{value}', expected <FORMAT>:<PATH>"))?;
+    let format = match format {
+        "junit" => ReportFormat::Junit,
+        "tap" => ReportFormat::Tap,
+        _ => {
+            return Err(format!(
+                "invalid report format '{format}', expected one of junit or tap"
+            ))
+        }
+    };
+    if path.is_empty() {
+        return Err(format!("missing path for {value} report"));
+    }
+
+    Ok(Report {
+        format,
+        path: Utf8PathBuf::from(path),
+    })
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, ValueEnum)]
 pub enum LinkTarget {
     RemoteCache,
@@ -243,7 +379,9 @@ impl Args {
                     run_args.single_package = is_single_package
                 }
 
-                if let Some(Command::Run(ref mut run_args)) = args.command {
+                if let Some(Command::Run(ref mut run_args) | Command::Watch(ref mut run_args)) =
+                    args.command
+                {
                     run_args.single_package = is_single_package;
                 }
 
@@ -292,7 +430,9 @@ impl Args {
 
     pub fn get_tasks(&self) -> &[String] {
         match &self.command {
-            Some(Command::Run(box RunArgs { tasks, .. })) => tasks,
+            Some(
+                Command::Run(box RunArgs { tasks, .. }) | Command::Watch(box RunArgs { tasks, .. }),
+            ) => tasks,
             _ => self
                 .run_args
                 .as_ref()
@@ -311,6 +451,20 @@ pub enum Command {
     // them as `{ "Bin": {} }` instead of as `"Bin"`.
     /// Get the path to the Turbo binary
     Bin {},
+    /// Check that workspaces only depend on the packages they're allowed to
+    ///
+    /// Reports dependencies that break the tag rules in the `boundaries` of
+    /// turbo.json, and imports of packages that aren't declared in a
+    /// workspace's package.json.
+    Boundaries {},
+    /// Inspect and clean up the local filesystem cache
+    Cache {
+        /// Override the filesystem cache directory.
+        #[clap(long, global = true)]
+        cache_dir: Option<Utf8PathBuf>,
+        #[clap(subcommand)]
+        command: CacheCommand,
+    },
     /// Generate the autocompletion script for the specified shell
     #[serde(skip)]
     Completion { shell: Shell },
@@ -323,6 +477,18 @@ pub enum Command {
         #[serde(flatten)]
         command: Option<DaemonCommand>,
     },
+    /// List external packages that are resolved to more than one version
+    ///
+    /// Reports which workspaces use each version, and whether they depend on
+    /// it directly or through another package.
+    Deps {
+        /// Pass --json to report in JSON format
+        #[clap(long)]
+        json: bool,
+        /// Exit with a non-zero code if any package has more than one version
+        #[clap(long)]
+        fail_on_mismatch: bool,
+    },
     /// Generate a new app / package
     #[clap(aliases = ["g", "gen"])]
     Generate {
@@ -387,6 +553,11 @@ pub enum Command {
         #[clap(long = "out-dir", default_value_t = String::from("out"), value_parser)]
         output_dir: String,
     },
+    /// Query information about the repository
+    Query {
+        #[clap(subcommand)]
+        command: QueryCommand,
+    },
 
     /// Run tasks across projects in your monorepo
     ///
@@ -405,6 +576,33 @@ pub enum Command {
         #[clap(long, value_enum, default_value_t = LinkTarget::RemoteCache)]
         target: LinkTarget,
     },
+    /// Run tasks and re-run them as their inputs change
+    ///
+    /// Runs the given tasks once, then uses the daemon to watch the repository
+    /// and re-runs the tasks of changed packages, along with their dependents.
+    /// Persistent tasks are started once and kept running.
+    Watch(Box<RunArgs>),
+    /// Run tasks on behalf of `turbo run --workers`
+    ///
+    /// Serves tasks sent by a coordinating `turbo run` from this checkout of
+    /// the repository. Outputs of each task are written to the cache, which
+    /// must be shared with the coordinator. The coordinator and its workers
+    /// must have the same TURBO_WORKER_TOKEN set.
+    Worker {
+        /// Address to listen on. Use 0.0.0.0 to serve coordinators on other
+        /// machines
+        #[clap(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+        host: IpAddr,
+        /// Port to listen on
+        #[clap(long, default_value_t = 9080)]
+        port: u16,
+        /// Number of tasks to run at once (default: number of CPUs)
+        #[clap(long)]
+        concurrency: Option<u32>,
+        /// Override the filesystem cache directory.
+        #[clap(long)]
+        cache_dir: Option<Utf8PathBuf>,
+    },
 }
 
 #[derive(Parser, Clone, Debug, Default, Serialize, PartialEq)]
@@ -474,6 +672,19 @@ pub struct RunArgs {
     /// Set the number of concurrent cache operations (default 10)
     #[clap(long, default_value_t = 10)]
     pub cache_workers: u32,
+    /// Evict the least recently used entries from the filesystem cache
+    /// before running once it is larger than this, e.g. 10GB or 500MB
+    #[clap(long, env = "TURBO_CACHE_MAX_SIZE", value_parser = parse_size)]
+    pub cache_max_size: Option<u64>,
+    /// Evict entries from the filesystem cache before running if they
+    /// haven't been used for this long, e.g. 7d or 12h
+    #[clap(long, env = "TURBO_CACHE_MAX_AGE", value_parser = humantime::parse_duration)]
+    pub cache_max_age: Option<Duration>,
+    /// Set how artifacts are stored in the cache. "content-addressed" stores
+    /// each distinct file once, shared between artifacts, and only uploads
+    /// files the remote cache doesn't have. (default tarball)
+    #[clap(long, env = "TURBO_CACHE_FORMAT", value_enum)]
+    pub cache_format: Option<CacheFormat>,
     /// Limit the concurrency of task execution. Use 1 for serial (i.e.
     /// one-at-a-time) execution.
     #[clap(long)]
@@ -561,9 +772,19 @@ pub struct RunArgs {
     /// turbo decide based on its own heuristics. (default auto)
     #[clap(long, env = "TURBO_LOG_ORDER", value_enum, default_value_t = LogOrder::Auto)]
     pub log_order: LogOrder,
+    /// Set how task progress and output are displayed. Use "stream" to
+    /// print task output as it is logged. Use "tui" for an interactive
+    /// dashboard with a scrollable log pane per task, which falls back to
+    /// "stream" when not run in a terminal. (default stream)
+    #[clap(long, env = "TURBO_UI", value_enum, default_value_t = UIMode::Stream)]
+    pub ui: UIMode,
     /// Only executes the tasks specified, does not execute parent tasks.
     #[clap(long)]
     pub only: bool,
+    /// Export a trace of the run, with a span for each task, to this
+    /// OpenTelemetry collector over OTLP/HTTP, e.g. http://localhost:4318
+    #[clap(long, env = "TURBO_OTEL_ENDPOINT", value_name = "URL")]
+    pub otel_endpoint: Option<String>,
     /// Execute all tasks in parallel.
     #[clap(long)]
     pub parallel: bool,
@@ -587,6 +808,11 @@ pub struct RunArgs {
     #[clap(long, env = "TURBO_REMOTE_CACHE_READ_ONLY", value_name = "BOOL", action = ArgAction::Set, default_value = "false", default_missing_value = "true", num_args = 0..=1)]
     #[serde(skip)]
     pub remote_cache_read_only: bool,
+    /// Write a report of each task's result once the run finishes, given
+    /// as <FORMAT>:<PATH>. Supported formats are "junit" and "tap". Can be
+    /// passed more than once.
+    #[clap(long, env = "TURBO_REPORT", value_delimiter = ',', value_name = "FORMAT:PATH", value_parser = parse_report)]
+    pub report: Vec<Report>,
     /// Specify package(s) to act as entry points for task execution.
     /// Supports globs.
     #[clap(long)]
@@ -599,6 +825,21 @@ pub struct RunArgs {
     /// Generate a summary of the turbo run
     #[clap(long, env = "TURBO_RUN_SUMMARY", default_missing_value = "true")]
     pub summarize: Option<Option<bool>>,
+    /// Explain why tasks missed the cache by comparing their hash inputs
+    /// against a previous run summary. Defaults to the most recent summary in
+    /// .turbo/runs. Saves a summary of this run
+    #[clap(long, num_args = 0..=1, default_missing_value = "", value_name = "SUMMARY")]
+    pub explain_hash: Option<String>,
+    /// Run tasks on `turbo worker` processes at these addresses, given as
+    /// host:port, rather than locally. Workers must share a cache with this
+    /// run, so their outputs can be restored here.
+    #[clap(
+        long,
+        env = "TURBO_WORKERS",
+        value_delimiter = ',',
+        value_name = "ADDRESSES"
+    )]
+    pub workers: Vec<String>,
 
     /// Use "none" to remove prefixes from task logs. Use "task" to get task id
     /// prefixing. Use "auto" to let turbo decide how to prefix the logs
@@ -703,7 +944,7 @@ pub async fn run(
     };
 
     // Set some run flags if we have the data and are executing a Run
-    if let Command::Run(run_args) = &mut command {
+    if let Command::Run(run_args) | Command::Watch(run_args) = &mut command {
         // Don't overwrite the flag if it's already been set for whatever reason
         run_args.single_package = run_args.single_package
             || repo_state
@@ -761,6 +1002,12 @@ pub async fn run(
 
             Ok(Payload::Rust(Ok(0)))
         }
+        Command::Boundaries { .. } => {
+            let base = CommandBase::new(cli_args, repo_root, version, ui);
+            let exit_code = boundaries::run(&base).await?;
+
+            Ok(Payload::Rust(Ok(exit_code)))
+        }
         #[allow(unused_variables)]
         Command::Daemon { command, idle_time } => {
             let base = CommandBase::new(cli_args.clone(), repo_root, version, ui);
@@ -777,6 +1024,17 @@ pub async fn run(
 
             Ok(Payload::Rust(Ok(0)))
         }
+        Command::Deps {
+            json,
+            fail_on_mismatch,
+        } => {
+            let json = *json;
+            let fail_on_mismatch = *fail_on_mismatch;
+            let base = CommandBase::new(cli_args, repo_root, version, ui);
+            let exit_code = deps::run(&base, json, fail_on_mismatch).await?;
+
+            Ok(Payload::Rust(Ok(exit_code)))
+        }
         Command::Generate {
             tag,
             generator_name,
@@ -879,10 +1137,27 @@ pub async fn run(
                 Ok(Payload::Go(Box::new(base)))
             } else {
                 use crate::commands::run;
-                let exit_code = run::run(base).await?;
-                Ok(Payload::Rust(Ok(exit_code)))
+                if let Some(otel_config) = run::otel_config(&base, args.otel_endpoint.as_deref()) {
+                    if let Err(e) = logger.enable_otel(&otel_config) {
+                        warn!("not exporting traces: {e}");
+                    }
+                }
+                let exit_code = run::run(base).await;
+                logger.flush_otel().await;
+                Ok(Payload::Rust(Ok(exit_code?)))
             }
         }
+        Command::Watch(args) => {
+            if args.tasks.is_empty() {
+                return Err(Error::NoTasks(backtrace::Backtrace::capture()));
+            }
+
+            let base = CommandBase::new(cli_args.clone(), repo_root, version, ui);
+
+            use crate::commands::run;
+            let exit_code = run::watch(base).await?;
+            Ok(Payload::Rust(Ok(exit_code)))
+        }
         Command::Prune {
             scope,
             scope_arg,
@@ -900,6 +1175,50 @@ pub async fn run(
             prune::prune(&base, &scope, docker, &output_dir).await?;
             Ok(Payload::Rust(Ok(0)))
         }
+        Command::Cache { cache_dir, command } => {
+            let cache_dir = cache_dir.clone();
+            let command = *command;
+            let base = CommandBase::new(cli_args, repo_root, version, ui);
+
+            match command {
+                CacheCommand::Prune { max_size, max_age } => {
+                    cache::prune(&base, cache_dir.as_deref(), max_size, max_age)?
+                }
+                CacheCommand::Stats { json } => cache::stats(&base, cache_dir.as_deref(), json)?,
+            }
+
+            Ok(Payload::Rust(Ok(0)))
+        }
+        Command::Query { command } => {
+            let command = command.clone();
+            let base = CommandBase::new(cli_args, repo_root, version, ui);
+
+            match command {
+                QueryCommand::Affected {
+                    base: from_ref,
+                    head,
+                    tasks,
+                    json,
+                } => query::affected(&base, &from_ref, &head, &tasks, json).await?,
+            }
+
+            Ok(Payload::Rust(Ok(0)))
+        }
+        Command::Worker {
+            host,
+            port,
+            concurrency,
+            cache_dir,
+        } => {
+            let addr = SocketAddr::new(*host, *port);
+            let concurrency = *concurrency;
+            let cache_dir = cache_dir.clone();
+            let base = CommandBase::new(cli_args, repo_root, version, ui);
+
+            worker::run(&base, addr, concurrency, cache_dir.as_deref()).await?;
+
+            Ok(Payload::Rust(Ok(0)))
+        }
         Command::Completion { shell } => {
             generate(*shell, &mut Args::command(), "turbo", &mut io::stdout());
 
@@ -910,7 +1229,11 @@ pub async fn run(
 
 #[cfg(test)]
 mod test {
-    use std::assert_matches::assert_matches;
+    use std::{
+        assert_matches::assert_matches,
+        net::{IpAddr, Ipv4Addr},
+        time::Duration,
+    };
 
     use camino::Utf8PathBuf;
     use clap::Parser;
@@ -969,7 +1292,9 @@ mod test {
     use anyhow::Result;
 
     use crate::cli::{
-        Args, Command, DryRunMode, EnvMode, LogOrder, LogPrefix, OutputLogsMode, RunArgs, Verbosity,
+        parse_report, parse_size, Args, CacheCommand, CacheFormat, Command, DryRunMode, EnvMode,
+        LogOrder, LogPrefix, OutputLogsMode, QueryCommand, Report, ReportFormat, RunArgs, UIMode,
+        Verbosity,
     };
 
     #[test_case::test_case(
@@ -1271,6 +1596,39 @@ mod test {
             ..Args::default()
         }
 	)]
+    #[test_case::test_case(
+		&["turbo", "run", "build", "--explain-hash"],
+        Args {
+            command: Some(Command::Run(Box::new(RunArgs {
+                tasks: vec!["build".to_string()],
+                explain_hash: Some("".to_string()),
+                ..get_default_run_args()
+            }))),
+            ..Args::default()
+        }
+	)]
+    #[test_case::test_case(
+		&["turbo", "run", "build", "--explain-hash", ".turbo/runs/previous.json"],
+        Args {
+            command: Some(Command::Run(Box::new(RunArgs {
+                tasks: vec!["build".to_string()],
+                explain_hash: Some(".turbo/runs/previous.json".to_string()),
+                ..get_default_run_args()
+            }))),
+            ..Args::default()
+        }
+	)]
+    #[test_case::test_case(
+		&["turbo", "run", "build", "--workers", "10.0.0.1:9080,10.0.0.2:9080"],
+        Args {
+            command: Some(Command::Run(Box::new(RunArgs {
+                tasks: vec!["build".to_string()],
+                workers: vec!["10.0.0.1:9080".to_string(), "10.0.0.2:9080".to_string()],
+                ..get_default_run_args()
+            }))),
+            ..Args::default()
+        }
+	)]
     #[test_case::test_case(
 		&["turbo", "run", "build", "--ignore", "foo.js"],
         Args {
@@ -1403,6 +1761,59 @@ mod test {
             ..Args::default()
         }
 	)]
+    #[test_case::test_case(
+		&["turbo", "run", "build", "--cache-format", "content-addressed"],
+        Args {
+            command: Some(Command::Run(Box::new(RunArgs {
+                tasks: vec!["build".to_string()],
+                cache_format: Some(CacheFormat::ContentAddressed),
+                ..get_default_run_args()
+            }))),
+            ..Args::default()
+        }
+	)]
+    #[test_case::test_case(
+		&["turbo", "run", "build", "--otel-endpoint", "http://localhost:4318"],
+        Args {
+            command: Some(Command::Run(Box::new(RunArgs {
+                tasks: vec!["build".to_string()],
+                otel_endpoint: Some("http://localhost:4318".to_string()),
+                ..get_default_run_args()
+            }))),
+            ..Args::default()
+        }
+	)]
+    #[test_case::test_case(
+		&["turbo", "run", "build", "--report", "junit:reports/turbo.xml", "--report", "tap:turbo.tap"],
+        Args {
+            command: Some(Command::Run(Box::new(RunArgs {
+                tasks: vec!["build".to_string()],
+                report: vec![
+                    Report {
+                        format: ReportFormat::Junit,
+                        path: Utf8PathBuf::from("reports/turbo.xml"),
+                    },
+                    Report {
+                        format: ReportFormat::Tap,
+                        path: Utf8PathBuf::from("turbo.tap"),
+                    },
+                ],
+                ..get_default_run_args()
+            }))),
+            ..Args::default()
+        }
+	)]
+    #[test_case::test_case(
+		&["turbo", "run", "build", "--ui", "tui"],
+        Args {
+            command: Some(Command::Run(Box::new(RunArgs {
+                tasks: vec!["build".to_string()],
+                ui: UIMode::Tui,
+                ..get_default_run_args()
+            }))),
+            ..Args::default()
+        }
+	)]
     #[test_case::test_case(
 		&["turbo", "run", "build", "--log-prefix", "auto"],
         Args {
@@ -1604,6 +2015,175 @@ mod test {
         .test();
     }
 
+    #[test]
+    fn test_parse_boundaries() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "boundaries"]).unwrap(),
+            Args {
+                command: Some(Command::Boundaries {}),
+                ..Args::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_deps() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "deps", "--json", "--fail-on-mismatch"]).unwrap(),
+            Args {
+                command: Some(Command::Deps {
+                    json: true,
+                    fail_on_mismatch: true,
+                }),
+                ..Args::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_cache() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "cache", "stats", "--json"]).unwrap(),
+            Args {
+                command: Some(Command::Cache {
+                    cache_dir: None,
+                    command: CacheCommand::Stats { json: true },
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert_eq!(
+            Args::try_parse_from([
+                "turbo",
+                "cache",
+                "prune",
+                "--max-size",
+                "1.5GB",
+                "--max-age",
+                "7d",
+                "--cache-dir",
+                "/tmp/turbo"
+            ])
+            .unwrap(),
+            Args {
+                command: Some(Command::Cache {
+                    cache_dir: Some(Utf8PathBuf::from("/tmp/turbo")),
+                    command: CacheCommand::Prune {
+                        max_size: Some(3 << 29),
+                        max_age: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+                    },
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert!(Args::try_parse_from(["turbo", "cache", "prune"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_query_affected() {
+        assert_eq!(
+            Args::try_parse_from([
+                "turbo",
+                "query",
+                "affected",
+                "--base",
+                "main",
+                "--tasks",
+                "build,test",
+                "--json"
+            ])
+            .unwrap(),
+            Args {
+                command: Some(Command::Query {
+                    command: QueryCommand::Affected {
+                        base: "main".to_string(),
+                        head: "HEAD".to_string(),
+                        tasks: vec!["build".to_string(), "test".to_string()],
+                        json: true,
+                    },
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert!(Args::try_parse_from(["turbo", "query", "affected"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_worker() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "worker"]).unwrap(),
+            Args {
+                command: Some(Command::Worker {
+                    host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    port: 9080,
+                    concurrency: None,
+                    cache_dir: None,
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert_eq!(
+            Args::try_parse_from([
+                "turbo",
+                "worker",
+                "--host",
+                "0.0.0.0",
+                "--port",
+                "9081",
+                "--concurrency",
+                "4",
+                "--cache-dir",
+                "/tmp/turbo-cache"
+            ])
+            .unwrap(),
+            Args {
+                command: Some(Command::Worker {
+                    host: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                    port: 9081,
+                    concurrency: Some(4),
+                    cache_dir: Some(Utf8PathBuf::from("/tmp/turbo-cache")),
+                }),
+                ..Args::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "watch", "build", "--filter", "web"]).unwrap(),
+            Args {
+                command: Some(Command::Watch(Box::new(RunArgs {
+                    tasks: vec!["build".to_string()],
+                    filter: vec!["web".to_string()],
+                    ..get_default_run_args()
+                }))),
+                ..Args::default()
+            }
+        );
+    }
+
+    #[test_case::test_case("1024", Ok(1024) ; "bytes")]
+    #[test_case::test_case("500mb", Ok(500 << 20) ; "megabytes")]
+    #[test_case::test_case("10 GB", Ok(10 << 30) ; "gigabytes with space")]
+    #[test_case::test_case("10PB", Err(()) ; "unknown unit")]
+    #[test_case::test_case("GB", Err(()) ; "missing number")]
+    fn test_parse_size(input: &str, expected: Result<u64, ()>) {
+        assert_eq!(parse_size(input).map_err(|_| ()), expected);
+    }
+
+    #[test_case::test_case("junit:C:\\reports\\turbo.xml", true ; "windows path")]
+    #[test_case::test_case("xml:turbo.xml", false ; "unknown format")]
+    #[test_case::test_case("junit:", false ; "missing path")]
+    #[test_case::test_case("turbo.xml", false ; "missing format")]
+    fn test_parse_report(input: &str, is_ok: bool) {
+        assert_eq!(parse_report(input).is_ok(), is_ok);
+    }
+
     #[test]
     fn test_parse_login() {
         assert_eq!(
@@ -0,0 +1,80 @@
+//! `turbo boundaries` lists every place a workspace depends on something it
+//! isn't allowed to, exiting with an error if there are any.
+use turborepo_repository::{
+    discovery::{LocalPackageDiscoveryBuilder, PackageDiscoveryBuilder},
+    package_graph::{self, PackageGraph},
+    package_json::PackageJson,
+};
+use turborepo_scm::SCM;
+use turborepo_ui::{BOLD_GREEN, BOLD_RED};
+
+use super::CommandBase;
+use crate::{
+    boundaries,
+    config::{self, TurboJson},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    PackageJson(#[from] turborepo_repository::package_json::Error),
+    #[error(transparent)]
+    PackageManager(#[from] turborepo_repository::package_manager::Error),
+    #[error(transparent)]
+    PackageGraphBuilder(#[from] package_graph::builder::Error),
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    Boundaries(#[from] boundaries::Error),
+}
+
+pub async fn run(base: &CommandBase) -> Result<i32, Error> {
+    let repo_root = &base.repo_root;
+    let root_package_json = PackageJson::load(&repo_root.join_component("package.json"))?;
+
+    let pkg_graph = PackageGraph::builder(repo_root, root_package_json.clone())
+        .with_package_discovery(
+            LocalPackageDiscoveryBuilder::new(
+                repo_root.clone(),
+                None,
+                Some(root_package_json.clone()),
+            )
+            .build()?,
+        )
+        .build()
+        .await?;
+    // Undeclared imports are still worth checking for without any tag rules
+    let root_turbo_json = match TurboJson::load(repo_root, &root_package_json, false) {
+        Err(config::Error::NoTurboJSON) => TurboJson::default(),
+        root_turbo_json => root_turbo_json?,
+    };
+
+    let violations = boundaries::check(
+        repo_root,
+        &pkg_graph,
+        &root_turbo_json,
+        &SCM::new(repo_root),
+    )?;
+
+    if violations.is_empty() {
+        println!(
+            "{}",
+            base.ui
+                .apply(BOLD_GREEN.apply_to("No boundary violations found"))
+        );
+        return Ok(0);
+    }
+
+    for violation in &violations {
+        println!("{violation}");
+    }
+    println!(
+        "\n{}",
+        base.ui.apply(BOLD_RED.apply_to(format!(
+            "{} boundary violation{} found",
+            violations.len(),
+            if violations.len() == 1 { "" } else { "s" }
+        )))
+    );
+    Ok(1)
+}
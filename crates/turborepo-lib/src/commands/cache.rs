@@ -0,0 +1,96 @@
+//! `turbo cache` inspects and cleans up the local filesystem cache.
+use std::time::{Duration, SystemTime};
+
+use camino::Utf8Path;
+use turborepo_cache::fs::{CacheStats, EvictionPolicy, FSCache};
+use turborepo_ui::GREY;
+
+use crate::{cli::Error, commands::CommandBase};
+
+pub fn prune(
+    base: &CommandBase,
+    cache_dir: Option<&Utf8Path>,
+    max_size: Option<u64>,
+    max_age: Option<Duration>,
+) -> Result<(), Error> {
+    let cache = FSCache::new(cache_dir, &base.repo_root, None)?;
+    let policy = EvictionPolicy { max_size, max_age };
+    let summary = cache.evict(&policy, SystemTime::now())?;
+
+    println!(
+        "Removed {} {}",
+        format_stats(&summary.evicted),
+        base.ui
+            .apply(GREY.apply_to(format!("({} remaining)", format_stats(&summary.remaining))))
+    );
+
+    Ok(())
+}
+
+pub fn stats(base: &CommandBase, cache_dir: Option<&Utf8Path>, json: bool) -> Result<(), Error> {
+    let cache = FSCache::new(cache_dir, &base.repo_root, None)?;
+    let stats = cache.stats()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        println!("{}", format_stats(&stats));
+    }
+
+    Ok(())
+}
+
+fn format_stats(stats: &CacheStats) -> String {
+    let noun = if stats.entries == 1 {
+        "entry"
+    } else {
+        "entries"
+    };
+    format!(
+        "{} {noun}, {}",
+        stats.entries,
+        format_size(stats.total_size)
+    )
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next_unit in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next_unit;
+    }
+    format!("{size:.1} {unit}")
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(0, "0 B" ; "empty")]
+    #[test_case(1023, "1023 B" ; "bytes")]
+    #[test_case(1536, "1.5 KB" ; "kilobytes")]
+    #[test_case(10 << 30, "10.0 GB" ; "gigabytes")]
+    fn test_format_size(bytes: u64, expected: &str) {
+        assert_eq!(format_size(bytes), expected);
+    }
+
+    #[test]
+    fn test_format_stats() {
+        let stats = CacheStats {
+            entries: 1,
+            total_size: 2048,
+        };
+        assert_eq!(format_stats(&stats), "1 entry, 2.0 KB");
+    }
+}
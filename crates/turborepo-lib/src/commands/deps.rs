@@ -0,0 +1,334 @@
+//! `turbo deps` finds external packages that the lockfile resolves to more
+//! than one version across the repository's workspaces.
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+use turborepo_lockfiles::NamedPackage;
+use turborepo_repository::{
+    discovery::{LocalPackageDiscoveryBuilder, PackageDiscoveryBuilder},
+    package_graph::{self, PackageGraph, WorkspaceName},
+    package_json::PackageJson,
+};
+use turborepo_ui::{BOLD, BOLD_GREEN, BOLD_RED, GREY, UI};
+
+use super::CommandBase;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    PackageJson(#[from] turborepo_repository::package_json::Error),
+    #[error(transparent)]
+    PackageManager(#[from] turborepo_repository::package_manager::Error),
+    #[error(transparent)]
+    PackageGraphBuilder(#[from] package_graph::builder::Error),
+    #[error("unable to read the lockfile, so dependencies can't be resolved")]
+    NoLockfile,
+    #[error("unable to resolve the dependencies of {workspace}: {source}")]
+    Resolve {
+        workspace: WorkspaceName,
+        #[source]
+        source: turborepo_lockfiles::Error,
+    },
+    #[error("json error while reporting dependencies: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Mismatch {
+    name: String,
+    versions: Vec<ResolvedVersion>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct ResolvedVersion {
+    version: String,
+    workspaces: Vec<Dependent>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Dependent {
+    name: WorkspaceName,
+    /// Whether the workspace depends on this version itself, rather than
+    /// through another package
+    direct: bool,
+}
+
+/// Lists the external packages resolved to more than one version. If
+/// `fail_on_mismatch` is set the exit code is non-zero when there are any.
+pub async fn run(base: &CommandBase, json: bool, fail_on_mismatch: bool) -> Result<i32, Error> {
+    let repo_root = &base.repo_root;
+    let root_package_json = PackageJson::load(&repo_root.join_component("package.json"))?;
+
+    let pkg_graph = PackageGraph::builder(repo_root, root_package_json.clone())
+        .with_package_discovery(
+            LocalPackageDiscoveryBuilder::new(
+                repo_root.clone(),
+                None,
+                Some(root_package_json.clone()),
+            )
+            .build()?,
+        )
+        .build()
+        .await?;
+    let lockfile = pkg_graph.lockfile().ok_or(Error::NoLockfile)?;
+
+    let mut closures = BTreeMap::new();
+    for (name, info) in pkg_graph.workspaces() {
+        let workspace_path = info.package_path().to_unix();
+        let closure = turborepo_lockfiles::named_transitive_closure(
+            lockfile,
+            workspace_path.as_str(),
+            info.unresolved_external_dependencies
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+        )
+        .map_err(|source| Error::Resolve {
+            workspace: name.clone(),
+            source,
+        })?;
+        closures.insert(name.clone(), closure);
+    }
+
+    let mismatches = find_mismatches(&closures);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&mismatches)?);
+    } else {
+        print!("{}", format_mismatches(&mismatches, base.ui));
+    }
+
+    Ok(if fail_on_mismatch && !mismatches.is_empty() {
+        1
+    } else {
+        0
+    })
+}
+
+fn find_mismatches(closures: &BTreeMap<WorkspaceName, HashSet<NamedPackage>>) -> Vec<Mismatch> {
+    // Name -> version -> workspace -> whether any copy of that version is a
+    // direct dependency. Some package managers keep several copies of the
+    // same version, those are treated as one.
+    let mut packages: BTreeMap<&str, BTreeMap<&str, BTreeMap<&WorkspaceName, bool>>> =
+        BTreeMap::new();
+    for (workspace, closure) in closures {
+        for package in closure {
+            let direct = packages
+                .entry(package.name.as_str())
+                .or_default()
+                .entry(package.package.version.as_str())
+                .or_default()
+                .entry(workspace)
+                .or_default();
+            *direct |= package.direct;
+        }
+    }
+
+    packages
+        .into_iter()
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|(name, versions)| Mismatch {
+            name: name.to_string(),
+            versions: versions
+                .into_iter()
+                .map(|(version, workspaces)| ResolvedVersion {
+                    version: version.to_string(),
+                    workspaces: workspaces
+                        .into_iter()
+                        .map(|(name, direct)| Dependent {
+                            name: name.clone(),
+                            direct,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn format_mismatches(mismatches: &[Mismatch], ui: UI) -> String {
+    if mismatches.is_empty() {
+        return format!(
+            "{}\n",
+            ui.apply(BOLD_GREEN.apply_to("Every package is resolved to a single version"))
+        );
+    }
+
+    let mut output = String::new();
+    for mismatch in mismatches {
+        output.push_str(&format!(
+            "{}\n",
+            ui.apply(BOLD.apply_to(format!(
+                "{} ({} versions)",
+                mismatch.name,
+                mismatch.versions.len()
+            )))
+        ));
+        for version in &mismatch.versions {
+            let workspaces = version
+                .workspaces
+                .iter()
+                .map(|workspace| {
+                    if workspace.direct {
+                        workspace.name.to_string()
+                    } else {
+                        format!(
+                            "{} {}",
+                            workspace.name,
+                            ui.apply(GREY.apply_to("(transitive)"))
+                        )
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            output.push_str(&format!("  {}: {workspaces}\n", version.version));
+        }
+    }
+    output.push_str(&format!(
+        "\n{}\n",
+        ui.apply(BOLD_RED.apply_to(format!(
+            "{} package{} resolved to multiple versions",
+            mismatches.len(),
+            if mismatches.len() == 1 { "" } else { "s" }
+        )))
+    ));
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use turborepo_lockfiles::Package;
+    use turborepo_ui::UI;
+
+    use super::*;
+
+    fn package(name: &str, key: &str, version: &str, direct: bool) -> NamedPackage {
+        NamedPackage {
+            name: name.to_string(),
+            package: Package::new(key, version),
+            direct,
+            dependencies: BTreeMap::new(),
+        }
+    }
+
+    fn closures() -> BTreeMap<WorkspaceName, HashSet<NamedPackage>> {
+        [
+            (
+                WorkspaceName::from("docs"),
+                [
+                    package("lodash", "node_modules/lodash", "3.10.1", true),
+                    package("react", "node_modules/react", "18.2.0", true),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+            (
+                WorkspaceName::from("web"),
+                [
+                    package("lodash", "apps/web/node_modules/lodash", "4.17.21", true),
+                    package("react", "node_modules/react", "18.2.0", true),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+            (
+                WorkspaceName::from("ui"),
+                [
+                    package("lodash", "node_modules/lodash", "3.10.1", false),
+                    package(
+                        "lodash",
+                        "node_modules/a/node_modules/lodash",
+                        "3.10.1",
+                        true,
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_find_mismatches() {
+        assert_eq!(
+            find_mismatches(&closures()),
+            vec![Mismatch {
+                name: "lodash".to_string(),
+                versions: vec![
+                    ResolvedVersion {
+                        version: "3.10.1".to_string(),
+                        workspaces: vec![
+                            Dependent {
+                                name: WorkspaceName::from("docs"),
+                                direct: true,
+                            },
+                            Dependent {
+                                name: WorkspaceName::from("ui"),
+                                direct: true,
+                            },
+                        ],
+                    },
+                    ResolvedVersion {
+                        version: "4.17.21".to_string(),
+                        workspaces: vec![Dependent {
+                            name: WorkspaceName::from("web"),
+                            direct: true,
+                        }],
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_format_mismatches() {
+        let mismatches = vec![Mismatch {
+            name: "lodash".to_string(),
+            versions: vec![
+                ResolvedVersion {
+                    version: "3.10.1".to_string(),
+                    workspaces: vec![Dependent {
+                        name: WorkspaceName::from("docs"),
+                        direct: false,
+                    }],
+                },
+                ResolvedVersion {
+                    version: "4.17.21".to_string(),
+                    workspaces: vec![
+                        Dependent {
+                            name: WorkspaceName::from("ui"),
+                            direct: true,
+                        },
+                        Dependent {
+                            name: WorkspaceName::from("web"),
+                            direct: true,
+                        },
+                    ],
+                },
+            ],
+        }];
+        assert_eq!(
+            format_mismatches(&mismatches, UI::new(true)),
+            "lodash (2 versions)\n  3.10.1: docs (transitive)\n  4.17.21: ui, web\n\n1 package \
+             resolved to multiple versions\n"
+        );
+        assert_eq!(
+            format_mismatches(&[], UI::new(true)),
+            "Every package is resolved to a single version\n"
+        );
+    }
+
+    #[test]
+    fn test_mismatches_json() {
+        let mismatches = find_mismatches(&closures());
+        assert_eq!(
+            serde_json::to_value(mismatches).unwrap()[0]["versions"][1],
+            serde_json::json!({
+                "version": "4.17.21",
+                "workspaces": [{ "name": "web", "direct": true }]
+            })
+        );
+    }
+}
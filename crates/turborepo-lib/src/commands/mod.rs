@@ -12,15 +12,20 @@ use crate::{
 };
 
 pub(crate) mod bin;
+pub(crate) mod boundaries;
+pub(crate) mod cache;
 pub(crate) mod daemon;
+pub(crate) mod deps;
 pub(crate) mod generate;
 pub(crate) mod info;
 pub(crate) mod link;
 pub(crate) mod login;
 pub(crate) mod logout;
 pub(crate) mod prune;
+pub(crate) mod query;
 pub(crate) mod run;
 pub(crate) mod unlink;
+pub(crate) mod worker;
 
 #[derive(Debug)]
 pub struct CommandBase {
@@ -1,6 +1,6 @@
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::sync::OnceLock;
+use std::{collections::HashSet, sync::OnceLock};
 
 use lazy_static::lazy_static;
 use tracing::trace;
@@ -14,7 +14,7 @@ use turborepo_repository::{
 use turborepo_ui::BOLD;
 
 use super::CommandBase;
-use crate::config::RawTurboJson;
+use crate::config::{RawTurboJson, TurboJson};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -40,8 +40,8 @@ pub enum Error {
     MissingWorkspace(WorkspaceName),
     #[error("Cannot prune without parsed lockfile")]
     MissingLockfile,
-    #[error("Prune is not supported for Bun")]
-    BunUnsupported,
+    #[error(transparent)]
+    Config(#[from] crate::config::Error),
 }
 
 // Files that should be copied from root and if they're required for install
@@ -87,13 +87,6 @@ pub async fn prune(
 ) -> Result<(), Error> {
     let prune = Prune::new(base, scope, docker, output_dir).await?;
 
-    if matches!(
-        prune.package_graph.package_manager(),
-        turborepo_repository::package_manager::PackageManager::Bun
-    ) {
-        return Err(Error::BunUnsupported);
-    }
-
     println!(
         "Generating pruned monorepo for {} in {}",
         base.ui.apply(BOLD.apply_to(scope.join(", "))),
@@ -113,13 +106,14 @@ pub async fn prune(
 
     let mut workspace_paths = Vec::new();
     let mut workspace_names = Vec::new();
-    let workspaces = prune.internal_dependencies();
+    let workspaces = prune.internal_dependencies()?;
     let lockfile_keys: Vec<_> = prune
         .package_graph
         .transitive_external_dependencies(workspaces.iter())
         .into_iter()
         .map(|pkg| pkg.key.clone())
         .collect();
+    let mut package_json_paths = Vec::new();
     for workspace in workspaces {
         let entry = prune
             .package_graph
@@ -128,30 +122,29 @@ pub async fn prune(
 
         // We don't want to do any copying for the root workspace
         if let WorkspaceName::Other(workspace) = workspace {
-            prune.copy_workspace(entry.package_json_path())?;
-            workspace_paths.push(
-                entry
-                    .package_json_path()
-                    .parent()
-                    .unwrap()
-                    .to_unix()
-                    .to_string(),
-            );
-
-            println!(" - Added {workspace}");
+            let package_json_path = entry.package_json_path().to_owned();
+            workspace_paths.push(package_json_path.parent().unwrap().to_unix().to_string());
+            package_json_paths.push(package_json_path);
             workspace_names.push(workspace);
         }
     }
     trace!("new workspaces: {}", workspace_paths.join(", "));
     trace!("lockfile keys: {}", lockfile_keys.join(", "));
 
+    // Encode the lockfile before copying anything so a lockfile that can't be
+    // written doesn't leave a partial output behind
     let lockfile = prune
         .package_graph
         .lockfile()
         .expect("Lockfile presence already checked")
         .subgraph(&workspace_paths, &lockfile_keys)?;
-
     let lockfile_contents = lockfile.encode()?;
+
+    for (package_json_path, workspace) in package_json_paths.iter().zip(&workspace_names) {
+        prune.copy_workspace(package_json_path)?;
+        println!(" - Added {workspace}");
+    }
+
     let lockfile_name = prune.package_graph.package_manager().lockfile_name();
     let lockfile_path = prune.out_directory.join_component(lockfile_name);
     lockfile_path.create_with_contents(&lockfile_contents)?;
@@ -393,24 +386,43 @@ impl<'a> Prune<'a> {
         Ok(())
     }
 
-    fn internal_dependencies(&self) -> Vec<WorkspaceName> {
-        let workspaces =
+    // Workspaces whose turbo.json is extended by an included workspace are
+    // needed to build its task definitions, so they're pulled in along with
+    // their own dependencies until nothing new is found.
+    fn internal_dependencies(&self) -> Result<Vec<WorkspaceName>, Error> {
+        let mut workspaces =
             std::iter::once(WorkspaceNode::Workspace(WorkspaceName::Root))
                 .chain(self.scope.iter().map(|workspace| {
                     WorkspaceNode::Workspace(WorkspaceName::Other(workspace.clone()))
                 }))
+                .collect::<HashSet<_>>();
+        loop {
+            let names = self
+                .package_graph
+                .transitive_closure(workspaces.iter())
+                .into_iter()
+                .filter_map(|node| match node {
+                    WorkspaceNode::Root => None,
+                    WorkspaceNode::Workspace(workspace) => Some(workspace.clone()),
+                })
                 .collect::<Vec<_>>();
-        let nodes = self.package_graph.transitive_closure(workspaces.iter());
-
-        let mut names: Vec<_> = nodes
-            .into_iter()
-            .filter_map(|node| match node {
-                WorkspaceNode::Root => None,
-                WorkspaceNode::Workspace(workspace) => Some(workspace.clone()),
-            })
-            .collect();
-        names.sort();
-        names
+
+            let mut extended = Vec::new();
+            for name in &names {
+                extended.extend(TurboJson::workspace_extends(
+                    &self.root,
+                    &self.package_graph,
+                    name,
+                )?);
+            }
+            let before = workspaces.len();
+            workspaces.extend(extended.into_iter().map(WorkspaceNode::Workspace));
+            if workspaces.len() == before {
+                let mut names = names;
+                names.sort();
+                return Ok(names);
+            }
+        }
     }
 
     fn copy_turbo_json(&self, workspaces: &[String]) -> Result<(), Error> {
@@ -0,0 +1,299 @@
+//! `turbo query` answers questions about the repository without running any
+//! tasks, so that other tooling such as CI pipelines can build on top of it.
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+use turborepo_repository::{
+    discovery::{LocalPackageDiscoveryBuilder, PackageDiscoveryBuilder},
+    package_graph::{self, PackageGraph, WorkspaceName, WorkspaceNode},
+    package_json::PackageJson,
+};
+use turborepo_scm::SCM;
+use turborepo_ui::{BOLD, GREY, UI};
+
+use super::CommandBase;
+use crate::{
+    config::TurboJson,
+    engine::{BuilderError, EngineBuilder, TaskNode},
+    run::{
+        scope::change_detector::{ChangeDetectError, PackageChangeReason, SCMChangeDetector},
+        task_id::TaskName,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    PackageJson(#[from] turborepo_repository::package_json::Error),
+    #[error(transparent)]
+    PackageManager(#[from] turborepo_repository::package_manager::Error),
+    #[error(transparent)]
+    PackageGraphBuilder(#[from] package_graph::builder::Error),
+    #[error(transparent)]
+    Config(#[from] crate::config::Error),
+    #[error("unable to detect changes: {0}")]
+    ChangeDetect(#[from] ChangeDetectError),
+    #[error("unable to find affected tasks: {0}")]
+    Builder(#[from] BuilderError),
+    #[error("json error while querying: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AffectedReport {
+    base: String,
+    head: String,
+    changed_files: Vec<String>,
+    packages: Vec<AffectedPackage>,
+    tasks: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct AffectedPackage {
+    name: WorkspaceName,
+    reason: PackageChangeReason,
+}
+
+/// Reports the files changed between `base` and `head`, the packages affected
+/// by them and which of `tasks` would need to run as a result. If no tasks are
+/// given every task in the root `turbo.json` is considered.
+pub async fn affected(
+    base: &CommandBase,
+    from_ref: &str,
+    to_ref: &str,
+    tasks: &[String],
+    json: bool,
+) -> Result<(), Error> {
+    let repo_root = &base.repo_root;
+    let root_package_json = PackageJson::load(&repo_root.join_component("package.json"))?;
+
+    let pkg_graph = PackageGraph::builder(repo_root, root_package_json.clone())
+        .with_package_discovery(
+            LocalPackageDiscoveryBuilder::new(
+                repo_root.clone(),
+                None,
+                Some(root_package_json.clone()),
+            )
+            .build()?,
+        )
+        .build()
+        .await?;
+    let root_turbo_json = TurboJson::load(repo_root, &root_package_json, false)?;
+
+    let scm = SCM::new(repo_root);
+    let change_detector = SCMChangeDetector::new(
+        repo_root,
+        &scm,
+        &pkg_graph,
+        root_turbo_json.global_deps.clone(),
+        vec![],
+    );
+    let changes = change_detector.changes(from_ref, to_ref)?;
+    let packages = with_dependents(&pkg_graph, changes.packages);
+
+    let tasks = if packages.is_empty() {
+        Vec::new()
+    } else {
+        affected_tasks(base, &pkg_graph, &root_turbo_json, &packages, tasks)?
+    };
+
+    let mut changed_files = changes
+        .files
+        .iter()
+        .map(|file| file.to_unix().to_string())
+        .collect::<Vec<_>>();
+    changed_files.sort();
+
+    let mut packages = packages
+        .into_iter()
+        .map(|(name, reason)| AffectedPackage { name, reason })
+        .collect::<Vec<_>>();
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let report = AffectedReport {
+        base: from_ref.to_string(),
+        head: to_ref.to_string(),
+        changed_files,
+        packages,
+        tasks,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", format_report(&report, base.ui));
+    }
+
+    Ok(())
+}
+
+// Packages that depend on a changed package are affected as well
+fn with_dependents(
+    pkg_graph: &PackageGraph,
+    changed: HashMap<WorkspaceName, PackageChangeReason>,
+) -> HashMap<WorkspaceName, PackageChangeReason> {
+    let mut affected = changed.clone();
+    for package in changed.into_keys() {
+        for dependent in pkg_graph.ancestors(&WorkspaceNode::Workspace(package)) {
+            if let WorkspaceNode::Workspace(name) = dependent {
+                PackageChangeReason::insert(
+                    &mut affected,
+                    name.clone(),
+                    PackageChangeReason::DependencyChange,
+                );
+            }
+        }
+    }
+    affected
+}
+
+fn affected_tasks(
+    base: &CommandBase,
+    pkg_graph: &PackageGraph,
+    root_turbo_json: &TurboJson,
+    packages: &HashMap<WorkspaceName, PackageChangeReason>,
+    tasks: &[String],
+) -> Result<Vec<String>, Error> {
+    let task_names = if tasks.is_empty() {
+        // Package specific entries such as `web#build` only apply to a single
+        // package so they aren't picked up as entry points
+        root_turbo_json
+            .pipeline
+            .keys()
+            .filter(|task| task.package().is_none())
+            .map(|task| task.task().to_string())
+            .collect::<BTreeSet<_>>()
+    } else {
+        tasks.iter().cloned().collect()
+    };
+
+    let engine = EngineBuilder::new(&base.repo_root, pkg_graph, false)
+        .with_root_tasks(root_turbo_json.pipeline.keys().cloned())
+        .with_turbo_jsons(Some(
+            Some((WorkspaceName::Root, root_turbo_json.clone()))
+                .into_iter()
+                .collect(),
+        ))
+        .with_workspaces(packages.keys().cloned().collect())
+        .with_tasks(
+            task_names
+                .iter()
+                .map(|task| TaskName::from(task.as_str()).into_owned()),
+        )
+        .build()?;
+
+    let mut task_ids = engine
+        .tasks()
+        .filter_map(|node| match node {
+            TaskNode::Task(task_id) => Some(task_id),
+            TaskNode::Root => None,
+        })
+        // Dependencies of affected tasks in unaffected packages are left to the
+        // cache, and tasks without a script never run
+        .filter(|task_id| packages.contains_key(&WorkspaceName::from(task_id.package())))
+        .filter(|task_id| {
+            pkg_graph
+                .workspace_info(&WorkspaceName::from(task_id.package()))
+                .and_then(|info| info.package_json.scripts.get(task_id.task()))
+                .map_or(false, |script| !script.is_empty())
+        })
+        .map(|task_id| task_id.to_string())
+        .collect::<Vec<_>>();
+    task_ids.sort();
+
+    Ok(task_ids)
+}
+
+fn format_report(report: &AffectedReport, ui: UI) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "{}\n",
+        ui.apply(BOLD.apply_to(format!("Changed files ({})", report.changed_files.len())))
+    ));
+    for file in &report.changed_files {
+        output.push_str(&format!("  {file}\n"));
+    }
+
+    output.push_str(&format!(
+        "{}\n",
+        ui.apply(BOLD.apply_to(format!("Affected packages ({})", report.packages.len())))
+    ));
+    for package in &report.packages {
+        let reason = match package.reason {
+            PackageChangeReason::GlobalDepChange => "global dependency change",
+            PackageChangeReason::DirectChange => "direct change",
+            PackageChangeReason::ExtendedConfigChange => "extended config change",
+            PackageChangeReason::LockfileChange => "lockfile change",
+            PackageChangeReason::DependencyChange => "dependency change",
+        };
+        output.push_str(&format!(
+            "  {} {}\n",
+            package.name,
+            ui.apply(GREY.apply_to(format!("({reason})")))
+        ));
+    }
+
+    output.push_str(&format!(
+        "{}\n",
+        ui.apply(BOLD.apply_to(format!("Affected tasks ({})", report.tasks.len())))
+    ));
+    for task in &report.tasks {
+        output.push_str(&format!("  {task}\n"));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use turborepo_ui::UI;
+
+    use super::*;
+
+    fn report() -> AffectedReport {
+        AffectedReport {
+            base: "main".into(),
+            head: "HEAD".into(),
+            changed_files: vec!["packages/ui/button.tsx".into()],
+            packages: vec![
+                AffectedPackage {
+                    name: WorkspaceName::from("ui"),
+                    reason: PackageChangeReason::DirectChange,
+                },
+                AffectedPackage {
+                    name: WorkspaceName::from("web"),
+                    reason: PackageChangeReason::DependencyChange,
+                },
+            ],
+            tasks: vec!["ui#build".into(), "web#build".into()],
+        }
+    }
+
+    #[test]
+    fn test_format_report() {
+        assert_eq!(
+            format_report(&report(), UI::new(true)),
+            "Changed files (1)\n  packages/ui/button.tsx\nAffected packages (2)\n  ui (direct \
+             change)\n  web (dependency change)\nAffected tasks (2)\n  ui#build\n  web#build\n"
+        );
+    }
+
+    #[test]
+    fn test_report_json() {
+        assert_eq!(
+            serde_json::to_value(report()).unwrap(),
+            serde_json::json!({
+                "base": "main",
+                "head": "HEAD",
+                "changedFiles": ["packages/ui/button.tsx"],
+                "packages": [
+                    { "name": "ui", "reason": "directChange" },
+                    { "name": "web", "reason": "dependencyChange" }
+                ],
+                "tasks": ["ui#build", "web#build"]
+            })
+        );
+    }
+}
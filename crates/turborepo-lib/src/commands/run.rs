@@ -1,8 +1,30 @@
 use tracing::{debug, error};
 
-use crate::{commands::CommandBase, run, run::Run, signal::SignalHandler};
+use crate::{
+    commands::CommandBase, config::RawTurboJson, otel, run, run::Run, signal::SignalHandler,
+};
 
 pub async fn run(base: CommandBase) -> Result<i32, run::Error> {
+    execute(Run::new(&base)).await
+}
+
+/// Where to export a trace of the run, if anywhere. This is needed before the
+/// run starts, so problems with turbo.json are left for the run to report.
+pub fn otel_config(
+    base: &CommandBase,
+    endpoint_flag: Option<&str>,
+) -> Option<otel::ExporterConfig> {
+    let turbo_json_otel = RawTurboJson::read(&base.repo_root.join_component("turbo.json"))
+        .ok()
+        .and_then(|turbo_json| turbo_json.otel);
+    otel::ExporterConfig::resolve(endpoint_flag, turbo_json_otel.as_ref())
+}
+
+pub async fn watch(base: CommandBase) -> Result<i32, run::Error> {
+    execute(Run::new(&base).with_watch()).await
+}
+
+async fn execute(mut run: Run<'_>) -> Result<i32, run::Error> {
     #[cfg(windows)]
     let signal = {
         let mut ctrl_c = tokio::signal::windows::ctrl_c().map_err(run::Error::SignalHandler)?;
@@ -29,7 +51,6 @@ pub async fn run(base: CommandBase) -> Result<i32, run::Error> {
 
     let handler = SignalHandler::new(signal);
 
-    let mut run = Run::new(&base);
     debug!("using the experimental rust codepath");
     debug!("configured run struct: {:?}", run);
     let run_fut = run.run(&handler);
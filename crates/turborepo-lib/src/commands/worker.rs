@@ -0,0 +1,107 @@
+//! `turbo worker` runs tasks for a `turbo run --workers` coordinator.
+use std::net::SocketAddr;
+
+use camino::Utf8Path;
+use futures::FutureExt;
+use tokio::signal::ctrl_c;
+use tracing::{error, info};
+use turborepo_cache::{AsyncCache, CacheOpts, RemoteCacheBackend, RemoteCacheOpts};
+use turborepo_repository::{
+    discovery::{LocalPackageDiscoveryBuilder, PackageDiscoveryBuilder},
+    package_graph::PackageGraph,
+    package_json::PackageJson,
+};
+
+use crate::{
+    cli::Error,
+    commands::CommandBase,
+    config::TurboJson,
+    distributed::{WorkerServer, WorkerToken, WorkspaceScripts},
+};
+
+pub async fn run(
+    base: &CommandBase,
+    addr: SocketAddr,
+    concurrency: Option<u32>,
+    cache_dir: Option<&Utf8Path>,
+) -> Result<(), Error> {
+    let token = WorkerToken::from_env()?;
+    let root_package_json = PackageJson::load(&base.repo_root.join_component("package.json"))?;
+    let package_graph = PackageGraph::builder(&base.repo_root, root_package_json.clone())
+        .with_package_discovery(
+            LocalPackageDiscoveryBuilder::new(
+                base.repo_root.clone(),
+                None,
+                Some(root_package_json),
+            )
+            .build()?,
+        )
+        .build()
+        .await?;
+
+    let cache = AsyncCache::new(
+        &cache_opts(base, cache_dir)?,
+        &base.repo_root,
+        base.api_client()?,
+        base.api_auth()?,
+        None,
+    )?;
+    let concurrency = concurrency.unwrap_or_else(|| num_cpus::get() as u32).max(1);
+
+    info!("serving tasks on {addr}, {concurrency} at a time");
+    let shutdown = ctrl_c().map(|result| {
+        if let Err(e) = result {
+            error!("error with signal handling: {e}");
+        }
+    });
+
+    WorkerServer::new(
+        base.repo_root.clone(),
+        cache,
+        WorkspaceScripts::new(&package_graph),
+        token,
+        concurrency,
+    )
+    .serve(addr, shutdown)
+    .await?;
+
+    Ok(())
+}
+
+// Mirrors how `turbo run` configures its cache, so that the worker reads
+// and writes the same artifacts as the coordinator
+fn cache_opts<'a>(
+    base: &CommandBase,
+    cache_dir: Option<&'a Utf8Path>,
+) -> Result<CacheOpts<'a>, Error> {
+    let config = base.config()?;
+    let is_linked = base
+        .api_auth()?
+        .map_or(false, |api_auth| api_auth.is_linked());
+    let remote_cache_backend = config.backend();
+    let needs_link = matches!(remote_cache_backend, RemoteCacheBackend::Vercel);
+    let skip_remote = if !is_linked && needs_link {
+        true
+    } else {
+        config.enabled.map_or(false, |enabled| !enabled)
+    };
+
+    let root_package_json = PackageJson::load(&base.repo_root.join_component("package.json"))?;
+    let remote_cache = TurboJson::load(&base.repo_root, &root_package_json, false)
+        .ok()
+        .and_then(|turbo_json| turbo_json.remote_cache)
+        .unwrap_or_default();
+
+    Ok(CacheOpts {
+        override_dir: cache_dir,
+        skip_remote,
+        workers: 10,
+        remote_cache_opts: Some(RemoteCacheOpts::new(
+            remote_cache.team_id.unwrap_or_default(),
+            remote_cache.signature.unwrap_or_default(),
+        )),
+        remote_cache_backend,
+        remote_cache_timeout: config.timeout(),
+        ..Default::default()
+    })
+}
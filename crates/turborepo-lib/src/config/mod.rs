@@ -5,7 +5,8 @@ use std::io;
 
 use thiserror::Error;
 pub use turbo::{
-    validate_extends, validate_no_package_task_syntax, RawTaskDefinition, RawTurboJson, TurboJson,
+    validate_extends, validate_no_package_task_syntax, BoundariesJson, OtelJson, RawTaskDefinition,
+    RawTurboJson, TagRulesJson, TurboJson,
 };
 pub use turbo_config::{ConfigurationOptions, TurborepoConfigBuilder};
 use turbopath::AbsoluteSystemPathBuf;
@@ -58,8 +59,17 @@ pub enum Error {
     PathError(#[from] turbopath::PathError),
     #[error("\"{actual}\". Use \"{wanted}\" instead")]
     UnnecessaryPackageTaskSyntax { actual: String, wanted: String },
-    #[error("You can only extend from the root workspace")]
-    ExtendFromNonRoot,
+    #[error("\"{value}\" is not a valid task timeout. Use a duration such as \"30s\" or \"5m\"")]
+    InvalidTaskTimeout {
+        value: String,
+        #[source]
+        source: humantime::DurationError,
+    },
+    #[error(
+        "\"{input}\" in \"inputs\" is invalid. \"$TURBO_ROOT$\" can only be used at the start of \
+         an input, followed by a path inside of the repository"
+    )]
+    InvalidTurboRootInput { input: String },
     #[error("No \"extends\" key found")]
     NoExtends,
     #[error("Failed to create APIClient: {0}")]
@@ -8,7 +8,11 @@ use std::{
 use camino::Utf8Path;
 use serde::{Deserialize, Serialize};
 use turbopath::{AbsoluteSystemPath, RelativeUnixPathBuf};
-use turborepo_repository::{package_graph::ROOT_PKG_NAME, package_json::PackageJson};
+use turborepo_repository::{
+    package_graph::{PackageGraph, WorkspaceName, ROOT_PKG_NAME},
+    package_json::PackageJson,
+};
+use turborepo_scm::package_deps::TURBO_ROOT;
 
 use crate::{
     cli::OutputLogsMode,
@@ -25,6 +29,38 @@ pub struct SpacesJson {
     pub other: Option<serde_json::Value>,
 }
 
+// Where to export OpenTelemetry traces of runs
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtelJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    // Sent with every export, e.g. for authenticating with the collector
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+// Rules for how workspaces may depend on each other, checked by `turbo
+// boundaries`
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundariesJson {
+    // Keyed by the tag of the dependent workspace
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, TagRulesJson>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub check_before_run: bool,
+}
+
+// The tags that a tagged workspace's dependencies must, or must not, have
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct TagRulesJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deny: Option<Vec<String>>,
+}
+
 // A turbo.json config that is synthesized but not yet resolved.
 // This means that we've done the work to synthesize the config from
 // package.json, but we haven't yet resolved the workspace
@@ -38,9 +74,13 @@ pub struct TurboJson {
     pub(crate) global_dot_env: Option<Vec<RelativeUnixPathBuf>>,
     pub(crate) global_env: Vec<String>,
     pub(crate) global_pass_through_env: Option<Vec<String>>,
+    pub(crate) load_dot_env: bool,
     pub(crate) pipeline: Pipeline,
     pub(crate) remote_cache: Option<ConfigurationOptions>,
     pub(crate) space_id: Option<String>,
+    pub(crate) otel: Option<OtelJson>,
+    pub(crate) boundaries: Option<BoundariesJson>,
+    pub(crate) tags: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, Clone)]
@@ -64,6 +104,9 @@ pub struct RawTurboJson {
     // .env files to consider, in order.
     #[serde(skip_serializing_if = "Option::is_none")]
     global_dot_env: Option<Vec<String>>,
+    // Whether the .env files are loaded into the environment of tasks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    load_dot_env: Option<bool>,
     // Pipeline is a map of Turbo pipeline entries which define the task graph
     // and cache behavior on a per task or per package-task basis.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -71,6 +114,15 @@ pub struct RawTurboJson {
     // Configuration options when interfacing with the remote cache
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) remote_cache: Option<ConfigurationOptions>,
+    // Where to export OpenTelemetry traces of runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) otel: Option<OtelJson>,
+    // Dependency rules between tagged workspaces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) boundaries: Option<BoundariesJson>,
+    // Tags of a workspace, matched against the root's `boundaries`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) tags: Option<Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, Clone)]
@@ -121,6 +173,10 @@ pub struct RawTaskDefinition {
     outputs: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     output_mode: Option<OutputLogsMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retries: Option<u32>,
 }
 
 macro_rules! set_field {
@@ -146,6 +202,8 @@ impl RawTaskDefinition {
         set_field!(self, other, env);
         set_field!(self, other, pass_through_env);
         set_field!(self, other, dot_env);
+        set_field!(self, other, timeout);
+        set_field!(self, other, retries);
     }
 }
 
@@ -258,10 +316,26 @@ impl TryFrom<RawTaskDefinition> for TaskDefinition {
                         )
                         .expect("unable to write to stderr");
                     }
+                    let glob = input.strip_prefix('!').unwrap_or(input);
+                    let is_valid = match glob.strip_prefix(TURBO_ROOT) {
+                        // Inputs can't reach outside of the repository
+                        Some(path) => {
+                            path.starts_with('/')
+                                && !path.contains(TURBO_ROOT)
+                                && !path.split(['/', '\\']).any(|segment| segment == "..")
+                        }
+                        None => !glob.contains(TURBO_ROOT),
+                    };
+                    if !is_valid {
+                        return Err(Error::InvalidTurboRootInput {
+                            input: input.clone(),
+                        });
+                    }
                 }
 
-                inputs
+                Ok(inputs)
             })
+            .transpose()?
             .unwrap_or_default();
 
         let pass_through_env = raw_task
@@ -290,6 +364,16 @@ impl TryFrom<RawTaskDefinition> for TaskDefinition {
             })
             .transpose()?;
 
+        let timeout = raw_task
+            .timeout
+            .map(|timeout| {
+                humantime::parse_duration(&timeout).map_err(|source| Error::InvalidTaskTimeout {
+                    value: timeout.clone(),
+                    source,
+                })
+            })
+            .transpose()?;
+
         Ok(TaskDefinition {
             outputs,
             cache: cache.unwrap_or(true),
@@ -301,6 +385,9 @@ impl TryFrom<RawTaskDefinition> for TaskDefinition {
             dot_env,
             output_mode: raw_task.output_mode.unwrap_or_default(),
             persistent: raw_task.persistent.unwrap_or_default(),
+            timeout,
+            retries: raw_task.retries.unwrap_or_default(),
+            defined_in: Vec::new(),
         })
     }
 }
@@ -404,12 +491,16 @@ impl TryFrom<RawTurboJson> for TurboJson {
                     Ok(global_dot_env)
                 })
                 .transpose()?,
+            load_dot_env: raw_turbo.load_dot_env.unwrap_or_default(),
             pipeline: raw_turbo.pipeline.unwrap_or_default(),
             // copy these over, we don't need any changes here.
             remote_cache: raw_turbo.remote_cache,
             extends: raw_turbo.extends.unwrap_or_default(),
             // Directly to space_id, we don't need to keep the struct
             space_id: raw_turbo.experimental_spaces.and_then(|s| s.id),
+            otel: raw_turbo.otel,
+            boundaries: raw_turbo.boundaries,
+            tags: raw_turbo.tags.unwrap_or_default(),
         })
     }
 }
@@ -517,6 +608,36 @@ impl TurboJson {
             .flat_map(|validation| validation(self))
             .collect()
     }
+
+    /// Returns the workspaces that the turbo.json of `workspace` extends,
+    /// leaving out the root. A workspace without a turbo.json doesn't extend
+    /// anything.
+    pub fn workspace_extends(
+        repo_root: &AbsoluteSystemPath,
+        package_graph: &PackageGraph,
+        workspace: &WorkspaceName,
+    ) -> Result<Vec<WorkspaceName>, Error> {
+        let (Some(package_json), Some(workspace_dir)) = (
+            package_graph.package_json(workspace),
+            package_graph.workspace_dir(workspace),
+        ) else {
+            return Ok(Vec::new());
+        };
+        let turbo_json = match Self::load(&repo_root.resolve(workspace_dir), package_json, false) {
+            Ok(turbo_json) => turbo_json,
+            Err(Error::NoTurboJSON) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(turbo_json
+            .extends
+            .iter()
+            .map(|extended| WorkspaceName::from(extended.as_str()))
+            .filter(|extended| {
+                !matches!(extended, WorkspaceName::Root)
+                    && package_graph.package_json(extended).is_some()
+            })
+            .collect())
+    }
 }
 
 type TurboJSONValidation = fn(&TurboJson) -> Vec<Error>;
@@ -534,12 +655,9 @@ pub fn validate_no_package_task_syntax(turbo_json: &TurboJson) -> Vec<Error> {
 }
 
 pub fn validate_extends(turbo_json: &TurboJson) -> Vec<Error> {
-    match turbo_json.extends.first() {
-        Some(package_name) if package_name != ROOT_PKG_NAME || turbo_json.extends.len() > 1 => {
-            vec![Error::ExtendFromNonRoot]
-        }
-        None => vec![Error::NoExtends],
-        _ => vec![],
+    match turbo_json.extends.is_empty() {
+        true => vec![Error::NoExtends],
+        false => vec![],
     }
 }
 
@@ -563,7 +681,7 @@ fn gather_env_vars(vars: Vec<String>, key: &str, into: &mut HashSet<String>) ->
 
 #[cfg(test)]
 mod tests {
-    use std::fs;
+    use std::{fs, time::Duration};
 
     use anyhow::Result;
     use pretty_assertions::assert_eq;
@@ -572,12 +690,12 @@ mod tests {
     use turbopath::{AbsoluteSystemPath, RelativeUnixPathBuf};
     use turborepo_repository::package_json::PackageJson;
 
-    use super::RawTurboJson;
+    use super::{BoundariesJson, OtelJson, RawTurboJson, TagRulesJson};
     use crate::{
         cli::OutputLogsMode,
         config::{
             turbo::{Pipeline, RawTaskDefinition},
-            TurboJson,
+            Error, TurboJson,
         },
         run::task_id::TaskName,
         task_graph::{TaskDefinition, TaskOutputs},
@@ -596,12 +714,41 @@ mod tests {
             ..TurboJson::default()
         }
     ; "global dot env (unsorted)")]
+    #[test_case(r#"{ "globalDotEnv": [".env"], "loadDotEnv": true }"#,
+        TurboJson {
+            global_dot_env: Some(vec![RelativeUnixPathBuf::new(".env").unwrap()]),
+            load_dot_env: true,
+            ..TurboJson::default()
+        }
+    ; "load dot env")]
     #[test_case(r#"{ "globalPassThroughEnv": ["GITHUB_TOKEN", "AWS_SECRET_KEY"] }"#,
         TurboJson {
             global_pass_through_env: Some(vec!["AWS_SECRET_KEY".to_string(), "GITHUB_TOKEN".to_string()]),
             ..TurboJson::default()
         }
     )]
+    #[test_case(r#"{ "otel": { "endpoint": "http://localhost:4318", "headers": { "x-token": "secret" } } }"#,
+        TurboJson {
+            otel: Some(OtelJson {
+                endpoint: Some("http://localhost:4318".to_string()),
+                headers: [("x-token".to_string(), "secret".to_string())].into_iter().collect(),
+            }),
+            ..TurboJson::default()
+        }
+    ; "otel")]
+    #[test_case(r#"{ "tags": ["ui"], "boundaries": { "tags": { "ui": { "deny": ["app"] } }, "checkBeforeRun": true } }"#,
+        TurboJson {
+            tags: vec!["ui".to_string()],
+            boundaries: Some(BoundariesJson {
+                tags: [(
+                    "ui".to_string(),
+                    TagRulesJson { allow: None, deny: Some(vec!["app".to_string()]) },
+                )].into_iter().collect(),
+                check_before_run: true,
+            }),
+            ..TurboJson::default()
+        }
+    ; "boundaries")]
     fn test_get_root_turbo_no_synthesizing(
         turbo_json_content: &str,
         expected_turbo_json: TurboJson,
@@ -725,7 +872,9 @@ mod tests {
           "cache": false,
           "inputs": ["package/a/src/**"],
           "outputMode": "full",
-          "persistent": true
+          "persistent": true,
+          "timeout": "5m",
+          "retries": 2
         }"#,
         RawTaskDefinition {
             depends_on: Some(vec!["cli#build".to_string()]),
@@ -737,6 +886,8 @@ mod tests {
             inputs: Some(vec!["package/a/src/**".to_string()]),
             output_mode: Some(OutputLogsMode::Full),
             persistent: Some(true),
+            timeout: Some("5m".to_string()),
+            retries: Some(2),
         },
         TaskDefinition {
           dot_env: Some(vec![RelativeUnixPathBuf::new("package/a/.env").unwrap()]),
@@ -752,6 +903,9 @@ mod tests {
           task_dependencies: vec!["cli#build".into()],
           topological_dependencies: vec![],
           persistent: true,
+          timeout: Some(Duration::from_secs(300)),
+          retries: 2,
+          defined_in: vec![],
         }
     )]
     fn test_deserialize_task_definition(
@@ -768,6 +922,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_invalid_task_timeout() {
+        let raw_task_definition: RawTaskDefinition =
+            serde_json::from_str(r#"{ "timeout": "five minutes" }"#).unwrap();
+        let result: Result<TaskDefinition, Error> = raw_task_definition.try_into();
+        assert!(matches!(
+            result,
+            Err(Error::InvalidTaskTimeout { value, .. }) if value == "five minutes"
+        ));
+    }
+
+    #[test_case("$TURBO_ROOT$/shared/**", true ; "root relative")]
+    #[test_case("!$TURBO_ROOT$/shared/**/*.md", true ; "root relative exclusion")]
+    #[test_case("src/$TURBO_ROOT$/**", false ; "not at start")]
+    #[test_case("$TURBO_ROOT$shared/**", false ; "missing separator")]
+    #[test_case("$TURBO_ROOT$/../outside/**", false ; "outside of repo")]
+    #[test_case("!$TURBO_ROOT$/shared/../../outside", false ; "exclusion outside of repo")]
+    #[test_case("$TURBO_ROOT$/shared\\..\\..\\outside", false ; "outside of repo with backslashes")]
+    #[test_case("$TURBO_ROOT$/shared/..config/**", true ; "dots in name")]
+    fn test_turbo_root_inputs(input: &str, is_valid: bool) {
+        let raw_task_definition = RawTaskDefinition {
+            inputs: Some(vec![input.to_string()]),
+            ..RawTaskDefinition::default()
+        };
+        let result: Result<TaskDefinition, Error> = raw_task_definition.try_into();
+        match result {
+            Ok(task_definition) => {
+                assert!(is_valid);
+                assert_eq!(task_definition.inputs, [input]);
+            }
+            Err(err) => {
+                assert!(!is_valid);
+                assert!(matches!(err, Error::InvalidTurboRootInput { .. }));
+            }
+        }
+    }
+
     #[test_case("[]", TaskOutputs::default())]
     #[test_case(r#"["target/**"]"#, TaskOutputs { inclusions: vec!["target/**".to_string()], exclusions: vec![] })]
     #[test_case(
@@ -3,6 +3,7 @@ use std::{collections::HashMap, ffi::OsString};
 use dirs_next::config_dir;
 use serde::{Deserialize, Serialize};
 use turbopath::AbsoluteSystemPathBuf;
+use turborepo_cache::RemoteCacheBackend;
 use turborepo_repository::package_json::{Error as PackageJsonError, PackageJson};
 
 use crate::{
@@ -47,6 +48,7 @@ pub struct ConfigurationOptions {
     pub(crate) preflight: Option<bool>,
     pub(crate) timeout: Option<u64>,
     pub(crate) enabled: Option<bool>,
+    pub(crate) backend: Option<RemoteCacheBackend>,
 }
 
 #[derive(Default)]
@@ -97,6 +99,10 @@ impl ConfigurationOptions {
     pub fn timeout(&self) -> u64 {
         self.timeout.unwrap_or(DEFAULT_TIMEOUT)
     }
+
+    pub fn backend(&self) -> RemoteCacheBackend {
+        self.backend.clone().unwrap_or_default()
+    }
 }
 
 trait ResolvedConfigurationOptions {
@@ -240,6 +246,8 @@ fn get_env_var_config(
 
         // Processed numbers
         timeout,
+
+        backend: None,
     };
 
     Ok(output)
@@ -283,6 +291,7 @@ fn get_override_env_var_config(
         preflight: None,
         enabled: None,
         timeout: None,
+        backend: None,
     };
 
     Ok(output)
@@ -372,6 +381,7 @@ impl TurborepoConfigBuilder {
     create_builder!(with_enabled, enabled, Option<bool>);
     create_builder!(with_preflight, preflight, Option<bool>);
     create_builder!(with_timeout, timeout, Option<u64>);
+    create_builder!(with_backend, backend, Option<RemoteCacheBackend>);
 
     pub fn build(&self) -> Result<ConfigurationOptions, ConfigError> {
         // Priority, from least significant to most significant:
@@ -450,6 +460,9 @@ impl TurborepoConfigBuilder {
                     if let Some(timeout) = current_source_config.timeout {
                         acc.timeout = Some(timeout);
                     }
+                    if let Some(backend) = current_source_config.backend.clone() {
+                        acc.backend = Some(backend);
+                    }
 
                     acc
                 })
@@ -476,6 +489,7 @@ mod test {
         assert!(defaults.enabled());
         assert!(!defaults.preflight());
         assert_eq!(defaults.timeout(), DEFAULT_TIMEOUT);
+        assert_eq!(defaults.backend(), RemoteCacheBackend::Vercel);
     }
 
     #[test]
@@ -589,4 +603,39 @@ mod test {
             None
         );
     }
+
+    #[test]
+    fn test_shared_backend() {
+        let tmp_dir = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp_dir.path()).unwrap();
+        let global_config_path = AbsoluteSystemPathBuf::try_from(
+            TempDir::new().unwrap().path().join("nonexistent.json"),
+        )
+        .unwrap();
+        repo_root
+            .join_component("turbo.json")
+            .create_with_contents(
+                r#"{
+                    "remoteCache": {
+                        "backend": { "type": "directory", "path": "/mnt/turbo-cache" }
+                    }
+                }"#,
+            )
+            .unwrap();
+
+        let builder = TurborepoConfigBuilder {
+            repo_root,
+            override_config: Default::default(),
+            global_config_path: Some(global_config_path),
+            environment: Default::default(),
+        };
+
+        let config = builder.build().unwrap();
+        assert_eq!(
+            config.backend(),
+            RemoteCacheBackend::Directory {
+                path: "/mnt/turbo-cache".to_string()
+            }
+        );
+    }
 }
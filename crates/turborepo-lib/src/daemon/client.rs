@@ -1,9 +1,10 @@
 use std::io;
 
+use futures::{Stream, StreamExt};
 use thiserror::Error;
 use tonic::{Code, Status};
 use tracing::info;
-use turbopath::AbsoluteSystemPathBuf;
+use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
 
 use super::{
     connector::{DaemonConnector, DaemonConnectorError},
@@ -131,6 +132,30 @@ impl<T> DaemonClient<T> {
 
         Ok(response)
     }
+
+    /// Subscribe to file changes in the repository. Each item is a batch of
+    /// repo-relative paths that the daemon saw change together.
+    pub async fn file_changes(
+        &mut self,
+    ) -> Result<impl Stream<Item = Result<Vec<AnchoredSystemPathBuf>, DaemonError>>, DaemonError>
+    {
+        let stream = self
+            .client
+            .file_changes(proto::FileChangesRequest {})
+            .await?
+            .into_inner();
+
+        Ok(stream.map(|response| {
+            response?
+                .changed_files
+                .into_iter()
+                .map(|file| {
+                    AnchoredSystemPathBuf::from_raw(file)
+                        .map_err(|_| DaemonError::MalformedResponse)
+                })
+                .collect()
+        }))
+    }
 }
 
 impl DaemonClient<DaemonConnector> {
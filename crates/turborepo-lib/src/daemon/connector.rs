@@ -644,6 +644,16 @@ mod test {
         ) -> Result<tonic::Response<proto::DiscoverPackagesResponse>, tonic::Status> {
             unimplemented!()
         }
+
+        type FileChangesStream =
+            tokio_stream::Empty<Result<proto::FileChangesResponse, tonic::Status>>;
+
+        async fn file_changes(
+            &self,
+            _req: tonic::Request<proto::FileChangesRequest>,
+        ) -> Result<tonic::Response<Self::FileChangesStream>, tonic::Status> {
+            unimplemented!()
+        }
     }
 
     #[tokio::test]
@@ -26,8 +26,9 @@ use semver::Version;
 use thiserror::Error;
 use tokio::{
     select,
-    sync::{mpsc, oneshot, watch, Mutex as AsyncMutex},
+    sync::{broadcast, mpsc, oneshot, watch, Mutex as AsyncMutex},
 };
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::transport::{NamedService, Server};
 use tower::ServiceBuilder;
 use tracing::{error, info, trace, warn};
@@ -64,11 +65,18 @@ pub enum CloseReason {
 }
 
 pub struct FileWatching {
-    _watcher: FileSystemWatcher,
+    watcher: FileSystemWatcher,
     pub glob_watcher: GlobWatcher,
     pub package_watcher: PackageWatcher,
 }
 
+impl FileWatching {
+    /// The watcher publishing the raw file events of the repository
+    pub fn watcher(&self) -> &FileSystemWatcher {
+        &self.watcher
+    }
+}
+
 #[derive(Debug, Error)]
 enum RpcError {
     #[error("deadline exceeded")]
@@ -113,7 +121,7 @@ async fn start_filewatching<PD: PackageDiscovery + Send + 'static>(
     // We can ignore failures here, it means the server is shutting down and
     // receivers have gone out of scope.
     let _ = watcher_tx.send(Some(Arc::new(FileWatching {
-        _watcher: watcher,
+        watcher,
         glob_watcher,
         package_watcher,
     })));
@@ -123,6 +131,10 @@ async fn start_filewatching<PD: PackageDiscovery + Send + 'static>(
 /// Timeout for every RPC the server handles
 const REQUEST_TIMEOUT: Duration = Duration::from_millis(100);
 
+/// How often an open file changes subscription bumps the idle timeout, so
+/// that a quiet repo doesn't shut down the daemon under a watching client.
+const FILE_CHANGES_KEEP_ALIVE: Duration = Duration::from_secs(30);
+
 pub struct TurboGrpcService<S, PDA, PDB> {
     watcher_tx: watch::Sender<Option<Arc<FileWatching>>>,
     watcher_rx: watch::Receiver<Option<Arc<FileWatching>>>,
@@ -278,6 +290,8 @@ where
             times_saved: Arc::new(Mutex::new(HashMap::new())),
             start_time: Instant::now(),
             log_file: log_file.to_owned(),
+            repo_root: repo_root.clone(),
+            bump_timeout: bump_timeout.clone(),
         };
         let server_fut = {
             let service = ServiceBuilder::new()
@@ -323,6 +337,8 @@ struct TurboGrpcServiceInner<PD> {
     start_time: Instant,
     log_file: AbsoluteSystemPathBuf,
     package_discovery: AsyncMutex<PD>,
+    repo_root: AbsoluteSystemPathBuf,
+    bump_timeout: Arc<BumpTimeout>,
 }
 
 impl<PD> TurboGrpcServiceInner<PD> {
@@ -392,7 +408,7 @@ async fn watch_root(
             return Ok(());
         };
 
-        fw._watcher.subscribe()
+        fw.watcher().subscribe()
     };
 
     loop {
@@ -535,6 +551,64 @@ where
             })
             .map_err(|e| tonic::Status::internal(format!("{}", e)))
     }
+
+    type FileChangesStream = ReceiverStream<Result<proto::FileChangesResponse, tonic::Status>>;
+
+    async fn file_changes(
+        &self,
+        _request: tonic::Request<proto::FileChangesRequest>,
+    ) -> Result<tonic::Response<Self::FileChangesStream>, tonic::Status> {
+        let fw = self.wait_for_filewatching().await?;
+        let mut events = fw.watcher().subscribe();
+        let repo_root = self.repo_root.clone();
+        let bump_timeout = self.bump_timeout.clone();
+        let (tx, rx) = mpsc::channel(16);
+
+        // Forward events until either the client goes away or filewatching
+        // stops. A subscribed client makes no other requests, so we keep
+        // bumping the idle timeout ourselves for as long as it's connected.
+        tokio::spawn(async move {
+            let mut keep_alive = tokio::time::interval(FILE_CHANGES_KEEP_ALIVE);
+            loop {
+                bump_timeout.reset();
+                let event = select! {
+                    _ = tx.closed() => return,
+                    _ = keep_alive.tick() => continue,
+                    event = events.recv() => event,
+                };
+                let changed_files = match event {
+                    Ok(Ok(event)) => event
+                        .paths
+                        .iter()
+                        .filter_map(|path| {
+                            let path = AbsoluteSystemPathBuf::try_from(path.as_path()).ok()?;
+                            Some(repo_root.anchor(path).ok()?.to_string())
+                        })
+                        .collect::<Vec<_>>(),
+                    Ok(Err(e)) => {
+                        let _ = tx
+                            .send(Err(tonic::Status::unavailable(e.to_string())))
+                            .await;
+                        return;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(count)) => {
+                        warn!("file changes subscriber lagged, dropped {count} events");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                if changed_files.is_empty() {
+                    continue;
+                }
+                let response = proto::FileChangesResponse { changed_files };
+                if tx.send(Ok(response)).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(tonic::Response::new(ReceiverStream::new(rx)))
+    }
 }
 
 /// Determine whether a server can serve a client's request based on its
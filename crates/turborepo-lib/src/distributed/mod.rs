@@ -0,0 +1,345 @@
+//! Distributed task execution. `turbo worker` serves the `Worker` gRPC
+//! service, and a `turbo run --workers` coordinator hands tasks from its
+//! engine to those workers through a `WorkerPool` instead of spawning them
+//! locally. Neither side copies files to the other: a worker restores the
+//! outputs of a task's dependencies from the cache before running it and
+//! caches the task's outputs afterwards, so both sides need to share a cache,
+//! e.g. the same `--cache-dir` on one machine or a remote cache.
+//!
+//! Workers only serve coordinators that present the token in
+//! `TURBO_WORKER_TOKEN`, and only run scripts of the packages in their own
+//! checkout of the repository.
+mod pool;
+mod server;
+
+pub use pool::{RemoteExit, RemoteTask, WorkerPool};
+pub use server::{WorkerServer, WorkspaceScripts};
+use thiserror::Error;
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    service::Interceptor,
+    Request, Status,
+};
+
+pub(crate) mod proto {
+    tonic::include_proto!("turboworker");
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("TURBO_WORKER_TOKEN must be set to a token shared by the coordinator and its workers")]
+    MissingToken,
+    #[error("TURBO_WORKER_TOKEN must only contain visible ASCII characters")]
+    InvalidToken,
+    #[error("invalid worker address {0}")]
+    InvalidAddress(String),
+    #[error("unable to connect to worker {address}: {source}")]
+    Connect {
+        address: String,
+        #[source]
+        source: tonic::transport::Error,
+    },
+    #[error("worker {address} runs turbo {version}, but the coordinator runs {expected}")]
+    VersionMismatch {
+        address: String,
+        version: String,
+        expected: String,
+    },
+    #[error("worker {address} failed: {status}")]
+    Grpc {
+        address: String,
+        status: tonic::Status,
+    },
+    #[error("no workers are available to run tasks")]
+    NoWorkers,
+    #[error("worker {0} closed the connection before the task finished")]
+    Disconnected(String),
+    #[error("worker {address} was unable to cache the outputs of {task_id}: {message}")]
+    Cache {
+        address: String,
+        task_id: String,
+        message: String,
+    },
+    #[error("unable to write task output: {0}")]
+    Output(#[from] std::io::Error),
+    #[error("unable to listen on {addr}: {source}")]
+    Listen {
+        addr: std::net::SocketAddr,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("unable to serve worker: {0}")]
+    Serve(#[from] tonic::transport::Error),
+}
+
+const WORKER_TOKEN_ENV: &str = "TURBO_WORKER_TOKEN";
+
+/// The token that a coordinator and its workers authenticate each other with
+#[derive(Clone)]
+pub struct WorkerToken(MetadataValue<Ascii>);
+
+impl WorkerToken {
+    pub fn new(token: &str) -> Result<Self, Error> {
+        if token.is_empty() {
+            return Err(Error::MissingToken);
+        }
+        format!("Bearer {token}")
+            .parse()
+            .map(Self)
+            .map_err(|_| Error::InvalidToken)
+    }
+
+    pub fn from_env() -> Result<Self, Error> {
+        let token = std::env::var(WORKER_TOKEN_ENV).map_err(|_| Error::MissingToken)?;
+        Self::new(&token)
+    }
+
+    // Compares every byte regardless of where the first difference is, so
+    // that response times don't give the token away
+    fn matches(&self, value: &MetadataValue<Ascii>) -> bool {
+        let expected = self.0.as_bytes();
+        let actual = value.as_bytes();
+        expected.len() == actual.len()
+            && expected
+                .iter()
+                .zip(actual)
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+// Never print the token itself
+impl std::fmt::Debug for WorkerToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WorkerToken(..)")
+    }
+}
+
+// Attaches the token to each request a coordinator sends
+impl Interceptor for WorkerToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request
+            .metadata_mut()
+            .insert("authorization", self.0.clone());
+        Ok(request)
+    }
+}
+
+// Rejects requests to a worker that don't carry its token
+#[derive(Clone, Debug)]
+struct RequireToken(WorkerToken);
+
+impl Interceptor for RequireToken {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        match request.metadata().get("authorization") {
+            Some(value) if self.0.matches(value) => Ok(request),
+            _ => Err(Status::unauthenticated("invalid or missing worker token")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use serde_json::json;
+    use tokio::{net::TcpListener, sync::oneshot};
+    use turbopath::AbsoluteSystemPathBuf;
+    use turborepo_repository::{package_graph::PackageGraph, package_json::PackageJson};
+
+    use super::*;
+    use crate::{
+        task_graph::TaskOutputs,
+        test_utils::{path_env, TestRepo},
+    };
+
+    const TOKEN: &str = "test-token";
+
+    struct TestWorker {
+        repo: TestRepo,
+        cache_dir: AbsoluteSystemPathBuf,
+        address: String,
+        _shutdown: oneshot::Sender<()>,
+    }
+
+    async fn start_worker() -> Result<TestWorker> {
+        let repo = test_repo()?;
+        let cache_dir = repo.root.join_component(".cache");
+        serve(repo, cache_dir).await
+    }
+
+    fn test_repo() -> Result<TestRepo> {
+        TestRepo::new(json!({
+            "build": "echo built > out.txt",
+            "copy": "cp out.txt copied.txt",
+            "fail": "exit 3",
+        }))
+    }
+
+    // Workers only share outputs through their caches, so workers of the same
+    // run need to be given the same `cache_dir`
+    async fn serve(repo: TestRepo, cache_dir: AbsoluteSystemPathBuf) -> Result<TestWorker> {
+        let root_package_json = PackageJson::load(&repo.root.join_component("package.json"))?;
+        let package_graph = PackageGraph::builder(&repo.root, root_package_json)
+            .build()
+            .await?;
+
+        let cache = repo.cache(&cache_dir)?;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?.to_string();
+        let (shutdown, stopped) = oneshot::channel();
+        let server = WorkerServer::new(
+            repo.root.clone(),
+            cache,
+            WorkspaceScripts::new(&package_graph),
+            WorkerToken::new(TOKEN)?,
+            2,
+        );
+        tokio::spawn(server.serve_with_listener(listener, async {
+            stopped.await.ok();
+        }));
+
+        Ok(TestWorker {
+            repo,
+            cache_dir,
+            address,
+            _shutdown: shutdown,
+        })
+    }
+
+    fn task(task_id: &str, hash: &str) -> RemoteTask {
+        RemoteTask {
+            task_id: task_id.to_string(),
+            hash: hash.to_string(),
+            pass_through_args: Vec::new(),
+            // The package manager needs to find node
+            env: path_env(),
+            outputs: TaskOutputs {
+                inclusions: vec!["packages/a/out.txt".to_string()],
+                exclusions: Vec::new(),
+            },
+            dependency_hashes: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pool_runs_tasks_on_worker() -> Result<()> {
+        let worker = start_worker().await?;
+        let pool = WorkerPool::connect(&[worker.address.clone()], WorkerToken::new(TOKEN)?).await?;
+
+        let mut output = Vec::new();
+        let exit = pool.run(task("a#build", "abc123"), &mut output).await?;
+        assert_eq!(exit, RemoteExit::Finished(0));
+        assert_eq!(
+            worker
+                .repo
+                .package_dir()
+                .join_component("out.txt")
+                .read_to_string()?
+                .trim(),
+            "built"
+        );
+        // The outputs were cached for the coordinator to restore
+        assert!(worker.cache_dir.join_component("abc123.tar.zst").exists());
+
+        let exit = pool.run(task("a#fail", "def456"), Vec::new()).await?;
+        assert_eq!(exit, RemoteExit::Finished(3));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pool_spreads_tasks_across_workers() -> Result<()> {
+        let first = start_worker().await?;
+        let second = serve(test_repo()?, first.cache_dir.clone()).await?;
+        let pool = WorkerPool::connect(
+            &[first.address.clone(), second.address.clone()],
+            WorkerToken::new(TOKEN)?,
+        )
+        .await?;
+        let out_txt = |worker: &TestWorker| worker.repo.package_dir().join_component("out.txt");
+
+        // With both workers idle, the first one gets the task
+        let exit = pool.run(task("a#build", "abc123"), Vec::new()).await?;
+        assert_eq!(exit, RemoteExit::Finished(0));
+        assert!(out_txt(&first).exists());
+        assert!(!out_txt(&second).exists());
+
+        // While the first worker is busy, the dependent task goes to the
+        // second, which restores the outputs of its dependency from the cache
+        let mut copy = task("a#copy", "def456");
+        copy.outputs.inclusions = vec!["packages/a/copied.txt".to_string()];
+        copy.dependency_hashes = vec!["abc123".to_string()];
+        let (build, copy) = tokio::join!(
+            pool.run(task("a#build", "ghi789"), Vec::new()),
+            pool.run(copy, Vec::new())
+        );
+        assert_eq!(build?, RemoteExit::Finished(0));
+        assert_eq!(copy?, RemoteExit::Finished(0));
+        assert_eq!(out_txt(&second).read_to_string()?.trim(), "built");
+        assert_eq!(
+            second
+                .repo
+                .package_dir()
+                .join_component("copied.txt")
+                .read_to_string()?
+                .trim(),
+            "built"
+        );
+        assert!(!first
+            .repo
+            .package_dir()
+            .join_component("copied.txt")
+            .exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_worker_rejects_wrong_token() -> Result<()> {
+        let worker = start_worker().await?;
+        let result =
+            WorkerPool::connect(&[worker.address.clone()], WorkerToken::new("wrong")?).await;
+        assert!(matches!(
+            result,
+            Err(Error::Grpc { status, .. }) if status.code() == tonic::Code::Unauthenticated
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_worker_only_runs_package_scripts() -> Result<()> {
+        let worker = start_worker().await?;
+        let pool = WorkerPool::connect(&[worker.address.clone()], WorkerToken::new(TOKEN)?).await?;
+
+        for task_id in ["a#test", "b#build", "build"] {
+            let result = pool.run(task(task_id, "abc123"), Vec::new()).await;
+            assert!(
+                matches!(&result, Err(Error::Grpc { status, .. })
+                    if matches!(status.code(), tonic::Code::NotFound | tonic::Code::InvalidArgument)),
+                "{task_id} was not rejected: {result:?}"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_worker_rejects_outputs_outside_repo() -> Result<()> {
+        let worker = start_worker().await?;
+        let pool = WorkerPool::connect(&[worker.address.clone()], WorkerToken::new(TOKEN)?).await?;
+
+        let mut task = task("a#build", "abc123");
+        task.outputs.inclusions = vec!["packages/a/../../../etc/passwd".to_string()];
+        let result = pool.run(task, Vec::new()).await;
+        assert!(matches!(
+            result,
+            Err(Error::Grpc { status, .. }) if status.code() == tonic::Code::InvalidArgument
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_must_not_be_empty() {
+        assert!(matches!(WorkerToken::new(""), Err(Error::MissingToken)));
+        assert!(matches!(WorkerToken::new("a\nb"), Err(Error::InvalidToken)));
+    }
+}
@@ -0,0 +1,196 @@
+use std::{collections::HashMap, io::Write, sync::Mutex};
+
+use tokio::sync::Semaphore;
+use tonic::{
+    codegen::InterceptedService,
+    transport::{Channel, Endpoint},
+};
+use tracing::debug;
+
+use super::{
+    proto::{self, run_task_event::Event, worker_client::WorkerClient},
+    Error, WorkerToken,
+};
+use crate::{get_version, task_graph::TaskOutputs};
+
+/// Everything a worker needs to run a task the same way the coordinator
+/// would. The worker looks up the task's script in its own checkout.
+#[derive(Debug, Clone)]
+pub struct RemoteTask {
+    pub task_id: String,
+    pub hash: String,
+    pub pass_through_args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub outputs: TaskOutputs,
+    pub dependency_hashes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteExit {
+    Finished(i32),
+    Killed,
+}
+
+/// Connections to every worker of a distributed run. Tasks go to whichever
+/// worker has the most free capacity, waiting if all of them are busy.
+#[derive(Debug)]
+pub struct WorkerPool {
+    workers: Vec<Worker>,
+    // How many tasks each worker is running, by the worker's index
+    active: Mutex<Vec<usize>>,
+    slots: Semaphore,
+}
+
+#[derive(Debug)]
+struct Worker {
+    address: String,
+    client: WorkerClient<InterceptedService<Channel, WorkerToken>>,
+    concurrency: usize,
+}
+
+// Gives a worker's slot back once its task is done, however it finishes
+struct ActiveTask<'a> {
+    active: &'a Mutex<Vec<usize>>,
+    worker: usize,
+}
+
+impl Drop for ActiveTask<'_> {
+    fn drop(&mut self) {
+        self.active.lock().expect("lock poisoned")[self.worker] -= 1;
+    }
+}
+
+impl WorkerPool {
+    /// Connects to each of `addresses`, given as `host:port`, and checks that
+    /// they accept `token` and run the same version of turbo
+    pub async fn connect(addresses: &[String], token: WorkerToken) -> Result<Self, Error> {
+        let mut workers = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let endpoint = Endpoint::from_shared(format!("http://{address}"))
+                .map_err(|_| Error::InvalidAddress(address.clone()))?;
+            let channel = endpoint.connect().await.map_err(|source| Error::Connect {
+                address: address.clone(),
+                source,
+            })?;
+            let mut client = WorkerClient::with_interceptor(channel, token.clone());
+
+            let hello = client
+                .hello(proto::HelloRequest {})
+                .await
+                .map_err(|status| Error::Grpc {
+                    address: address.clone(),
+                    status,
+                })?
+                .into_inner();
+            if hello.version != get_version() {
+                return Err(Error::VersionMismatch {
+                    address: address.clone(),
+                    version: hello.version,
+                    expected: get_version().to_string(),
+                });
+            }
+
+            debug!(
+                "connected to worker {address} running {} tasks at once",
+                hello.concurrency
+            );
+            workers.push(Worker {
+                address: address.clone(),
+                client,
+                concurrency: hello.concurrency.max(1) as usize,
+            });
+        }
+
+        let active = Mutex::new(vec![0; workers.len()]);
+        let slots = Semaphore::new(workers.iter().map(|worker| worker.concurrency).sum());
+        Ok(Self {
+            workers,
+            active,
+            slots,
+        })
+    }
+
+    /// Runs `task` on the least busy worker, writing its logs to `output` as
+    /// they arrive. Dropping the returned future stops the task on the worker.
+    pub async fn run(&self, task: RemoteTask, mut output: impl Write) -> Result<RemoteExit, Error> {
+        // The pool never closes its semaphore, but if it did there would be
+        // no worker left to run the task on
+        let Ok(_permit) = self.slots.acquire().await else {
+            return Err(Error::NoWorkers);
+        };
+        let (worker, _active) = self.claim_worker().ok_or(Error::NoWorkers)?;
+        let address = &worker.address;
+        let task_id = task.task_id.clone();
+        debug!("running {task_id} on worker {address}");
+
+        let mut client = worker.client.clone();
+        let mut events = client
+            .run_task(proto::RunTaskRequest::from(task))
+            .await
+            .map_err(|status| Error::Grpc {
+                address: address.clone(),
+                status,
+            })?
+            .into_inner();
+
+        while let Some(event) = events.message().await.map_err(|status| Error::Grpc {
+            address: address.clone(),
+            status,
+        })? {
+            match event.event {
+                Some(Event::Output(bytes)) => output.write_all(&bytes)?,
+                Some(Event::Finished(finished)) if finished.killed => {
+                    return Ok(RemoteExit::Killed)
+                }
+                Some(Event::Finished(finished)) if !finished.cache_error.is_empty() => {
+                    return Err(Error::Cache {
+                        address: address.clone(),
+                        task_id,
+                        message: finished.cache_error,
+                    })
+                }
+                Some(Event::Finished(finished)) => {
+                    return Ok(RemoteExit::Finished(finished.exit_code))
+                }
+                None => (),
+            }
+        }
+
+        Err(Error::Disconnected(address.clone()))
+    }
+
+    // Holding a semaphore permit guarantees that at least one worker has a
+    // free slot. The worker is picked and claimed under one lock so that tasks
+    // starting at the same time don't all pick the same worker.
+    fn claim_worker(&self) -> Option<(&Worker, ActiveTask)> {
+        let mut active = self.active.lock().expect("lock poisoned");
+        let (index, worker) = self
+            .workers
+            .iter()
+            .enumerate()
+            // Compare the share of each worker that's busy, scaled to avoid floats
+            .min_by_key(|(index, worker)| active[*index] * 1000 / worker.concurrency)?;
+        active[index] += 1;
+        Some((
+            worker,
+            ActiveTask {
+                active: &self.active,
+                worker: index,
+            },
+        ))
+    }
+}
+
+impl From<RemoteTask> for proto::RunTaskRequest {
+    fn from(task: RemoteTask) -> Self {
+        Self {
+            task_id: task.task_id,
+            hash: task.hash,
+            pass_through_args: task.pass_through_args,
+            env: task.env,
+            output_globs: task.outputs.inclusions,
+            output_exclusion_globs: task.outputs.exclusions,
+            dependency_hashes: task.dependency_hashes,
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    future::Future,
+    io::{BufWriter, Write},
+    net::SocketAddr,
+    process::Stdio,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    net::TcpListener,
+    process::Command,
+    sync::{mpsc, Semaphore},
+};
+use tokio_stream::wrappers::{TcpListenerStream, UnboundedReceiverStream};
+use tonic::transport::Server;
+use tracing::{debug, warn};
+use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
+use turborepo_cache::AsyncCache;
+use turborepo_repository::{package_graph::PackageGraph, package_manager::PackageManager};
+use which::which;
+
+use super::{
+    proto::{self, run_task_event::Event},
+    Error, RequireToken, WorkerToken,
+};
+use crate::{
+    get_version,
+    process::{ChildExit, ProcessManager},
+    run::task_id::TaskId,
+    task_graph::TaskDefinition,
+};
+
+type EventSender = mpsc::UnboundedSender<Result<proto::RunTaskEvent, tonic::Status>>;
+
+/// The tasks a worker is willing to run: the scripts in the package.json of
+/// each package in its own checkout of the repository
+#[derive(Debug, Clone)]
+pub struct WorkspaceScripts {
+    package_manager: PackageManager,
+    workspaces: HashMap<String, Workspace>,
+}
+
+#[derive(Debug, Clone)]
+struct Workspace {
+    directory: AnchoredSystemPathBuf,
+    scripts: HashSet<String>,
+}
+
+// How to run a task, as resolved by the worker
+#[derive(Debug, PartialEq)]
+struct TaskCommand {
+    program: &'static str,
+    args: Vec<String>,
+    directory: AnchoredSystemPathBuf,
+    log_file: AnchoredSystemPathBuf,
+}
+
+impl WorkspaceScripts {
+    pub fn new(package_graph: &PackageGraph) -> Self {
+        let workspaces = package_graph
+            .workspaces()
+            .map(|(name, info)| {
+                let workspace = Workspace {
+                    directory: info.package_path().to_owned(),
+                    scripts: info.package_json.scripts.keys().cloned().collect(),
+                };
+                (name.to_string(), workspace)
+            })
+            .collect();
+        Self {
+            package_manager: *package_graph.package_manager(),
+            workspaces,
+        }
+    }
+
+    // Coordinators only name the task to run, so that a worker never runs a
+    // command that isn't a script of one of its packages
+    fn command(
+        &self,
+        task_id: &str,
+        pass_through_args: &[String],
+    ) -> Result<TaskCommand, tonic::Status> {
+        let task_id = TaskId::try_from(task_id)
+            .map_err(|_| tonic::Status::invalid_argument(format!("invalid task id {task_id}")))?;
+        let workspace = self.workspaces.get(task_id.package()).ok_or_else(|| {
+            tonic::Status::not_found(format!("no package named {}", task_id.package()))
+        })?;
+        if !workspace.scripts.contains(task_id.task()) {
+            return Err(tonic::Status::not_found(format!(
+                "{} has no script named {}",
+                task_id.package(),
+                task_id.task()
+            )));
+        }
+
+        let mut args = vec!["run".to_string(), task_id.task().to_string()];
+        if !pass_through_args.is_empty() {
+            args.extend(
+                self.package_manager
+                    .arg_separator(pass_through_args)
+                    .map(|s| s.to_string()),
+            );
+            args.extend(pass_through_args.iter().cloned());
+        }
+        let log_file = TaskDefinition::workspace_relative_log_file(task_id.task());
+
+        Ok(TaskCommand {
+            program: self.package_manager.command(),
+            args,
+            log_file: workspace.directory.join(&log_file),
+            directory: workspace.directory.clone(),
+        })
+    }
+}
+
+/// Runs tasks for a coordinator in its own checkout of the repository
+pub struct WorkerServer {
+    repo_root: AbsoluteSystemPathBuf,
+    cache: Arc<AsyncCache>,
+    scripts: WorkspaceScripts,
+    token: WorkerToken,
+    manager: ProcessManager,
+    concurrency: u32,
+    slots: Arc<Semaphore>,
+}
+
+impl WorkerServer {
+    pub fn new(
+        repo_root: AbsoluteSystemPathBuf,
+        cache: AsyncCache,
+        scripts: WorkspaceScripts,
+        token: WorkerToken,
+        concurrency: u32,
+    ) -> Self {
+        Self {
+            repo_root,
+            cache: Arc::new(cache),
+            scripts,
+            token,
+            manager: ProcessManager::new(),
+            concurrency,
+            slots: Arc::new(Semaphore::new(concurrency as usize)),
+        }
+    }
+
+    /// Serves tasks on `addr` until `shutdown` resolves, then stops any tasks
+    /// that are still running
+    pub async fn serve(
+        self,
+        addr: SocketAddr,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), Error> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|source| Error::Listen { addr, source })?;
+        self.serve_with_listener(listener, shutdown).await
+    }
+
+    pub(crate) async fn serve_with_listener(
+        self,
+        listener: TcpListener,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), Error> {
+        let manager = self.manager.clone();
+        let require_token = RequireToken(self.token.clone());
+        let result = Server::builder()
+            .add_service(proto::worker_server::WorkerServer::with_interceptor(
+                self,
+                require_token,
+            ))
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown)
+            .await;
+        manager.stop().await;
+        Ok(result?)
+    }
+}
+
+#[tonic::async_trait]
+impl proto::worker_server::Worker for WorkerServer {
+    async fn hello(
+        &self,
+        _request: tonic::Request<proto::HelloRequest>,
+    ) -> Result<tonic::Response<proto::HelloResponse>, tonic::Status> {
+        Ok(tonic::Response::new(proto::HelloResponse {
+            version: get_version().to_string(),
+            concurrency: self.concurrency,
+        }))
+    }
+
+    type RunTaskStream = UnboundedReceiverStream<Result<proto::RunTaskEvent, tonic::Status>>;
+
+    async fn run_task(
+        &self,
+        request: tonic::Request<proto::RunTaskRequest>,
+    ) -> Result<tonic::Response<Self::RunTaskStream>, tonic::Status> {
+        let request = request.into_inner();
+        check_output_globs(&request.output_globs)?;
+        check_output_globs(&request.output_exclusion_globs)?;
+        let job = Job {
+            command: self
+                .scripts
+                .command(&request.task_id, &request.pass_through_args)?,
+            repo_root: self.repo_root.clone(),
+            cache: self.cache.clone(),
+            manager: self.manager.clone(),
+            request,
+        };
+        let slots = self.slots.clone();
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let event = match slots.acquire_owned().await {
+                Ok(_permit) => job.run(&tx).await,
+                Err(_) => Err(tonic::Status::unavailable("worker is shutting down")),
+            }
+            .map(|finished| proto::RunTaskEvent {
+                event: Some(Event::Finished(finished)),
+            });
+            // The coordinator may have already gone away
+            tx.send(event).ok();
+        });
+
+        Ok(tonic::Response::new(UnboundedReceiverStream::new(rx)))
+    }
+}
+
+// Outputs are read into the cache, so their globs must stay inside the
+// repository
+fn check_output_globs(globs: &[String]) -> Result<(), tonic::Status> {
+    for glob in globs {
+        let is_absolute = glob.starts_with(['/', '\\']) || glob.chars().nth(1) == Some(':');
+        let is_outside = glob.split(['/', '\\']).any(|segment| segment == "..");
+        if is_absolute || is_outside {
+            return Err(tonic::Status::invalid_argument(format!(
+                "output glob {glob} is outside of the repository"
+            )));
+        }
+    }
+    Ok(())
+}
+
+struct Job {
+    request: proto::RunTaskRequest,
+    command: TaskCommand,
+    repo_root: AbsoluteSystemPathBuf,
+    cache: Arc<AsyncCache>,
+    manager: ProcessManager,
+}
+
+impl Job {
+    async fn run(&self, tx: &EventSender) -> Result<proto::TaskFinished, tonic::Status> {
+        let task_id = &self.request.task_id;
+        debug!("running {task_id} ({})", self.request.hash);
+
+        // A dependency without cached outputs either has no outputs or ran
+        // somewhere that doesn't share our cache. Either way the task gets a
+        // chance to run and report any problems itself.
+        for hash in &self.request.dependency_hashes {
+            if let Err(e) = self.cache.fetch(&self.repo_root, hash).await {
+                warn!("unable to restore outputs of dependency {hash} for {task_id}: {e}");
+            }
+        }
+
+        let program = which(self.command.program).map_err(|e| {
+            tonic::Status::failed_precondition(format!(
+                "unable to find {}: {e}",
+                self.command.program
+            ))
+        })?;
+        let mut cmd = Command::new(program);
+        cmd.args(&self.command.args);
+        cmd.current_dir(self.repo_root.resolve(&self.command.directory).as_path());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+        cmd.env_clear();
+        cmd.envs(&self.request.env);
+
+        let log_file = self.repo_root.resolve(&self.command.log_file);
+        let mut output = TaskOutput {
+            tx,
+            log: create_log_file(&log_file).map_err(|e| {
+                tonic::Status::internal(format!("unable to create log file {log_file}: {e}"))
+            })?,
+        };
+
+        let task_start = Instant::now();
+        let mut process = match self.manager.spawn(cmd, Duration::from_millis(500)) {
+            Some(Ok(child)) => child,
+            Some(Err(e)) => {
+                return Err(tonic::Status::internal(format!(
+                    "unable to spawn {task_id}: {e}"
+                )))
+            }
+            None => return Err(tonic::Status::unavailable("worker is shutting down")),
+        };
+
+        // Stop the task if the coordinator stops listening for it, e.g. it hit
+        // its timeout or the run was interrupted
+        let mut stopper = process.clone();
+        let exit = tokio::select! {
+            exit = process.wait_with_piped_outputs(&mut output, None) => exit,
+            _ = tx.closed() => {
+                stopper.stop().await;
+                return Err(tonic::Status::cancelled("coordinator went away"));
+            }
+        };
+        let exit_code = match exit {
+            Ok(Some(ChildExit::Finished(Some(code)))) => code,
+            Ok(_) => {
+                return Ok(proto::TaskFinished {
+                    killed: true,
+                    ..Default::default()
+                })
+            }
+            Err(e) => {
+                return Err(tonic::Status::internal(format!(
+                    "unable to read output of {task_id}: {e}"
+                )))
+            }
+        };
+        output
+            .log
+            .flush()
+            .map_err(|e| tonic::Status::internal(format!("unable to write log file: {e}")))?;
+
+        let cache_error = match exit_code {
+            0 => self
+                .save_outputs(task_start.elapsed())
+                .await
+                .err()
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+
+        Ok(proto::TaskFinished {
+            exit_code,
+            killed: false,
+            cache_error,
+        })
+    }
+
+    // Cache writes happen in the background, so we wait for them and check
+    // the entry exists before telling the coordinator it can fetch it
+    async fn save_outputs(&self, duration: Duration) -> Result<(), String> {
+        let files = globwalk::globwalk(
+            &self.repo_root,
+            &self.request.output_globs,
+            &self.request.output_exclusion_globs,
+            globwalk::WalkType::All,
+        )
+        .map_err(|e| e.to_string())?;
+        let mut files = files
+            .iter()
+            .map(|file| AnchoredSystemPathBuf::relative_path_between(&self.repo_root, file))
+            .collect::<Vec<_>>();
+        files.sort();
+
+        self.cache
+            .put(
+                self.repo_root.clone(),
+                self.request.hash.clone(),
+                files,
+                duration.as_millis() as u64,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        self.cache.wait().await;
+
+        match self.cache.exists(&self.request.hash).await {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err("outputs were not written to the cache".to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+fn create_log_file(path: &AbsoluteSystemPathBuf) -> std::io::Result<BufWriter<File>> {
+    path.ensure_dir()?;
+    Ok(BufWriter::new(path.create()?))
+}
+
+// Sends task output to the coordinator while keeping a copy in the log file,
+// which is cached along with the task's outputs
+struct TaskOutput<'a> {
+    tx: &'a EventSender,
+    log: BufWriter<File>,
+}
+
+impl Write for TaskOutput<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.log.write_all(buf)?;
+        // If the coordinator is gone the task is about to be stopped anyway
+        self.tx
+            .send(Ok(proto::RunTaskEvent {
+                event: Some(Event::Output(buf.to_vec())),
+            }))
+            .ok();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.log.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    fn scripts() -> WorkspaceScripts {
+        let workspace = Workspace {
+            directory: AnchoredSystemPathBuf::from_raw("packages/a").unwrap(),
+            scripts: HashSet::from(["build".to_string(), "build:prod".to_string()]),
+        };
+        WorkspaceScripts {
+            package_manager: PackageManager::Npm,
+            workspaces: HashMap::from([("a".to_string(), workspace)]),
+        }
+    }
+
+    #[test]
+    fn test_command_runs_package_script() {
+        let command = scripts()
+            .command("a#build:prod", &["--watch".to_string()])
+            .unwrap();
+        assert_eq!(
+            command,
+            TaskCommand {
+                program: "npm",
+                args: vec![
+                    "run".to_string(),
+                    "build:prod".to_string(),
+                    "--".to_string(),
+                    "--watch".to_string()
+                ],
+                directory: AnchoredSystemPathBuf::from_raw("packages/a").unwrap(),
+                log_file: AnchoredSystemPathBuf::from_raw("packages/a")
+                    .unwrap()
+                    .join(&TaskDefinition::workspace_relative_log_file("build:prod")),
+            }
+        );
+    }
+
+    #[test_case("a#test" ; "missing script")]
+    #[test_case("b#build" ; "missing package")]
+    #[test_case("build" ; "missing package name")]
+    #[test_case("#build" ; "empty package name")]
+    fn test_command_rejects_unknown_tasks(task_id: &str) {
+        assert!(scripts().command(task_id, &[]).is_err());
+    }
+
+    #[test_case("packages/a/dist/**", true ; "inside repo")]
+    #[test_case("packages/a/..dist/**", true ; "dots in name")]
+    #[test_case("packages/a/../../../etc/**", false ; "parent segments")]
+    #[test_case("..", false ; "parent")]
+    #[test_case("packages\\a\\..\\..\\..\\etc", false ; "windows parent segments")]
+    #[test_case("/etc/passwd", false ; "absolute")]
+    #[test_case("C:\\Windows\\**", false ; "windows absolute")]
+    fn test_check_output_globs(glob: &str, allowed: bool) {
+        assert_eq!(check_output_globs(&[glob.to_string()]).is_ok(), allowed);
+    }
+}
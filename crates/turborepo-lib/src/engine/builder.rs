@@ -37,6 +37,20 @@ pub enum Error {
     Graph(#[from] graph::Error),
     #[error("Invalid task name {task_name}: {reason}")]
     InvalidTaskName { task_name: String, reason: String },
+    #[error("turbo.json in \"{workspace}\" extends \"{extends}\", which is not a workspace")]
+    MissingExtendsWorkspace {
+        workspace: WorkspaceName,
+        extends: String,
+    },
+    #[error(
+        "turbo.json in \"{workspace}\" extends \"{extends}\", which does not have a turbo.json"
+    )]
+    MissingExtendsTurboJson {
+        workspace: WorkspaceName,
+        extends: WorkspaceName,
+    },
+    #[error("turbo.json \"extends\" forms a cycle: {cycle}")]
+    ExtendsCycle { cycle: String },
 }
 
 pub struct EngineBuilder<'a> {
@@ -176,13 +190,24 @@ impl<'a> EngineBuilder<'a> {
                     task_id: task_id.to_string(),
                 });
             }
-            let raw_task_definition = RawTaskDefinition::from_iter(self.task_definition_chain(
+            let task_definition_chain = self.task_definition_chain(
                 &mut turbo_jsons,
                 &task_id,
                 &task_id.as_non_workspace_task_name(),
-            )?);
-
-            let task_definition = TaskDefinition::try_from(raw_task_definition)?;
+            )?;
+            let defined_in = task_definition_chain
+                .iter()
+                .filter(|(workspace, _)| !matches!(workspace, WorkspaceName::Root))
+                .map(|(workspace, _)| workspace.to_string())
+                .collect();
+            let raw_task_definition = RawTaskDefinition::from_iter(
+                task_definition_chain
+                    .into_iter()
+                    .map(|(_, definition)| definition),
+            );
+
+            let mut task_definition = TaskDefinition::try_from(raw_task_definition)?;
+            task_definition.defined_in = defined_in;
 
             // Skip this iteration of the loop if we've already seen this taskID
             if visited.contains(&task_id) {
@@ -270,39 +295,37 @@ impl<'a> EngineBuilder<'a> {
         task_name: &TaskName<'static>,
         task_id: &TaskId,
     ) -> Result<bool, Error> {
-        let turbo_json = self
-            .turbo_json(turbo_jsons, workspace)
-            // If there was no turbo.json in the workspace, fallback to the root turbo.json
-            .or_else(|e| {
-                if e.is_missing_turbo_json() && !matches!(workspace, WorkspaceName::Root) {
-                    Ok(None)
-                } else {
-                    Err(e)
+        let task_id_as_name = task_id.as_task_name();
+        if !matches!(workspace, WorkspaceName::Root) {
+            // If there was no turbo.json in the workspace the chain is empty and we
+            // fallback to the root turbo.json
+            for extended in self.extends_chain(turbo_jsons, workspace)? {
+                let turbo_json = self.turbo_json(turbo_jsons, &extended)?;
+                if turbo_json.map_or(false, |turbo_json| {
+                    turbo_json.pipeline.contains_key(&task_id_as_name)
+                        || turbo_json.pipeline.contains_key(task_name)
+                }) {
+                    return Ok(true);
                 }
-            })?;
+            }
+        }
 
-        let Some(turbo_json) = turbo_json else {
-            return self.has_task_definition(turbo_jsons, &WorkspaceName::Root, task_name, task_id);
+        let Some(root_turbo_json) = self.turbo_json(turbo_jsons, &WorkspaceName::Root)? else {
+            return Ok(false);
         };
-
-        let task_id_as_name = task_id.as_task_name();
-        if turbo_json.pipeline.contains_key(&task_id_as_name)
-            || turbo_json.pipeline.contains_key(task_name)
-        {
-            Ok(true)
-        } else if !matches!(workspace, WorkspaceName::Root) {
-            self.has_task_definition(turbo_jsons, &WorkspaceName::Root, task_name, task_id)
-        } else {
-            Ok(false)
-        }
+        Ok(root_turbo_json.pipeline.contains_key(&task_id_as_name)
+            || root_turbo_json.pipeline.contains_key(task_name))
     }
 
+    // Returns the definitions of a task that are merged together to resolve it,
+    // along with the workspace each one is from. The root turbo.json comes first,
+    // followed by the workspaces extended from, ending with the task's own.
     fn task_definition_chain(
         &self,
         turbo_jsons: &mut HashMap<WorkspaceName, TurboJson>,
         task_id: &TaskId,
         task_name: &TaskName,
-    ) -> Result<Vec<RawTaskDefinition>, Error> {
+    ) -> Result<Vec<(WorkspaceName, RawTaskDefinition)>, Error> {
         let mut task_definitions = Vec::new();
 
         let root_turbo_json = self
@@ -310,7 +333,7 @@ impl<'a> EngineBuilder<'a> {
             .ok_or(Error::Config(crate::config::Error::NoTurboJSON))?;
 
         if let Some(root_definition) = root_turbo_json.task(task_id, task_name) {
-            task_definitions.push(root_definition)
+            task_definitions.push((WorkspaceName::Root, root_definition))
         }
 
         if self.is_single {
@@ -323,27 +346,23 @@ impl<'a> EngineBuilder<'a> {
         }
 
         if task_id.package() != ROOT_PKG_NAME {
-            match self.turbo_json(turbo_jsons, &WorkspaceName::from(task_id.package())) {
-                Ok(Some(workspace_json)) => {
-                    let validation_errors = workspace_json
-                        .validate(&[validate_no_package_task_syntax, validate_extends]);
-                    if !validation_errors.is_empty() {
-                        let error_lines = validation_errors
-                            .into_iter()
-                            .map(|err| format!(" - {err}"))
-                            .join("\n");
-                        return Err(Error::Validation { error_lines });
-                    }
-
-                    if let Some(workspace_def) = workspace_json.pipeline.get(task_name) {
-                        task_definitions.push(workspace_def.clone());
-                    }
+            let workspace = WorkspaceName::from(task_id.package());
+            for extended in self.extends_chain(turbo_jsons, &workspace)? {
+                let Some(turbo_json) = self.turbo_json(turbo_jsons, &extended)? else {
+                    continue;
+                };
+                let validation_errors =
+                    turbo_json.validate(&[validate_no_package_task_syntax, validate_extends]);
+                if !validation_errors.is_empty() {
+                    let error_lines = validation_errors
+                        .into_iter()
+                        .map(|err| format!(" - {err}"))
+                        .join("\n");
+                    return Err(Error::Validation { error_lines });
                 }
-                Ok(None) => (),
-                // swallow the error where the config file doesn't exist, but bubble up other things
-                Err(e) if e.is_missing_turbo_json() => (),
-                Err(e) => {
-                    return Err(e);
+
+                if let Some(definition) = turbo_json.pipeline.get(task_name) {
+                    task_definitions.push((extended, definition.clone()));
                 }
             }
         }
@@ -358,6 +377,83 @@ impl<'a> EngineBuilder<'a> {
         Ok(task_definitions)
     }
 
+    // Resolves the workspaces whose turbo.json a workspace inherits from,
+    // starting with the furthest ancestor and ending with the workspace itself.
+    // The root is left out since its turbo.json is always the base. A workspace
+    // without a turbo.json has an empty chain.
+    fn extends_chain(
+        &self,
+        turbo_jsons: &mut HashMap<WorkspaceName, TurboJson>,
+        workspace: &WorkspaceName,
+    ) -> Result<Vec<WorkspaceName>, Error> {
+        let mut chain = Vec::new();
+        match self.collect_extends(turbo_jsons, workspace, &mut Vec::new(), &mut chain) {
+            // swallow the error where the config file doesn't exist, but bubble up other things
+            Err(e) if e.is_missing_turbo_json() => Ok(Vec::new()),
+            result => result.map(|()| chain),
+        }
+    }
+
+    fn collect_extends(
+        &self,
+        turbo_jsons: &mut HashMap<WorkspaceName, TurboJson>,
+        workspace: &WorkspaceName,
+        stack: &mut Vec<WorkspaceName>,
+        chain: &mut Vec<WorkspaceName>,
+    ) -> Result<(), Error> {
+        if let Some(start) = stack.iter().position(|ancestor| ancestor == workspace) {
+            return Err(Error::ExtendsCycle {
+                cycle: stack[start..]
+                    .iter()
+                    .chain(std::iter::once(workspace))
+                    .join(" -> "),
+            });
+        }
+        // Shared configs may be reached through more than one path, they're
+        // only applied once at their earliest position
+        if chain.contains(workspace) {
+            return Ok(());
+        }
+
+        let turbo_json = match (self.turbo_json(turbo_jsons, workspace), stack.last()) {
+            (Ok(turbo_json), _) => turbo_json,
+            (Err(e), Some(extended_by)) if e.is_missing_turbo_json() => {
+                return Err(Error::MissingExtendsTurboJson {
+                    workspace: extended_by.clone(),
+                    extends: workspace.clone(),
+                });
+            }
+            (Err(e), _) => return Err(e),
+        };
+        let Some(turbo_json) = turbo_json else {
+            return Ok(());
+        };
+
+        let extends = turbo_json.extends.clone();
+        stack.push(workspace.clone());
+        for extended in extends {
+            let extended_workspace = WorkspaceName::from(extended.as_str());
+            if matches!(extended_workspace, WorkspaceName::Root) {
+                continue;
+            }
+            if self
+                .package_graph
+                .package_json(&extended_workspace)
+                .is_none()
+            {
+                return Err(Error::MissingExtendsWorkspace {
+                    workspace: workspace.clone(),
+                    extends: extended,
+                });
+            }
+            self.collect_extends(turbo_jsons, &extended_workspace, stack, chain)?;
+        }
+        stack.pop();
+        chain.push(workspace.clone());
+
+        Ok(())
+    }
+
     fn turbo_json<'b>(
         &self,
         turbo_jsons: &'b mut HashMap<WorkspaceName, TurboJson>,
@@ -1046,6 +1142,136 @@ mod test {
         assert_eq!(all_dependencies(&engine), expected);
     }
 
+    #[test]
+    fn test_extends_shared_config() {
+        let repo_root_dir = TempDir::new("repo").unwrap();
+        let repo_root = AbsoluteSystemPathBuf::new(repo_root_dir.path().to_str().unwrap()).unwrap();
+        let package_graph = mock_package_graph(
+            &repo_root,
+            package_jsons! {
+                repo_root,
+                "a" => [],
+                "b" => [],
+                "config" => []
+            },
+        );
+        let turbo_jsons = vec![
+            (
+                WorkspaceName::Root,
+                turbo_json(json!({
+                    "pipeline": {
+                        "build": { "inputs": ["primary"] },
+                    }
+                })),
+            ),
+            (
+                WorkspaceName::from("config"),
+                turbo_json(json!({
+                    "extends": ["//"],
+                    "pipeline": {
+                        "build": { "outputs": ["dist/**"] },
+                    }
+                })),
+            ),
+            (
+                WorkspaceName::from("b"),
+                turbo_json(json!({
+                    "extends": ["//", "config"],
+                    "pipeline": {}
+                })),
+            ),
+            (
+                WorkspaceName::from("a"),
+                turbo_json(json!({
+                    "extends": ["b", "config"],
+                    "pipeline": {
+                        "build": { "inputs": ["src/**"] },
+                    }
+                })),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let engine = EngineBuilder::new(&repo_root, &package_graph, false)
+            .with_turbo_jsons(Some(turbo_jsons))
+            .with_tasks(Some(TaskName::from("build")))
+            .with_workspaces(vec![WorkspaceName::from("a")])
+            .build()
+            .unwrap();
+
+        let task_definition = engine
+            .task_definition(&TaskId::new("a", "build").into_owned())
+            .unwrap();
+        assert_eq!(task_definition.inputs, vec!["src/**".to_string()]);
+        assert_eq!(
+            task_definition.outputs.inclusions,
+            vec!["dist/**".to_string()]
+        );
+        assert_eq!(
+            task_definition.defined_in,
+            vec!["config".to_string(), "a".to_string()]
+        );
+    }
+
+    #[test_case(
+        json!({ "extends": ["b"], "pipeline": {} }),
+        json!({ "extends": ["a"], "pipeline": {} }),
+        "turbo.json \"extends\" forms a cycle: a -> b -> a"
+        ; "cycle"
+    )]
+    #[test_case(
+        json!({ "extends": ["a"], "pipeline": {} }),
+        json!({ "extends": ["//"], "pipeline": {} }),
+        "turbo.json \"extends\" forms a cycle: a -> a"
+        ; "extends itself"
+    )]
+    #[test_case(
+        json!({ "extends": ["//", "@acme/config"], "pipeline": {} }),
+        json!({ "extends": ["//"], "pipeline": {} }),
+        "turbo.json in \"a\" extends \"@acme/config\", which is not a workspace"
+        ; "missing workspace"
+    )]
+    #[test_case(
+        json!({ "extends": ["c"], "pipeline": {} }),
+        json!({ "extends": ["//"], "pipeline": {} }),
+        "turbo.json in \"a\" extends \"c\", which does not have a turbo.json"
+        ; "missing turbo json"
+    )]
+    fn test_extends_errors(a: serde_json::Value, b: serde_json::Value, expected: &str) {
+        let repo_root_dir = TempDir::new("repo").unwrap();
+        let repo_root = AbsoluteSystemPathBuf::new(repo_root_dir.path().to_str().unwrap()).unwrap();
+        let package_graph = mock_package_graph(
+            &repo_root,
+            package_jsons! {
+                repo_root,
+                "a" => [],
+                "b" => [],
+                "c" => []
+            },
+        );
+        let turbo_jsons = vec![
+            (
+                WorkspaceName::Root,
+                turbo_json(json!({
+                    "pipeline": {
+                        "build": {},
+                    }
+                })),
+            ),
+            (WorkspaceName::from("a"), turbo_json(a)),
+            (WorkspaceName::from("b"), turbo_json(b)),
+        ]
+        .into_iter()
+        .collect();
+        let result = EngineBuilder::new(&repo_root, &package_graph, false)
+            .with_turbo_jsons(Some(turbo_jsons))
+            .with_tasks(Some(TaskName::from("build")))
+            .with_workspaces(vec![WorkspaceName::from("a")])
+            .build();
+
+        assert_eq!(result.unwrap_err().to_string(), expected);
+    }
+
     #[test_case("build", None)]
     #[test_case("build:prod", None)]
     #[test_case("build$colon$prod", Some("task contains invalid string '$colon$'"))]
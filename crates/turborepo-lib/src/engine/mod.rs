@@ -144,6 +144,89 @@ impl Engine<Built> {
         &self.task_definitions
     }
 
+    /// Creates an engine with the tasks belonging to `changed_packages` and
+    /// every task that transitively depends on them. Persistent tasks are left
+    /// out since they are expected to already be running.
+    pub fn create_engine_for_subgraph(
+        &self,
+        changed_packages: &HashSet<WorkspaceName>,
+    ) -> Engine<Built> {
+        let mut affected = HashSet::new();
+        let mut stack = self
+            .task_lookup
+            .iter()
+            .filter(|(task_id, _)| {
+                changed_packages.contains(&WorkspaceName::from(task_id.package()))
+            })
+            .map(|(_, index)| *index)
+            .collect::<Vec<_>>();
+        while let Some(index) = stack.pop() {
+            if affected.insert(index) {
+                stack.extend(
+                    self.task_graph
+                        .neighbors_directed(index, petgraph::Direction::Incoming),
+                );
+            }
+        }
+
+        self.retain_tasks(|task_id| {
+            self.task_lookup
+                .get(task_id)
+                .map_or(false, |index| affected.contains(index))
+                && !self.is_persistent(task_id)
+        })
+    }
+
+    /// Creates an engine with only the persistent tasks, e.g. dev servers.
+    pub fn create_engine_for_persistent_tasks(&self) -> Engine<Built> {
+        self.retain_tasks(|task_id| self.is_persistent(task_id))
+    }
+
+    /// Creates an engine with every task that is expected to exit.
+    pub fn create_engine_without_persistent_tasks(&self) -> Engine<Built> {
+        self.retain_tasks(|task_id| !self.is_persistent(task_id))
+    }
+
+    fn is_persistent(&self, task_id: &TaskId<'static>) -> bool {
+        self.task_definitions
+            .get(task_id)
+            .map_or(false, |task_def| task_def.persistent)
+    }
+
+    // Dependencies on tasks that get filtered out are dropped, so a retained
+    // task left without dependencies gets connected to the root.
+    fn retain_tasks(&self, predicate: impl Fn(&TaskId<'static>) -> bool) -> Engine<Built> {
+        let mut engine = Engine::<Building>::new();
+        for (task_id, index) in &self.task_lookup {
+            if !predicate(task_id) {
+                continue;
+            }
+            let source = engine.get_index(task_id);
+            if let Some(definition) = self.task_definitions.get(task_id) {
+                engine.add_definition(task_id.clone(), definition.clone());
+            }
+
+            let mut has_dependencies = false;
+            for dep_index in self
+                .task_graph
+                .neighbors_directed(*index, petgraph::Direction::Outgoing)
+            {
+                let Some(TaskNode::Task(dep_id)) = self.task_graph.node_weight(dep_index) else {
+                    continue;
+                };
+                if predicate(dep_id) {
+                    let target = engine.get_index(dep_id);
+                    engine.task_graph.add_edge(source, target, ());
+                    has_dependencies = true;
+                }
+            }
+            if !has_dependencies {
+                engine.connect_to_root(task_id);
+            }
+        }
+        engine.seal()
+    }
+
     pub fn validate(
         &self,
         package_graph: &PackageGraph,
@@ -260,3 +343,87 @@ impl fmt::Display for TaskNode {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // a#build <- b#build <- c#build, with b#dev depending on a#build
+    fn engine() -> Engine {
+        let mut engine = Engine::<Building>::new();
+        let a_build = TaskId::new("a", "build").into_owned();
+        let b_build = TaskId::new("b", "build").into_owned();
+        let c_build = TaskId::new("c", "build").into_owned();
+        let b_dev = TaskId::new("b", "dev").into_owned();
+
+        engine.connect_to_root(&a_build);
+        for (task_id, dep_id) in [
+            (&b_build, &a_build),
+            (&c_build, &b_build),
+            (&b_dev, &a_build),
+        ] {
+            let source = engine.get_index(task_id);
+            let target = engine.get_index(dep_id);
+            engine.task_graph.add_edge(source, target, ());
+        }
+        for task_id in [&a_build, &b_build, &c_build] {
+            engine.add_definition(task_id.clone(), TaskDefinition::default());
+        }
+        engine.add_definition(
+            b_dev,
+            TaskDefinition {
+                persistent: true,
+                ..Default::default()
+            },
+        );
+        engine.seal()
+    }
+
+    fn task_ids(engine: &Engine) -> Vec<String> {
+        let mut task_ids = engine
+            .tasks()
+            .filter_map(|node| match node {
+                TaskNode::Task(task_id) => Some(task_id.to_string()),
+                TaskNode::Root => None,
+            })
+            .collect::<Vec<_>>();
+        task_ids.sort();
+        task_ids
+    }
+
+    #[test]
+    fn test_subgraph_includes_dependents() {
+        let engine = engine();
+        let subgraph =
+            engine.create_engine_for_subgraph(&[WorkspaceName::from("b")].into_iter().collect());
+        assert_eq!(task_ids(&subgraph), vec!["b#build", "c#build"]);
+
+        // b#build lost its dependency on a#build, so it now hangs off the root
+        let b_build = TaskId::new("b", "build").into_owned();
+        assert_eq!(
+            subgraph.dependencies(&b_build),
+            Some([&TaskNode::Root].into_iter().collect())
+        );
+    }
+
+    #[test]
+    fn test_subgraph_skips_persistent_tasks() {
+        let engine = engine();
+        let subgraph =
+            engine.create_engine_for_subgraph(&[WorkspaceName::from("a")].into_iter().collect());
+        assert_eq!(task_ids(&subgraph), vec!["a#build", "b#build", "c#build"]);
+    }
+
+    #[test]
+    fn test_split_persistent_tasks() {
+        let engine = engine();
+        assert_eq!(
+            task_ids(&engine.create_engine_for_persistent_tasks()),
+            vec!["b#dev"]
+        );
+        assert_eq!(
+            task_ids(&engine.create_engine_without_persistent_tasks()),
+            vec!["a#build", "b#build", "c#build"]
+        );
+    }
+}
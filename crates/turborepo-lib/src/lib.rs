@@ -9,17 +9,20 @@
 #![allow(clippy::needless_pass_by_ref_mut)]
 #![allow(dead_code)]
 
+mod boundaries;
 mod child;
 mod cli;
 mod commands;
 mod config;
 mod daemon;
+mod distributed;
 mod engine;
 mod execution_state;
 mod framework;
 pub(crate) mod globwatcher;
 mod hash;
 mod opts;
+mod otel;
 mod process;
 mod rewrite_json;
 mod run;
@@ -27,6 +30,8 @@ mod shim;
 mod signal;
 mod task_graph;
 mod task_hash;
+#[cfg(test)]
+mod test_utils;
 mod tracing;
 
 pub use child::spawn_child;
@@ -2,10 +2,14 @@ use std::backtrace;
 
 use thiserror::Error;
 use turbopath::AnchoredSystemPathBuf;
-use turborepo_cache::CacheOpts;
+use turborepo_cache::{fs::EvictionPolicy, ArtifactFormat, CacheOpts};
+use turborepo_ci::Capabilities;
 
 use crate::{
-    cli::{Command, DryRunMode, EnvMode, LogOrder, LogPrefix, OutputLogsMode, RunArgs},
+    cli::{
+        CacheFormat, Command, DryRunMode, EnvMode, LogOrder, LogPrefix, OutputLogsMode, Report,
+        RunArgs, UIMode,
+    },
     run::task_id::TaskId,
     Args,
 };
@@ -83,7 +87,7 @@ impl<'a> TryFrom<&'a Args> for Opts<'a> {
     type Error = self::Error;
 
     fn try_from(args: &'a Args) -> Result<Self, Self::Error> {
-        let Some(Command::Run(run_args)) = &args.command else {
+        let Some(Command::Run(run_args) | Command::Watch(run_args)) = &args.command else {
             return Err(Error::ExpectedRun);
         };
         let run_opts = RunOpts::try_from(run_args.as_ref())?;
@@ -135,9 +139,16 @@ pub struct RunOpts<'a> {
     pub(crate) single_package: bool,
     pub log_prefix: ResolvedLogPrefix,
     pub log_order: ResolvedLogOrder,
+    pub ui_mode: UIMode,
     pub summarize: Option<Option<bool>>,
+    // An empty path means the most recent run summary
+    pub(crate) explain_hash: Option<&'a str>,
+    pub(crate) workers: &'a [String],
+    pub(crate) reports: &'a [Report],
     pub(crate) experimental_space_id: Option<String>,
-    pub is_github_actions: bool,
+    // What the CI vendor's log viewer can do with turbo's output, if turbo
+    // takes advantage of it
+    pub ci: Capabilities,
 }
 
 impl<'a> RunOpts<'a> {
@@ -191,9 +202,12 @@ impl<'a> TryFrom<&'a RunArgs> for RunOpts<'a> {
             f => GraphOpts::File(f),
         });
 
-        let (is_github_actions, log_order, log_prefix) = match args.log_order {
-            LogOrder::Auto if turborepo_ci::Vendor::get_constant() == Some("GITHUB_ACTIONS") => (
-                true,
+        let ci = turborepo_ci::Vendor::infer()
+            .map(|vendor| vendor.capabilities())
+            .unwrap_or_default();
+        let (ci, log_order, log_prefix) = match args.log_order {
+            LogOrder::Auto if ci.group.is_some() => (
+                ci,
                 ResolvedLogOrder::Grouped,
                 match args.log_prefix {
                     LogPrefix::Task => ResolvedLogPrefix::Task,
@@ -201,18 +215,38 @@ impl<'a> TryFrom<&'a RunArgs> for RunOpts<'a> {
                 },
             ),
 
-            // Streaming is the default behavior except when running on GitHub Actions
-            LogOrder::Auto | LogOrder::Stream => {
-                (false, ResolvedLogOrder::Stream, args.log_prefix.into())
+            // Streaming is the default behavior except when running on a CI
+            // vendor that can collapse each task's logs into a group
+            LogOrder::Auto | LogOrder::Stream => (
+                Capabilities::default(),
+                ResolvedLogOrder::Stream,
+                args.log_prefix.into(),
+            ),
+            LogOrder::Grouped => (
+                Capabilities::default(),
+                ResolvedLogOrder::Grouped,
+                args.log_prefix.into(),
+            ),
+        };
+
+        // The dashboard needs a terminal to take over and has nothing to show
+        // for a dry run
+        let ui_mode = match args.ui {
+            UIMode::Tui if args.dry_run.is_none() && turborepo_ui::tui::is_supported() => {
+                UIMode::Tui
             }
-            LogOrder::Grouped => (false, ResolvedLogOrder::Grouped, args.log_prefix.into()),
+            UIMode::Tui | UIMode::Stream => UIMode::Stream,
         };
 
         Ok(Self {
             tasks: args.tasks.as_slice(),
             log_prefix,
             log_order,
+            ui_mode,
             summarize: args.summarize,
+            explain_hash: args.explain_hash.as_deref(),
+            workers: &args.workers,
+            reports: &args.report,
             experimental_space_id: args.experimental_space_id.clone(),
             framework_inference: args.framework_inference,
             env_mode: args.env_mode,
@@ -226,7 +260,7 @@ impl<'a> TryFrom<&'a RunArgs> for RunOpts<'a> {
             single_package: args.single_package,
             graph,
             dry_run: args.dry_run,
-            is_github_actions,
+            ci,
         })
     }
 }
@@ -348,6 +382,14 @@ impl<'a> From<&'a RunArgs> for CacheOpts<'a> {
             skip_filesystem: run_args.remote_only,
             remote_cache_read_only: run_args.remote_cache_read_only,
             workers: run_args.cache_workers,
+            eviction_policy: EvictionPolicy {
+                max_size: run_args.cache_max_size,
+                max_age: run_args.cache_max_age,
+            },
+            artifact_format: match run_args.cache_format {
+                Some(CacheFormat::ContentAddressed) => ArtifactFormat::ContentAddressed,
+                Some(CacheFormat::Tarball) | None => ArtifactFormat::Tarball,
+            },
             ..CacheOpts::default()
         }
     }
@@ -355,9 +397,9 @@ impl<'a> From<&'a RunArgs> for CacheOpts<'a> {
 
 impl<'a> RunOpts<'a> {
     pub fn should_redirect_stderr_to_stdout(&self) -> bool {
-        // If we're running on Github Actions, force everything to stdout
-        // so as not to have out-of-order log lines
-        matches!(self.log_order, ResolvedLogOrder::Grouped) && self.is_github_actions
+        // If task logs are grouped by the CI vendor, force everything to
+        // stdout so as not to have out-of-order log lines
+        matches!(self.log_order, ResolvedLogOrder::Grouped) && self.ci.group.is_some()
     }
 }
 
@@ -378,7 +420,7 @@ mod test {
 
     use super::{LegacyFilter, RunOpts};
     use crate::{
-        cli::DryRunMode,
+        cli::{DryRunMode, UIMode},
         opts::{Opts, RunCacheOpts, ScopeOpts},
     };
 
@@ -529,9 +571,13 @@ mod test {
             single_package: false,
             log_prefix: crate::opts::ResolvedLogPrefix::Task,
             log_order: crate::opts::ResolvedLogOrder::Stream,
+            ui_mode: UIMode::Stream,
             summarize: None,
+            explain_hash: None,
+            workers: &[],
+            reports: &[],
             experimental_space_id: None,
-            is_github_actions: false,
+            ci: Default::default(),
         };
         let cache_opts = CacheOpts::default();
         let runcache_opts = RunCacheOpts::default();
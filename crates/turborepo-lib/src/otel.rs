@@ -0,0 +1,196 @@
+//! Exports traces of `turbo run` to an OpenTelemetry collector over
+//! OTLP/HTTP. A run is a single trace, with a span for each task and spans
+//! for the cache lookups made by that task nested under it.
+use std::collections::BTreeMap;
+
+use opentelemetry::{trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::{SpanExporterBuilder, WithExportConfig};
+use opentelemetry_sdk::{
+    runtime,
+    trace::{self, Tracer, TracerProvider},
+    Resource,
+};
+use thiserror::Error;
+use tracing::Level;
+use tracing_subscriber::filter::Targets;
+
+use crate::{config::OtelJson, get_version};
+
+pub const RUN_TARGET: &str = "turbo::run";
+pub const TASK_TARGET: &str = "turbo::task";
+// Cache spans are created by `turborepo-cache` with a `turbo::cache` target,
+// so exported spans are picked by target rather than by module
+const TARGET_PREFIX: &str = "turbo::";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid OpenTelemetry endpoint {0}: {1}")]
+    InvalidEndpoint(String, url::ParseError),
+    #[error("failed to create OpenTelemetry exporter: {0}")]
+    Exporter(#[from] opentelemetry::trace::TraceError),
+}
+
+/// Where traces are sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExporterConfig {
+    /// The base URL of the collector's OTLP/HTTP receiver. Traces are sent to
+    /// `/v1/traces` under it.
+    pub endpoint: String,
+    pub headers: BTreeMap<String, String>,
+}
+
+impl ExporterConfig {
+    /// `--otel-endpoint` takes precedence over the endpoint in turbo.json,
+    /// but headers always come from turbo.json
+    pub fn resolve(endpoint_flag: Option<&str>, turbo_json: Option<&OtelJson>) -> Option<Self> {
+        let endpoint = endpoint_flag
+            .map(str::to_string)
+            .or_else(|| turbo_json.and_then(|otel| otel.endpoint.clone()))?;
+        let headers = turbo_json
+            .map(|otel| otel.headers.clone())
+            .unwrap_or_default();
+
+        Some(Self { endpoint, headers })
+    }
+}
+
+pub fn tracer_provider(config: &ExporterConfig) -> Result<TracerProvider, Error> {
+    url::Url::parse(&config.endpoint)
+        .map_err(|e| Error::InvalidEndpoint(config.endpoint.clone(), e))?;
+    // The HTTP exporter sends spans to the endpoint as is, so the traces path
+    // has to be part of it
+    let endpoint = format!("{}/v1/traces", config.endpoint.trim_end_matches('/'));
+    let exporter = SpanExporterBuilder::from(
+        opentelemetry_otlp::new_exporter()
+            .http()
+            .with_endpoint(endpoint)
+            .with_headers(config.headers.clone().into_iter().collect()),
+    )
+    .build_span_exporter()?;
+
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_config(trace::config().with_resource(Resource::new([
+            KeyValue::new("service.name", "turbo"),
+            KeyValue::new("service.version", get_version()),
+        ])))
+        .build())
+}
+
+pub fn tracer(provider: &TracerProvider) -> Tracer {
+    provider.tracer("turbo")
+}
+
+/// Only the run, task, and cache spans are exported. Everything else turbo
+/// traces is too fine grained to be useful in a collector.
+pub fn filter() -> Targets {
+    Targets::new().with_target(TARGET_PREFIX, Level::INFO)
+}
+
+/// Sends every span that has ended but hasn't been exported yet.
+pub async fn flush(provider: TracerProvider) {
+    // Flushing blocks until the batch exporter, which runs on the runtime,
+    // is done, so it can't block a runtime thread
+    let _ = tokio::task::spawn_blocking(move || provider.force_flush()).await;
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::TcpListener;
+
+    use anyhow::Result;
+    use axum::{body::Bytes, http::HeaderMap, routing::post, Router};
+    use tokio::sync::mpsc;
+    use tracing_subscriber::{layer::SubscriberExt, Layer, Registry};
+
+    use super::*;
+
+    // Stands in for a collector, passing along each export it receives
+    fn start_collector() -> Result<(u16, mpsc::UnboundedReceiver<(HeaderMap, Bytes)>)> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let app = Router::new().route(
+            "/v1/traces",
+            post(move |headers: HeaderMap, body: Bytes| {
+                let sender = sender.clone();
+                async move {
+                    sender.send((headers, body)).ok();
+                }
+            }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let server = axum::Server::from_tcp(listener)?.serve(app.into_make_service());
+        tokio::spawn(server);
+
+        Ok((port, receiver))
+    }
+
+    fn contains(haystack: &[u8], needle: &str) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle.as_bytes())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_exports_run_and_task_spans() -> Result<()> {
+        let (port, mut exports) = start_collector()?;
+        let provider = tracer_provider(&ExporterConfig {
+            endpoint: format!("http://127.0.0.1:{port}"),
+            headers: [("x-collector-token".to_string(), "secret".to_string())].into(),
+        })?;
+        let subscriber = Registry::default().with(
+            tracing_opentelemetry::layer()
+                .with_tracer(tracer(&provider))
+                .with_filter(filter()),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            let run = tracing::info_span!(target: RUN_TARGET, "run");
+            let _run = run.enter();
+            let task = tracing::info_span!(
+                target: TASK_TARGET,
+                "task",
+                "turbo.task.hash" = "0123abcd",
+                "turbo.task.exit_code" = tracing::field::Empty,
+            );
+            task.record("turbo.task.exit_code", 1);
+            let _internal = tracing::info_span!("internal_bookkeeping");
+        });
+        flush(provider).await;
+
+        let (headers, body) = exports.recv().await.expect("spans were exported");
+        assert_eq!(headers["x-collector-token"], "secret");
+        assert!(contains(&body, "0123abcd"));
+        assert!(contains(&body, "turbo.task.exit_code"));
+        assert!(!contains(&body, "internal_bookkeeping"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_flag_overrides_turbo_json_endpoint() {
+        let turbo_json = OtelJson {
+            endpoint: Some("http://collector:4318".to_string()),
+            headers: [("authorization".to_string(), "token".to_string())].into(),
+        };
+
+        let config = ExporterConfig::resolve(Some("http://localhost:4318"), Some(&turbo_json))
+            .expect("endpoint is set");
+        assert_eq!(config.endpoint, "http://localhost:4318");
+        assert_eq!(config.headers, turbo_json.headers);
+        assert_eq!(
+            ExporterConfig::resolve(None, Some(&turbo_json)).map(|config| config.endpoint),
+            turbo_json.endpoint
+        );
+        assert_eq!(ExporterConfig::resolve(None, None), None);
+    }
+
+    #[test]
+    fn test_invalid_endpoint() {
+        let result = tracer_provider(&ExporterConfig {
+            endpoint: "not a url".to_string(),
+            headers: BTreeMap::new(),
+        });
+        assert!(matches!(result, Err(Error::InvalidEndpoint(..))));
+    }
+}
@@ -70,6 +70,11 @@ impl ProcessManager {
         Some(child)
     }
 
+    #[cfg(test)]
+    pub fn children(&self) -> Vec<Child> {
+        self.0.lock().unwrap().children.clone()
+    }
+
     /// Stop the process manager, closing all child processes. On posix
     /// systems this will send a SIGINT, and on windows it will just kill
     /// the process immediately.
@@ -365,4 +365,31 @@ impl TaskCache {
     pub fn expanded_outputs(&self) -> &[AnchoredSystemPathBuf] {
         &self.expanded_outputs
     }
+
+    /// Whether outputs are both written to and read from the cache. Tasks
+    /// that run on a worker hand their outputs back through it.
+    pub fn is_shared(&self) -> bool {
+        !(self.caching_disabled || self.run_cache.reads_disabled || self.run_cache.writes_disabled)
+    }
+
+    pub fn outputs(&self) -> &TaskOutputs {
+        &self.repo_relative_globs
+    }
+
+    /// Restores the outputs a worker cached after running this task. Unlike
+    /// `restore_outputs` the logs aren't replayed, as they were already
+    /// streamed while the task ran.
+    pub async fn fetch_outputs(&mut self) -> Result<bool, Error> {
+        let Some((_, restored_files)) = self
+            .run_cache
+            .cache
+            .fetch(&self.run_cache.repo_root, &self.hash)
+            .await?
+        else {
+            return Ok(false);
+        };
+        self.expanded_outputs = restored_files;
+
+        Ok(true)
+    }
 }
@@ -4,7 +4,7 @@ use turborepo_repository::package_graph;
 
 use super::graph_visualizer;
 use crate::{
-    config, daemon, engine, opts,
+    boundaries, config, daemon, distributed, engine, opts,
     run::{global_hash, scope},
     task_graph, task_hash,
 };
@@ -13,6 +13,10 @@ use crate::{
 pub enum Error {
     #[error("error preparing engine: Invalid persistent task configuration:\n{0}")]
     EngineValidation(String),
+    #[error("boundary violations found, run `turbo boundaries` for details:\n{0}")]
+    BoundaryViolations(String),
+    #[error(transparent)]
+    Boundaries(#[from] boundaries::Error),
     #[error(transparent)]
     Graph(#[from] graph_visualizer::Error),
     #[error(transparent)]
@@ -32,17 +36,29 @@ pub enum Error {
     #[error(transparent)]
     DaemonConnector(#[from] daemon::DaemonConnectorError),
     #[error(transparent)]
+    Daemon(#[from] daemon::DaemonError),
+    #[error("`turbo watch` needs the daemon for file events, but it could not be started")]
+    WatchRequiresDaemon,
+    #[error(transparent)]
     Cache(#[from] turborepo_cache::CacheError),
     #[error(transparent)]
     Path(#[from] turbopath::PathError),
     #[error(transparent)]
     Scope(#[from] scope::ResolutionError),
     #[error(transparent)]
+    ChangeDetect(#[from] scope::change_detector::ChangeDetectError),
+    #[error(transparent)]
     GlobalHash(#[from] global_hash::Error),
     #[error(transparent)]
     TaskHash(#[from] task_hash::Error),
     #[error(transparent)]
     Visitor(#[from] task_graph::VisitorError),
+    #[error(transparent)]
+    Tui(#[from] turborepo_ui::tui::Error),
+    #[error("terminal UI stopped unexpectedly: {0}")]
+    TuiTask(#[from] tokio::task::JoinError),
+    #[error(transparent)]
+    Workers(#[from] distributed::Error),
     #[error("error registering signal handler: {0}")]
     SignalHandler(std::io::Error),
 }
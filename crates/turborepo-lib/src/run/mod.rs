@@ -5,14 +5,15 @@ mod error;
 pub(crate) mod global_hash;
 mod graph_visualizer;
 pub(crate) mod package_discovery;
-mod scope;
+pub(crate) mod scope;
 pub(crate) mod summary;
 pub mod task_id;
+mod watch;
 
 use std::{
     collections::HashSet,
     io::{IsTerminal, Write},
-    sync::Arc,
+    sync::{Arc, RwLock},
     time::SystemTime,
 };
 
@@ -20,10 +21,10 @@ pub use cache::{RunCache, TaskCache};
 use chrono::{DateTime, Local};
 use itertools::Itertools;
 use rayon::iter::ParallelBridge;
-use tracing::debug;
+use tracing::{debug, field, Instrument};
 use turborepo_analytics::{start_analytics, AnalyticsHandle, AnalyticsSender};
 use turborepo_api_client::{APIAuth, APIClient};
-use turborepo_cache::{AsyncCache, RemoteCacheOpts};
+use turborepo_cache::{AsyncCache, RemoteCacheBackend, RemoteCacheOpts};
 use turborepo_ci::Vendor;
 use turborepo_env::EnvironmentVariableMap;
 use turborepo_repository::{
@@ -32,19 +33,32 @@ use turborepo_repository::{
     package_json::PackageJson,
 };
 use turborepo_scm::SCM;
-use turborepo_ui::{cprint, cprintln, ColorSelector, BOLD_GREY, GREY};
+use turborepo_ui::{
+    cprint, cprintln,
+    tui::{self, AppExit, AppSender},
+    ColorSelector, BOLD_GREY, GREY,
+};
 
-use self::task_id::TaskName;
+use self::{
+    task_id::TaskName,
+    watch::{WatchRun, WatchState},
+};
 pub use crate::run::error::Error;
 use crate::{
-    cli::{DryRunMode, EnvMode},
+    boundaries,
+    cli::{DryRunMode, EnvMode, UIMode},
     commands::CommandBase,
     config::TurboJson,
     daemon::DaemonConnector,
+    distributed::{WorkerPool, WorkerToken},
     engine::{Engine, EngineBuilder},
     opts::Opts,
+    otel,
     process::ProcessManager,
-    run::{global_hash::get_global_hash_inputs, summary::RunTracker},
+    run::{
+        global_hash::{get_global_hash_inputs, GlobalHashableInputs},
+        summary::RunTracker,
+    },
     shim::TurboState,
     signal::{SignalHandler, SignalSubscriber},
     task_graph::Visitor,
@@ -55,12 +69,24 @@ use crate::{
 pub struct Run<'a> {
     base: &'a CommandBase,
     processes: ProcessManager,
+    watch: bool,
 }
 
 impl<'a> Run<'a> {
     pub fn new(base: &'a CommandBase) -> Self {
         let processes = ProcessManager::new();
-        Self { base, processes }
+        Self {
+            base,
+            processes,
+            watch: false,
+        }
+    }
+
+    /// Keep the run alive after the initial execution, re-running tasks as
+    /// files change.
+    pub fn with_watch(mut self) -> Self {
+        self.watch = true;
+        self
     }
 
     fn connect_process_manager(&self, signal_subscriber: SignalSubscriber) {
@@ -177,7 +203,12 @@ impl<'a> Run<'a> {
         let is_linked = api_auth
             .as_ref()
             .map_or(false, |api_auth| api_auth.is_linked());
-        if !is_linked {
+        // Self-hosted backends don't need the repo to be linked to Vercel
+        let remote_cache_backend = config.backend();
+        let needs_link = matches!(remote_cache_backend, RemoteCacheBackend::Vercel);
+        opts.cache_opts.remote_cache_backend = remote_cache_backend;
+        opts.cache_opts.remote_cache_timeout = config.timeout();
+        if !is_linked && needs_link {
             opts.cache_opts.skip_remote = true;
         } else if let Some(enabled) = config.enabled {
             // We're linked, but if the user has explicitly enabled or disabled, use that
@@ -192,7 +223,13 @@ impl<'a> Run<'a> {
 
         let is_ci_or_not_tty = turborepo_ci::is_ci() || !std::io::stdout().is_terminal();
 
-        let daemon = match (is_ci_or_not_tty, opts.run_opts.daemon) {
+        // Watch mode gets its file events from the daemon, so it always connects
+        let use_daemon = if self.watch {
+            Some(true)
+        } else {
+            opts.run_opts.daemon
+        };
+        let daemon = match (is_ci_or_not_tty, use_daemon) {
             (true, None) => {
                 debug!("skipping turbod since we appear to be in a non-interactive context");
                 None
@@ -222,19 +259,13 @@ impl<'a> Run<'a> {
             }
         };
 
-        let mut pkg_dep_graph =
-            PackageGraph::builder(&self.base.repo_root, root_package_json.clone())
-                .with_single_package_mode(opts.run_opts.single_package)
-                .with_package_discovery(
-                    LocalPackageDiscoveryBuilder::new(
-                        self.base.repo_root.clone(),
-                        None,
-                        Some(root_package_json.clone()),
-                    )
-                    .build()?,
-                )
-                .build()
-                .await?;
+        let watch_daemon = match (self.watch, &daemon) {
+            (false, _) => None,
+            (true, Some(client)) => Some(client.clone()),
+            (true, None) => return Err(Error::WatchRequiresDaemon),
+        };
+
+        let mut pkg_dep_graph = self.build_package_graph(&opts, &root_package_json).await?;
 
         let root_turbo_json =
             TurboJson::load(&self.base.repo_root, &root_package_json, is_single_package)?;
@@ -261,31 +292,24 @@ impl<'a> Run<'a> {
 
         let scm = SCM::new(&self.base.repo_root);
 
-        let filtered_pkgs = {
-            let (mut filtered_pkgs, is_all_packages) = scope::resolve_packages(
-                &opts.scope_opts,
-                &self.base.repo_root,
-                &pkg_dep_graph,
-                &scm,
-            )?;
-
-            if is_all_packages {
-                for target in self.targets() {
-                    let mut task_name = TaskName::from(target.as_str());
-                    // If it's not a package task, we convert to a root task
-                    if !task_name.is_package_task() {
-                        task_name = task_name.into_root_task()
-                    }
-
-                    if root_turbo_json.pipeline.contains_key(&task_name) {
-                        filtered_pkgs.insert(WorkspaceName::Root);
-                        break;
-                    }
-                }
-            };
+        if root_turbo_json
+            .boundaries
+            .as_ref()
+            .map_or(false, |boundaries| boundaries.check_before_run)
+        {
+            let violations =
+                boundaries::check(&self.base.repo_root, &pkg_dep_graph, &root_turbo_json, &scm)?;
+            if !violations.is_empty() {
+                return Err(Error::BoundaryViolations(
+                    violations
+                        .iter()
+                        .map(|violation| format!(" - {violation}"))
+                        .join("\n"),
+                ));
+            }
+        }
 
-            filtered_pkgs
-        };
+        let filtered_pkgs = self.resolve_packages(&opts, &pkg_dep_graph, &scm, &root_turbo_json)?;
 
         let env_at_execution_start = EnvironmentVariableMap::infer();
 
@@ -387,15 +411,28 @@ impl<'a> Run<'a> {
         let pkg_dep_graph = Arc::new(pkg_dep_graph);
         let engine = Arc::new(engine);
 
-        let global_env = {
-            let mut env = env_at_execution_start
-                .from_wildcards(global_hash_inputs.pass_through_env.unwrap_or_default())
-                .map_err(Error::Env)?;
-            if let Some(resolved_global) = &global_hash_inputs.resolved_env_vars {
-                env.union(&resolved_global.all);
-            }
-            env
-        };
+        let global_env = global_env(&env_at_execution_start, &global_hash_inputs)?;
+
+        if let Some(daemon) = watch_daemon {
+            let watch_run = WatchRun {
+                run: self,
+                opts: &opts,
+                scm: &scm,
+                root_turbo_json: RwLock::new(root_turbo_json),
+                runcache,
+                env_at_execution_start: &env_at_execution_start,
+                global_env_mode,
+                api_client,
+                api_auth,
+            };
+            let state = WatchState {
+                pkg_dep_graph,
+                engine,
+                global_hash,
+                global_env,
+            };
+            return watch_run.watch(daemon, state).await;
+        }
 
         let run_tracker = RunTracker::new(
             start_at,
@@ -425,16 +462,55 @@ impl<'a> Run<'a> {
             &self.base.repo_root,
             global_env,
         );
+        visitor.load_dot_env(&root_turbo_json)?;
 
         if opts.run_opts.dry_run.is_some() {
             visitor.dry_run();
+        } else if !opts.run_opts.workers.is_empty() {
+            let token = WorkerToken::from_env()?;
+            visitor.use_workers(WorkerPool::connect(opts.run_opts.workers, token).await?);
         }
 
+        let dashboard = (opts.run_opts.ui_mode == UIMode::Tui).then(|| {
+            let (sender, receiver) = AppSender::new();
+            let tasks = visitor.tui_tasks(&engine);
+            visitor.use_tui(sender.clone());
+            let signal_handler = signal_handler.clone();
+            let handle = tokio::spawn(async move {
+                let exit = tokio::task::spawn_blocking(move || tui::run_app(tasks, receiver)).await;
+                // The terminal is in raw mode, so Ctrl-C arrives as a key press
+                // rather than a signal
+                if matches!(exit, Ok(Ok(AppExit::Interrupted))) {
+                    signal_handler.close().await;
+                }
+                Ok::<_, Error>(exit??)
+            });
+            (sender, handle)
+        });
+
         // we look for this log line to mark the start of the run
         // in benchmarks, so please don't remove it
         debug!("running visitor");
 
-        let errors = visitor.visit(engine.clone()).await?;
+        // Exported as the root of the run's trace when exporting to an
+        // OpenTelemetry collector
+        let run_span = tracing::info_span!(
+            target: otel::RUN_TARGET,
+            "run",
+            "turbo.global_hash" = %global_hash,
+            "turbo.exit_code" = field::Empty,
+            "otel.status_code" = field::Empty,
+        );
+        let visit_result = visitor
+            .visit(engine.clone())
+            .instrument(run_span.clone())
+            .await;
+        // Give the terminal back before reporting anything
+        if let Some((sender, handle)) = dashboard {
+            sender.stop();
+            handle.await??;
+        }
+        let errors = visit_result?;
 
         let exit_code = errors
             .iter()
@@ -442,14 +518,15 @@ impl<'a> Run<'a> {
             .max()
             // We hit some error, it shouldn't be exit code 0
             .unwrap_or(if errors.is_empty() { 0 } else { 1 });
+        run_span.record("turbo.exit_code", exit_code);
+        if exit_code != 0 {
+            run_span.record("otel.status_code", "ERROR");
+        }
 
-        let error_prefix = if opts.run_opts.is_github_actions {
-            "::error::"
-        } else {
-            ""
-        };
         for err in &errors {
-            writeln!(std::io::stderr(), "{error_prefix}{err}").ok();
+            let err = err.to_string();
+            let line = opts.run_opts.ci.error_annotation(&err).unwrap_or(err);
+            writeln!(std::io::stderr(), "{line}").ok();
         }
 
         visitor
@@ -465,6 +542,55 @@ impl<'a> Run<'a> {
         Ok(exit_code)
     }
 
+    async fn build_package_graph(
+        &self,
+        opts: &Opts<'_>,
+        root_package_json: &PackageJson,
+    ) -> Result<PackageGraph, Error> {
+        Ok(
+            PackageGraph::builder(&self.base.repo_root, root_package_json.clone())
+                .with_single_package_mode(opts.run_opts.single_package)
+                .with_package_discovery(
+                    LocalPackageDiscoveryBuilder::new(
+                        self.base.repo_root.clone(),
+                        None,
+                        Some(root_package_json.clone()),
+                    )
+                    .build()?,
+                )
+                .build()
+                .await?,
+        )
+    }
+
+    fn resolve_packages(
+        &self,
+        opts: &Opts,
+        pkg_dep_graph: &PackageGraph,
+        scm: &SCM,
+        root_turbo_json: &TurboJson,
+    ) -> Result<HashSet<WorkspaceName>, Error> {
+        let (mut filtered_pkgs, is_all_packages) =
+            scope::resolve_packages(&opts.scope_opts, &self.base.repo_root, pkg_dep_graph, scm)?;
+
+        if is_all_packages {
+            for target in self.targets() {
+                let mut task_name = TaskName::from(target.as_str());
+                // If it's not a package task, we convert to a root task
+                if !task_name.is_package_task() {
+                    task_name = task_name.into_root_task()
+                }
+
+                if root_turbo_json.pipeline.contains_key(&task_name) {
+                    filtered_pkgs.insert(WorkspaceName::Root);
+                    break;
+                }
+            }
+        };
+
+        Ok(filtered_pkgs)
+    }
+
     fn build_engine(
         &self,
         pkg_dep_graph: &PackageGraph,
@@ -510,3 +636,18 @@ impl<'a> Run<'a> {
         Ok(engine)
     }
 }
+
+// The environment every task starts with: the global pass through variables
+// along with the variables that make up the global hash
+fn global_env(
+    env_at_execution_start: &EnvironmentVariableMap,
+    global_hash_inputs: &GlobalHashableInputs,
+) -> Result<EnvironmentVariableMap, Error> {
+    let mut env = env_at_execution_start
+        .from_wildcards(global_hash_inputs.pass_through_env.unwrap_or_default())
+        .map_err(Error::Env)?;
+    if let Some(resolved_global) = &global_hash_inputs.resolved_env_vars {
+        env.union(&resolved_global.all);
+    }
+    Ok(env)
+}
@@ -1,10 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
+use serde::Serialize;
 use turbopath::{AbsoluteSystemPath, AnchoredSystemPath, AnchoredSystemPathBuf};
 use turborepo_repository::package_graph::{ChangedPackagesError, PackageGraph, WorkspaceName};
 use turborepo_scm::SCM;
 use wax::Pattern;
 
+use crate::config::TurboJson;
+
 pub trait PackageChangeDetector {
     /// Get the list of changed packages between two refs.
     fn changed_packages(
@@ -14,6 +17,44 @@ pub trait PackageChangeDetector {
     ) -> Result<HashSet<WorkspaceName>, ChangeDetectError>;
 }
 
+/// Why a package is considered changed. Variants are ordered from the most to
+/// the least specific so a package with several reasons reports the first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::enum_variant_names)]
+pub enum PackageChangeReason {
+    /// A global dependency such as the root `turbo.json` changed
+    GlobalDepChange,
+    /// A file inside the package changed
+    DirectChange,
+    /// The `turbo.json` of a workspace the package extends changed
+    ExtendedConfigChange,
+    /// The package's external dependencies changed in the lockfile
+    LockfileChange,
+    /// A package it depends on changed
+    DependencyChange,
+}
+
+impl PackageChangeReason {
+    /// Records `reason` for `package`, keeping any more specific reason
+    pub fn insert(
+        packages: &mut HashMap<WorkspaceName, PackageChangeReason>,
+        package: WorkspaceName,
+        reason: PackageChangeReason,
+    ) {
+        packages
+            .entry(package)
+            .and_modify(|existing| *existing = (*existing).min(reason))
+            .or_insert(reason);
+    }
+}
+
+/// The files that changed between two refs and the packages they change
+pub struct Changes {
+    pub files: HashSet<AnchoredSystemPathBuf>,
+    pub packages: HashMap<WorkspaceName, PackageChangeReason>,
+}
+
 pub struct SCMChangeDetector<'a> {
     turbo_root: &'a AbsoluteSystemPath,
 
@@ -30,6 +71,37 @@ impl<'a> PackageChangeDetector for SCMChangeDetector<'a> {
         from_ref: &str,
         to_ref: &str,
     ) -> Result<HashSet<WorkspaceName>, ChangeDetectError> {
+        Ok(self
+            .changes(from_ref, to_ref)?
+            .packages
+            .into_keys()
+            .collect())
+    }
+}
+
+impl<'a> SCMChangeDetector<'a> {
+    const DEFAULT_GLOBAL_DEPS: [&'static str; 2] = ["package.json", "turbo.json"];
+
+    pub fn new(
+        turbo_root: &'a AbsoluteSystemPath,
+
+        scm: &'a SCM,
+        pkg_graph: &'a PackageGraph,
+        global_deps: Vec<String>,
+        ignore_patterns: Vec<String>,
+    ) -> Self {
+        Self {
+            turbo_root,
+            scm,
+            pkg_graph,
+            global_deps,
+            ignore_patterns,
+        }
+    }
+
+    /// Get the files changed between two refs along with the packages they
+    /// change and why. Dependents of those packages are not included.
+    pub fn changes(&self, from_ref: &str, to_ref: &str) -> Result<Changes, ChangeDetectError> {
         let mut changed_files = HashSet::new();
         if !from_ref.is_empty() {
             changed_files = self
@@ -41,51 +113,143 @@ impl<'a> PackageChangeDetector for SCMChangeDetector<'a> {
             self.repo_global_file_has_changed(&Self::DEFAULT_GLOBAL_DEPS, &changed_files)?;
 
         if global_change {
-            return Ok(self
-                .pkg_graph
-                .workspaces()
-                .map(|(n, _)| n.to_owned())
-                .collect());
+            let packages = self.all_packages(PackageChangeReason::GlobalDepChange);
+            return Ok(Changes {
+                files: changed_files,
+                packages,
+            });
         }
 
         // get filtered files and add the packages that contain them
         let filtered_changed_files = self.filter_ignored_files(changed_files.iter())?;
-        let mut changed_pkgs =
-            self.get_changed_packages(filtered_changed_files.into_iter(), self.pkg_graph)?;
+        let extending_packages = self.extending_packages(filtered_changed_files.iter().copied())?;
+        let mut packages = self
+            .get_changed_packages(filtered_changed_files.into_iter(), self.pkg_graph)?
+            .into_iter()
+            .map(|package| (package, PackageChangeReason::DirectChange))
+            .collect::<HashMap<_, _>>();
+        for package in extending_packages {
+            PackageChangeReason::insert(
+                &mut packages,
+                package,
+                PackageChangeReason::ExtendedConfigChange,
+            );
+        }
 
         // if we run into issues, don't error, just assume all pacakges have changed
-        let lockfile_changes = self.get_changes_from_lockfile(&changed_files, from_ref);
+        let lockfile_changes = self
+            .get_changes_from_lockfile(&changed_files, from_ref)
+            .unwrap_or_else(|_| {
+                self.pkg_graph
+                    .workspaces()
+                    .map(|(n, _)| n.to_owned())
+                    .collect()
+            });
+        for package in lockfile_changes {
+            PackageChangeReason::insert(
+                &mut packages,
+                package,
+                PackageChangeReason::LockfileChange,
+            );
+        }
+
+        Ok(Changes {
+            files: changed_files,
+            packages,
+        })
+    }
 
-        if let Ok(lockfile_changes) = lockfile_changes {
-            changed_pkgs.extend(lockfile_changes);
-        } else {
+    fn all_packages(
+        &self,
+        reason: PackageChangeReason,
+    ) -> HashMap<WorkspaceName, PackageChangeReason> {
+        self.pkg_graph
+            .workspaces()
+            .map(|(n, _)| (n.to_owned(), reason))
+            .collect()
+    }
+
+    /// Get the packages containing the given files. There is no ref to diff
+    /// the lockfile against, so any lockfile change marks every package as
+    /// changed.
+    pub fn changed_packages_for_files(
+        &self,
+        changed_files: &HashSet<AnchoredSystemPathBuf>,
+    ) -> Result<HashSet<WorkspaceName>, ChangeDetectError> {
+        let filtered_changed_files = self
+            .filter_ignored_files(changed_files.iter())?
+            .into_iter()
+            .cloned()
+            .collect::<HashSet<_>>();
+        let lockfile_path = self
+            .pkg_graph
+            .package_manager()
+            .lockfile_path(self.turbo_root);
+
+        if self.repo_global_file_has_changed(&Self::DEFAULT_GLOBAL_DEPS, &filtered_changed_files)?
+            || Self::lockfile_changed(self.turbo_root, &filtered_changed_files, &lockfile_path)?
+        {
             return Ok(self
                 .pkg_graph
                 .workspaces()
                 .map(|(n, _)| n.to_owned())
                 .collect());
         }
-        Ok(changed_pkgs)
+
+        let mut packages =
+            self.get_changed_packages(filtered_changed_files.iter(), self.pkg_graph)?;
+        packages.extend(self.extending_packages(&filtered_changed_files)?);
+        Ok(packages)
     }
-}
 
-impl<'a> SCMChangeDetector<'a> {
-    const DEFAULT_GLOBAL_DEPS: [&'static str; 2] = ["package.json", "turbo.json"];
+    /// Get the packages whose `turbo.json` extends, directly or through
+    /// another workspace, a workspace whose `turbo.json` is among the changed
+    /// files. Their task definitions change even though none of their own
+    /// files did.
+    fn extending_packages<'b>(
+        &self,
+        changed_files: impl IntoIterator<Item = &'b AnchoredSystemPathBuf>,
+    ) -> Result<HashSet<WorkspaceName>, ChangeDetectError> {
+        let changed_files = changed_files.into_iter().collect::<HashSet<_>>();
+        let mut changed_configs = self
+            .pkg_graph
+            .workspaces()
+            .filter(|(name, _)| !matches!(name, WorkspaceName::Root))
+            .filter(|(_, info)| {
+                info.package_json_path
+                    .parent()
+                    .map(|dir| dir.join_component("turbo.json"))
+                    .map_or(false, |turbo_json| changed_files.contains(&turbo_json))
+            })
+            .map(|(name, _)| name.to_owned())
+            .collect::<HashSet<_>>();
+        if changed_configs.is_empty() {
+            return Ok(HashSet::new());
+        }
 
-    pub fn new(
-        turbo_root: &'a AbsoluteSystemPath,
+        let mut extends = HashMap::new();
+        for (name, _) in self.pkg_graph.workspaces() {
+            extends.insert(
+                name.to_owned(),
+                TurboJson::workspace_extends(self.turbo_root, self.pkg_graph, name)?,
+            );
+        }
 
-        scm: &'a SCM,
-        pkg_graph: &'a PackageGraph,
-        global_deps: Vec<String>,
-        ignore_patterns: Vec<String>,
-    ) -> Self {
-        Self {
-            turbo_root,
-            scm,
-            pkg_graph,
-            global_deps,
-            ignore_patterns,
+        let mut extending = HashSet::new();
+        loop {
+            let newly_changed = extends
+                .iter()
+                .filter(|(name, _)| !changed_configs.contains(*name))
+                .filter(|(_, extended)| extended.iter().any(|e| changed_configs.contains(e)))
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            if newly_changed.is_empty() {
+                return Ok(extending);
+            }
+            for name in newly_changed {
+                changed_configs.insert(name.clone());
+                extending.insert(name);
+            }
         }
     }
 
@@ -201,6 +365,8 @@ pub enum ChangeDetectError {
     NoLockfile,
     #[error("Lockfile error: {0}")]
     Lockfile(turborepo_lockfiles::Error),
+    #[error(transparent)]
+    Config(#[from] crate::config::Error),
 }
 
 impl From<ChangedPackagesError> for ChangeDetectError {
@@ -214,9 +380,108 @@ impl From<ChangedPackagesError> for ChangeDetectError {
 
 #[cfg(test)]
 mod test {
+    use std::collections::{HashMap, HashSet};
+
+    use tempfile::TempDir;
     use test_case::test_case;
+    use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
+    use turborepo_repository::{
+        discovery::{DiscoveryResponse, PackageDiscovery},
+        package_graph::{PackageGraph, WorkspaceName},
+        package_json::PackageJson,
+        package_manager::PackageManager,
+    };
+    use turborepo_scm::SCM;
+
+    use super::{ChangeDetectError, PackageChangeReason, SCMChangeDetector};
+
+    struct MockDiscovery;
+    impl PackageDiscovery for MockDiscovery {
+        async fn discover_packages(
+            &mut self,
+        ) -> Result<DiscoveryResponse, turborepo_repository::discovery::Error> {
+            Ok(DiscoveryResponse {
+                package_manager: PackageManager::Npm,
+                workspaces: vec![], // we don't care about this
+            })
+        }
+    }
+
+    #[test_case("packages/a/turbo.json", &["a", "b", "c"] ; "extended config")]
+    #[test_case("packages/b/turbo.json", &["b", "c"] ; "transitively extended config")]
+    #[test_case("packages/c/turbo.json", &["c"] ; "config not extended")]
+    #[test_case("packages/a/index.js", &["a"] ; "other file in extended workspace")]
+    fn test_extended_turbo_json_changes(changed_file: &str, expected: &[&str]) {
+        let tmp_dir = TempDir::new().unwrap();
+        let turbo_root = AbsoluteSystemPathBuf::try_from(tmp_dir.path()).unwrap();
+        let extends = [("a", None), ("b", Some("a")), ("c", Some("b")), ("d", None)];
+        let mut package_jsons = HashMap::new();
+        for (name, extended) in extends {
+            let package_dir = turbo_root.join_components(&["packages", name]);
+            package_dir.create_dir_all().unwrap();
+            let turbo_json = match extended {
+                Some(extended) => format!(r#"{{"extends": ["//", "{extended}"]}}"#),
+                None => r#"{"extends": ["//"]}"#.to_string(),
+            };
+            package_dir
+                .join_component("turbo.json")
+                .create_with_contents(turbo_json)
+                .unwrap();
+            package_jsons.insert(
+                package_dir.join_component("package.json"),
+                PackageJson {
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                },
+            );
+        }
+        let pkg_graph = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(
+                PackageGraph::builder(&turbo_root, PackageJson::default())
+                    .with_package_discovery(MockDiscovery)
+                    .with_package_jsons(Some(package_jsons))
+                    .build(),
+            )
+            .unwrap();
+        let scm = SCM::new(&turbo_root);
+        let detector = SCMChangeDetector::new(&turbo_root, &scm, &pkg_graph, vec![], vec![]);
+
+        let changed_files = [AnchoredSystemPathBuf::from_raw(changed_file).unwrap()]
+            .into_iter()
+            .collect();
+        let changed = detector.changed_packages_for_files(&changed_files).unwrap();
 
-    use super::{ChangeDetectError, SCMChangeDetector};
+        let expected = expected
+            .iter()
+            .map(|name| WorkspaceName::from(*name))
+            .collect::<HashSet<_>>();
+        assert_eq!(changed, expected);
+    }
+
+    #[test]
+    fn test_most_specific_reason_is_kept() {
+        let web = WorkspaceName::from("web");
+        let mut packages = HashMap::new();
+        PackageChangeReason::insert(
+            &mut packages,
+            web.clone(),
+            PackageChangeReason::DependencyChange,
+        );
+        PackageChangeReason::insert(
+            &mut packages,
+            web.clone(),
+            PackageChangeReason::DirectChange,
+        );
+        PackageChangeReason::insert(
+            &mut packages,
+            web.clone(),
+            PackageChangeReason::LockfileChange,
+        );
+        assert_eq!(packages[&web], PackageChangeReason::DirectChange);
+    }
 
     #[cfg(unix)]
     #[test_case("/a/b/c", &["package.lock"], "/a/b/c/package.lock", Ok(true) ; "simple")]
@@ -1,4 +1,4 @@
-mod change_detector;
+pub(crate) mod change_detector;
 mod filter;
 mod simple_glob;
 mod target_selector;
@@ -195,6 +195,8 @@ pub struct TaskTracker<T> {
     sender: mpsc::Sender<Message>,
     started_at: T,
     task_id: TaskId<'static>,
+    retries: u32,
+    timed_out: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -222,6 +224,16 @@ pub struct TaskExecutionSummary {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     pub exit_code: Option<i32>,
+    // How many times the task was re-run after failing or timing out
+    #[serde(skip_serializing_if = "is_zero")]
+    pub retries: u32,
+    // Whether the final attempt was stopped for running past its timeout
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+}
+
+pub(super) fn is_zero(n: &u32) -> bool {
+    *n == 0
 }
 
 impl TaskExecutionSummary {
@@ -264,6 +276,8 @@ impl ExecutionTracker {
             sender: self.sender.clone(),
             task_id,
             started_at: (),
+            retries: 0,
+            timed_out: false,
         }
     }
 
@@ -302,6 +316,8 @@ impl TaskTracker<()> {
             sender,
             started_at,
             task_id,
+            retries: 0,
+            timed_out: false,
         }
     }
 
@@ -329,11 +345,23 @@ impl TaskTracker<chrono::DateTime<Local>> {
     // internal turbo error
     pub fn cancel(self) {}
 
+    // Track that the task failed and is being run again
+    pub fn retried(&mut self) {
+        self.retries += 1;
+    }
+
+    // Track that the task was stopped for exceeding its timeout
+    pub fn timed_out(&mut self) {
+        self.timed_out = true;
+    }
+
     pub async fn cached(self) -> TaskExecutionSummary {
         let Self {
             sender,
             started_at,
             task_id,
+            retries,
+            timed_out,
         } = self;
 
         let ended_at = Local::now();
@@ -343,6 +371,8 @@ impl TaskTracker<chrono::DateTime<Local>> {
             // Go synthesizes a zero exit code on cache hits
             exit_code: Some(0),
             error: None,
+            retries,
+            timed_out,
         };
 
         let state = TaskState {
@@ -364,6 +394,8 @@ impl TaskTracker<chrono::DateTime<Local>> {
             sender,
             started_at,
             task_id,
+            retries,
+            timed_out,
         } = self;
 
         let ended_at = Local::now();
@@ -372,6 +404,8 @@ impl TaskTracker<chrono::DateTime<Local>> {
             end_time: ended_at.timestamp_millis(),
             exit_code: Some(exit_code),
             error: None,
+            retries,
+            timed_out,
         };
 
         let state = TaskState {
@@ -397,6 +431,8 @@ impl TaskTracker<chrono::DateTime<Local>> {
             sender,
             started_at,
             task_id,
+            retries,
+            timed_out,
         } = self;
 
         let ended_at = Local::now();
@@ -405,6 +441,8 @@ impl TaskTracker<chrono::DateTime<Local>> {
             end_time: ended_at.timestamp_millis(),
             exit_code,
             error: Some(error.to_string()),
+            retries,
+            timed_out,
         };
 
         let state = TaskState {
@@ -518,7 +556,9 @@ mod test {
             start_time: 123,
             end_time: 234,
             exit_code: Some(0),
-            error: None
+            error: None,
+            retries: 0,
+            timed_out: false,
         },
         json!({ "startTime": 123, "endTime": 234, "exitCode": 0 })
         ; "success"
@@ -529,10 +569,31 @@ mod test {
             end_time: 234,
             exit_code: Some(1),
             error: Some("cannot find anything".into()),
+            retries: 0,
+            timed_out: false,
         },
         json!({ "startTime": 123, "endTime": 234, "exitCode": 1, "error": "cannot find anything" })
         ; "failure"
     )]
+    #[test_case(
+        TaskExecutionSummary {
+            start_time: 123,
+            end_time: 234,
+            exit_code: None,
+            error: Some("command timed out after 5m".into()),
+            retries: 2,
+            timed_out: true,
+        },
+        json!({
+            "startTime": 123,
+            "endTime": 234,
+            "exitCode": null,
+            "error": "command timed out after 5m",
+            "retries": 2,
+            "timedOut": true
+        })
+        ; "timed out after retries"
+    )]
     fn test_serialization(value: impl serde::Serialize, expected: serde_json::Value) {
         assert_eq!(serde_json::to_value(value).unwrap(), expected);
     }
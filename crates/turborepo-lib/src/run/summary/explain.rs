@@ -0,0 +1,510 @@
+//! Explains cache misses for `--explain-hash` by diffing the hash inputs
+//! recorded in two run summaries. The current run is read back from its own
+//! JSON summary so that both sides of the comparison have the same shape.
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
+use serde::Deserialize;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+use turborepo_ui::{cprintln, BOLD, BOLD_CYAN, GREY, UI};
+
+use super::Error;
+
+/// The parts of a run summary that feed into task hashes
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashInputs {
+    id: String,
+    #[serde(rename = "globalCacheInputs")]
+    global: GlobalInputs,
+    tasks: Vec<TaskInputs>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GlobalInputs {
+    root_key: String,
+    files: BTreeMap<String, String>,
+    hash_of_external_dependencies: String,
+    environment_variables: GlobalEnvVars,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlobalEnvVars {
+    configured: Option<Vec<String>>,
+    inferred: Option<Vec<String>>,
+    #[serde(rename = "passthrough")]
+    pass_through: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskInputs {
+    task_id: String,
+    hash: String,
+    cache: TaskCache,
+    inputs: BTreeMap<String, String>,
+    hash_of_external_dependencies: String,
+    command: String,
+    cli_arguments: Vec<String>,
+    dependencies: Vec<String>,
+    resolved_task_definition: serde_json::Value,
+    environment_variables: TaskEnvVars,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskCache {
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskEnvVars {
+    configured: Vec<String>,
+    inferred: Vec<String>,
+    #[serde(rename = "passthrough")]
+    pass_through: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A single hash input that differs between the two runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum HashChange {
+    RootKey,
+    GlobalFile(String, Change),
+    GlobalExternalDependencies,
+    GlobalEnvVar(String, Change),
+    File(String, Change),
+    EnvVar(String, Change),
+    Dependency(String, Change),
+    ExternalDependencies,
+    Command,
+    CliArguments,
+    TaskDefinition,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TaskExplanation {
+    /// The task didn't run as part of the previous run
+    NotInPreviousRun,
+    /// The hash is the same, so the artifact is missing from the cache rather
+    /// than any input having changed
+    HashUnchanged,
+    Changed(Vec<HashChange>),
+}
+
+#[derive(Debug)]
+pub struct Explanation {
+    previous_run: String,
+    global: Vec<HashChange>,
+    tasks: Vec<(String, TaskExplanation)>,
+}
+
+/// Finds the most recent summary in `.turbo/runs`. Summary file names are
+/// KSUIDs, which sort by creation time.
+pub fn latest_summary(runs_dir: &AbsoluteSystemPath) -> Result<AbsoluteSystemPathBuf, Error> {
+    let latest = match std::fs::read_dir(runs_dir.as_std_path()) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name.ends_with(".json"))
+            .max(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    latest
+        .map(|name| runs_dir.join_component(&name))
+        .ok_or_else(|| Error::NoPreviousSummary(runs_dir.to_owned()))
+}
+
+/// Explains every cache miss in `current` in terms of how its inputs differ
+/// from the same task in `previous`
+pub fn explain(previous: &HashInputs, current: &HashInputs) -> Explanation {
+    let previous_tasks = previous
+        .tasks
+        .iter()
+        .map(|task| (task.task_id.as_str(), task))
+        .collect::<HashMap<_, _>>();
+    let previous_hashes = task_hashes(previous);
+    let current_hashes = task_hashes(current);
+
+    let tasks = current
+        .tasks
+        .iter()
+        .filter(|task| task.cache.status == "MISS")
+        .map(|task| {
+            let explanation = match previous_tasks.get(task.task_id.as_str()) {
+                None => TaskExplanation::NotInPreviousRun,
+                Some(previous_task) if previous_task.hash == task.hash => {
+                    TaskExplanation::HashUnchanged
+                }
+                Some(previous_task) => TaskExplanation::Changed(diff_task(
+                    previous_task,
+                    task,
+                    &previous_hashes,
+                    &current_hashes,
+                )),
+            };
+            (task.task_id.clone(), explanation)
+        })
+        .collect();
+
+    Explanation {
+        previous_run: previous.id.clone(),
+        global: diff_global(&previous.global, &current.global),
+        tasks,
+    }
+}
+
+fn task_hashes(run: &HashInputs) -> HashMap<&str, &str> {
+    run.tasks
+        .iter()
+        .map(|task| (task.task_id.as_str(), task.hash.as_str()))
+        .collect()
+}
+
+fn diff_global(previous: &GlobalInputs, current: &GlobalInputs) -> Vec<HashChange> {
+    let mut changes = Vec::new();
+    if previous.root_key != current.root_key {
+        changes.push(HashChange::RootKey);
+    }
+    changes.extend(
+        diff_maps(&previous.files, &current.files)
+            .map(|(file, change)| HashChange::GlobalFile(file, change)),
+    );
+    if previous.hash_of_external_dependencies != current.hash_of_external_dependencies {
+        changes.push(HashChange::GlobalExternalDependencies);
+    }
+
+    let env_vars = |vars: &GlobalEnvVars| {
+        env_var_map(
+            [&vars.configured, &vars.inferred, &vars.pass_through]
+                .into_iter()
+                .flatten()
+                .flatten(),
+        )
+    };
+    changes.extend(
+        diff_maps(
+            &env_vars(&previous.environment_variables),
+            &env_vars(&current.environment_variables),
+        )
+        .map(|(name, change)| HashChange::GlobalEnvVar(name, change)),
+    );
+
+    changes
+}
+
+fn diff_task(
+    previous: &TaskInputs,
+    current: &TaskInputs,
+    previous_hashes: &HashMap<&str, &str>,
+    current_hashes: &HashMap<&str, &str>,
+) -> Vec<HashChange> {
+    let mut changes = diff_maps(&previous.inputs, &current.inputs)
+        .map(|(file, change)| HashChange::File(file, change))
+        .collect::<Vec<_>>();
+
+    let env_vars = |vars: &TaskEnvVars| {
+        env_var_map(
+            vars.configured
+                .iter()
+                .chain(&vars.inferred)
+                .chain(vars.pass_through.iter().flatten()),
+        )
+    };
+    changes.extend(
+        diff_maps(
+            &env_vars(&previous.environment_variables),
+            &env_vars(&current.environment_variables),
+        )
+        .map(|(name, change)| HashChange::EnvVar(name, change)),
+    );
+
+    let previous_deps = previous.dependencies.iter().collect::<BTreeSet<_>>();
+    let current_deps = current.dependencies.iter().collect::<BTreeSet<_>>();
+    for dependency in previous_deps.union(&current_deps) {
+        let change = match (
+            previous_deps.contains(dependency),
+            current_deps.contains(dependency),
+        ) {
+            (false, _) => Some(Change::Added),
+            (_, false) => Some(Change::Removed),
+            _ => {
+                let previous_hash = previous_hashes.get(dependency.as_str());
+                let current_hash = current_hashes.get(dependency.as_str());
+                (previous_hash != current_hash).then_some(Change::Modified)
+            }
+        };
+        if let Some(change) = change {
+            changes.push(HashChange::Dependency(dependency.to_string(), change));
+        }
+    }
+
+    if previous.hash_of_external_dependencies != current.hash_of_external_dependencies {
+        changes.push(HashChange::ExternalDependencies);
+    }
+    if previous.command != current.command {
+        changes.push(HashChange::Command);
+    }
+    if previous.cli_arguments != current.cli_arguments {
+        changes.push(HashChange::CliArguments);
+    }
+    if previous.resolved_task_definition != current.resolved_task_definition {
+        changes.push(HashChange::TaskDefinition);
+    }
+
+    changes
+}
+
+// Env vars are recorded as `NAME=<hash of value>`
+fn env_var_map<'a>(vars: impl Iterator<Item = &'a String>) -> BTreeMap<String, String> {
+    vars.map(|var| match var.split_once('=') {
+        Some((name, value)) => (name.to_string(), value.to_string()),
+        None => (var.to_string(), String::new()),
+    })
+    .collect()
+}
+
+fn diff_maps<'a>(
+    previous: &'a BTreeMap<String, String>,
+    current: &'a BTreeMap<String, String>,
+) -> impl Iterator<Item = (String, Change)> + 'a {
+    let keys = previous
+        .keys()
+        .chain(current.keys())
+        .collect::<BTreeSet<_>>();
+    keys.into_iter().filter_map(move |key| {
+        let change = match (previous.get(key), current.get(key)) {
+            (None, Some(_)) => Change::Added,
+            (Some(_), None) => Change::Removed,
+            (Some(previous), Some(current)) if previous != current => Change::Modified,
+            _ => return None,
+        };
+        Some((key.clone(), change))
+    })
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Change::Added => "added",
+            Change::Removed => "removed",
+            Change::Modified => "changed",
+        })
+    }
+}
+
+impl fmt::Display for HashChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashChange::RootKey => write!(f, "global cache key changed"),
+            HashChange::GlobalFile(file, change) => write!(f, "global file {file} {change}"),
+            HashChange::GlobalExternalDependencies => {
+                write!(f, "root external dependencies changed")
+            }
+            HashChange::GlobalEnvVar(name, change) => write!(f, "global env var {name} {change}"),
+            HashChange::File(file, change) => write!(f, "file {file} {change}"),
+            HashChange::EnvVar(name, change) => write!(f, "env var {name} {change}"),
+            HashChange::Dependency(task_id, change) => write!(f, "dependency {task_id} {change}"),
+            HashChange::ExternalDependencies => write!(f, "external dependencies changed"),
+            HashChange::Command => write!(f, "command changed"),
+            HashChange::CliArguments => write!(f, "passed through arguments changed"),
+            HashChange::TaskDefinition => write!(f, "task definition changed"),
+        }
+    }
+}
+
+impl Explanation {
+    pub fn print(&self, ui: UI) {
+        cprintln!(
+            ui,
+            BOLD_CYAN,
+            "\nCache misses compared to run {}",
+            self.previous_run
+        );
+
+        if !self.global.is_empty() {
+            cprintln!(ui, BOLD, "Global hash inputs");
+            for change in &self.global {
+                cprintln!(ui, GREY, "  {}", change);
+            }
+        }
+
+        for (task_id, explanation) in &self.tasks {
+            cprintln!(ui, BOLD, "{}", task_id);
+            match explanation {
+                TaskExplanation::NotInPreviousRun => {
+                    cprintln!(ui, GREY, "  not part of the previous run")
+                }
+                TaskExplanation::HashUnchanged => {
+                    cprintln!(ui, GREY, "  hash unchanged, the cache entry is missing")
+                }
+                TaskExplanation::Changed(changes) if changes.is_empty() => {
+                    cprintln!(ui, GREY, "  only global hash inputs changed")
+                }
+                TaskExplanation::Changed(changes) => {
+                    for change in changes {
+                        cprintln!(ui, GREY, "  {}", change);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn run(id: &str, global_file_hash: &str, tasks: serde_json::Value) -> HashInputs {
+        serde_json::from_value(json!({
+            "id": id,
+            "globalCacheInputs": {
+                "rootKey": "key",
+                "files": { "turbo.json": global_file_hash },
+                "hashOfExternalDependencies": "ext",
+                "environmentVariables": {
+                    "configured": [],
+                    "inferred": [],
+                    "passthrough": null
+                }
+            },
+            "tasks": tasks,
+        }))
+        .unwrap()
+    }
+
+    fn task(
+        task_id: &str,
+        hash: &str,
+        status: &str,
+        inputs: serde_json::Value,
+        env: &[&str],
+        dependencies: &[&str],
+    ) -> serde_json::Value {
+        json!({
+            "taskId": task_id,
+            "hash": hash,
+            "cache": { "status": status },
+            "inputs": inputs,
+            "hashOfExternalDependencies": "ext",
+            "command": "next build",
+            "cliArguments": [],
+            "dependencies": dependencies,
+            "resolvedTaskDefinition": { "outputs": [".next/**"] },
+            "environmentVariables": {
+                "configured": env,
+                "inferred": [],
+                "passthrough": null
+            }
+        })
+    }
+
+    #[test]
+    fn test_explains_changed_inputs() {
+        let previous = run(
+            "previous",
+            "a",
+            json!([
+                task("ui#build", "1", "MISS", json!({}), &[], &[]),
+                task(
+                    "web#build",
+                    "2",
+                    "MISS",
+                    json!({ "src/index.ts": "a", "src/old.ts": "b" }),
+                    &["API_URL=x"],
+                    &["ui#build"]
+                ),
+            ]),
+        );
+        let current = run(
+            "current",
+            "a",
+            json!([
+                task("ui#build", "3", "MISS", json!({}), &[], &[]),
+                task(
+                    "web#build",
+                    "4",
+                    "MISS",
+                    json!({ "src/index.ts": "c", "src/new.ts": "d" }),
+                    &["API_URL=y"],
+                    &["ui#build"]
+                ),
+            ]),
+        );
+
+        let explanation = explain(&previous, &current);
+        assert_eq!(explanation.previous_run, "previous");
+        assert!(explanation.global.is_empty());
+        assert_eq!(
+            explanation.tasks[1],
+            (
+                "web#build".to_string(),
+                TaskExplanation::Changed(vec![
+                    HashChange::File("src/index.ts".into(), Change::Modified),
+                    HashChange::File("src/new.ts".into(), Change::Added),
+                    HashChange::File("src/old.ts".into(), Change::Removed),
+                    HashChange::EnvVar("API_URL".into(), Change::Modified),
+                    HashChange::Dependency("ui#build".into(), Change::Modified),
+                ])
+            )
+        );
+    }
+
+    #[test]
+    fn test_explains_global_changes_and_missing_tasks() {
+        let previous = run(
+            "previous",
+            "a",
+            json!([task("web#build", "1", "MISS", json!({}), &[], &[])]),
+        );
+        let current = run(
+            "current",
+            "b",
+            json!([
+                task("web#build", "2", "MISS", json!({}), &[], &[]),
+                task("web#lint", "3", "MISS", json!({}), &[], &[]),
+                task("docs#build", "4", "HIT", json!({}), &[], &[]),
+            ]),
+        );
+
+        let explanation = explain(&previous, &current);
+        assert_eq!(
+            explanation.global,
+            vec![HashChange::GlobalFile(
+                "turbo.json".into(),
+                Change::Modified
+            )]
+        );
+        assert_eq!(
+            explanation.tasks,
+            vec![
+                ("web#build".to_string(), TaskExplanation::Changed(vec![])),
+                ("web#lint".to_string(), TaskExplanation::NotInPreviousRun),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_hash() {
+        let tasks = json!([task("web#build", "1", "MISS", json!({}), &[], &[])]);
+        let previous = run("previous", "a", tasks.clone());
+        let current = run("current", "a", tasks);
+
+        assert_eq!(
+            explain(&previous, &current).tasks,
+            vec![("web#build".to_string(), TaskExplanation::HashUnchanged)]
+        );
+    }
+}
@@ -6,7 +6,9 @@
 #[allow(dead_code)]
 mod duration;
 mod execution;
+mod explain;
 mod global_hash;
+mod report;
 mod scm;
 mod spaces;
 mod task;
@@ -15,7 +17,7 @@ use std::{collections::HashSet, io, io::Write};
 
 use chrono::{DateTime, Local};
 pub use duration::TurboDuration;
-pub use execution::{TaskExecutionSummary, TaskTracker};
+pub use execution::{ExecutionTracker, TaskExecutionSummary, TaskTracker};
 pub use global_hash::GlobalHashSummary;
 use itertools::Itertools;
 use serde::Serialize;
@@ -36,11 +38,11 @@ use self::{
 use super::task_id::TaskId;
 use crate::{
     cli,
-    cli::DryRunMode,
+    cli::{DryRunMode, Report},
     engine::Engine,
     opts::RunOpts,
     run::summary::{
-        execution::{ExecutionSummary, ExecutionTracker},
+        execution::ExecutionSummary,
         scm::SCMState,
         spaces::{SpaceRequest, SpacesClient, SpacesClientHandle},
         task::TaskSummary,
@@ -68,6 +70,10 @@ pub enum Error {
     Env(#[source] turborepo_env::Error),
     #[error("failed to construct task summary: {0}")]
     TaskSummary(#[from] task_factory::Error),
+    #[error(transparent)]
+    Path(#[from] turbopath::PathError),
+    #[error("no previous run summary found in {0}")]
+    NoPreviousSummary(AbsoluteSystemPathBuf),
 }
 
 // NOTE: When changing this, please ensure that the server side is updated to
@@ -124,6 +130,10 @@ pub struct RunSummary<'a> {
     #[serde(skip)]
     should_save: bool,
     #[serde(skip)]
+    explain_hash: Option<&'a str>,
+    #[serde(skip)]
+    reports: &'a [Report],
+    #[serde(skip)]
     run_type: RunType,
     #[serde(skip)]
     spaces_client_handle: Option<SpacesClientHandle>,
@@ -206,7 +216,10 @@ impl RunTracker {
         task_factory: TaskSummaryFactory<'a>,
     ) -> Result<RunSummary<'a>, Error> {
         let single_package = run_opts.single_package;
-        let should_save = run_opts.summarize.flatten().is_some_and(|s| s);
+        // Explaining a run's cache misses also saves it, so the next run has
+        // something to be compared against
+        let should_save =
+            run_opts.summarize.flatten().is_some_and(|s| s) || run_opts.explain_hash.is_some();
 
         let run_type = match run_opts.dry_run {
             None => RunType::Real,
@@ -246,6 +259,8 @@ impl RunTracker {
             monorepo: !single_package,
             repo_root,
             should_save,
+            explain_hash: run_opts.explain_hash,
+            reports: run_opts.reports,
             run_type,
             spaces_client_handle: self.spaces_client_handle,
         })
@@ -379,6 +394,16 @@ impl<'a> RunSummary<'a> {
         pkg_dep_graph: &PackageGraph,
         ui: UI,
     ) -> Result<(), Error> {
+        // Anything printed would end up in the middle of the JSON output
+        if let Some(against) = self
+            .explain_hash
+            .filter(|_| !matches!(self.run_type, RunType::DryJson))
+        {
+            if let Err(err) = self.explain_cache_misses(against, ui) {
+                warn!("Error explaining cache misses: {}", err)
+            }
+        }
+
         if matches!(self.run_type, RunType::DryJson | RunType::DryText) {
             return self.close_dry_run(pkg_dep_graph, ui);
         }
@@ -389,6 +414,12 @@ impl<'a> RunSummary<'a> {
             }
         }
 
+        for report in self.reports {
+            if let Err(err) = report::write(report, self.repo_root, &self.tasks) {
+                warn!("Error writing report to {}: {}", report.path, err)
+            }
+        }
+
         if let Some(execution) = &self.execution {
             let path = self.get_path();
             let failed_tasks = self.get_failed_tasks();
@@ -769,6 +800,20 @@ impl<'a> RunSummary<'a> {
         self.tasks.sort_by(|a, b| a.task_id.cmp(&b.task_id));
     }
 
+    // An empty path compares against the most recent saved summary
+    fn explain_cache_misses(&mut self, against: &str, ui: UI) -> Result<(), Error> {
+        let previous_path = match against {
+            "" => explain::latest_summary(&self.repo_root.join_components(&[".turbo", "runs"]))?,
+            path => AbsoluteSystemPathBuf::from_cwd(path)?,
+        };
+        let previous: explain::HashInputs = serde_json::from_str(&previous_path.read_to_string()?)?;
+        let current: explain::HashInputs = serde_json::from_str(&self.format_json()?)?;
+
+        explain::explain(&previous, &current).print(ui);
+
+        Ok(())
+    }
+
     fn get_path(&self) -> AbsoluteSystemPathBuf {
         let filename = format!("{}.json", self.id);
 
@@ -0,0 +1,302 @@
+//! Reports of a run's task results for CI systems to display natively. Each
+//! task is a test case, and failed tasks carry the end of their logs.
+use std::fmt::Write;
+
+use itertools::Itertools;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use super::{execution::TaskExecutionSummary, task::TaskSummary, Error};
+use crate::cli::{Report, ReportFormat};
+
+// Only the end of a failed task's logs is included, which is where the error
+// usually is, so reports stay small enough for CI systems to ingest
+const MAX_LOG_LINES: usize = 100;
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Passed,
+    Failed { message: String, logs: String },
+    // Tasks that never ran because the run stopped before reaching them
+    Skipped,
+}
+
+#[derive(Debug)]
+struct TestCase<'a> {
+    package: &'a str,
+    task: &'a str,
+    duration_ms: i64,
+    outcome: Outcome,
+}
+
+pub(crate) fn write(
+    report: &Report,
+    repo_root: &AbsoluteSystemPath,
+    tasks: &[TaskSummary],
+) -> Result<(), Error> {
+    let test_cases = tasks
+        .iter()
+        .sorted_by(|a, b| a.task_id.cmp(&b.task_id))
+        .map(|task| TestCase::new(repo_root, task))
+        .collect::<Vec<_>>();
+    let contents = match report.format {
+        ReportFormat::Junit => junit(&test_cases),
+        ReportFormat::Tap => tap(&test_cases),
+    };
+
+    let path = AbsoluteSystemPathBuf::from_cwd(report.path.clone())?;
+    path.ensure_dir()?;
+    Ok(path.create_with_contents(contents)?)
+}
+
+impl<'a> TestCase<'a> {
+    fn new(repo_root: &AbsoluteSystemPath, task: &'a TaskSummary) -> Self {
+        let execution = task.shared.execution.as_ref();
+        let outcome = match execution {
+            None => Outcome::Skipped,
+            Some(execution) if execution.is_failure() => Outcome::Failed {
+                message: failure_message(execution),
+                // The log file is missing if the task failed to start
+                logs: std::fs::read_to_string(repo_root.as_std_path().join(&task.shared.log_file))
+                    .map(|logs| trim_logs(&logs))
+                    .unwrap_or_default(),
+            },
+            Some(_) => Outcome::Passed,
+        };
+
+        Self {
+            package: &task.package,
+            task: &task.task,
+            duration_ms: execution.map_or(0, |e| e.end_time - e.start_time),
+            outcome,
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("{}#{}", self.package, self.task)
+    }
+
+    fn seconds(&self) -> String {
+        format!("{:.3}", self.duration_ms as f64 / 1000.0)
+    }
+}
+
+fn failure_message(execution: &TaskExecutionSummary) -> String {
+    if execution.timed_out {
+        return "timed out".to_string();
+    }
+    match (execution.exit_code, &execution.error) {
+        (Some(exit_code), _) => format!("exited with code {exit_code}"),
+        (None, Some(error)) => error.clone(),
+        (None, None) => "exited without an exit code".to_string(),
+    }
+}
+
+fn trim_logs(logs: &str) -> String {
+    let logs = console::strip_ansi_codes(logs);
+    let lines = logs.lines().collect::<Vec<_>>();
+    let start = lines.len().saturating_sub(MAX_LOG_LINES);
+    let mut trimmed = String::new();
+    if start > 0 {
+        let _ = writeln!(trimmed, "... {start} earlier lines omitted");
+    }
+    trimmed.push_str(&lines[start..].join("\n"));
+    trimmed
+}
+
+fn junit(test_cases: &[TestCase]) -> String {
+    let count = |outcome: fn(&Outcome) -> bool| {
+        test_cases
+            .iter()
+            .filter(|test_case| outcome(&test_case.outcome))
+            .count()
+    };
+    let failures = count(|outcome| matches!(outcome, Outcome::Failed { .. }));
+    let skipped = count(|outcome| matches!(outcome, Outcome::Skipped));
+    let duration_ms = test_cases.iter().map(|t| t.duration_ms).sum::<i64>();
+
+    let mut xml = String::new();
+    // Writing to a `String` can't fail
+    let _ = writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        xml,
+        r#"<testsuites name="turbo" tests="{}" failures="{failures}" skipped="{skipped}" time="{:.3}">"#,
+        test_cases.len(),
+        duration_ms as f64 / 1000.0,
+    );
+    // A suite per package, so CI systems group a package's tasks together
+    for (package, test_cases) in &test_cases.iter().group_by(|test_case| test_case.package) {
+        let test_cases = test_cases.collect::<Vec<_>>();
+        let _ = writeln!(
+            xml,
+            r#"  <testsuite name="{}" tests="{}">"#,
+            escape_xml(package),
+            test_cases.len()
+        );
+        for test_case in test_cases {
+            let _ = write!(
+                xml,
+                r#"    <testcase name="{}" classname="{}" time="{}""#,
+                escape_xml(&test_case.name()),
+                escape_xml(package),
+                test_case.seconds()
+            );
+            match &test_case.outcome {
+                Outcome::Passed => {
+                    let _ = writeln!(xml, " />");
+                }
+                Outcome::Failed { message, logs } => {
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                        escape_xml(message),
+                        escape_xml(logs)
+                    );
+                }
+                Outcome::Skipped => {
+                    let _ = writeln!(xml, ">\n      <skipped />\n    </testcase>");
+                }
+            }
+        }
+        let _ = writeln!(xml, "  </testsuite>");
+    }
+    let _ = writeln!(xml, "</testsuites>");
+
+    xml
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Other control characters aren't allowed in XML 1.0, even escaped
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn tap(test_cases: &[TestCase]) -> String {
+    let mut tap = String::new();
+    let _ = writeln!(tap, "TAP version 13");
+    let _ = writeln!(tap, "1..{}", test_cases.len());
+    for (i, test_case) in test_cases.iter().enumerate() {
+        let number = i + 1;
+        // `#` starts a directive in TAP, so it can't be used in a description
+        let name = test_case.name().replace('#', ":");
+        match &test_case.outcome {
+            Outcome::Passed => {
+                let _ = writeln!(tap, "ok {number} - {name}");
+            }
+            Outcome::Failed { message, logs } => {
+                let _ = writeln!(tap, "not ok {number} - {name}");
+                let _ = writeln!(tap, "  ---");
+                let _ = writeln!(tap, "  message: {message:?}");
+                let _ = writeln!(tap, "  duration_ms: {}", test_case.duration_ms);
+                if !logs.is_empty() {
+                    let _ = writeln!(tap, "  logs: |");
+                    for line in logs.lines() {
+                        let _ = writeln!(tap, "    {line}");
+                    }
+                }
+                let _ = writeln!(tap, "  ...");
+            }
+            Outcome::Skipped => {
+                let _ = writeln!(tap, "ok {number} - {name} # SKIP not run");
+            }
+        }
+    }
+
+    tap
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn test_cases() -> Vec<TestCase<'static>> {
+        vec![
+            TestCase {
+                package: "docs",
+                task: "build",
+                duration_ms: 1500,
+                outcome: Outcome::Passed,
+            },
+            TestCase {
+                package: "web",
+                task: "build",
+                duration_ms: 250,
+                outcome: Outcome::Failed {
+                    message: "exited with code 1".to_string(),
+                    logs: "error: expected `<T>` & found \"U\"\u{1b}".to_string(),
+                },
+            },
+            TestCase {
+                package: "web",
+                task: "test",
+                duration_ms: 0,
+                outcome: Outcome::Skipped,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_junit() {
+        assert_eq!(
+            junit(&test_cases()),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="turbo" tests="3" failures="1" skipped="1" time="1.750">
+  <testsuite name="docs" tests="1">
+    <testcase name="docs#build" classname="docs" time="1.500" />
+  </testsuite>
+  <testsuite name="web" tests="2">
+    <testcase name="web#build" classname="web" time="0.250">
+      <failure message="exited with code 1">error: expected `&lt;T&gt;` &amp; found &quot;U&quot;</failure>
+    </testcase>
+    <testcase name="web#test" classname="web" time="0.000">
+      <skipped />
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+
+    #[test]
+    fn test_tap() {
+        assert_eq!(
+            tap(&test_cases()),
+            "TAP version 13
+1..3
+ok 1 - docs:build
+not ok 2 - web:build
+  ---
+  message: \"exited with code 1\"
+  duration_ms: 250
+  logs: |
+    error: expected `<T>` & found \"U\"\u{1b}
+  ...
+ok 3 - web:test # SKIP not run
+"
+        );
+    }
+
+    #[test]
+    fn test_trim_logs() {
+        let logs = (1..=150)
+            .map(|i| format!("\u{1b}[2mline {i}\u{1b}[0m"))
+            .join("\n");
+        let trimmed = trim_logs(&logs);
+
+        assert!(trimmed.starts_with("... 50 earlier lines omitted\nline 51\n"));
+        assert!(trimmed.ends_with("line 150"));
+    }
+}
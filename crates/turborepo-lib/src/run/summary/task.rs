@@ -6,7 +6,10 @@ use turbopath::{AnchoredSystemPathBuf, RelativeUnixPathBuf};
 use turborepo_cache::CacheHitMetadata;
 use turborepo_env::{DetailedMap, EnvironmentVariableMap};
 
-use super::{execution::TaskExecutionSummary, EnvMode};
+use super::{
+    execution::{is_zero, TaskExecutionSummary},
+    EnvMode,
+};
 use crate::{
     cli::OutputLogsMode,
     run::task_id::TaskId,
@@ -106,6 +109,12 @@ pub struct TaskSummaryTaskDefinition {
     env: Vec<String>,
     pass_through_env: Option<Vec<String>>,
     dot_env: Option<Vec<RelativeUnixPathBuf>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<String>,
+    #[serde(skip_serializing_if = "is_zero")]
+    retries: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    defined_in: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -285,6 +294,9 @@ impl From<TaskDefinition> for TaskSummaryTaskDefinition {
             mut inputs,
             output_mode,
             persistent,
+            timeout,
+            retries,
+            defined_in,
         } = value;
 
         let mut outputs = inclusions;
@@ -320,6 +332,9 @@ impl From<TaskDefinition> for TaskSummaryTaskDefinition {
             pass_through_env,
             // This should _not_ be sorted.
             dot_env,
+            timeout: timeout.map(|timeout| humantime::format_duration(timeout).to_string()),
+            retries,
+            defined_in,
         }
     }
 }
@@ -381,6 +396,46 @@ mod test {
         })
         ; "resolved task definition"
     )]
+    #[test_case(
+        TaskSummaryTaskDefinition {
+            timeout: Some("5m".into()),
+            retries: 2,
+            ..Default::default()
+        },
+        json!({
+            "outputs": [],
+            "cache": false,
+            "dependsOn": [],
+            "inputs": [],
+            "outputMode": "full",
+            "persistent": false,
+            "env": [],
+            "passThroughEnv": null,
+            "dotEnv": null,
+            "timeout": "5m",
+            "retries": 2,
+        })
+        ; "task definition with timeout and retries"
+    )]
+    #[test_case(
+        TaskSummaryTaskDefinition {
+            defined_in: vec!["config".into(), "web".into()],
+            ..Default::default()
+        },
+        json!({
+            "outputs": [],
+            "cache": false,
+            "dependsOn": [],
+            "inputs": [],
+            "outputMode": "full",
+            "persistent": false,
+            "env": [],
+            "passThroughEnv": null,
+            "dotEnv": null,
+            "definedIn": ["config", "web"],
+        })
+        ; "task definition from extended workspaces"
+    )]
     fn test_serialization(value: impl serde::Serialize, expected: serde_json::Value) {
         assert_eq!(serde_json::to_value(value).unwrap(), expected);
     }
@@ -0,0 +1,356 @@
+//! `turbo watch` keeps a run alive, re-running the tasks of changed packages
+//! whenever the daemon reports file changes.
+
+use std::{
+    collections::HashSet,
+    io::Write,
+    pin::pin,
+    sync::{Arc, RwLock, RwLockReadGuard},
+    time::Duration,
+};
+
+use chrono::Local;
+use futures::{Stream, StreamExt};
+use rayon::iter::ParallelBridge;
+use tracing::debug;
+use turbopath::AnchoredSystemPathBuf;
+use turborepo_api_client::{APIAuth, APIClient};
+use turborepo_ci::Vendor;
+use turborepo_env::EnvironmentVariableMap;
+use turborepo_repository::{
+    package_graph::{PackageGraph, WorkspaceName},
+    package_json::PackageJson,
+};
+use turborepo_scm::SCM;
+use turborepo_ui::{cprintln, GREY};
+
+use super::{
+    global_env, global_hash::get_global_hash_inputs, scope::change_detector::SCMChangeDetector,
+    summary::RunTracker, Error, Run, RunCache,
+};
+use crate::{
+    cli::EnvMode,
+    config::TurboJson,
+    daemon::{DaemonClient, DaemonConnector, DaemonError},
+    engine::{Engine, TaskNode},
+    opts::Opts,
+    task_graph::Visitor,
+    task_hash::{get_external_deps_hash, PackageInputsHashes},
+};
+
+/// How long to wait for further file events before acting on a batch. A
+/// single save or branch switch usually produces a burst of events.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Files that never count as task inputs.
+const IGNORED_FILES: [&str; 3] = [".git/**", "**/.turbo/**", "**/node_modules/**"];
+
+pub(crate) struct WatchRun<'a> {
+    pub run: &'a Run<'a>,
+    pub opts: &'a Opts<'a>,
+    pub scm: &'a SCM,
+    /// Reloaded along with the package graph, so edits to `turbo.json` take
+    /// effect on the next re-run
+    pub root_turbo_json: RwLock<TurboJson>,
+    pub runcache: Arc<RunCache>,
+    pub env_at_execution_start: &'a EnvironmentVariableMap,
+    pub global_env_mode: EnvMode,
+    pub api_client: APIClient,
+    pub api_auth: Option<APIAuth>,
+}
+
+/// Everything a watch run derives from the state of the repository, which is
+/// brought up to date before each re-run
+pub(crate) struct WatchState {
+    pub pkg_dep_graph: Arc<PackageGraph>,
+    pub engine: Arc<Engine>,
+    pub global_hash: String,
+    pub global_env: EnvironmentVariableMap,
+}
+
+impl<'a> WatchRun<'a> {
+    /// Runs every task in `state.engine` once, then keeps the persistent tasks
+    /// alive while re-running the rest as their packages change. Only returns
+    /// once the daemon stops sending file changes.
+    pub async fn watch(
+        &self,
+        mut daemon: DaemonClient<DaemonConnector>,
+        state: WatchState,
+    ) -> Result<i32, Error> {
+        // Subscribe before the initial run so that edits made while it
+        // executes still trigger a re-run.
+        let changes = daemon.file_changes().await?;
+
+        let engine = Arc::new(state.engine.create_engine_without_persistent_tasks());
+        let exit_code = self.execute(engine, &state).await?;
+        debug!("initial watch run finished with exit code {exit_code}");
+
+        // Persistent tasks keep running with the state they were started with
+        let persistent_engine = Arc::new(state.engine.create_engine_for_persistent_tasks());
+        let (persistent_result, watch_result) =
+            tokio::join!(self.execute(persistent_engine, &state), async {
+                let result = self.watch_changes(&state, changes).await;
+                // Nothing will re-run anymore, so don't leave dev servers behind
+                self.run.processes.stop().await;
+                result
+            });
+
+        watch_result?;
+        persistent_result
+    }
+
+    async fn watch_changes(
+        &self,
+        initial_state: &WatchState,
+        changes: impl Stream<Item = Result<Vec<AnchoredSystemPathBuf>, DaemonError>>,
+    ) -> Result<(), Error> {
+        let mut pkg_dep_graph = initial_state.pkg_dep_graph.clone();
+        let mut engine = initial_state.engine.clone();
+
+        let mut changes = pin!(changes);
+        while let Some(batch) = changes.next().await {
+            let mut changed_files = batch?.into_iter().collect::<HashSet<_>>();
+            while let Ok(Some(batch)) = tokio::time::timeout(DEBOUNCE, changes.next()).await {
+                changed_files.extend(batch?);
+            }
+
+            // Packages may have been added, removed or had their dependencies
+            // or task definitions changed. Until the repository is valid
+            // again, e.g. while a package.json is half edited, keep using the
+            // last valid graph.
+            if self.changes_package_graph(&pkg_dep_graph, &changed_files) {
+                match self.load_package_graph().await {
+                    Ok((new_pkg_dep_graph, new_engine)) => {
+                        pkg_dep_graph = Arc::new(new_pkg_dep_graph);
+                        engine = Arc::new(new_engine);
+                    }
+                    Err(e) => {
+                        writeln!(std::io::stderr(), "unable to reload packages: {e}").ok();
+                        continue;
+                    }
+                }
+            }
+
+            let change_detector = SCMChangeDetector::new(
+                &self.run.base.repo_root,
+                self.scm,
+                &pkg_dep_graph,
+                self.root_turbo_json().global_deps.clone(),
+                self.ignore_patterns(&pkg_dep_graph, &engine),
+            );
+            let changed_packages = change_detector.changed_packages_for_files(&changed_files)?;
+            let subgraph = engine.create_engine_for_subgraph(&changed_packages);
+            if !subgraph
+                .tasks()
+                .any(|node| matches!(node, TaskNode::Task(_)))
+            {
+                continue;
+            }
+
+            let mut packages = changed_packages
+                .iter()
+                .map(|workspace_name| workspace_name.to_string())
+                .collect::<Vec<_>>();
+            packages.sort();
+            cprintln!(
+                self.run.base.ui,
+                GREY,
+                "• Changes detected in: {}",
+                packages.join(", ")
+            );
+
+            // Global dependencies, such as files in `globalDependencies` or
+            // the lockfile, may have changed along with the packages
+            let state = match self.global_state(pkg_dep_graph.clone(), engine.clone()) {
+                Ok(state) => state,
+                Err(e) => {
+                    writeln!(std::io::stderr(), "unable to calculate global hash: {e}").ok();
+                    continue;
+                }
+            };
+            self.execute(Arc::new(subgraph), &state).await?;
+        }
+
+        // The stream only ends if the daemon went away
+        Err(DaemonError::Unavailable.into())
+    }
+
+    // Whether any of the files the package graph or the engine are built from
+    // changed
+    fn changes_package_graph(
+        &self,
+        pkg_dep_graph: &PackageGraph,
+        changed_files: &HashSet<AnchoredSystemPathBuf>,
+    ) -> bool {
+        let package_manager = pkg_dep_graph.package_manager();
+        let repo_root = &self.run.base.repo_root;
+        // Bun repos can still be on the binary lockfile
+        let lockfile = repo_root
+            .anchor(package_manager.lockfile_path(repo_root))
+            .ok();
+        changed_files.iter().any(|file| {
+            let is_lockfile = Some(file) == lockfile.as_ref();
+            let file = file.to_unix();
+            let file = file.as_str();
+            is_lockfile
+                || Some(file) == package_manager.workspace_configuration_path()
+                || file == "package.json"
+                || file.ends_with("/package.json")
+                || file == "turbo.json"
+                || file.ends_with("/turbo.json")
+        })
+    }
+
+    async fn load_package_graph(&self) -> Result<(PackageGraph, Engine), Error> {
+        let repo_root = &self.run.base.repo_root;
+        let root_package_json = PackageJson::load(&repo_root.join_component("package.json"))?;
+        let mut pkg_dep_graph = self
+            .run
+            .build_package_graph(self.opts, &root_package_json)
+            .await?;
+        pkg_dep_graph.validate()?;
+        let root_turbo_json = TurboJson::load(
+            repo_root,
+            &root_package_json,
+            self.opts.run_opts.single_package,
+        )?;
+        let filtered_pkgs =
+            self.run
+                .resolve_packages(self.opts, &pkg_dep_graph, self.scm, &root_turbo_json)?;
+        if self.opts.run_opts.parallel {
+            pkg_dep_graph.remove_workspace_dependencies();
+        }
+        let engine =
+            self.run
+                .build_engine(&pkg_dep_graph, self.opts, &root_turbo_json, &filtered_pkgs)?;
+        // Only replaced once everything built from it is valid
+        *self
+            .root_turbo_json
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = root_turbo_json;
+        Ok((pkg_dep_graph, engine))
+    }
+
+    fn global_state(
+        &self,
+        pkg_dep_graph: Arc<PackageGraph>,
+        engine: Arc<Engine>,
+    ) -> Result<WatchState, Error> {
+        let root_external_dependencies_hash = (!self.opts.run_opts.single_package)
+            .then(|| {
+                pkg_dep_graph
+                    .workspace_info(&WorkspaceName::Root)
+                    .map(|root| get_external_deps_hash(&root.transitive_dependencies))
+            })
+            .flatten();
+        let root_turbo_json = self.root_turbo_json();
+        let mut global_hash_inputs = get_global_hash_inputs(
+            root_external_dependencies_hash.as_deref(),
+            &self.run.base.repo_root,
+            pkg_dep_graph.package_manager(),
+            pkg_dep_graph.lockfile(),
+            &root_turbo_json.global_deps,
+            self.env_at_execution_start,
+            &root_turbo_json.global_env,
+            root_turbo_json.global_pass_through_env.as_deref(),
+            self.opts.run_opts.env_mode,
+            self.opts.run_opts.framework_inference,
+            root_turbo_json.global_dot_env.as_deref(),
+        )?;
+        let global_hash = global_hash_inputs.calculate_global_hash_from_inputs();
+        debug!("global hash: {global_hash}");
+        let global_env = global_env(self.env_at_execution_start, &global_hash_inputs)?;
+
+        Ok(WatchState {
+            pkg_dep_graph,
+            engine,
+            global_hash,
+            global_env,
+        })
+    }
+
+    fn root_turbo_json(&self) -> RwLockReadGuard<'_, TurboJson> {
+        // Only ever replaced whole, so a poisoned lock still holds a valid
+        // turbo.json
+        self.root_turbo_json
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Task outputs are ignored too, otherwise a task writing its outputs would
+    // keep triggering itself.
+    fn ignore_patterns(&self, pkg_dep_graph: &PackageGraph, engine: &Engine) -> Vec<String> {
+        let mut patterns = IGNORED_FILES.map(String::from).to_vec();
+        for (task_id, task_definition) in engine.task_definitions() {
+            let Some(package_dir) = pkg_dep_graph
+                .workspace_info(&WorkspaceName::from(task_id.package()))
+                .and_then(|info| info.package_json_path.parent())
+                .map(|package_dir| package_dir.to_unix())
+            else {
+                continue;
+            };
+            patterns.extend(task_definition.outputs.inclusions.iter().map(|output| {
+                if package_dir.as_str().is_empty() {
+                    output.clone()
+                } else {
+                    format!("{}/{output}", package_dir.as_str())
+                }
+            }));
+        }
+        patterns
+    }
+
+    async fn execute(&self, engine: Arc<Engine>, state: &WatchState) -> Result<i32, Error> {
+        let base = self.run.base;
+        let workspaces = state.pkg_dep_graph.workspaces().collect();
+        let package_inputs_hashes = PackageInputsHashes::calculate_file_hashes(
+            self.scm,
+            engine.tasks().par_bridge(),
+            workspaces,
+            engine.task_definitions(),
+            &base.repo_root,
+        )?;
+
+        let run_tracker = RunTracker::new(
+            Local::now(),
+            self.opts.synthesize_command(),
+            self.opts.scope_opts.pkg_inference_root.as_deref(),
+            self.env_at_execution_start,
+            &base.repo_root,
+            base.version(),
+            self.opts.run_opts.experimental_space_id.clone(),
+            self.api_client.clone(),
+            self.api_auth.clone(),
+            Vendor::get_user(),
+        );
+
+        let mut visitor = Visitor::new(
+            state.pkg_dep_graph.clone(),
+            self.runcache.clone(),
+            run_tracker,
+            self.opts,
+            package_inputs_hashes,
+            self.env_at_execution_start,
+            &state.global_hash,
+            self.global_env_mode,
+            base.ui,
+            false,
+            self.run.processes.clone(),
+            &base.repo_root,
+            state.global_env.clone(),
+        );
+        visitor.load_dot_env(&self.root_turbo_json())?;
+
+        // Watch runs never finish the visitor, so no run summary is written
+        let errors = visitor.visit(engine).await?;
+        for err in &errors {
+            writeln!(std::io::stderr(), "{err}").ok();
+        }
+
+        Ok(errors
+            .iter()
+            .filter_map(|err| err.exit_code())
+            .max()
+            .unwrap_or(if errors.is_empty() { 0 } else { 1 }))
+    }
+}
@@ -1,5 +1,7 @@
 mod visitor;
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 use turbopath::{AnchoredSystemPath, AnchoredSystemPathBuf, RelativeUnixPathBuf};
 pub use visitor::{Error as VisitorError, Visitor};
@@ -53,6 +55,19 @@ pub struct TaskDefinition {
     // Persistent indicates whether the Task is expected to exit or not
     // Tasks marked Persistent do not exit (e.g. --watch mode or dev servers)
     pub persistent: bool,
+
+    // Timeout is how long a single attempt of the task may run before it is
+    // stopped and considered failed
+    pub(crate) timeout: Option<Duration>,
+
+    // Retries is how many more times a failed or timed out task is run
+    // before its failure is reported
+    pub(crate) retries: u32,
+
+    // DefinedIn lists the workspaces, other than the root, whose turbo.json
+    // contributed to this definition, starting with the furthest ancestor
+    #[serde(default)]
+    pub(crate) defined_in: Vec<String>,
 }
 
 impl Default for TaskDefinition {
@@ -68,6 +83,9 @@ impl Default for TaskDefinition {
             output_mode: Default::default(),
             persistent: Default::default(),
             dot_env: Default::default(),
+            timeout: Default::default(),
+            retries: Default::default(),
+            defined_in: Default::default(),
         }
     }
 }
@@ -1,12 +1,14 @@
 use std::{
     borrow::Cow,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     io::Write,
+    path::{Path, PathBuf},
     process::Stdio,
     sync::{Arc, Mutex, OnceLock},
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
+use chrono::{DateTime, Local};
 use console::{Style, StyledObject};
 use futures::{stream::FuturesUnordered, StreamExt};
 use regex::Regex;
@@ -14,21 +16,30 @@ use tokio::{
     process::Command,
     sync::{mpsc, oneshot},
 };
-use tracing::{debug, error, Span};
-use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
-use turborepo_ci::github_header_footer;
+use tracing::{debug, error, field, Instrument, Span};
+use turbopath::{
+    AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf, RelativeUnixPathBuf,
+};
+use turborepo_cache::{CacheHitMetadata, CacheSource};
+use turborepo_ci::{Capabilities, LogGroup};
 use turborepo_env::{EnvironmentVariableMap, ResolvedEnvMode};
 use turborepo_repository::{
     package_graph::{PackageGraph, WorkspaceName, ROOT_PKG_NAME},
     package_manager::PackageManager,
 };
-use turborepo_ui::{ColorSelector, OutputClient, OutputSink, OutputWriter, PrefixedUI, UI};
+use turborepo_ui::{
+    tui::{AppSender, TuiTask},
+    ColorSelector, OutputClient, OutputSink, OutputWriter, PrefixedUI, UI,
+};
 use which::which;
 
 use crate::{
     cli::EnvMode,
-    engine::{Engine, ExecutionOptions, StopExecution},
+    config::TurboJson,
+    distributed::{RemoteExit, RemoteTask, WorkerPool},
+    engine::{Engine, ExecutionOptions, StopExecution, TaskNode},
     opts::Opts,
+    otel,
     process::{ChildExit, ProcessManager},
     run::{
         global_hash::GlobalHashableInputs,
@@ -39,13 +50,16 @@ use crate::{
         task_id::TaskId,
         RunCache, TaskCache,
     },
+    task_graph::TaskDefinition,
     task_hash::{self, PackageInputsHashes, TaskHashTracker, TaskHashTrackerState, TaskHasher},
 };
 
 // This holds the whole world
 pub struct Visitor<'a> {
     color_cache: ColorSelector,
+    dot_env: Option<DotEnvLoading>,
     dry: bool,
+    env_at_execution_start: &'a EnvironmentVariableMap,
     global_env: EnvironmentVariableMap,
     global_env_mode: EnvMode,
     manager: ProcessManager,
@@ -56,7 +70,9 @@ pub struct Visitor<'a> {
     run_tracker: RunTracker,
     sink: OutputSink<StdWriter>,
     task_hasher: TaskHasher<'a>,
+    tui_sender: Option<AppSender>,
     ui: UI,
+    workers: Option<Arc<WorkerPool>>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -78,6 +94,29 @@ pub enum Error {
     TaskHash(#[from] task_hash::Error),
     #[error(transparent)]
     RunSummary(#[from] summary::Error),
+    #[error("unable to read {path}: {source}")]
+    DotEnvRead {
+        path: AbsoluteSystemPathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Path(#[from] turbopath::PathError),
+    #[error(transparent)]
+    Env(#[from] turborepo_env::Error),
+}
+
+// The `.env` files of the root turbo.json, which are loaded into the
+// environment of every task after the task's own
+struct DotEnvLoading {
+    // Paths and contents of `globalDotEnv`, in order
+    global_files: Vec<(String, String)>,
+    // `globalEnv` and `globalPassThroughEnv`, which let variables through in
+    // strict mode along with the task's
+    global_env: Vec<String>,
+    // The variables in the global hash, which files can reference along with
+    // the ones in the task's hash
+    global_hashed_env: EnvironmentVariableMap,
 }
 
 impl<'a> Visitor<'a> {
@@ -111,7 +150,9 @@ impl<'a> Visitor<'a> {
 
         Self {
             color_cache,
+            dot_env: None,
             dry: false,
+            env_at_execution_start,
             global_env_mode,
             manager,
             opts,
@@ -121,8 +162,10 @@ impl<'a> Visitor<'a> {
             run_tracker,
             sink,
             task_hasher,
+            tui_sender: None,
             ui,
             global_env,
+            workers: None,
         }
     }
 
@@ -202,9 +245,18 @@ impl<'a> Visitor<'a> {
             // We do this calculation earlier than we do in Go due to the `task_hasher`
             // being !Send. In the future we can look at doing this right before
             // task execution instead.
-            let execution_env =
+            let mut execution_env =
                 self.task_hasher
                     .env(&info, task_env_mode, task_definition, &self.global_env)?;
+            if let Some(dot_env) = &self.dot_env {
+                execution_env.union(&self.task_dot_env(
+                    dot_env,
+                    &info,
+                    task_definition,
+                    &self.repo_root.resolve(workspace_info.package_path()),
+                    task_env_mode,
+                )?);
+            }
 
             let task_cache = self.run_cache.task_cache(
                 task_definition,
@@ -234,31 +286,53 @@ impl<'a> Visitor<'a> {
                     }
 
                     let workspace_directory = self.repo_root.resolve(workspace_info.package_path());
-
+                    let tui_task = self
+                        .tui_sender
+                        .as_ref()
+                        .map(|sender| sender.new_task(self.display_task_id(&info)));
+
+                    // Fields other than the hash are filled in as the task finishes
+                    let task_span = tracing::info_span!(
+                        target: otel::TASK_TARGET,
+                        "task",
+                        "otel.name" = %info,
+                        "otel.status_code" = field::Empty,
+                        "turbo.task.id" = %info,
+                        "turbo.task.hash" = %task_hash,
+                        "turbo.task.exit_code" = field::Empty,
+                        "turbo.task.duration_ms" = field::Empty,
+                        "turbo.cache.status" = field::Empty,
+                        "turbo.cache.source" = field::Empty,
+                    );
                     let mut exec_context = factory.exec_context(
                         info.clone(),
                         task_hash,
                         task_cache,
                         workspace_directory,
                         execution_env,
+                        task_definition,
+                        tui_task.clone(),
                     );
 
-                    let output_client = self.output_client(&info);
+                    let output_client = self.output_client(tui_task);
                     let tracker = self.run_tracker.track_task(info.clone().into_owned());
                     let spaces_client = self.run_tracker.spaces_task_client();
                     let parent_span = Span::current();
 
-                    tasks.push(tokio::spawn(async move {
-                        exec_context
-                            .execute(
-                                parent_span.id(),
-                                tracker,
-                                output_client,
-                                callback,
-                                spaces_client,
-                            )
-                            .await;
-                    }));
+                    tasks.push(tokio::spawn(
+                        async move {
+                            exec_context
+                                .execute(
+                                    parent_span.id(),
+                                    tracker,
+                                    output_client,
+                                    callback,
+                                    spaces_client,
+                                )
+                                .await;
+                        }
+                        .instrument(task_span),
+                    ));
                 }
             }
         }
@@ -338,7 +412,7 @@ impl<'a> Visitor<'a> {
         OutputSink::new(out, err)
     }
 
-    fn output_client(&self, task_id: &TaskId) -> OutputClient<impl std::io::Write> {
+    fn output_client(&self, tui_task: Option<TuiTask>) -> OutputClient<impl std::io::Write> {
         let behavior = match self.opts.run_opts.log_order {
             crate::opts::ResolvedLogOrder::Stream if self.run_tracker.spaces_enabled() => {
                 turborepo_ui::OutputClientBehavior::InMemoryBuffer
@@ -349,20 +423,26 @@ impl<'a> Visitor<'a> {
             crate::opts::ResolvedLogOrder::Grouped => turborepo_ui::OutputClientBehavior::Grouped,
         };
 
-        let mut logger = self.sink.logger(behavior);
-        if self.opts.run_opts.is_github_actions {
-            let package = if self.opts.run_opts.single_package {
-                None
-            } else {
-                Some(task_id.package())
+        if let Some(task) = tui_task {
+            // Each task logs to its own pane so there is no interleaving to
+            // avoid by grouping
+            let behavior = match behavior {
+                turborepo_ui::OutputClientBehavior::Grouped => {
+                    turborepo_ui::OutputClientBehavior::Passthrough
+                }
+                behavior => behavior,
             };
-            let (header, footer) = github_header_footer(package, task_id.task());
-            logger.with_header_footer(Some(header), Some(footer));
+            return OutputSink::new(task.clone().into(), task.into()).logger(behavior);
         }
-        logger
+
+        self.sink.logger(behavior)
     }
 
     fn prefix<'b>(&self, task_id: &'b TaskId) -> Cow<'b, str> {
+        // The dashboard already shows which task a log pane belongs to
+        if self.tui_sender.is_some() {
+            return "".into();
+        }
         match self.opts.run_opts.log_prefix {
             crate::opts::ResolvedLogPrefix::Task if self.opts.run_opts.single_package => {
                 task_id.task().into()
@@ -374,6 +454,14 @@ impl<'a> Visitor<'a> {
         }
     }
 
+    // Name of the group a task's logs are collapsed into by the CI vendor
+    fn log_group_name(&self, task_id: &TaskId) -> String {
+        match self.opts.run_opts.single_package {
+            true => task_id.task().to_string(),
+            false => format!("{}:{}", task_id.package(), task_id.task()),
+        }
+    }
+
     // Task ID as displayed in error messages
     fn display_task_id(&self, task_id: &TaskId) -> String {
         match self.opts.run_opts.single_package {
@@ -384,7 +472,7 @@ impl<'a> Visitor<'a> {
 
     fn prefixed_ui<W: Write>(
         ui: UI,
-        is_github_actions: bool,
+        grouped_by_ci: bool,
         client: &OutputClient<W>,
         prefix: StyledObject<String>,
     ) -> PrefixedUI<OutputWriter<'_, W>> {
@@ -395,7 +483,7 @@ impl<'a> Visitor<'a> {
                 Style::new().apply_to(format!("{}ERROR: ", ui.apply(prefix.clone()))),
             )
             .with_warn_prefix(prefix);
-        if is_github_actions {
+        if grouped_by_ci {
             prefixed_ui = prefixed_ui
                 .with_error_prefix(Style::new().apply_to("[ERROR] ".to_string()))
                 .with_warn_prefix(Style::new().apply_to("[WARN] ".to_string()));
@@ -412,14 +500,115 @@ impl<'a> Visitor<'a> {
     pub fn dry_run(&mut self) {
         self.dry = true;
     }
+
+    /// Load the variables of the `.env` files in turbo.json into the
+    /// environment of tasks, if the root turbo.json asks for it
+    pub fn load_dot_env(&mut self, root_turbo_json: &TurboJson) -> Result<(), Error> {
+        if !root_turbo_json.load_dot_env {
+            return Ok(());
+        }
+        let global_files = root_turbo_json
+            .global_dot_env
+            .iter()
+            .flatten()
+            .map(|path| read_dot_env(self.repo_root, path))
+            .collect::<Result<_, _>>()?;
+        let global_env = root_turbo_json
+            .global_env
+            .iter()
+            .chain(root_turbo_json.global_pass_through_env.iter().flatten())
+            .cloned()
+            .collect();
+        let global_hashed_env = turborepo_env::get_global_hashable_env_vars(
+            self.env_at_execution_start,
+            &root_turbo_json.global_env,
+        )?
+        .all;
+        self.dot_env = Some(DotEnvLoading {
+            global_files,
+            global_env,
+            global_hashed_env,
+        });
+        Ok(())
+    }
+
+    // Variables from the `.env` files of a task that aren't already set. In
+    // strict mode only the ones a task could get from the environment are kept.
+    fn task_dot_env(
+        &self,
+        dot_env: &DotEnvLoading,
+        task_id: &TaskId,
+        task_definition: &TaskDefinition,
+        workspace_directory: &AbsoluteSystemPath,
+        task_env_mode: ResolvedEnvMode,
+    ) -> Result<EnvironmentVariableMap, Error> {
+        let mut files = task_definition
+            .dot_env
+            .iter()
+            .flatten()
+            .map(|path| read_dot_env(workspace_directory, path))
+            .collect::<Result<Vec<_>, _>>()?;
+        files.extend(dot_env.global_files.iter().cloned());
+
+        let mut hashed_env = self.task_hasher.hashed_env(task_id)?;
+        hashed_env.union(&dot_env.global_hashed_env);
+        let vars =
+            turborepo_env::dot_env::resolve(self.env_at_execution_start, &hashed_env, &files)?;
+        match task_env_mode {
+            ResolvedEnvMode::Loose => Ok(vars),
+            ResolvedEnvMode::Strict => {
+                let allowed = dot_env
+                    .global_env
+                    .iter()
+                    .chain(&task_definition.env)
+                    .chain(task_definition.pass_through_env.iter().flatten())
+                    .collect::<Vec<_>>();
+                Ok(vars.from_wildcards(&allowed)?)
+            }
+        }
+    }
+
+    /// Run tasks on these workers rather than locally. Tasks whose outputs
+    /// can't go through the cache still run locally.
+    pub fn use_workers(&mut self, workers: WorkerPool) {
+        self.workers = Some(Arc::new(workers));
+    }
+
+    /// Report task progress and output to the dashboard instead of the
+    /// terminal
+    pub fn use_tui(&mut self, sender: AppSender) {
+        self.tui_sender = Some(sender);
+    }
+
+    /// Names of the tasks that will run, as the dashboard displays them
+    pub fn tui_tasks(&self, engine: &Engine) -> Vec<String> {
+        let mut tasks = engine
+            .tasks()
+            .filter_map(|node| match node {
+                TaskNode::Task(task_id) => Some(task_id),
+                TaskNode::Root => None,
+            })
+            // Tasks without a script are skipped when visiting
+            .filter(|task_id| {
+                self.package_graph
+                    .workspace_info(&WorkspaceName::from(task_id.package()))
+                    .and_then(|info| info.package_json.scripts.get(task_id.task()))
+                    .map_or(false, |script| !script.is_empty())
+            })
+            .map(|task_id| self.display_task_id(task_id))
+            .collect::<Vec<_>>();
+        tasks.sort();
+        tasks
+    }
 }
 
-// A tiny enum that allows us to use the same type for stdout and stderr without
-// the use of Box<dyn Write>
+// A tiny enum that allows us to use the same type for stdout, stderr, and
+// dashboard panes without the use of Box<dyn Write>
 enum StdWriter {
     Out(std::io::Stdout),
     Err(std::io::Stderr),
     Null(std::io::Sink),
+    Tui(TuiTask),
 }
 
 impl StdWriter {
@@ -428,6 +617,7 @@ impl StdWriter {
             StdWriter::Out(out) => out,
             StdWriter::Err(err) => err,
             StdWriter::Null(null) => null,
+            StdWriter::Tui(task) => task,
         }
     }
 }
@@ -450,6 +640,12 @@ impl From<std::io::Sink> for StdWriter {
     }
 }
 
+impl From<TuiTask> for StdWriter {
+    fn from(value: TuiTask) -> Self {
+        Self::Tui(value)
+    }
+}
+
 impl std::io::Write for StdWriter {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
         self.writer().write(buf)
@@ -460,6 +656,21 @@ impl std::io::Write for StdWriter {
     }
 }
 
+// Missing files are skipped, as they are when hashing
+fn read_dot_env(
+    dir: &AbsoluteSystemPath,
+    path: &RelativeUnixPathBuf,
+) -> Result<(String, String), Error> {
+    let path = dir.join_unix_path(path)?;
+    let contents = path
+        .read_existing_to_string_or(Ok(""))
+        .map_err(|source| Error::DotEnvRead {
+            path: path.clone(),
+            source,
+        })?;
+    Ok((path.to_string(), contents))
+}
+
 fn turbo_regex() -> &'static Regex {
     static RE: OnceLock<Regex> = OnceLock::new();
     RE.get_or_init(|| Regex::new(r"(?:^|\s)turbo(?:$|\s)").unwrap())
@@ -480,14 +691,15 @@ enum TaskErrorCause {
     Spawn { msg: String },
     #[error("command {command} exited ({exit_code})")]
     Exit { command: String, exit_code: i32 },
+    #[error("command {command} timed out after {timeout}")]
+    Timeout { command: String, timeout: String },
+    #[error("unable to run on worker: {msg}")]
+    Worker { msg: String },
 }
 
 impl TaskError {
     pub fn exit_code(&self) -> Option<i32> {
-        match self.cause {
-            TaskErrorCause::Exit { exit_code, .. } => Some(exit_code),
-            _ => None,
-        }
+        self.cause.exit_code()
     }
 
     fn from_spawn(task_id: String, err: std::io::Error) -> Self {
@@ -517,6 +729,26 @@ impl TaskErrorCause {
     fn from_execution(command: String, exit_code: i32) -> Self {
         TaskErrorCause::Exit { command, exit_code }
     }
+
+    fn from_timeout(command: String, timeout: Duration) -> Self {
+        TaskErrorCause::Timeout {
+            command,
+            timeout: humantime::format_duration(timeout).to_string(),
+        }
+    }
+
+    fn from_worker(msg: impl ToString) -> Self {
+        TaskErrorCause::Worker {
+            msg: msg.to_string(),
+        }
+    }
+
+    fn exit_code(&self) -> Option<i32> {
+        match self {
+            TaskErrorCause::Exit { exit_code, .. } => Some(*exit_code),
+            _ => None,
+        }
+    }
 }
 
 struct ExecContextFactory<'a> {
@@ -541,6 +773,7 @@ impl<'a> ExecContextFactory<'a> {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn exec_context(
         &self,
         task_id: TaskId<'static>,
@@ -548,13 +781,34 @@ impl<'a> ExecContextFactory<'a> {
         task_cache: TaskCache,
         workspace_directory: AbsoluteSystemPathBuf,
         execution_env: EnvironmentVariableMap,
+        task_definition: &TaskDefinition,
+        tui_task: Option<TuiTask>,
     ) -> ExecContext {
         let task_id_for_display = self.visitor.display_task_id(&task_id);
         let pass_through_args = self.visitor.opts.run_opts.args_for_task(&task_id);
+        // Persistent tasks never finish, so there would be no outputs to hand back
+        let remote = self
+            .visitor
+            .workers
+            .as_ref()
+            .filter(|_| task_cache.is_shared() && !task_definition.persistent)
+            .map(|workers| RemoteExecution {
+                workers: workers.clone(),
+                directory: AnchoredSystemPathBuf::relative_path_between(
+                    self.visitor.repo_root,
+                    &workspace_directory,
+                ),
+                dependency_hashes: self.dependency_hashes(&task_id),
+            });
+        // The dashboard shows each task's logs in its own pane instead
+        let log_group = tui_task
+            .is_none()
+            .then(|| self.visitor.log_group_name(&task_id));
         ExecContext {
             engine: self.engine.clone(),
             ui: self.visitor.ui,
-            is_github_actions: self.visitor.opts.run_opts.is_github_actions,
+            ci: self.visitor.opts.run_opts.ci,
+            log_group,
             pretty_prefix: self
                 .visitor
                 .color_cache
@@ -564,6 +818,8 @@ impl<'a> ExecContextFactory<'a> {
             task_cache,
             hash_tracker: self.visitor.task_hasher.task_hash_tracker(),
             package_manager: *self.visitor.package_graph.package_manager(),
+            package_manager_binary: which(self.visitor.package_graph.package_manager().command())
+                .ok(),
             workspace_directory,
             manager: self.manager.clone(),
             task_hash,
@@ -571,9 +827,26 @@ impl<'a> ExecContextFactory<'a> {
             continue_on_error: self.visitor.opts.run_opts.continue_on_error,
             pass_through_args,
             errors: self.errors.clone(),
+            timeout: task_definition.timeout,
+            retries: task_definition.retries,
+            tui_task,
+            remote,
         }
     }
 
+    fn dependency_hashes(&self, task_id: &TaskId) -> Vec<String> {
+        let hash_tracker = self.visitor.task_hasher.task_hash_tracker();
+        self.engine
+            .dependencies(task_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|node| match node {
+                TaskNode::Task(dependency) => hash_tracker.hash(dependency),
+                TaskNode::Root => None,
+            })
+            .collect()
+    }
+
     pub fn dry_run_exec_context(
         &self,
         task_id: TaskId<'static>,
@@ -590,13 +863,15 @@ impl<'a> ExecContextFactory<'a> {
 struct ExecContext {
     engine: Arc<Engine>,
     ui: UI,
-    is_github_actions: bool,
+    ci: Capabilities,
+    log_group: Option<String>,
     pretty_prefix: StyledObject<String>,
     task_id: TaskId<'static>,
     task_id_for_display: String,
     task_cache: TaskCache,
     hash_tracker: TaskHashTracker,
     package_manager: PackageManager,
+    package_manager_binary: Option<PathBuf>,
     workspace_directory: AbsoluteSystemPathBuf,
     manager: ProcessManager,
     task_hash: String,
@@ -604,6 +879,24 @@ struct ExecContext {
     continue_on_error: bool,
     pass_through_args: Option<Vec<String>>,
     errors: Arc<Mutex<Vec<TaskError>>>,
+    timeout: Option<Duration>,
+    retries: u32,
+    tui_task: Option<TuiTask>,
+    remote: Option<RemoteExecution>,
+}
+
+// Set when a task runs on a worker rather than locally
+struct RemoteExecution {
+    workers: Arc<WorkerPool>,
+    directory: AnchoredSystemPathBuf,
+    dependency_hashes: Vec<String>,
+}
+
+// How a single attempt at running a task went
+enum Attempt {
+    Succeeded,
+    Failed(TaskErrorCause),
+    TimedOut(TaskErrorCause),
 }
 
 enum ExecOutcome {
@@ -636,12 +929,35 @@ impl ExecContext {
         &mut self,
         parent_span_id: Option<tracing::Id>,
         tracker: TaskTracker<()>,
-        output_client: OutputClient<impl std::io::Write>,
+        mut output_client: OutputClient<impl std::io::Write>,
         callback: oneshot::Sender<Result<(), StopExecution>>,
         spaces_client: Option<SpacesTaskClient>,
     ) {
-        let tracker = tracker.start().await;
-        let mut result = self.execute_inner(parent_span_id, &output_client).await;
+        let started_at = SystemTime::now();
+        let mut tracker = tracker.start().await;
+        if let Some(task) = &self.tui_task {
+            task.start();
+        }
+        let span = tracing::debug_span!("execute_task", task = %self.task_id.task());
+        span.follows_from(parent_span_id);
+        let mut result = self
+            .execute_inner(&output_client, &mut tracker)
+            .instrument(span)
+            .await;
+
+        // Grouped logs are written once the task finishes, so the group can
+        // reflect how it went
+        if let Some(name) = &self.log_group {
+            let group = LogGroup {
+                name,
+                started_at,
+                ended_at: SystemTime::now(),
+                failed: matches!(result, ExecOutcome::Task { .. }),
+            };
+            if let Some((header, footer)) = self.ci.header_footer(&group) {
+                output_client.with_header_footer(Some(header), Some(footer));
+            }
+        }
 
         let logs = match output_client.finish() {
             Ok(logs) => logs,
@@ -652,12 +968,21 @@ impl ExecContext {
             }
         };
 
+        if let Some(task) = &self.tui_task {
+            match result {
+                ExecOutcome::Success(SuccessOutcome::CacheHit) => task.succeeded(true),
+                ExecOutcome::Success(SuccessOutcome::Run) => task.succeeded(false),
+                ExecOutcome::Internal | ExecOutcome::Task { .. } => task.failed(),
+            }
+        }
+
         match result {
             ExecOutcome::Success(outcome) => {
                 let task_summary = match outcome {
                     SuccessOutcome::CacheHit => tracker.cached().await,
                     SuccessOutcome::Run => tracker.build_succeeded(0).await,
                 };
+                self.record_span(&task_summary);
                 callback.send(Ok(())).ok();
                 if let Some(client) = spaces_client {
                     let logs = logs.expect("spaces enabled logs should be collected");
@@ -672,6 +997,7 @@ impl ExecContext {
             }
             ExecOutcome::Task { exit_code, message } => {
                 let task_summary = tracker.build_failed(exit_code, message).await;
+                self.record_span(&task_summary);
                 callback
                     .send(match self.continue_on_error {
                         true => Ok(()),
@@ -706,17 +1032,14 @@ impl ExecContext {
 
     async fn execute_inner(
         &mut self,
-        parent_span_id: Option<tracing::Id>,
         output_client: &OutputClient<impl std::io::Write>,
+        tracker: &mut TaskTracker<DateTime<Local>>,
     ) -> ExecOutcome {
-        let span = tracing::debug_span!("execute_task", task = %self.task_id.task());
         let task_start = Instant::now();
-        span.follows_from(parent_span_id);
-        let _enter = span.enter();
 
         let mut prefixed_ui = Visitor::prefixed_ui(
             self.ui,
-            self.is_github_actions,
+            self.ci.group.is_some(),
             output_client,
             self.pretty_prefix.clone(),
         );
@@ -738,42 +1061,123 @@ impl ExecContext {
             }
         }
 
-        let Ok(package_manager_binary) = which(self.package_manager.command()) else {
+        let Some(package_manager_binary) = self.package_manager_binary.clone() else {
             return ExecOutcome::Internal;
         };
 
-        let mut cmd = Command::new(package_manager_binary);
-        let mut args = vec!["run".to_string(), self.task_id.task().to_string()];
-        if let Some(pass_through_args) = &self.pass_through_args {
-            args.extend(
-                self.package_manager
-                    .arg_separator(pass_through_args.as_slice())
-                    .map(|s| s.to_string()),
-            );
-            args.extend(pass_through_args.iter().cloned());
-        }
-        cmd.args(args);
-        cmd.current_dir(self.workspace_directory.as_path());
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
+        let mut attempt = 0;
+        let (cause, mut stdout_writer) = loop {
+            // Each attempt opens a fresh writer, which truncates the log file, so
+            // only the final attempt's output ends up in the cache
+            let mut stdout_writer = match self
+                .task_cache
+                .output_writer(self.pretty_prefix.clone(), output_client.stdout())
+            {
+                Ok(w) => w,
+                Err(e) => {
+                    error!("failed to capture outputs for \"{}\": {e}", self.task_id);
+                    return ExecOutcome::Internal;
+                }
+            };
+            let outcome = match &self.remote {
+                Some(remote) => Ok(self.remote_attempt(remote, &mut stdout_writer).await),
+                None => {
+                    self.local_attempt(
+                        &package_manager_binary,
+                        &mut stdout_writer,
+                        &mut prefixed_ui,
+                    )
+                    .await
+                }
+            };
+            let (cause, timed_out) = match outcome {
+                Ok(Attempt::Succeeded) => break (None, stdout_writer),
+                Ok(Attempt::Failed(cause)) => (cause, false),
+                Ok(Attempt::TimedOut(cause)) => (cause, true),
+                Err(outcome) => return outcome,
+            };
 
-        // We clear the env before populating it with variables we expect
-        cmd.env_clear();
-        cmd.envs(self.execution_env.iter());
-        // Always last to make sure it overwrites any user configured env var.
-        cmd.env("TURBO_HASH", &self.task_hash);
+            if attempt < self.retries {
+                if let Err(e) = stdout_writer.flush() {
+                    error!("error flushing logs: {e}");
+                }
+                drop(stdout_writer);
+                attempt += 1;
+                tracker.retried();
+                prefixed_ui.warn(format!(
+                    "{cause}, retrying (attempt {} of {})",
+                    attempt + 1,
+                    self.retries + 1
+                ));
+                continue;
+            }
+            if timed_out {
+                tracker.timed_out();
+            }
+            break (Some(cause), stdout_writer);
+        };
+        let task_duration = task_start.elapsed();
 
-        let mut stdout_writer = match self
-            .task_cache
-            .output_writer(self.pretty_prefix.clone(), output_client.stdout())
-        {
-            Ok(w) => w,
-            Err(e) => {
-                error!("failed to capture outputs for \"{}\": {e}", self.task_id);
-                return ExecOutcome::Internal;
+        let Some(error) = cause else {
+            if let Err(e) = stdout_writer.flush() {
+                error!("{e}");
+            } else if self.remote.is_some() {
+                // The worker already cached the outputs, we only need to bring
+                // them over
+                match self.task_cache.fetch_outputs().await {
+                    Ok(true) => self.hash_tracker.insert_expanded_outputs(
+                        self.task_id.clone(),
+                        self.task_cache.expanded_outputs().to_vec(),
+                    ),
+                    Ok(false) => {
+                        error!("outputs of \"{}\" are missing from the cache", self.task_id)
+                    }
+                    Err(e) => error!("error fetching outputs: {e}"),
+                }
+            } else if let Err(e) = self
+                .task_cache
+                .save_outputs(&mut prefixed_ui, task_duration)
+                .await
+            {
+                error!("error caching output: {e}");
+            } else {
+                self.hash_tracker.insert_expanded_outputs(
+                    self.task_id.clone(),
+                    self.task_cache.expanded_outputs().to_vec(),
+                );
             }
+
+            return ExecOutcome::Success(SuccessOutcome::Run);
         };
 
+        // If there was an error, flush the buffered output
+        if let Err(e) = stdout_writer.flush() {
+            error!("error flushing logs: {e}");
+        }
+        if let Err(e) = self.task_cache.on_error(&mut prefixed_ui) {
+            error!("error reading logs: {e}");
+        }
+        let exit_code = error.exit_code();
+        let message = error.to_string();
+        if self.continue_on_error {
+            prefixed_ui.warn("command finished with error, but continuing...");
+        } else {
+            prefixed_ui.error(format!("command finished with error: {error}"));
+        }
+        self.errors.lock().expect("lock poisoned").push(TaskError {
+            task_id: self.task_id_for_display.clone(),
+            cause: error,
+        });
+        ExecOutcome::Task { exit_code, message }
+    }
+
+    async fn local_attempt(
+        &self,
+        package_manager_binary: &Path,
+        stdout_writer: &mut impl Write,
+        prefixed_ui: &mut PrefixedUI<impl Write>,
+    ) -> Result<Attempt, ExecOutcome> {
+        let cmd = self.command(package_manager_binary);
         let mut process = match self.manager.spawn(cmd, Duration::from_millis(500)) {
             Some(Ok(child)) => child,
             // Turbo was unable to spawn a process
@@ -785,84 +1189,163 @@ impl ExecContext {
                     .lock()
                     .expect("lock poisoned")
                     .push(TaskError::from_spawn(self.task_id_for_display.clone(), e));
-                return ExecOutcome::Task {
+                return Err(ExecOutcome::Task {
                     exit_code: None,
                     message: error_string,
-                };
+                });
             }
             // Turbo is shutting down
             None => {
-                return ExecOutcome::Internal;
+                return Err(ExecOutcome::Internal);
             }
         };
 
-        let exit_status = match process
-            .wait_with_piped_outputs(&mut stdout_writer, None)
-            .await
-        {
-            Ok(Some(exit_status)) => exit_status,
-            Err(e) => {
-                error!("unable to pipe outputs from command: {e}");
-                return ExecOutcome::Internal;
-            }
-            Ok(None) => {
+        let wait = process.wait_with_piped_outputs(stdout_writer, None);
+        // `None` indicates that the task ran past its timeout
+        let exit_status = match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, wait).await.ok(),
+            None => Some(wait.await),
+        };
+
+        match exit_status {
+            Some(Ok(Some(ChildExit::Finished(Some(0))))) => Ok(Attempt::Succeeded),
+            Some(Ok(Some(ChildExit::Finished(Some(code))))) => Ok(Attempt::Failed(
+                TaskErrorCause::from_execution(process.label().to_string(), code),
+            )),
+            // All of these indicate a failure where we don't know how to recover
+            Some(Ok(Some(
+                ChildExit::Finished(None)
+                | ChildExit::Killed
+                | ChildExit::KilledExternal
+                | ChildExit::Failed,
+            ))) => Err(ExecOutcome::Internal),
+            Some(Ok(None)) => {
                 // TODO: how can this happen? we only update the
                 // exit status with Some and it is only initialized with
                 // None. Is it still running?
                 error!("unable to determine why child exited");
-                return ExecOutcome::Internal;
+                Err(ExecOutcome::Internal)
+            }
+            Some(Err(e)) => {
+                error!("unable to pipe outputs from command: {e}");
+                Err(ExecOutcome::Internal)
             }
+            None => {
+                // Stopping sends a SIGINT and escalates to a kill if the
+                // task hasn't exited by the manager's stop timeout
+                process.stop().await;
+                let timeout = self.timeout.expect("only times out with a timeout");
+                Ok(Attempt::TimedOut(TaskErrorCause::from_timeout(
+                    process.label().to_string(),
+                    timeout,
+                )))
+            }
+        }
+    }
+
+    // Problems reaching the worker are reported as task failures, so that
+    // they can be retried like any other
+    async fn remote_attempt(
+        &self,
+        remote: &RemoteExecution,
+        stdout_writer: &mut impl Write,
+    ) -> Attempt {
+        let program = self.package_manager.command().to_string();
+        let args = self.args();
+        let label = format!("({}) {} {}", remote.directory, program, args.join(" "));
+
+        let mut env = self
+            .execution_env
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<HashMap<_, _>>();
+        env.insert("TURBO_HASH".to_string(), self.task_hash.clone());
+
+        let task = RemoteTask {
+            task_id: self.task_id.to_string(),
+            hash: self.task_hash.clone(),
+            pass_through_args: self.pass_through_args.clone().unwrap_or_default(),
+            env,
+            outputs: self.task_cache.outputs().clone(),
+            dependency_hashes: remote.dependency_hashes.clone(),
+        };
+        let run = remote.workers.run(task, stdout_writer);
+        let exit = match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, run).await {
+                Ok(exit) => exit,
+                // Dropping the run stops the task on the worker
+                Err(_) => return Attempt::TimedOut(TaskErrorCause::from_timeout(label, timeout)),
+            },
+            None => run.await,
         };
-        let task_duration = task_start.elapsed();
 
-        match exit_status {
-            ChildExit::Finished(Some(0)) => {
-                if let Err(e) = stdout_writer.flush() {
-                    error!("{e}");
-                } else if let Err(e) = self
-                    .task_cache
-                    .save_outputs(&mut prefixed_ui, task_duration)
-                    .await
-                {
-                    error!("error caching output: {e}");
-                } else {
-                    self.hash_tracker.insert_expanded_outputs(
-                        self.task_id.clone(),
-                        self.task_cache.expanded_outputs().to_vec(),
-                    );
-                }
+        match exit {
+            Ok(RemoteExit::Finished(0)) => Attempt::Succeeded,
+            Ok(RemoteExit::Finished(code)) => {
+                Attempt::Failed(TaskErrorCause::from_execution(label, code))
+            }
+            Ok(RemoteExit::Killed) => {
+                Attempt::Failed(TaskErrorCause::from_worker("the task was killed"))
+            }
+            Err(e) => Attempt::Failed(TaskErrorCause::from_worker(e)),
+        }
+    }
+
+    fn args(&self) -> Vec<String> {
+        let mut args = vec!["run".to_string(), self.task_id.task().to_string()];
+        if let Some(pass_through_args) = &self.pass_through_args {
+            args.extend(
+                self.package_manager
+                    .arg_separator(pass_through_args.as_slice())
+                    .map(|s| s.to_string()),
+            );
+            args.extend(pass_through_args.iter().cloned());
+        }
+        args
+    }
+
+    fn command(&self, package_manager_binary: &Path) -> Command {
+        let mut cmd = Command::new(package_manager_binary);
+        cmd.args(self.args());
+        cmd.current_dir(self.workspace_directory.as_path());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        // We clear the env before populating it with variables we expect
+        cmd.env_clear();
+        cmd.envs(self.execution_env.iter());
+        // Always last to make sure it overwrites any user configured env var.
+        cmd.env("TURBO_HASH", &self.task_hash);
+        cmd
+    }
 
-                ExecOutcome::Success(SuccessOutcome::Run)
+    // Fills in the task's span, which this runs within, once the task is done
+    fn record_span(&self, summary: &TaskExecutionSummary) {
+        let span = Span::current();
+        span.record(
+            "turbo.task.duration_ms",
+            summary.end_time - summary.start_time,
+        );
+        if let Some(exit_code) = summary.exit_code {
+            span.record("turbo.task.exit_code", exit_code);
+        }
+        if summary.is_failure() {
+            span.record("otel.status_code", "ERROR");
+        }
+        match self.hash_tracker.cache_status(&self.task_id) {
+            Some(CacheHitMetadata { source, .. }) => {
+                span.record("turbo.cache.status", "HIT");
+                span.record(
+                    "turbo.cache.source",
+                    match source {
+                        CacheSource::Local => "LOCAL",
+                        CacheSource::Remote => "REMOTE",
+                    },
+                );
             }
-            ChildExit::Finished(Some(code)) => {
-                // If there was an error, flush the buffered output
-                if let Err(e) = stdout_writer.flush() {
-                    error!("error flushing logs: {e}");
-                }
-                if let Err(e) = self.task_cache.on_error(&mut prefixed_ui) {
-                    error!("error reading logs: {e}");
-                }
-                let error = TaskErrorCause::from_execution(process.label().to_string(), code);
-                let message = error.to_string();
-                if self.continue_on_error {
-                    prefixed_ui.warn("command finished with error, but continuing...");
-                } else {
-                    prefixed_ui.error(format!("command finished with error: {error}"));
-                }
-                self.errors.lock().expect("lock poisoned").push(TaskError {
-                    task_id: self.task_id_for_display.clone(),
-                    cause: error,
-                });
-                ExecOutcome::Task {
-                    exit_code: Some(code),
-                    message,
-                }
+            None => {
+                span.record("turbo.cache.status", "MISS");
             }
-            // All of these indicate a failure where we don't know how to recover
-            ChildExit::Finished(None)
-            | ChildExit::Killed
-            | ChildExit::KilledExternal
-            | ChildExit::Failed => ExecOutcome::Internal,
         }
     }
 
@@ -903,3 +1386,166 @@ impl DryRunExecContext {
         tracker.dry_run().await;
     }
 }
+
+#[cfg(all(test, unix))]
+mod test {
+    use anyhow::Result;
+    use serde_json::json;
+    use turborepo_repository::package_graph::WorkspaceInfo;
+    use turborepo_ui::OutputClientBehavior;
+
+    use super::*;
+    use crate::{
+        opts::RunCacheOpts,
+        process::ChildExit,
+        run::summary::ExecutionTracker,
+        test_utils::{path_env, TestRepo},
+    };
+
+    struct TestTask {
+        repo: TestRepo,
+        context: ExecContext,
+    }
+
+    // Runs `script` with `sh` in place of the package manager, so that tests
+    // don't depend on npm or node: `sh run build` runs the package's `run` file
+    fn test_task(script: &str, timeout: Option<Duration>, retries: u32) -> Result<TestTask> {
+        let repo = TestRepo::new(json!({"build": script}))?;
+        repo.package_dir()
+            .join_component("run")
+            .create_with_contents(script)?;
+
+        let cache = repo.cache(&repo.root.join_component(".cache"))?;
+        let ui = UI::new(true);
+        let run_cache = Arc::new(RunCache::new(
+            cache,
+            &repo.root,
+            &RunCacheOpts::default(),
+            ColorSelector::default(),
+            None,
+            ui,
+            false,
+        ));
+
+        let task_id = TaskId::new("a", "build").into_owned();
+        let task_definition = TaskDefinition {
+            timeout,
+            retries,
+            ..Default::default()
+        };
+        let workspace_info = WorkspaceInfo {
+            package_json_path: AnchoredSystemPathBuf::from_raw("packages/a/package.json")?,
+            ..Default::default()
+        };
+        let task_cache =
+            run_cache.task_cache(&task_definition, &workspace_info, task_id.clone(), "abc123");
+
+        let context = ExecContext {
+            engine: Arc::new(Engine::default().seal()),
+            ui,
+            ci: Capabilities::default(),
+            log_group: None,
+            pretty_prefix: console::style("a:build: ".to_string()),
+            task_id_for_display: task_id.to_string(),
+            task_id,
+            task_cache,
+            hash_tracker: TaskHashTracker::new(HashMap::new()),
+            package_manager: PackageManager::Npm,
+            package_manager_binary: Some(PathBuf::from("/bin/sh")),
+            workspace_directory: repo.package_dir(),
+            manager: ProcessManager::new(),
+            task_hash: "abc123".to_string(),
+            execution_env: path_env().into(),
+            continue_on_error: false,
+            pass_through_args: None,
+            errors: Arc::new(Mutex::new(Vec::new())),
+            timeout: task_definition.timeout,
+            retries: task_definition.retries,
+            tui_task: None,
+            remote: None,
+        };
+
+        Ok(TestTask { repo, context })
+    }
+
+    async fn run(context: &mut ExecContext) -> ExecOutcome {
+        let sink = OutputSink::new(Vec::new(), Vec::new());
+        let output_client = sink.logger(OutputClientBehavior::Passthrough);
+        let execution_tracker = ExecutionTracker::new();
+        let mut tracker = execution_tracker
+            .task_tracker(context.task_id.clone())
+            .start()
+            .await;
+        context.execute_inner(&output_client, &mut tracker).await
+    }
+
+    #[tokio::test]
+    async fn test_flaky_task_succeeds_on_retry() -> Result<()> {
+        // Fails the first time it runs, and succeeds once the marker exists
+        let script = "if [ -e attempted ]; then echo second attempt; else touch attempted; echo \
+                      first attempt; exit 1; fi";
+        let TestTask { repo, mut context } = test_task(script, None, 1)?;
+
+        let outcome = run(&mut context).await;
+        assert!(matches!(outcome, ExecOutcome::Success(SuccessOutcome::Run)));
+        assert!(context.errors.lock().unwrap().is_empty());
+
+        // Only the attempt that succeeded is kept in the log
+        let log = repo
+            .package_dir()
+            .join_components(&[".turbo", "turbo-build.log"])
+            .read_to_string()?;
+        assert!(log.lines().any(|line| line == "second attempt"), "{log}");
+        assert!(!log.lines().any(|line| line == "first attempt"), "{log}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_task_fails_once_retries_run_out() -> Result<()> {
+        let TestTask { repo, mut context } =
+            test_task("echo attempt >> attempts; exit 3", None, 1)?;
+
+        let outcome = run(&mut context).await;
+        assert!(
+            matches!(
+                outcome,
+                ExecOutcome::Task {
+                    exit_code: Some(3),
+                    ..
+                }
+            ),
+            "{:?}",
+            context.errors.lock().unwrap()
+        );
+        let attempts = repo
+            .package_dir()
+            .join_component("attempts")
+            .read_to_string()?;
+        assert_eq!(attempts.lines().count(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills_task() -> Result<()> {
+        let TestTask {
+            repo: _repo,
+            mut context,
+        } = test_task("sleep 10", Some(Duration::from_millis(200)), 0)?;
+
+        let outcome = run(&mut context).await;
+        assert!(matches!(outcome, ExecOutcome::Task { .. }));
+        assert!(matches!(
+            context.errors.lock().unwrap().as_slice(),
+            [TaskError {
+                cause: TaskErrorCause::Timeout { .. },
+                ..
+            }]
+        ));
+
+        // The task was stopped rather than left to exit on its own
+        let mut children = context.manager.children();
+        assert_eq!(children.len(), 1);
+        assert!(matches!(children[0].wait().await, Some(ChildExit::Killed)));
+        Ok(())
+    }
+}
@@ -381,6 +381,16 @@ impl<'a> TaskHasher<'a> {
         self.task_hash_tracker.clone()
     }
 
+    /// The environment variables that went into the hash of a task, not
+    /// counting the ones in the global hash
+    pub fn hashed_env(&self, task_id: &TaskId) -> Result<EnvironmentVariableMap, Error> {
+        let tracker_env = self
+            .task_hash_tracker
+            .env_vars(task_id)
+            .ok_or_else(|| Error::MissingEnvVars(task_id.clone().into_owned()))?;
+        Ok(tracker_env.all)
+    }
+
     pub fn env(
         &self,
         task_id: &TaskId,
@@ -525,7 +535,11 @@ impl TaskHashTracker {
 
 #[cfg(test)]
 mod test {
+    use clap::Parser;
+    use turborepo_env::dot_env;
+
     use super::*;
+    use crate::Args;
 
     #[test]
     fn test_hash_tracker_is_send_and_sync() {
@@ -536,4 +550,61 @@ mod test {
         assert_send::<TaskHashTracker>();
         assert_sync::<TaskHashTracker>();
     }
+
+    #[test]
+    fn test_interpolated_dot_env_vars_are_hashed() {
+        let args = Args::try_parse_from(["turbo", "run", "build"]).unwrap();
+        let opts = Opts::try_from(&args).unwrap();
+        let task_id = TaskId::new("web", "build").into_owned();
+        let task_definition = TaskDefinition {
+            env: vec!["API_URL".to_string()],
+            ..Default::default()
+        };
+        let workspace = WorkspaceInfo {
+            package_json_path: AnchoredSystemPathBuf::from_raw("apps/web/package.json").unwrap(),
+            ..Default::default()
+        };
+        let dot_env_file = [(".env", "URL=${API_URL}/v1\nTOKEN=${SECRET}")];
+
+        let hash_and_dot_env = |api_url: &str, secret: &str| {
+            let env = EnvironmentVariableMap::from(HashMap::from([
+                ("API_URL".to_string(), api_url.to_string()),
+                ("SECRET".to_string(), secret.to_string()),
+            ]));
+            let hasher = TaskHasher::new(
+                PackageInputsHashes {
+                    hashes: HashMap::from([(task_id.clone(), "files".to_string())]),
+                    expanded_hashes: HashMap::new(),
+                },
+                &opts,
+                &env,
+                "global",
+            );
+            let hash = hasher
+                .calculate_task_hash(
+                    &task_id,
+                    &task_definition,
+                    ResolvedEnvMode::Loose,
+                    &workspace,
+                    HashSet::new(),
+                )
+                .unwrap();
+            let hashed_env = hasher.hashed_env(&task_id).unwrap();
+            let vars = dot_env::resolve(&env, &hashed_env, &dot_env_file).unwrap();
+            (hash, vars.into_inner())
+        };
+
+        let (hash, vars) = hash_and_dot_env("https://example.com", "hunter2");
+        assert_eq!(vars["URL"], "https://example.com/v1");
+        // Not part of the hash, so it can't leak into the environment
+        assert_eq!(vars["TOKEN"], "");
+
+        let (changed_hash, changed_vars) = hash_and_dot_env("https://example.org", "hunter2");
+        assert_eq!(changed_vars["URL"], "https://example.org/v1");
+        assert_ne!(hash, changed_hash);
+
+        let (unchanged_hash, unchanged_vars) = hash_and_dot_env("https://example.com", "hunter3");
+        assert_eq!(unchanged_vars, vars);
+        assert_eq!(hash, unchanged_hash);
+    }
 }
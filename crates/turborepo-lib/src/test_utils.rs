@@ -0,0 +1,61 @@
+//! Fixtures shared by the tests of different modules
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use camino::Utf8Path;
+use serde_json::json;
+use tempfile::TempDir;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+use turborepo_api_client::APIClient;
+use turborepo_cache::{AsyncCache, CacheOpts};
+
+/// An npm repository with a single package, `a` in `packages/a`
+pub struct TestRepo {
+    // Deleted when dropped, so keep it bound while the repo is used
+    _dir: TempDir,
+    pub root: AbsoluteSystemPathBuf,
+}
+
+impl TestRepo {
+    pub fn new(scripts: serde_json::Value) -> Result<Self> {
+        let dir = tempfile::tempdir()?;
+        let root = AbsoluteSystemPathBuf::try_from(dir.path())?;
+        root.join_component("package.json").create_with_contents(
+            json!({"name": "root", "packageManager": "npm@8.19.4", "workspaces": ["packages/*"]})
+                .to_string(),
+        )?;
+        let package_json = root.join_components(&["packages", "a", "package.json"]);
+        package_json.ensure_dir()?;
+        package_json.create_with_contents(json!({"name": "a", "scripts": scripts}).to_string())?;
+        Ok(Self { _dir: dir, root })
+    }
+
+    pub fn package_dir(&self) -> AbsoluteSystemPathBuf {
+        self.root.join_components(&["packages", "a"])
+    }
+
+    /// A local cache in `cache_dir`, which can be shared with other repos
+    pub fn cache(&self, cache_dir: &AbsoluteSystemPath) -> Result<AsyncCache> {
+        Ok(AsyncCache::new(
+            &CacheOpts {
+                override_dir: Some(Utf8Path::new(cache_dir.as_str())),
+                skip_remote: true,
+                workers: 1,
+                ..Default::default()
+            },
+            &self.root,
+            APIClient::new("http://localhost:1", 200, "2.0.0", true)?,
+            None,
+            None,
+        )?)
+    }
+}
+
+/// Tasks run with a cleared environment, this only lets them find the
+/// programs they run
+pub fn path_env() -> HashMap<String, String> {
+    std::env::var("PATH")
+        .map(|path| HashMap::from([("PATH".to_string(), path)]))
+        .unwrap_or_default()
+}
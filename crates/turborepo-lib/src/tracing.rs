@@ -1,6 +1,7 @@
 use std::{io::Stderr, marker::PhantomData, path::Path, sync::Mutex};
 
 use chrono::Local;
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
 use owo_colors::{
     colors::{Black, Default, Red, Yellow},
     Color, OwoColorize,
@@ -8,6 +9,7 @@ use owo_colors::{
 use tracing::{field::Visit, metadata::LevelFilter, trace, Event, Level, Subscriber};
 use tracing_appender::{non_blocking::NonBlocking, rolling::RollingFileAppender};
 use tracing_chrome::ChromeLayer;
+use tracing_opentelemetry::OpenTelemetryLayer;
 pub use tracing_subscriber::reload::Error;
 use tracing_subscriber::{
     filter::{Filtered, Targets},
@@ -24,6 +26,8 @@ use tracing_subscriber::{
 };
 use turborepo_ui::UI;
 
+use crate::otel;
+
 // a lot of types to make sure we record the right relationships
 
 /// Note that we cannot express the type of `std::io::stderr` directly, so
@@ -70,6 +74,14 @@ type ChromeReload = reload::Layer<Option<ChromeLog>, DaemonLogLayered>;
 /// `ChromeLogLayered`, which forms the base for the next layer.
 type ChromeLogLayered = layer::Layered<ChromeReload, DaemonLogLayered>;
 
+/// A layer that exports spans to an OpenTelemetry collector. It is applied
+/// on top of the `ChromeLogLayered` layer.
+type OtelLog = OpenTelemetryLayer<ChromeLogLayered, Tracer>;
+/// This layer can be reloaded. `None` means the layer is disabled.
+type OtelReload = reload::Layer<Option<OtelLog>, ChromeLogLayered>;
+/// We filter this down to the spans for runs, tasks, and cache operations.
+type OtelLogFiltered = Filtered<OtelReload, Targets, ChromeLogLayered>;
+
 pub struct TurboSubscriber {
     daemon_update: Handle<Option<DaemonLog>, StdErrLogLayered>,
 
@@ -80,6 +92,11 @@ pub struct TurboSubscriber {
     chrome_update: Handle<Option<ChromeLog>, DaemonLogLayered>,
     chrome_guard: Mutex<Option<tracing_chrome::FlushGuard>>,
 
+    otel_update: Handle<Option<OtelLog>, ChromeLogLayered>,
+    /// Spans are exported in batches, so the provider is kept around to flush
+    /// the last batch before exiting.
+    otel_provider: Mutex<Option<TracerProvider>>,
+
     #[cfg(feature = "pprof")]
     pprof_guard: pprof::ProfilerGuard<'static>,
 }
@@ -102,6 +119,7 @@ impl TurboSubscriber {
     ///  formatter.
     /// - `enable_chrome_tracing` enables logging to a file, using the chrome
     ///  tracing formatter.
+    /// - `enable_otel` enables exporting spans to an OpenTelemetry collector.
     pub fn new_with_verbosity(verbosity: usize, ui: &UI) -> Self {
         let level_override = match verbosity {
             0 => None,
@@ -139,10 +157,14 @@ impl TurboSubscriber {
 
         let (chrome, chrome_update) = reload::Layer::new(Option::<ChromeLog>::None);
 
+        let (otel, otel_update) = reload::Layer::new(Option::<OtelLog>::None);
+        let otel: OtelLogFiltered = otel.with_filter(otel::filter());
+
         let registry = Registry::default()
             .with(stderr)
             .with(logrotate)
-            .with(chrome);
+            .with(chrome)
+            .with(otel);
 
         #[cfg(feature = "pprof")]
         let pprof_guard = pprof::ProfilerGuardBuilder::default()
@@ -158,6 +180,8 @@ impl TurboSubscriber {
             daemon_guard: Mutex::new(None),
             chrome_update,
             chrome_guard: Mutex::new(None),
+            otel_update,
+            otel_provider: Mutex::new(None),
             #[cfg(feature = "pprof")]
             pprof_guard,
         }
@@ -205,6 +229,33 @@ impl TurboSubscriber {
 
         Ok(())
     }
+
+    /// Enables exporting run, task, and cache spans to an OpenTelemetry
+    /// collector. Must be called from within a tokio runtime, and
+    /// `flush_otel` must be called before that runtime shuts down.
+    #[tracing::instrument(skip(self))]
+    pub fn enable_otel(&self, config: &otel::ExporterConfig) -> Result<(), otel::Error> {
+        let provider = otel::tracer_provider(config)?;
+        let layer: OtelLog = tracing_opentelemetry::layer().with_tracer(otel::tracer(&provider));
+
+        // Reloading only fails if the subscriber has been dropped, in which
+        // case there's nothing left to export
+        let _ = self.otel_update.reload(Some(layer));
+        self.otel_provider
+            .lock()
+            .expect("not poisoned")
+            .replace(provider);
+
+        Ok(())
+    }
+
+    /// Exports any spans that are still buffered.
+    pub async fn flush_otel(&self) {
+        let provider = self.otel_provider.lock().expect("not poisoned").take();
+        if let Some(provider) = provider {
+            otel::flush(provider).await;
+        }
+    }
 }
 
 impl Drop for TurboSubscriber {
@@ -5,6 +5,9 @@ use serde::Deserialize;
 use crate::Lockfile;
 
 mod de;
+mod text;
+
+pub use text::BunTextLockfile;
 
 type Map<K, V> = std::collections::BTreeMap<K, V>;
 
@@ -14,9 +17,14 @@ pub enum Error {
     SymlParse(String),
     #[error("unable to convert to structured syml: {0}")]
     SymlStructure(#[from] serde_json::Error),
-    #[error("unexpected non-utf8 yarn.lock")]
+    #[error("unexpected non-utf8 bun lockfile")]
     NonUTF8(#[from] std::str::Utf8Error),
-    #[error("Turborepo cannot serialize Bun lockfiles.")]
+    #[error("unable to parse bun.lock: {0}")]
+    TextParse(String),
+    #[error(
+        "Turborepo cannot write the binary bun.lockb lockfile. Run `bun install \
+         --save-text-lockfile` to switch to the text bun.lock lockfile."
+    )]
     NotImplemented(),
 }
 
@@ -143,6 +151,22 @@ fn possible_keys<'a>(name: &'a str, version: &'a str) -> impl Iterator<Item = St
 mod test {
     use super::*;
     const FULL: &str = include_str!("../../fixtures/yarn1full.lock");
+    const BUN: &str = include_str!("../../fixtures/bun.lock.txt");
+
+    #[test]
+    fn test_encode_is_unsupported() {
+        let lockfile = BunLockfile::from_str(BUN).unwrap();
+        assert!(matches!(
+            lockfile.encode(),
+            Err(crate::Error::Bun(Error::NotImplemented()))
+        ));
+    }
+
+    #[test]
+    fn test_non_utf8_error() {
+        let err = BunLockfile::from_bytes(&[0xff, 0xfe]).unwrap_err();
+        assert_eq!(err.to_string(), "unexpected non-utf8 bun lockfile");
+    }
 
     #[test]
     fn test_key_splitting() {
@@ -0,0 +1,815 @@
+use std::{any::Any, collections::HashMap, fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use turbopath::RelativeUnixPathBuf;
+
+use super::{Error, Map};
+use crate::Lockfile;
+
+/// The text `bun.lock` lockfile Bun writes since v1.1.39. Unlike the binary
+/// `bun.lockb` it can be pruned and written back out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BunTextLockfile {
+    data: BunLockfileData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BunLockfileData {
+    lockfile_version: u32,
+    workspaces: Map<String, WorkspaceEntry>,
+    trusted_dependencies: Option<Vec<String>>,
+    patched_dependencies: Option<Map<String, String>>,
+    overrides: Option<Map<String, String>>,
+    packages: Map<String, PackageEntry>,
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceEntry {
+    name: Option<String>,
+    version: Option<String>,
+    bin: Option<Value>,
+    dependencies: Option<Map<String, String>>,
+    dev_dependencies: Option<Map<String, String>>,
+    optional_dependencies: Option<Map<String, String>>,
+    peer_dependencies: Option<Map<String, String>>,
+    optional_peers: Option<Vec<String>>,
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+/// A package is written as an array that starts with its `<name>@<version>`
+/// ident. What follows depends on where the package comes from, e.g. npm
+/// packages have the registry, the package info and the integrity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PackageEntry {
+    ident: String,
+    fields: Vec<PackageField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PackageField {
+    Info(PackageInfo),
+    Other(Value),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageInfo {
+    dependencies: Option<Map<String, String>>,
+    optional_dependencies: Option<Map<String, String>>,
+    peer_dependencies: Option<Map<String, String>>,
+    optional_peers: Option<Vec<String>>,
+    bin: Option<Value>,
+    bin_dir: Option<String>,
+    os: Option<Value>,
+    cpu: Option<Value>,
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+impl BunTextLockfile {
+    pub fn from_bytes(input: &[u8]) -> Result<Self, crate::Error> {
+        let input = std::str::from_utf8(input).map_err(Error::from)?;
+        Self::from_str(input)
+    }
+}
+
+impl FromStr for BunTextLockfile {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // bun.lock is JSON with trailing commas
+        let value = jsonc_parser::parse_to_serde_value(s, &Default::default())
+            .map_err(|err| Error::TextParse(err.to_string()))?
+            .ok_or_else(|| Error::TextParse("lockfile is empty".into()))?;
+        let data = serde_json::from_value(value)?;
+        Ok(Self { data })
+    }
+}
+
+impl Lockfile for BunTextLockfile {
+    #[tracing::instrument(skip(self))]
+    fn resolve_package(
+        &self,
+        workspace_path: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<Option<crate::Package>, crate::Error> {
+        let workspace = self
+            .data
+            .workspaces
+            .get(workspace_path)
+            .ok_or_else(|| crate::Error::MissingWorkspace(workspace_path.to_string()))?;
+
+        // Bun installs the version a workspace needs under `<workspace>/<name>`
+        // if it conflicts with the hoisted `<name>`. Dependencies of packages
+        // are already given by their key by `all_dependencies`.
+        let is_declared = workspace
+            .dependency_entries()
+            .any(|(dependency, specifier)| dependency == name && specifier == version);
+        let workspace_key = workspace
+            .name
+            .as_ref()
+            .filter(|_| is_declared)
+            .map(|workspace_name| format!("{workspace_name}/{name}"));
+
+        Ok(workspace_key
+            .into_iter()
+            .chain(std::iter::once(name.to_string()))
+            .find_map(|key| {
+                let entry = self.data.packages.get(&key)?;
+                Some(crate::Package {
+                    version: entry.version().to_string(),
+                    key,
+                })
+            }))
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn all_dependencies(&self, key: &str) -> Result<Option<HashMap<String, String>>, crate::Error> {
+        let Some(entry) = self.data.packages.get(key) else {
+            return Ok(None);
+        };
+        let Some(info) = entry.info() else {
+            return Ok(None);
+        };
+
+        // Like node_modules, a dependency is looked up next to the package
+        // first and then in each of its parents
+        let parents = key_names(key);
+        let dependencies = info
+            .dependency_entries()
+            .filter_map(|(name, _)| {
+                (0..=parents.len()).rev().find_map(|depth| {
+                    let dependency_key = match depth {
+                        0 => name.to_string(),
+                        _ => format!("{}/{name}", parents[..depth].join("/")),
+                    };
+                    let dependency = self.data.packages.get(&dependency_key)?;
+                    Some((dependency_key, dependency.version().to_string()))
+                })
+            })
+            .collect::<HashMap<_, _>>();
+
+        Ok(match dependencies.is_empty() {
+            false => Some(dependencies),
+            true => None,
+        })
+    }
+
+    fn subgraph(
+        &self,
+        workspace_packages: &[String],
+        packages: &[String],
+    ) -> Result<Box<dyn Lockfile>, crate::Error> {
+        let mut workspaces = Map::new();
+        for workspace in std::iter::once("").chain(workspace_packages.iter().map(String::as_str)) {
+            let entry = self
+                .data
+                .workspaces
+                .get(workspace)
+                .ok_or_else(|| crate::Error::MissingWorkspace(workspace.to_string()))?;
+            workspaces.insert(workspace.to_string(), entry.clone());
+        }
+
+        let mut pruned_packages = Map::new();
+        for key in packages {
+            let entry = self
+                .data
+                .packages
+                .get(key)
+                .ok_or_else(|| crate::Error::MissingPackage(key.clone()))?;
+            pruned_packages.insert(key.clone(), entry.clone());
+        }
+        // The entries linking the remaining workspaces
+        for (key, entry) in &self.data.packages {
+            if entry
+                .workspace_path()
+                .is_some_and(|path| workspaces.contains_key(path))
+            {
+                pruned_packages.insert(key.clone(), entry.clone());
+            }
+        }
+
+        let patched_dependencies = self.data.patched_dependencies.as_ref().map(|patches| {
+            patches
+                .iter()
+                .filter(|(ident, _)| pruned_packages.values().any(|entry| &entry.ident == *ident))
+                .map(|(ident, patch)| (ident.clone(), patch.clone()))
+                .collect()
+        });
+
+        Ok(Box::new(Self {
+            data: BunLockfileData {
+                workspaces,
+                patched_dependencies,
+                packages: pruned_packages,
+                ..self.data.clone()
+            },
+        }))
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, crate::Error> {
+        Ok(self.to_string().into_bytes())
+    }
+
+    fn patches(&self) -> Result<Vec<RelativeUnixPathBuf>, crate::Error> {
+        let mut patches = self
+            .data
+            .patched_dependencies
+            .iter()
+            .flatten()
+            .map(|(_, patch)| RelativeUnixPathBuf::new(patch))
+            .collect::<Result<Vec<_>, turbopath::PathError>>()?;
+        patches.sort();
+        Ok(patches)
+    }
+
+    fn global_change(&self, other: &dyn Lockfile) -> bool {
+        let any_other = other as &dyn Any;
+        if let Some(other) = any_other.downcast_ref::<Self>() {
+            self.data.lockfile_version != other.data.lockfile_version
+                || self.data.overrides != other.data.overrides
+                || self.data.trusted_dependencies != other.data.trusted_dependencies
+        } else {
+            true
+        }
+    }
+}
+
+impl WorkspaceEntry {
+    fn dependency_entries(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.dependencies
+            .iter()
+            .chain(&self.dev_dependencies)
+            .chain(&self.optional_dependencies)
+            .flatten()
+            .map(|(name, specifier)| (name.as_str(), specifier.as_str()))
+    }
+
+    fn fields(&self) -> Vec<(&str, Value)> {
+        let mut fields = Vec::new();
+        push_field(&mut fields, "name", &self.name);
+        push_field(&mut fields, "version", &self.version);
+        push_field(&mut fields, "bin", &self.bin);
+        push_field(&mut fields, "dependencies", &self.dependencies);
+        push_field(&mut fields, "devDependencies", &self.dev_dependencies);
+        push_field(
+            &mut fields,
+            "optionalDependencies",
+            &self.optional_dependencies,
+        );
+        push_field(&mut fields, "peerDependencies", &self.peer_dependencies);
+        push_field(&mut fields, "optionalPeers", &self.optional_peers);
+        fields.extend(self.other.iter().map(|(k, v)| (k.as_str(), v.clone())));
+        fields
+    }
+}
+
+impl PackageEntry {
+    fn info(&self) -> Option<&PackageInfo> {
+        self.fields.iter().find_map(|field| match field {
+            PackageField::Info(info) => Some(info),
+            PackageField::Other(_) => None,
+        })
+    }
+
+    fn version(&self) -> &str {
+        // The name can start with an `@` scope, the version can't
+        self.ident
+            .rsplit_once('@')
+            .map_or("", |(_, version)| version)
+    }
+
+    fn workspace_path(&self) -> Option<&str> {
+        self.version().strip_prefix("workspace:")
+    }
+}
+
+impl PackageInfo {
+    fn dependency_entries(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        // Bun installs peer dependencies unless they're optional
+        let peer_dependencies = self.peer_dependencies.iter().flatten().filter(|(name, _)| {
+            !self
+                .optional_peers
+                .iter()
+                .flatten()
+                .any(|optional| optional == *name)
+        });
+        self.dependencies
+            .iter()
+            .chain(&self.optional_dependencies)
+            .flatten()
+            .chain(peer_dependencies)
+            .map(|(name, specifier)| (name.as_str(), specifier.as_str()))
+    }
+
+    fn fields(&self) -> Vec<(&str, Value)> {
+        let mut fields = Vec::new();
+        push_field(&mut fields, "dependencies", &self.dependencies);
+        push_field(
+            &mut fields,
+            "optionalDependencies",
+            &self.optional_dependencies,
+        );
+        push_field(&mut fields, "peerDependencies", &self.peer_dependencies);
+        push_field(&mut fields, "optionalPeers", &self.optional_peers);
+        push_field(&mut fields, "bin", &self.bin);
+        push_field(&mut fields, "binDir", &self.bin_dir);
+        push_field(&mut fields, "os", &self.os);
+        push_field(&mut fields, "cpu", &self.cpu);
+        fields.extend(self.other.iter().map(|(k, v)| (k.as_str(), v.clone())));
+        fields
+    }
+}
+
+impl<'de> Deserialize<'de> for PackageEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut values = Vec::<Value>::deserialize(deserializer)?.into_iter();
+        let Some(Value::String(ident)) = values.next() else {
+            return Err(de::Error::custom("package entry must start with its ident"));
+        };
+        let fields = values
+            .map(|value| match value {
+                Value::Object(_) => serde_json::from_value(value)
+                    .map(PackageField::Info)
+                    .map_err(de::Error::custom),
+                value => Ok(PackageField::Other(value)),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { ident, fields })
+    }
+}
+
+fn push_field<'a, T: Serialize>(
+    fields: &mut Vec<(&'a str, Value)>,
+    key: &'a str,
+    value: &Option<T>,
+) {
+    if let Some(value) = value {
+        fields.push((
+            key,
+            serde_json::to_value(value).expect("lockfile values are valid json"),
+        ));
+    }
+}
+
+/// Splits a package key into the names of the packages it's nested in, e.g.
+/// `web/@babel/core/semver` into `web`, `@babel/core` and `semver`.
+fn key_names(key: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = key;
+    while !rest.is_empty() {
+        // A scoped name contains one more `/`
+        let skip = match rest.starts_with('@') {
+            true => rest.find('/').map_or(rest.len(), |i| i + 1),
+            false => 0,
+        };
+        let end = rest[skip..].find('/').map_or(rest.len(), |i| skip + i);
+        names.push(&rest[..end]);
+        rest = rest.get(end + 1..).unwrap_or_default();
+    }
+    names
+}
+
+// bun.lock is written with two space indentation and a trailing comma after
+// every entry. Packages are each written on a single line.
+impl fmt::Display for BunTextLockfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = &self.data;
+        f.write_str("{\n")?;
+        writeln!(f, "  \"lockfileVersion\": {},", data.lockfile_version)?;
+
+        f.write_str("  \"workspaces\": {\n")?;
+        for (path, workspace) in &data.workspaces {
+            write!(f, "    {}: ", json_string(path))?;
+            write_object(f, 4, &workspace.fields())?;
+            f.write_str(",\n")?;
+        }
+        f.write_str("  },\n")?;
+
+        let mut fields = Vec::new();
+        push_field(
+            &mut fields,
+            "trustedDependencies",
+            &data.trusted_dependencies,
+        );
+        push_field(
+            &mut fields,
+            "patchedDependencies",
+            &data.patched_dependencies,
+        );
+        push_field(&mut fields, "overrides", &data.overrides);
+        fields.extend(data.other.iter().map(|(k, v)| (k.as_str(), v.clone())));
+        for (key, value) in &fields {
+            write!(f, "  {}: ", json_string(key))?;
+            write_multiline(f, 2, value)?;
+            f.write_str(",\n")?;
+        }
+
+        f.write_str("  \"packages\": {\n")?;
+        for (i, (key, entry)) in data.packages.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(
+                f,
+                "    {}: [{}",
+                json_string(key),
+                json_string(&entry.ident)
+            )?;
+            for field in &entry.fields {
+                f.write_str(", ")?;
+                match field {
+                    PackageField::Info(info) => write_inline_object(f, &info.fields())?,
+                    PackageField::Other(value) => write_inline(f, value)?,
+                }
+            }
+            f.write_str("],\n")?;
+        }
+        f.write_str("  }\n}\n")
+    }
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::to_string(s).expect("strings are valid json")
+}
+
+fn write_object(
+    f: &mut fmt::Formatter<'_>,
+    indent: usize,
+    fields: &[(&str, Value)],
+) -> fmt::Result {
+    if fields.is_empty() {
+        return f.write_str("{}");
+    }
+    f.write_str("{\n")?;
+    for (key, value) in fields {
+        write!(
+            f,
+            "{:indent$}{}: ",
+            "",
+            json_string(key),
+            indent = indent + 2
+        )?;
+        write_multiline(f, indent + 2, value)?;
+        f.write_str(",\n")?;
+    }
+    write!(f, "{:indent$}}}", "", indent = indent)
+}
+
+fn write_multiline(f: &mut fmt::Formatter<'_>, indent: usize, value: &Value) -> fmt::Result {
+    match value {
+        Value::Object(map) => {
+            let fields = map
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.clone()))
+                .collect::<Vec<_>>();
+            write_object(f, indent, &fields)
+        }
+        Value::Array(items) if !items.is_empty() => {
+            f.write_str("[\n")?;
+            for item in items {
+                write!(f, "{:indent$}", "", indent = indent + 2)?;
+                write_multiline(f, indent + 2, item)?;
+                f.write_str(",\n")?;
+            }
+            write!(f, "{:indent$}]", "", indent = indent)
+        }
+        value => write_inline(f, value),
+    }
+}
+
+fn write_inline_object(f: &mut fmt::Formatter<'_>, fields: &[(&str, Value)]) -> fmt::Result {
+    if fields.is_empty() {
+        return f.write_str("{}");
+    }
+    f.write_str("{ ")?;
+    for (i, (key, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}: ", json_string(key))?;
+        write_inline(f, value)?;
+    }
+    f.write_str(" }")
+}
+
+fn write_inline(f: &mut fmt::Formatter<'_>, value: &Value) -> fmt::Result {
+    match value {
+        Value::Object(map) => {
+            let fields = map
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.clone()))
+                .collect::<Vec<_>>();
+            write_inline_object(f, &fields)
+        }
+        Value::Array(items) => {
+            f.write_str("[")?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write_inline(f, item)?;
+            }
+            f.write_str("]")
+        }
+        value => write!(f, "{value}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{transitive_closure, Package};
+
+    const BUN_LOCK: &str = include_str!("../../fixtures/bun.lock");
+
+    #[test]
+    fn test_roundtrip() {
+        let lockfile = BunTextLockfile::from_str(BUN_LOCK).unwrap();
+        let encoded = String::from_utf8(lockfile.encode().unwrap()).unwrap();
+        assert_eq!(encoded, BUN_LOCK);
+    }
+
+    #[test]
+    fn test_key_names() {
+        assert_eq!(key_names("react"), vec!["react"]);
+        assert_eq!(key_names("@types/react"), vec!["@types/react"]);
+        assert_eq!(
+            key_names("web/@babel/core/semver"),
+            vec!["web", "@babel/core", "semver"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_package() {
+        let lockfile = BunTextLockfile::from_str(BUN_LOCK).unwrap();
+        // docs has its own copy of typescript, the root uses the hoisted one
+        assert_eq!(
+            lockfile
+                .resolve_package("apps/docs", "typescript", "4.8.4")
+                .unwrap(),
+            Some(Package::new("docs/typescript", "4.8.4"))
+        );
+        assert_eq!(
+            lockfile
+                .resolve_package("", "typescript", "^5.2.2")
+                .unwrap(),
+            Some(Package::new("typescript", "5.2.2"))
+        );
+        assert_eq!(
+            lockfile
+                .resolve_package("apps/web", "@types/scheduler", "^0.16.2")
+                .unwrap(),
+            Some(Package::new("@types/scheduler", "0.16.2"))
+        );
+        assert_eq!(
+            lockfile
+                .resolve_package("apps/web", "left-pad", "^1.0.0")
+                .unwrap(),
+            None
+        );
+        assert!(lockfile
+            .resolve_package("apps/missing", "react", "^18.2.0")
+            .is_err());
+    }
+
+    #[test]
+    fn test_all_dependencies() {
+        let lockfile = BunTextLockfile::from_str(BUN_LOCK).unwrap();
+        assert_eq!(
+            lockfile.all_dependencies("react-dom").unwrap(),
+            Some(HashMap::from([
+                ("loose-envify".to_string(), "1.4.0".to_string()),
+                ("scheduler".to_string(), "0.23.0".to_string()),
+                ("react".to_string(), "18.2.0".to_string()),
+            ]))
+        );
+        assert_eq!(lockfile.all_dependencies("js-tokens").unwrap(), None);
+    }
+
+    #[test]
+    fn test_nested_dependencies() {
+        let lockfile = BunTextLockfile::from_str(
+            r#"{
+  "lockfileVersion": 1,
+  "workspaces": {
+    "": {
+      "dependencies": {
+        "a": "^1.0.0",
+        "c": "^1.0.0",
+      },
+    },
+  },
+  "packages": {
+    "a": ["a@1.0.0", "", { "dependencies": { "b": "^1.0.0", "c": "^2.0.0" } }, "sha512-a"],
+
+    "a/c": ["c@2.0.0", "", {}, "sha512-ac"],
+
+    "b": ["b@1.0.0", "", { "dependencies": { "c": "^2.0.0" } }, "sha512-b"],
+
+    "c": ["c@1.0.0", "", {}, "sha512-c"],
+  }
+}
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            lockfile.all_dependencies("a").unwrap(),
+            Some(HashMap::from([
+                ("b".to_string(), "1.0.0".to_string()),
+                ("a/c".to_string(), "2.0.0".to_string()),
+            ]))
+        );
+        // `a/c` isn't visible from `b`, which isn't nested in `a`
+        assert_eq!(
+            lockfile.all_dependencies("b").unwrap(),
+            Some(HashMap::from([("c".to_string(), "1.0.0".to_string())]))
+        );
+    }
+
+    #[test]
+    fn test_transitive_closure() {
+        let lockfile = BunTextLockfile::from_str(BUN_LOCK).unwrap();
+        let closure = transitive_closure(
+            &lockfile,
+            "apps/web",
+            HashMap::from([
+                ("react".to_string(), "^18.2.0".to_string()),
+                ("react-dom".to_string(), "^18.2.0".to_string()),
+                ("@types/scheduler".to_string(), "^0.16.2".to_string()),
+            ]),
+        )
+        .unwrap();
+        let mut closure = closure.into_iter().collect::<Vec<_>>();
+        closure.sort();
+        assert_eq!(
+            closure,
+            vec![
+                Package::new("@types/scheduler", "0.16.2"),
+                Package::new("js-tokens", "4.0.0"),
+                Package::new("loose-envify", "1.4.0"),
+                Package::new("react", "18.2.0"),
+                Package::new("react-dom", "18.2.0"),
+                Package::new("scheduler", "0.23.0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_subgraph() {
+        let lockfile = BunTextLockfile::from_str(BUN_LOCK).unwrap();
+        let pruned = lockfile
+            .subgraph(
+                &["apps/docs".into(), "packages/ui".into()],
+                &[
+                    "docs/typescript".into(),
+                    "js-tokens".into(),
+                    "loose-envify".into(),
+                    "object-assign".into(),
+                    "react".into(),
+                    "typescript".into(),
+                ],
+            )
+            .unwrap();
+        let encoded = String::from_utf8(pruned.encode().unwrap()).unwrap();
+        assert_eq!(
+            encoded,
+            r#"{
+  "lockfileVersion": 1,
+  "workspaces": {
+    "": {
+      "name": "bun-monorepo",
+      "devDependencies": {
+        "typescript": "^5.2.2",
+      },
+    },
+    "apps/docs": {
+      "name": "docs",
+      "version": "0.1.0",
+      "dependencies": {
+        "react": "^18.2.0",
+        "ui": "workspace:*",
+      },
+      "devDependencies": {
+        "typescript": "4.8.4",
+      },
+    },
+    "packages/ui": {
+      "name": "ui",
+      "dependencies": {
+        "object-assign": "^4.1.1",
+      },
+      "peerDependencies": {
+        "react": "^18.2.0",
+      },
+    },
+  },
+  "trustedDependencies": [
+    "typescript",
+  ],
+  "packages": {
+    "docs": ["docs@workspace:apps/docs"],
+
+    "docs/typescript": ["typescript@4.8.4", "", { "bin": { "tsc": "bin/tsc", "tsserver": "bin/tsserver" } }, "sha512-QCh+85mCy+h0IGff8r5XWzOVSbBO+KfeYrMQh7NJ58QujwcE22u+NUSmUxqF+un70P9GXKxa2HCNiTTMJknyjQ=="],
+
+    "js-tokens": ["js-tokens@4.0.0", "", {}, "sha512-RdJUflcE3cUzKiMqQgsCu06FHv6X2+c6PPmZE2fL9sB9X2lTU8KNt8GAuSI9UzlQb1d5DG2zHxZO9NqZpK5JnQ=="],
+
+    "loose-envify": ["loose-envify@1.4.0", "", { "dependencies": { "js-tokens": "^3.0.0 || ^4.0.0" }, "bin": { "loose-envify": "cli.js" } }, "sha512-lyuxPGr/Wfhrlem2CL/UcnUc1zcqKAImBDzukY7Y5F/yQiNdko6+fRLevlw1HgMySw7f611UIY408EtxRSoK3Q=="],
+
+    "object-assign": ["object-assign@4.1.1", "", {}, "sha512-rJgTQnkUnH1sFw8yT6VSU3zD3sWmu6sZhIseY8VX+GRu3P6F7Fu+JNDoXfklElbLJSnc3FUQHVe4cU5hj+BcUg=="],
+
+    "react": ["react@18.2.0", "", { "dependencies": { "loose-envify": "^1.1.0" } }, "sha512-/3IjMdb2L9QbBdWiW5e3P2/npwMBaU9mHCSCUzNln0ZCYbcfTsGbTJrU/kGemdH2IWmB2ioZ+zkxtmq6g09fGQ=="],
+
+    "typescript": ["typescript@5.2.2", "", { "bin": { "tsc": "bin/tsc", "tsserver": "bin/tsserver" } }, "sha512-mI4WrpHsbCIcwT9cF4FZvr80QUeKvsUsUvKDoR+X/7XHQH98xYD8YHZg7ANtz2GtZt/CBq2QJ0thkGJMHfqc1w=="],
+
+    "ui": ["ui@workspace:packages/ui"],
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_resolve_after_subgraph() {
+        let lockfile = BunTextLockfile::from_str(BUN_LOCK).unwrap();
+        let pruned = lockfile
+            .subgraph(&["apps/docs".into()], &["docs/typescript".into()])
+            .unwrap();
+        let reparsed = BunTextLockfile::from_bytes(&pruned.encode().unwrap()).unwrap();
+        assert_eq!(
+            reparsed
+                .resolve_package("apps/docs", "typescript", "4.8.4")
+                .unwrap(),
+            Some(Package::new("docs/typescript", "4.8.4"))
+        );
+        assert_eq!(
+            reparsed
+                .resolve_package("apps/docs", "react", "^18.2.0")
+                .unwrap(),
+            None
+        );
+        assert!(reparsed
+            .resolve_package("apps/web", "react", "^18.2.0")
+            .is_err());
+    }
+
+    #[test]
+    fn test_prune_patches() {
+        let lockfile = BunTextLockfile::from_str(
+            r#"{
+  "lockfileVersion": 1,
+  "workspaces": {
+    "": {
+      "name": "root",
+    },
+    "packages/a": {
+      "name": "a",
+      "dependencies": {
+        "is-odd": "^3.0.1",
+      },
+    },
+    "packages/b": {
+      "name": "b",
+      "dependencies": {
+        "is-even": "^1.0.0",
+      },
+    },
+  },
+  "patchedDependencies": {
+    "is-even@1.0.0": "patches/is-even@1.0.0.patch",
+    "is-odd@3.0.1": "patches/is-odd@3.0.1.patch",
+  },
+  "packages": {
+    "is-even": ["is-even@1.0.0", "", {}, "sha512-even"],
+
+    "is-odd": ["is-odd@3.0.1", "", {}, "sha512-odd"],
+  }
+}
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            lockfile.patches().unwrap(),
+            vec![
+                RelativeUnixPathBuf::new("patches/is-even@1.0.0.patch").unwrap(),
+                RelativeUnixPathBuf::new("patches/is-odd@3.0.1.patch").unwrap(),
+            ]
+        );
+        let pruned = lockfile
+            .subgraph(&["packages/a".into()], &["is-odd".into()])
+            .unwrap();
+        assert_eq!(
+            pruned.patches().unwrap(),
+            vec![RelativeUnixPathBuf::new("patches/is-odd@3.0.1.patch").unwrap()]
+        );
+    }
+}
@@ -10,11 +10,11 @@ mod yarn1;
 
 use std::{
     any::Any,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
 };
 
 pub use berry::{Error as BerryError, *};
-pub use bun::BunLockfile;
+pub use bun::{BunLockfile, BunTextLockfile};
 pub use error::Error;
 pub use npm::*;
 pub use pnpm::{pnpm_global_change, pnpm_subgraph, PnpmLockfile};
@@ -28,6 +28,20 @@ pub struct Package {
     pub version: String,
 }
 
+/// A package in a workspace's transitive closure, along with the name it's
+/// depended on by
+#[derive(Debug, PartialEq, Eq, Clone, PartialOrd, Ord, Hash, Serialize)]
+pub struct NamedPackage {
+    pub name: String,
+    pub package: Package,
+    /// Whether the workspace depends on the package itself rather than
+    /// through another package
+    pub direct: bool,
+    /// The names of the package's own dependencies mapped to their locked
+    /// versions
+    pub dependencies: BTreeMap<String, String>,
+}
+
 // This trait will only be used when migrating the Go lockfile implementations
 // to Rust. Once the migration is complete we will leverage petgraph for doing
 // our graph calculations.
@@ -123,6 +137,73 @@ fn transitive_closure_helper<L: Lockfile + ?Sized>(
     Ok(())
 }
 
+/// Calculates the transitive closure of a workspace like
+/// `transitive_closure`, but keeps the name each package was resolved from
+/// and the locked versions of its dependencies
+pub fn named_transitive_closure<L: Lockfile + ?Sized>(
+    lockfile: &L,
+    workspace_path: &str,
+    unresolved_deps: HashMap<String, String>,
+) -> Result<HashSet<NamedPackage>, Error> {
+    let mut resolved: HashMap<Package, NamedPackage> = HashMap::new();
+    let mut to_visit = Vec::new();
+    // Direct dependencies are all resolved first so a package the workspace
+    // depends on itself is never recorded as transitive
+    for (name, specifier) in unresolved_deps {
+        if let Some(package) = lockfile.resolve_package(workspace_path, &name, &specifier)? {
+            to_visit.push(package.clone());
+            resolved.insert(
+                package.clone(),
+                NamedPackage {
+                    name,
+                    package,
+                    direct: true,
+                    dependencies: BTreeMap::new(),
+                },
+            );
+        }
+    }
+
+    while let Some(package) = to_visit.pop() {
+        let mut dependencies = BTreeMap::new();
+        for (name, specifier) in lockfile
+            .all_dependencies(&package.key)?
+            .into_iter()
+            .flatten()
+        {
+            let Some(dependency) = lockfile.resolve_package(workspace_path, &name, &specifier)?
+            else {
+                continue;
+            };
+            // npm gives the dependencies of a package by their key, which
+            // ends with the package's name
+            let name = match name.rsplit_once("node_modules/") {
+                Some((_, name)) => name.to_string(),
+                None => name,
+            };
+            dependencies.insert(name.clone(), dependency.version.clone());
+            if resolved.contains_key(&dependency) {
+                continue;
+            }
+            to_visit.push(dependency.clone());
+            resolved.insert(
+                dependency.clone(),
+                NamedPackage {
+                    name,
+                    package: dependency,
+                    direct: false,
+                    dependencies: BTreeMap::new(),
+                },
+            );
+        }
+        if let Some(named) = resolved.get_mut(&package) {
+            named.dependencies = dependencies;
+        }
+    }
+
+    Ok(resolved.into_values().collect())
+}
+
 impl Package {
     pub fn new(key: impl Into<String>, version: impl Into<String>) -> Self {
         let key = key.into();
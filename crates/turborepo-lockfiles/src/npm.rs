@@ -422,6 +422,106 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_named_transitive_closure() -> Result<(), Error> {
+        let lockfile = NpmLockfile::load(include_bytes!("../fixtures/npm-lock.json"))?;
+        let closure = crate::named_transitive_closure(
+            &lockfile,
+            "apps/web",
+            vec![("react".to_string(), "18.2.0".to_string())]
+                .into_iter()
+                .collect(),
+        )?;
+        let mut closure = closure
+            .into_iter()
+            .map(|package| (package.name, package.package.version, package.direct))
+            .collect::<Vec<_>>();
+        closure.sort();
+        assert_eq!(
+            closure,
+            vec![
+                ("js-tokens".to_string(), "4.0.0".to_string(), false),
+                ("loose-envify".to_string(), "1.4.0".to_string(), false),
+                ("react".to_string(), "18.2.0".to_string(), true),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_transitive_closure_nested_copies() -> Result<(), Error> {
+        let lockfile = NpmLockfile::load(include_bytes!("../fixtures/npm-lock.json"))?;
+        let closure = crate::named_transitive_closure(
+            &lockfile,
+            "packages/ui",
+            vec![
+                ("@types/react".to_string(), "^17.0.37".to_string()),
+                ("@types/react-dom".to_string(), "^17.0.11".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+        )?;
+        let mut closure = closure.into_iter().collect::<Vec<_>>();
+        closure.sort();
+        // Both copies of @types/react are kept, only the one installed for the
+        // workspace itself is direct
+        assert_eq!(
+            closure
+                .iter()
+                .map(|package| (
+                    package.name.as_str(),
+                    package.package.key.as_str(),
+                    package.package.version.as_str(),
+                    package.direct
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "@types/prop-types",
+                    "node_modules/@types/prop-types",
+                    "15.7.5",
+                    false
+                ),
+                (
+                    "@types/react",
+                    "node_modules/@types/react-dom/node_modules/@types/react",
+                    "17.0.50",
+                    false
+                ),
+                (
+                    "@types/react",
+                    "packages/ui/node_modules/@types/react",
+                    "17.0.50",
+                    true
+                ),
+                (
+                    "@types/react-dom",
+                    "node_modules/@types/react-dom",
+                    "17.0.17",
+                    true
+                ),
+                (
+                    "@types/scheduler",
+                    "node_modules/@types/scheduler",
+                    "0.16.2",
+                    false
+                ),
+                ("csstype", "node_modules/csstype", "3.1.1", false),
+            ]
+        );
+        let react_dom = closure
+            .iter()
+            .find(|package| package.name == "@types/react-dom")
+            .unwrap();
+        assert_eq!(
+            react_dom.dependencies,
+            [("@types/react".to_string(), "17.0.50".to_string())]
+                .into_iter()
+                .collect()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_workspace_peer_dependencies() -> Result<(), Error> {
         let lockfile =
@@ -906,4 +906,47 @@ c:
             ]
         )
     }
+
+    #[test]
+    fn test_named_transitive_closure() {
+        let lockfile = PnpmLockfile::from_bytes(PNPM8).unwrap();
+        let closure = |workspace: &str, dependencies: &[(&str, &str)]| {
+            let mut closure = crate::named_transitive_closure(
+                &lockfile,
+                workspace,
+                dependencies
+                    .iter()
+                    .map(|(name, specifier)| (name.to_string(), specifier.to_string()))
+                    .collect(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|package| (package.name, package.package.version, package.direct))
+            .collect::<Vec<_>>();
+            closure.sort();
+            closure
+        };
+
+        assert_eq!(
+            closure("packages/a", &[("c", "workspace:*"), ("is-odd", "^3.0.1")]),
+            vec![
+                ("is-number".to_string(), "6.0.0".to_string(), false),
+                ("is-odd".to_string(), "3.0.1".to_string(), true),
+            ]
+        );
+        assert_eq!(
+            closure("packages/b", &[("c", "workspace:*"), ("is-even", "^1.0.0")]),
+            vec![
+                ("is-buffer".to_string(), "1.1.6".to_string(), false),
+                (
+                    "is-even".to_string(),
+                    "1.0.0_trwuddosrpxsvtoqztvint6pca".to_string(),
+                    true
+                ),
+                ("is-number".to_string(), "3.0.0".to_string(), false),
+                ("is-odd".to_string(), "0.1.2".to_string(), false),
+                ("kind-of".to_string(), "3.2.2".to_string(), false),
+            ]
+        );
+    }
 }
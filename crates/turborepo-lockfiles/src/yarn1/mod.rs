@@ -176,4 +176,50 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_named_transitive_closure() {
+        let lockfile = Yarn1Lockfile::from_str(MINIMAL).unwrap();
+        let mut closure = crate::named_transitive_closure(
+            &lockfile,
+            "",
+            [("turbo".to_string(), "^1.9.3".to_string())]
+                .into_iter()
+                .collect(),
+        )
+        .unwrap()
+        .into_iter()
+        .collect::<Vec<_>>();
+        closure.sort();
+
+        let platforms = [
+            "turbo-darwin-64",
+            "turbo-darwin-arm64",
+            "turbo-linux-64",
+            "turbo-linux-arm64",
+            "turbo-windows-64",
+            "turbo-windows-arm64",
+        ];
+        assert_eq!(
+            closure
+                .iter()
+                .map(|package| (
+                    package.name.as_str(),
+                    package.package.version.as_str(),
+                    package.direct
+                ))
+                .collect::<Vec<_>>(),
+            vec![("turbo", "1.9.3", true)]
+                .into_iter()
+                .chain(platforms.iter().map(|name| (*name, "1.9.3", false)))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            closure[0].dependencies,
+            platforms
+                .iter()
+                .map(|name| (name.to_string(), "1.9.3".to_string()))
+                .collect()
+        );
+    }
 }
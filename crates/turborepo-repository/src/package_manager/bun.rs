@@ -1,8 +1,15 @@
-use turbopath::AbsoluteSystemPath;
+use std::collections::HashSet;
 
-use crate::package_manager::{Error, PackageManager};
+use serde_json::Value;
+use turbopath::{AbsoluteSystemPath, RelativeUnixPath};
 
-pub const LOCKFILE: &str = "bun.lockb";
+use crate::{
+    package_json::PackageJson,
+    package_manager::{Error, PackageManager},
+};
+
+pub const LOCKFILE: &str = "bun.lock";
+pub const BINARY_LOCKFILE: &str = "bun.lockb";
 
 pub struct BunDetector<'a> {
     repo_root: &'a AbsoluteSystemPath,
@@ -27,9 +34,11 @@ impl<'a> Iterator for BunDetector<'a> {
         }
 
         self.found = true;
-        let package_json = self.repo_root.join_component(LOCKFILE);
+        let has_lockfile = [LOCKFILE, BINARY_LOCKFILE]
+            .into_iter()
+            .any(|lockfile| self.repo_root.join_component(lockfile).exists());
 
-        if package_json.exists() {
+        if has_lockfile {
             Some(Ok(PackageManager::Bun))
         } else {
             None
@@ -37,27 +46,71 @@ impl<'a> Iterator for BunDetector<'a> {
     }
 }
 
+pub(crate) fn prune_patches<R: AsRef<RelativeUnixPath>>(
+    package_json: &PackageJson,
+    patches: &[R],
+) -> PackageJson {
+    let mut pruned_json = package_json.clone();
+    let patches = patches.iter().map(|r| r.as_ref()).collect::<HashSet<_>>();
+
+    if let Some(Value::Object(existing_patches)) = pruned_json.other.get_mut("patchedDependencies")
+    {
+        existing_patches.retain(|_, patch_path| {
+            patch_path
+                .as_str()
+                .and_then(|patch_path| RelativeUnixPath::new(patch_path).ok())
+                .is_some_and(|patch_path| patches.contains(patch_path))
+        });
+    }
+
+    pruned_json
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs::File;
 
     use anyhow::Result;
+    use serde_json::json;
     use tempfile::tempdir;
-    use turbopath::AbsoluteSystemPathBuf;
+    use test_case::test_case;
+    use turbopath::{AbsoluteSystemPathBuf, RelativeUnixPathBuf};
 
-    use super::LOCKFILE;
-    use crate::package_manager::PackageManager;
+    use super::{prune_patches, BINARY_LOCKFILE, LOCKFILE};
+    use crate::{package_json::PackageJson, package_manager::PackageManager};
 
-    #[test]
-    fn test_detect_bun() -> Result<()> {
+    #[test_case(LOCKFILE ; "text lockfile")]
+    #[test_case(BINARY_LOCKFILE ; "binary lockfile")]
+    fn test_detect_bun(lockfile: &str) -> Result<()> {
         let repo_root = tempdir()?;
         let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
 
-        let lockfile_path = repo_root.path().join(LOCKFILE);
+        let lockfile_path = repo_root.path().join(lockfile);
         File::create(lockfile_path)?;
         let package_manager = PackageManager::detect_package_manager(&repo_root_path)?;
         assert_eq!(package_manager, PackageManager::Bun);
 
         Ok(())
     }
+
+    #[test]
+    fn test_prune_patches() -> Result<()> {
+        let package_json = PackageJson::from_value(json!({
+            "name": "bun-patches",
+            "patchedDependencies": {
+                "is-even@1.0.0": "patches/is-even@1.0.0.patch",
+                "is-odd@3.0.1": "patches/is-odd@3.0.1.patch",
+            }
+        }))?;
+        let pruned = prune_patches(
+            &package_json,
+            &[RelativeUnixPathBuf::new("patches/is-odd@3.0.1.patch")?],
+        );
+        assert_eq!(
+            pruned.other.get("patchedDependencies"),
+            Some(&json!({"is-odd@3.0.1": "patches/is-odd@3.0.1.patch"}))
+        );
+
+        Ok(())
+    }
 }
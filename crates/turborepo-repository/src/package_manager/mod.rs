@@ -500,7 +500,8 @@ impl PackageManager {
     ) -> Result<Box<dyn Lockfile>, Error> {
         let lockfile_path = self.lockfile_path(root_path);
         let contents = match self {
-            PackageManager::Bun => {
+            // The binary lockfile can only be read by having bun print it
+            PackageManager::Bun if lockfile_path.file_name() == Some(bun::BINARY_LOCKFILE) => {
                 Command::new(which("bun")?)
                     .arg(lockfile_path.to_string())
                     .current_dir(root_path.to_string())
@@ -526,6 +527,15 @@ impl PackageManager {
             PackageManager::Yarn => {
                 Box::new(turborepo_lockfiles::Yarn1Lockfile::from_bytes(contents)?)
             }
+            // bun.lock is JSON while bun prints bun.lockb in the yarn v1 format
+            PackageManager::Bun
+                if contents
+                    .iter()
+                    .find(|byte| !byte.is_ascii_whitespace())
+                    .is_some_and(|byte| *byte == b'{') =>
+            {
+                Box::new(turborepo_lockfiles::BunTextLockfile::from_bytes(contents)?)
+            }
             PackageManager::Bun => {
                 Box::new(turborepo_lockfiles::BunLockfile::from_bytes(contents)?)
             }
@@ -552,14 +562,23 @@ impl PackageManager {
             PackageManager::Pnpm6 | PackageManager::Pnpm => {
                 pnpm::prune_patches(package_json, patches)
             }
-            PackageManager::Yarn | PackageManager::Npm | PackageManager::Bun => {
-                unreachable!("bun, npm, and yarn 1 don't have a concept of patches")
+            PackageManager::Bun => bun::prune_patches(package_json, patches),
+            PackageManager::Yarn | PackageManager::Npm => {
+                unreachable!("npm and yarn 1 don't have a concept of patches")
             }
         }
     }
 
     pub fn lockfile_path(&self, turbo_root: &AbsoluteSystemPath) -> AbsoluteSystemPathBuf {
-        turbo_root.join_component(self.lockfile_name())
+        let lockfile_path = turbo_root.join_component(self.lockfile_name());
+        // Repos that haven't moved to bun.lock yet only have the binary lockfile
+        if matches!(self, PackageManager::Bun) && !lockfile_path.exists() {
+            let binary_lockfile_path = turbo_root.join_component(bun::BINARY_LOCKFILE);
+            if binary_lockfile_path.exists() {
+                return binary_lockfile_path;
+            }
+        }
+        lockfile_path
     }
 
     pub fn arg_separator(&self, user_args: &[String]) -> Option<&str> {
@@ -4,10 +4,13 @@ use globwalk::fix_glob_pattern;
 use hex::ToHex;
 use ignore::WalkBuilder;
 use sha1::{Digest, Sha1};
-use turbopath::{AbsoluteSystemPath, AnchoredSystemPath, IntoUnix};
+use turbopath::{AbsoluteSystemPath, AnchoredSystemPath, AnchoredSystemPathBuf, IntoUnix};
 use wax::{any, Glob, Pattern};
 
-use crate::{package_deps::GitHashes, Error};
+use crate::{
+    package_deps::{GitHashes, TURBO_ROOT},
+    Error,
+};
 
 fn git_like_hash_file(path: &AbsoluteSystemPath) -> Result<String, Error> {
     let mut hasher = Sha1::new();
@@ -57,17 +60,48 @@ pub(crate) fn get_package_file_hashes_from_processing_gitignore<S: AsRef<str>>(
     let full_package_path = turbo_root.resolve(package_path);
     let mut hashes = GitHashes::new();
 
-    let mut walker_builder = WalkBuilder::new(&full_package_path);
+    // Patterns starting with `$TURBO_ROOT$` can match files outside of the
+    // package. If there are any, the whole repo is walked and the other
+    // patterns are made relative to the repo root. As with git, the package's
+    // own configuration is always included then.
+    let walks_repo = inputs.iter().any(|pattern| {
+        let pattern = pattern.as_ref();
+        pattern
+            .strip_prefix('!')
+            .unwrap_or(pattern)
+            .starts_with(TURBO_ROOT)
+    });
+    let walk_root: &AbsoluteSystemPath = if walks_repo {
+        turbo_root
+    } else {
+        &full_package_path
+    };
+    let package_prefix = package_path.to_unix();
+    let walk_relative = |pattern: &str| match pattern.strip_prefix(TURBO_ROOT) {
+        Some(pattern) => pattern.trim_start_matches('/').to_string(),
+        None if walks_repo && !package_prefix.as_str().is_empty() => {
+            format!("{}/{}", package_prefix, pattern)
+        }
+        None => pattern.to_string(),
+    };
+    let configuration = ["package.json", "turbo.json"];
+    let inputs = inputs
+        .iter()
+        .map(|pattern| pattern.as_ref())
+        .chain(configuration.into_iter().filter(|_| walks_repo));
+
+    let mut walker_builder = WalkBuilder::new(walk_root);
     let mut includes = Vec::new();
     let mut excludes = Vec::new();
     for pattern in inputs {
-        let pattern = pattern.as_ref();
         if let Some(exclusion) = pattern.strip_prefix('!') {
-            let glob = fix_glob_pattern(exclusion).into_unix();
+            let exclusion = walk_relative(exclusion);
+            let glob = fix_glob_pattern(&exclusion).into_unix();
             let g = Glob::new(glob.as_str()).map(|g| g.into_owned())?;
             excludes.push(g);
         } else {
-            let glob = fix_glob_pattern(pattern).into_unix();
+            let pattern = walk_relative(pattern);
+            let glob = fix_glob_pattern(&pattern).into_unix();
             let g = Glob::new(glob.as_str()).map(|g| g.into_owned())?;
             includes.push(g);
         }
@@ -97,7 +131,7 @@ pub(crate) fn get_package_file_hashes_from_processing_gitignore<S: AsRef<str>>(
             continue;
         }
         let path = AbsoluteSystemPath::from_std_path(dirent.path())?;
-        let relative_path = full_package_path.anchor(path)?;
+        let relative_path = walk_root.anchor(path)?;
         let relative_path = relative_path.to_unix();
         if let Some(include_pattern) = include_pattern.as_ref() {
             if !include_pattern.is_match(relative_path.as_str()) {
@@ -114,7 +148,9 @@ pub(crate) fn get_package_file_hashes_from_processing_gitignore<S: AsRef<str>>(
             continue;
         }
         let hash = git_like_hash_file(path)?;
-        hashes.insert(relative_path, hash);
+        let package_relative_path =
+            AnchoredSystemPathBuf::relative_path_between(&full_package_path, path).to_unix();
+        hashes.insert(package_relative_path, hash);
     }
     Ok(hashes)
 }
@@ -8,6 +8,10 @@ use crate::{hash_object::hash_objects, Error, Git, SCM};
 
 pub type GitHashes = HashMap<RelativeUnixPathBuf, String>;
 
+/// Starts task inputs that are relative to the repo root rather than the
+/// package
+pub const TURBO_ROOT: &str = "$TURBO_ROOT$";
+
 impl SCM {
     pub fn get_hashes_for_files(
         &self,
@@ -22,6 +26,10 @@ impl SCM {
         }
     }
 
+    /// Hashes the files of a package that match `inputs`, or all of them if
+    /// there are no inputs. Inputs starting with `$TURBO_ROOT$/` match files
+    /// anywhere in the repo instead. Files are keyed by their path relative to
+    /// the package, so those outside of it start with `../`.
     #[tracing::instrument(skip(self, turbo_root, package_path, inputs))]
     pub fn get_package_file_hashes<S: AsRef<str>>(
         &self,
@@ -160,12 +168,18 @@ impl Git {
         // error further upstream, but since we haven't pulled the switch yet,
         // we need to mimic the Go behavior here and trim leading `/`
         // characters.
+        //
+        // Patterns starting with `$TURBO_ROOT$` are already relative to the repo root.
+        let repo_relative = |glob: &str| match glob.strip_prefix(TURBO_ROOT) {
+            Some(glob) => glob.trim_start_matches('/').to_string(),
+            None => [package_unix_path, glob.trim_start_matches('/')].join("/"),
+        };
         let (inclusions, exclusions): (Vec<String>, Vec<String>) =
             inputs.into_iter().partition_map(|raw_glob| {
                 if let Some(exclusion) = raw_glob.strip_prefix('!') {
-                    Either::Right([package_unix_path, exclusion.trim_start_matches('/')].join("/"))
+                    Either::Right(repo_relative(exclusion))
                 } else {
-                    Either::Left([package_unix_path, raw_glob.trim_start_matches('/')].join("/"))
+                    Either::Left(repo_relative(&raw_glob))
                 }
             });
         let files = globwalk::globwalk(
@@ -405,6 +419,65 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_get_package_deps_from_repo_root() -> Result<(), Error> {
+        let (_repo_root_tmp, repo_root) = tmp_dir();
+        for (path, contents) in [
+            ("my-pkg/package.json", "{}"),
+            ("my-pkg/src/index.js", "index"),
+            ("other-pkg/src/util.ts", "util"),
+            ("shared/config/base.json", "base"),
+            ("shared/config/README.md", "readme"),
+        ] {
+            let file = repo_root.join_unix_path(RelativeUnixPathBuf::new(path)?)?;
+            file.ensure_dir()?;
+            file.create_with_contents(contents)?;
+        }
+        setup_repository(&repo_root);
+        commit_all(&repo_root);
+        let package_path = AnchoredSystemPathBuf::from_raw("my-pkg")?;
+
+        for scm in [SCM::new(&repo_root), SCM::Manual] {
+            let hashes = scm.get_package_file_hashes(
+                &repo_root,
+                &package_path,
+                &[
+                    "src/**",
+                    "$TURBO_ROOT$/shared/config/**",
+                    "!$TURBO_ROOT$/shared/**/*.md",
+                    "$TURBO_ROOT$/other-pkg/src/**",
+                ],
+            )?;
+            assert_eq!(
+                hashes[&RelativeUnixPathBuf::new("../shared/config/base.json")?],
+                "8681f8b8f32615a16703053bc1eaffb3e5e720a5"
+            );
+            for key in [
+                "src/index.js",
+                "../shared/config/base.json",
+                "../other-pkg/src/util.ts",
+            ] {
+                assert!(
+                    hashes.contains_key(&RelativeUnixPathBuf::new(key)?),
+                    "{key}"
+                );
+            }
+            assert!(!hashes.contains_key(&RelativeUnixPathBuf::new("../shared/config/README.md")?));
+
+            // Only the package's own configuration is hashed along with files
+            // outside of it
+            let hashes = scm.get_package_file_hashes(
+                &repo_root,
+                &package_path,
+                &["$TURBO_ROOT$/shared/config/*.json"],
+            )?;
+            let mut keys = hashes.keys().map(|key| key.as_str()).collect::<Vec<_>>();
+            keys.sort();
+            assert_eq!(keys, ["../shared/config/base.json", "package.json"]);
+        }
+        Ok(())
+    }
+
     fn to_hash_map(pairs: &[(&str, &str)]) -> GitHashes {
         HashMap::from_iter(
             pairs
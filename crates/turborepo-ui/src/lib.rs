@@ -1,11 +1,13 @@
 //! Turborepo's terminal UI library. Handles elements like spinners, colors,
 //! and logging. Includes a `PrefixedUI` struct that can be used to prefix
 //! output, and a `ColorSelector` that lets multiple concurrent resources get
-//! an assigned color.
+//! an assigned color. The `tui` module provides an interactive dashboard as
+//! an alternative to prefixed output.
 mod color_selector;
 mod logs;
 mod output;
 mod prefixed;
+pub mod tui;
 
 use std::{borrow::Cow, env, f64::consts::PI, time::Duration};
 
@@ -0,0 +1,441 @@
+use std::{
+    io::{self, Stdout, Write},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+
+use super::{
+    event::{Event, TaskResult},
+    handle::AppReceiver,
+    task::{Task, TaskStatus},
+    Error,
+};
+
+// How often the dashboard redraws while events are arriving
+const FRAMERATE: Duration = Duration::from_millis(60);
+// How many lines PageUp and PageDown move by when the pane hasn't been drawn
+const DEFAULT_PAGE: usize = 10;
+
+/// Why the dashboard stopped rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppExit {
+    /// The run finished or every sender was dropped
+    Finished,
+    /// The user pressed Ctrl-C. Raw mode swallows the signal, so the caller is
+    /// responsible for shutting the run down.
+    Interrupted,
+}
+
+struct App {
+    tasks: Vec<Task>,
+    list_state: ListState,
+    // First visible log line of the selected task, `None` follows new output
+    scroll: Option<usize>,
+    pane_height: usize,
+}
+
+/// Takes over the terminal and renders events from `receiver` until the run
+/// stops or the user interrupts it. Logs of failed tasks are printed once the
+/// terminal has been restored so they outlive the dashboard.
+pub fn run_app(tasks: Vec<String>, receiver: AppReceiver) -> Result<AppExit, Error> {
+    let mut terminal = startup()?;
+    let mut app = App::new(tasks);
+
+    let result = run_app_inner(&mut terminal, &mut app, &receiver);
+    let cleanup_result = cleanup(terminal);
+    let exit = result?;
+    cleanup_result?;
+
+    app.print_failed_task_logs(io::stdout().lock())
+        .map_err(Error::Logs)?;
+    Ok(exit)
+}
+
+fn run_app_inner(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    app: &mut App,
+    receiver: &AppReceiver,
+) -> Result<AppExit, Error> {
+    loop {
+        terminal.draw(|f| view(app, f)).map_err(Error::Draw)?;
+
+        while crossterm::event::poll(Duration::ZERO).map_err(Error::Input)? {
+            let crossterm::event::Event::Key(key) =
+                crossterm::event::read().map_err(Error::Input)?
+            else {
+                continue;
+            };
+            // Windows also reports key releases
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if let Some(exit) = app.handle_key(key) {
+                return Ok(exit);
+            }
+        }
+
+        let deadline = Instant::now() + FRAMERATE;
+        loop {
+            match receiver.recv(deadline) {
+                Ok(event) => {
+                    if let Some(exit) = app.update(event) {
+                        return Ok(exit);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                // Nothing is left to report on
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(AppExit::Finished),
+            }
+        }
+    }
+}
+
+fn startup() -> Result<Terminal<CrosstermBackend<Stdout>>, Error> {
+    crossterm::terminal::enable_raw_mode().map_err(Error::Setup)?;
+    let mut stdout = io::stdout();
+    crossterm::execute!(stdout, EnterAlternateScreen).map_err(Error::Setup)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout)).map_err(Error::Setup)?;
+    terminal.hide_cursor().map_err(Error::Setup)?;
+    Ok(terminal)
+}
+
+fn cleanup(mut terminal: Terminal<CrosstermBackend<Stdout>>) -> Result<(), Error> {
+    crossterm::execute!(terminal.backend_mut(), LeaveAlternateScreen).map_err(Error::Cleanup)?;
+    crossterm::terminal::disable_raw_mode().map_err(Error::Cleanup)?;
+    terminal.show_cursor().map_err(Error::Cleanup)?;
+    Ok(())
+}
+
+impl App {
+    fn new(tasks: Vec<String>) -> Self {
+        let tasks = tasks.into_iter().map(Task::new).collect::<Vec<_>>();
+        let list_state = ListState::default().with_selected((!tasks.is_empty()).then_some(0));
+        Self {
+            tasks,
+            list_state,
+            scroll: None,
+            pane_height: DEFAULT_PAGE,
+        }
+    }
+
+    fn update(&mut self, event: Event) -> Option<AppExit> {
+        match event {
+            Event::StartTask { task } => self.task_mut(task).start(),
+            Event::TaskOutput { task, output } => self.task_mut(task).push_output(&output),
+            Event::EndTask { task, result } => self.task_mut(task).finish(result),
+            Event::Stop => return Some(AppExit::Finished),
+        }
+        None
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<AppExit> {
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(AppExit::Interrupted)
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.select(|selected, _| selected.checked_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.select(|selected, len| (selected + 1 < len).then_some(selected + 1))
+            }
+            KeyCode::PageUp => self.scroll_up(self.pane_height),
+            KeyCode::PageDown => self.scroll_down(self.pane_height),
+            KeyCode::Home => self.scroll = Some(0),
+            KeyCode::End => self.scroll = None,
+            _ => (),
+        }
+        None
+    }
+
+    fn task_mut(&mut self, name: String) -> &mut Task {
+        let index = match self.tasks.iter().position(|task| task.name() == name) {
+            Some(index) => index,
+            // Tasks that weren't planned up front are still worth showing
+            None => {
+                self.tasks.push(Task::new(name));
+                if self.list_state.selected().is_none() {
+                    self.list_state.select(Some(0));
+                }
+                self.tasks.len() - 1
+            }
+        };
+        &mut self.tasks[index]
+    }
+
+    fn selected_task(&self) -> Option<&Task> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.tasks.get(index))
+    }
+
+    fn select(&mut self, next: impl FnOnce(usize, usize) -> Option<usize>) {
+        let Some(selected) = self.list_state.selected() else {
+            return;
+        };
+        if let Some(index) = next(selected, self.tasks.len()) {
+            self.list_state.select(Some(index));
+            self.scroll = None;
+        }
+    }
+
+    fn bottom(&self) -> usize {
+        self.selected_task()
+            .map_or(0, |task| task.line_count().saturating_sub(self.pane_height))
+    }
+
+    fn scroll_up(&mut self, lines: usize) {
+        let start = self.scroll.unwrap_or_else(|| self.bottom());
+        self.scroll = Some(start.saturating_sub(lines));
+    }
+
+    fn scroll_down(&mut self, lines: usize) {
+        let Some(start) = self.scroll else {
+            return;
+        };
+        let start = start + lines;
+        self.scroll = (start < self.bottom()).then_some(start);
+    }
+
+    fn print_failed_task_logs(&self, mut out: impl Write) -> io::Result<()> {
+        for task in &self.tasks {
+            if !matches!(
+                task.status(),
+                TaskStatus::Finished {
+                    result: TaskResult::Failure,
+                    ..
+                }
+            ) {
+                continue;
+            }
+            for line in task.lines() {
+                writeln!(out, "{}: {line}", task.name())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn view(app: &mut App, f: &mut Frame) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(f.size());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(rows[1]);
+
+    f.render_widget(progress(&app.tasks), rows[0]);
+    let list = task_list(&app.tasks);
+    f.render_stateful_widget(list, columns[0], &mut app.list_state);
+    render_logs(app, f, columns[1]);
+    f.render_widget(
+        Paragraph::new("↑/↓ select task  PgUp/PgDn scroll  End follow output  Ctrl-C interrupt")
+            .style(Style::default().add_modifier(Modifier::DIM)),
+        rows[2],
+    );
+}
+
+fn progress(tasks: &[Task]) -> Gauge<'static> {
+    let (mut finished, mut running, mut failed) = (0, 0, 0);
+    for task in tasks {
+        match task.status() {
+            TaskStatus::Planned => (),
+            TaskStatus::Running { .. } => running += 1,
+            TaskStatus::Finished { result, .. } => {
+                finished += 1;
+                if result == TaskResult::Failure {
+                    failed += 1;
+                }
+            }
+        }
+    }
+    let ratio = match tasks.len() {
+        0 => 0.0,
+        total => finished as f64 / total as f64,
+    };
+    let color = if failed > 0 { Color::Red } else { Color::Green };
+
+    Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title("turbo run"))
+        .gauge_style(Style::default().fg(color))
+        .ratio(ratio)
+        .label(format!(
+            "{finished}/{} tasks  {running} running  {failed} failed",
+            tasks.len()
+        ))
+}
+
+fn task_list(tasks: &[Task]) -> List<'static> {
+    let items = tasks
+        .iter()
+        .map(|task| {
+            let (symbol, style, detail) = match task.status() {
+                TaskStatus::Planned => ("·", Style::default().add_modifier(Modifier::DIM), None),
+                TaskStatus::Running { started_at } => (
+                    "»",
+                    Style::default().fg(Color::Yellow),
+                    Some(format_duration(started_at.elapsed())),
+                ),
+                TaskStatus::Finished {
+                    result: TaskResult::Success,
+                    duration,
+                } => (
+                    "✓",
+                    Style::default().fg(Color::Green),
+                    Some(format_duration(duration)),
+                ),
+                TaskStatus::Finished {
+                    result: TaskResult::CacheHit,
+                    ..
+                } => (
+                    "✓",
+                    Style::default().fg(Color::Cyan),
+                    Some("cached".to_string()),
+                ),
+                TaskStatus::Finished {
+                    result: TaskResult::Failure,
+                    duration,
+                } => (
+                    "✗",
+                    Style::default().fg(Color::Red),
+                    Some(format_duration(duration)),
+                ),
+            };
+            let mut spans = vec![
+                Span::styled(format!("{symbol} "), style),
+                Span::raw(task.name().to_string()),
+            ];
+            if let Some(detail) = detail {
+                spans.push(Span::styled(
+                    format!(" {detail}"),
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect::<Vec<_>>();
+
+    List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Tasks"))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+}
+
+fn render_logs(app: &mut App, f: &mut Frame, area: Rect) {
+    // Borders take up a line on either side
+    app.pane_height = usize::from(area.height.saturating_sub(2)).max(1);
+    let start = app.scroll.unwrap_or_else(|| app.bottom());
+    let (title, lines) = match app.selected_task() {
+        Some(task) => (
+            task.name().to_string(),
+            task.lines()
+                .skip(start)
+                .take(app.pane_height)
+                .map(|line| Line::from(line.to_string()))
+                .collect::<Vec<_>>(),
+        ),
+        None => ("Logs".to_string(), Vec::new()),
+    };
+
+    f.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)),
+        area,
+    );
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1}s", duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_unplanned_tasks_are_added() {
+        let mut app = App::new(vec!["web#build".into()]);
+        app.update(Event::StartTask {
+            task: "docs#build".into(),
+        });
+        app.update(Event::TaskOutput {
+            task: "docs#build".into(),
+            output: b"building\n".to_vec(),
+        });
+        assert_eq!(app.tasks.len(), 2);
+        assert_eq!(app.tasks[1].lines().collect::<Vec<_>>(), vec!["building"]);
+        assert_eq!(app.update(Event::Stop), Some(AppExit::Finished));
+    }
+
+    #[test]
+    fn test_selection_stays_in_bounds() {
+        let mut app = App::new(vec!["a#build".into(), "b#build".into()]);
+        app.handle_key(key(KeyCode::Up));
+        assert_eq!(app.list_state.selected(), Some(0));
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Down));
+        assert_eq!(app.list_state.selected(), Some(1));
+        assert_eq!(
+            app.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(AppExit::Interrupted)
+        );
+    }
+
+    #[test]
+    fn test_scrolling_returns_to_following_output() {
+        let mut app = App::new(vec!["web#build".into()]);
+        app.pane_height = 5;
+        for i in 0..20 {
+            app.update(Event::TaskOutput {
+                task: "web#build".into(),
+                output: format!("line {i}\n").into_bytes(),
+            });
+        }
+        app.handle_key(key(KeyCode::PageUp));
+        assert_eq!(app.scroll, Some(10));
+        app.handle_key(key(KeyCode::PageDown));
+        assert_eq!(app.scroll, None);
+    }
+
+    #[test]
+    fn test_failed_task_logs_are_printed() {
+        let mut app = App::new(vec!["web#build".into(), "docs#build".into()]);
+        for (task, result) in [
+            ("web#build", TaskResult::Failure),
+            ("docs#build", TaskResult::Success),
+        ] {
+            app.update(Event::StartTask { task: task.into() });
+            app.update(Event::TaskOutput {
+                task: task.into(),
+                output: b"oops\n".to_vec(),
+            });
+            app.update(Event::EndTask {
+                task: task.into(),
+                result,
+            });
+        }
+
+        let mut out = Vec::new();
+        app.print_failed_task_logs(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "web#build: oops\n");
+    }
+}
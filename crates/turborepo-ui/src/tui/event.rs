@@ -0,0 +1,14 @@
+pub enum Event {
+    StartTask { task: String },
+    TaskOutput { task: String, output: Vec<u8> },
+    EndTask { task: String, result: TaskResult },
+    Stop,
+}
+
+/// How a task finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskResult {
+    Success,
+    CacheHit,
+    Failure,
+}
@@ -0,0 +1,110 @@
+use std::{
+    io,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use super::event::{Event, TaskResult};
+
+/// Sends task progress and output to the dashboard. Cheap to clone, so each
+/// task can hold its own copy.
+#[derive(Debug, Clone)]
+pub struct AppSender {
+    primary: mpsc::Sender<Event>,
+}
+
+/// Receives the events sent by any `AppSender`
+pub struct AppReceiver {
+    primary: mpsc::Receiver<Event>,
+}
+
+/// Handle for a single task. Output written to it shows up in the task's log
+/// pane.
+#[derive(Debug, Clone)]
+pub struct TuiTask {
+    name: String,
+    handle: AppSender,
+}
+
+impl AppSender {
+    pub fn new() -> (Self, AppReceiver) {
+        let (primary, receiver) = mpsc::channel();
+        (Self { primary }, AppReceiver { primary: receiver })
+    }
+
+    /// Handle for reporting on `task`
+    pub fn new_task(&self, task: String) -> TuiTask {
+        TuiTask {
+            name: task,
+            handle: self.clone(),
+        }
+    }
+
+    /// Stop rendering and give the terminal back
+    pub fn stop(&self) {
+        // If the receiver is gone the dashboard has already exited
+        self.primary.send(Event::Stop).ok();
+    }
+
+    fn send(&self, event: Event) {
+        self.primary.send(event).ok();
+    }
+}
+
+impl AppReceiver {
+    /// Wait for the next event, giving up once `deadline` has passed
+    pub(crate) fn recv(&self, deadline: Instant) -> Result<Event, mpsc::RecvTimeoutError> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        if timeout == Duration::ZERO {
+            return self.primary.try_recv().map_err(|err| match err {
+                mpsc::TryRecvError::Empty => mpsc::RecvTimeoutError::Timeout,
+                mpsc::TryRecvError::Disconnected => mpsc::RecvTimeoutError::Disconnected,
+            });
+        }
+        self.primary.recv_timeout(timeout)
+    }
+}
+
+impl TuiTask {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn start(&self) {
+        self.handle.send(Event::StartTask {
+            task: self.name.clone(),
+        });
+    }
+
+    pub fn succeeded(&self, is_cache_hit: bool) {
+        self.finish(match is_cache_hit {
+            true => TaskResult::CacheHit,
+            false => TaskResult::Success,
+        });
+    }
+
+    pub fn failed(&self) {
+        self.finish(TaskResult::Failure);
+    }
+
+    fn finish(&self, result: TaskResult) {
+        self.handle.send(Event::EndTask {
+            task: self.name.clone(),
+            result,
+        });
+    }
+}
+
+impl io::Write for TuiTask {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.handle.send(Event::TaskOutput {
+            task: self.name.clone(),
+            output: buf.to_vec(),
+        });
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
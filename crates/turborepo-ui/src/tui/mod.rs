@@ -0,0 +1,31 @@
+//! An interactive terminal dashboard for `turbo run --ui=tui`. Tasks report
+//! their progress and output through an `AppSender`, while `run_app` owns the
+//! terminal and renders a task list next to a log pane for the selected task.
+mod app;
+mod event;
+mod handle;
+mod task;
+
+pub use app::{run_app, AppExit};
+pub use event::TaskResult;
+pub use handle::{AppReceiver, AppSender, TuiTask};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("unable to set up the terminal: {0}")]
+    Setup(#[source] std::io::Error),
+    #[error("unable to draw to the terminal: {0}")]
+    Draw(#[source] std::io::Error),
+    #[error("unable to read terminal input: {0}")]
+    Input(#[source] std::io::Error),
+    #[error("unable to restore the terminal: {0}")]
+    Cleanup(#[source] std::io::Error),
+    #[error("unable to print task logs: {0}")]
+    Logs(#[source] std::io::Error),
+}
+
+/// The dashboard needs an interactive terminal to draw to and read keys from
+pub fn is_supported() -> bool {
+    atty::is(atty::Stream::Stdout) && atty::is(atty::Stream::Stdin)
+}
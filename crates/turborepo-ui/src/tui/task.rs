@@ -0,0 +1,162 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use super::event::TaskResult;
+
+// Older lines are dropped once a task has logged this many
+const MAX_LOG_LINES: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    Planned,
+    Running {
+        started_at: Instant,
+    },
+    Finished {
+        result: TaskResult,
+        duration: Duration,
+    },
+}
+
+/// A task as displayed in the dashboard along with the output it has logged
+pub struct Task {
+    name: String,
+    status: TaskStatus,
+    lines: VecDeque<String>,
+    // Output that has been written without a trailing newline yet
+    partial_line: String,
+}
+
+impl Task {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            status: TaskStatus::Planned,
+            lines: VecDeque::with_capacity(0),
+            partial_line: String::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn status(&self) -> TaskStatus {
+        self.status
+    }
+
+    pub fn start(&mut self) {
+        self.status = TaskStatus::Running {
+            started_at: Instant::now(),
+        };
+    }
+
+    pub fn finish(&mut self, result: TaskResult) {
+        let duration = match self.status {
+            TaskStatus::Running { started_at } => started_at.elapsed(),
+            _ => Duration::ZERO,
+        };
+        self.status = TaskStatus::Finished { result, duration };
+    }
+
+    /// Appends task output to the log. Colors are stripped as the pane applies
+    /// its own styling, and carriage returns overwrite the current line like
+    /// they would in a terminal.
+    pub fn push_output(&mut self, output: &[u8]) {
+        let output = String::from_utf8_lossy(output);
+        let output = console::strip_ansi_codes(&output);
+        let mut segments = output.split('\n').peekable();
+        while let Some(segment) = segments.next() {
+            // A trailing carriage return is part of a CRLF line ending
+            let mut segment = segment.strip_suffix('\r').unwrap_or(segment);
+            if let Some((_, after)) = segment.rsplit_once('\r') {
+                self.partial_line.clear();
+                segment = after;
+            }
+            self.partial_line.push_str(segment);
+            if segments.peek().is_some() {
+                let line = std::mem::take(&mut self.partial_line);
+                self.push_line(line);
+            }
+        }
+    }
+
+    /// All complete lines followed by any unterminated one
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines
+            .iter()
+            .map(String::as_str)
+            .chain((!self.partial_line.is_empty()).then_some(self.partial_line.as_str()))
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len() + usize::from(!self.partial_line.is_empty())
+    }
+
+    fn push_line(&mut self, line: String) {
+        if self.lines.len() == MAX_LOG_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_output_split_into_lines() {
+        let mut task = Task::new("web#build".into());
+        task.push_output(b"compiling\nbund");
+        task.push_output(b"ling\ndone");
+        assert_eq!(
+            task.lines().collect::<Vec<_>>(),
+            vec!["compiling", "bundling", "done"]
+        );
+        assert_eq!(task.line_count(), 3);
+    }
+
+    #[test]
+    fn test_output_strips_colors() {
+        let mut task = Task::new("web#build".into());
+        task.push_output(b"\x1b[32mready\x1b[0m\n");
+        assert_eq!(task.lines().collect::<Vec<_>>(), vec!["ready"]);
+    }
+
+    #[test]
+    fn test_carriage_return_overwrites_line() {
+        let mut task = Task::new("web#build".into());
+        task.push_output(b"progress 10%\rprogress 50%");
+        task.push_output(b"\rprogress 100%\r\n");
+        assert_eq!(task.lines().collect::<Vec<_>>(), vec!["progress 100%"]);
+    }
+
+    #[test]
+    fn test_log_is_capped() {
+        let mut task = Task::new("web#build".into());
+        for i in 0..MAX_LOG_LINES + 10 {
+            task.push_output(format!("line {i}\n").as_bytes());
+        }
+        assert_eq!(task.line_count(), MAX_LOG_LINES);
+        assert_eq!(task.lines().next(), Some("line 10"));
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        let mut task = Task::new("web#build".into());
+        assert_eq!(task.status(), TaskStatus::Planned);
+        task.start();
+        assert!(matches!(task.status(), TaskStatus::Running { .. }));
+        task.finish(TaskResult::CacheHit);
+        assert!(matches!(
+            task.status(),
+            TaskStatus::Finished {
+                result: TaskResult::CacheHit,
+                ..
+            }
+        ));
+    }
+}
@@ -3,6 +3,7 @@ export type {
   Schema,
   Pipeline,
   RemoteCache,
+  RemoteCacheBackend,
 } from "./types/config";
 
 export type { DryRun } from "./types/dry";
@@ -33,11 +33,23 @@ export interface WorkspaceSchema extends BaseSchema {
    * and overrides with the keys provided
    * in your Workspace Configs.
    *
-   * Currently, only the "//" value is allowed.
+   * Besides the root ("//"), any workspace with a `turbo.json` can be
+   * extended from by its package name. They are applied in order after
+   * the root, and may extend from other workspaces themselves.
    *
    * @defaultValue ["//"]
    */
   extends: Array<string>;
+
+  /**
+   * Tags for this workspace, which the `boundaries` of the root `turbo.json`
+   * restrict the dependencies of.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#tags
+   *
+   * @defaultValue []
+   */
+  tags?: Array<string>;
 }
 
 export interface RootSchema extends BaseSchema {
@@ -103,6 +115,18 @@ export interface RootSchema extends BaseSchema {
    */
   globalDotEnv?: null | Array<AnchoredUnixPath>;
 
+  /**
+   * Load the variables of the `globalDotEnv` and `dotEnv` files into the
+   * environment tasks are run with. Variables that are already set are
+   * not overridden, and in strict env mode only variables the task could
+   * get from the environment are loaded.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#loaddotenv
+   *
+   * @defaultValue false
+   */
+  loadDotEnv?: boolean;
+
   /**
    * Configuration options that control how turbo interfaces with the remote cache.
    *
@@ -111,6 +135,26 @@ export interface RootSchema extends BaseSchema {
    * @defaultValue `{}`
    */
   remoteCache?: RemoteCache;
+
+  /**
+   * Where to export an OpenTelemetry trace of every run, with a span for
+   * each task.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#otel
+   *
+   * @defaultValue undefined
+   */
+  otel?: OpenTelemetry;
+
+  /**
+   * Rules for which workspaces may depend on each other, checked by
+   * `turbo boundaries`.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#boundaries
+   *
+   * @defaultValue undefined
+   */
+  boundaries?: Boundaries;
 }
 
 export interface Pipeline {
@@ -214,6 +258,9 @@ export interface Pipeline {
    *
    * If omitted or empty, all files in the package are considered as inputs.
    *
+   * Globs are relative to the package, unless they start with `$TURBO_ROOT$/`,
+   * which makes them relative to the root of the repository.
+   *
    * Documentation: https://turbo.build/repo/docs/reference/configuration#inputs
    *
    * @defaultValue []
@@ -249,6 +296,68 @@ export interface Pipeline {
    * @defaultValue false
    */
   persistent?: boolean;
+
+  /**
+   * The maximum time a single run of the task may take, written as a
+   * duration such as "30s" or "5m". A task that runs longer is stopped and
+   * treated as failed.
+   *
+   * @defaultValue no timeout
+   */
+  timeout?: string;
+
+  /**
+   * The number of times to re-run the task after it fails or times out
+   * before reporting the failure.
+   *
+   * @defaultValue 0
+   */
+  retries?: number;
+}
+
+export interface OpenTelemetry {
+  /**
+   * The base URL of an OpenTelemetry collector's OTLP/HTTP receiver, e.g.
+   * `http://localhost:4318`. Traces are sent to `/v1/traces` under it.
+   * `--otel-endpoint` takes precedence over this.
+   */
+  endpoint?: string;
+
+  /**
+   * Headers to send with every export, e.g. to authenticate with the collector.
+   *
+   * @defaultValue {}
+   */
+  headers?: Record<string, string>;
+}
+
+export interface Boundaries {
+  /**
+   * Rules for the dependencies of workspaces with each tag, keyed by the tag.
+   *
+   * @defaultValue {}
+   */
+  tags?: Record<string, TagRules>;
+
+  /**
+   * Check boundaries before every `turbo run`, failing the run if any are
+   * violated.
+   *
+   * @defaultValue false
+   */
+  checkBeforeRun?: boolean;
+}
+
+export interface TagRules {
+  /**
+   * The tags a dependency must have at least one of.
+   */
+  allow?: Array<string>;
+
+  /**
+   * The tags a dependency must not have.
+   */
+  deny?: Array<string>;
 }
 
 export interface RemoteCache {
@@ -271,6 +380,60 @@ export interface RemoteCache {
    * @defaultValue true
    */
   enabled?: boolean;
+
+  /**
+   * Where remote cache artifacts are stored. Defaults to the Vercel Remote Cache.
+   * Self-hosted backends don't require `turbo login` or `turbo link`.
+   *
+   * @defaultValue `{ "type": "vercel" }`
+   */
+  backend?: RemoteCacheBackend;
+}
+
+export type RemoteCacheBackend =
+  | VercelRemoteCacheBackend
+  | S3RemoteCacheBackend
+  | DirectoryRemoteCacheBackend;
+
+export interface VercelRemoteCacheBackend {
+  type: "vercel";
+}
+
+export interface S3RemoteCacheBackend {
+  type: "s3";
+
+  /**
+   * The bucket that artifacts are stored in. Requests are signed with the
+   * credentials from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and
+   * `AWS_SESSION_TOKEN`.
+   */
+  bucket: string;
+
+  /**
+   * @defaultValue `"us-east-1"`
+   */
+  region?: string;
+
+  /**
+   * The endpoint of an S3 compatible object store, e.g. a MinIO server. When set,
+   * objects are addressed path-style as `<endpoint>/<bucket>/<key>`.
+   */
+  endpoint?: string;
+
+  /**
+   * Prepended to the key of every artifact.
+   */
+  prefix?: string;
+}
+
+export interface DirectoryRemoteCacheBackend {
+  type: "directory";
+
+  /**
+   * A directory shared between machines, e.g. an NFS mount. Relative paths are
+   * resolved from the repository root.
+   */
+  path: string;
 }
 
 export type OutputMode =
@@ -0,0 +1,21 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup.sh
+  $ . ${TESTDIR}/../../../helpers/copy_fixture.sh $(pwd) bun_monorepo ${TESTDIR}/../../fixtures
+
+Prune docs
+We expect docs to keep its own copy of typescript and to drop everything only web uses
+  $ ${TURBO} prune docs
+  Generating pruned monorepo for docs in .*out (re)
+   - Added docs
+   - Added ui
+  $ grep -E '^    "[^"]+": \[' out/bun.lock | cut -d: -f1
+      "docs"
+      "docs/typescript"
+      "js-tokens"
+      "loose-envify"
+      "object-assign"
+      "react"
+      "typescript"
+      "ui"
+  $ grep -F '"apps/web"' out/bun.lock
+  [1]
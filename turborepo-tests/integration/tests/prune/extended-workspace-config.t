@@ -0,0 +1,20 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh monorepo_with_root_dep pnpm@7.25.1
+
+Extend the docs workspace's turbo.json from web
+  $ echo '{"extends": ["//", "docs"]}' > apps/web/turbo.json
+
+Make sure that the extended docs workspace is part of the prune output
+  $ ${TURBO} prune web
+  Generating pruned monorepo for web in .*(\/|\\)out (re)
+   - Added docs
+   - Added shared
+   - Added util
+   - Added web
+  $ cat out/apps/docs/turbo.json
+  {
+    "extends": ["//"],
+    "pipeline": {
+      "new-task": {}
+    }
+  }
@@ -0,0 +1,37 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh
+
+A worker refuses to start without a token
+  $ ${TURBO} worker --port=9090 > worker.log 2>&1
+  [1]
+  $ grep --quiet "TURBO_WORKER_TOKEN must be set" worker.log
+
+Start a worker that shares a cache directory with the run
+  $ export TURBO_WORKER_TOKEN=integration-test-token
+  $ ${TURBO} worker -vv --port=9090 --cache-dir=.worker-cache > worker.log 2>&1 &
+  $ WORKER_PID=$!
+  $ for i in $(seq 1 100); do grep --quiet "serving tasks on 127.0.0.1:9090" worker.log && break; sleep 0.1; done
+  $ grep --quiet "serving tasks on 127.0.0.1:9090" worker.log
+
+The task runs on the worker, and its logs are restored from the shared cache
+  $ ${TURBO} run build --filter=my-app --workers=127.0.0.1:9090 --cache-dir=.worker-cache > run.log 2>&1
+  $ grep --quiet "my-app:build: building" run.log
+  $ grep --quiet "1 successful, 1 total" run.log
+  $ grep --quiet "building" apps/my-app/.turbo/turbo-build.log
+
+A second worker sharing the cache takes tasks while the first is busy
+  $ ${TURBO} worker -vv --port=9091 --cache-dir=.worker-cache > worker2.log 2>&1 &
+  $ WORKER2_PID=$!
+  $ for i in $(seq 1 100); do grep --quiet "serving tasks on 127.0.0.1:9091" worker2.log && break; sleep 0.1; done
+  $ ${TURBO} run build --workers=127.0.0.1:9090,127.0.0.1:9091 --cache-dir=.worker-cache --force > run.log 2>&1
+  $ grep --quiet "2 successful, 2 total" run.log
+  $ grep --quiet "running .*#build" worker.log
+  $ grep --quiet "running .*#build" worker2.log
+  $ kill $WORKER2_PID
+
+A run with a different token is turned away
+  $ TURBO_WORKER_TOKEN=wrong ${TURBO} run build --filter=my-app --workers=127.0.0.1:9090 --cache-dir=.worker-cache --force > run.log 2>&1
+  [1]
+  $ grep --quiet "invalid or missing worker token" run.log
+
+  $ kill $WORKER_PID
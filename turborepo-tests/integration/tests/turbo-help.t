@@ -9,15 +9,21 @@ Test help flag
   
   Commands:
     bin         Get the path to the Turbo binary
+    boundaries  Check that workspaces only depend on the packages they're allowed to
+    cache       Inspect and clean up the local filesystem cache
     completion  Generate the autocompletion script for the specified shell
     daemon      Runs the Turborepo background daemon
+    deps        List external packages that are resolved to more than one version
     generate    Generate a new app / package
     link        Link your local directory to a Vercel organization and enable remote caching
     login       Login to your Vercel account
     logout      Logout to your Vercel account
     prune       Prepare a subset of your monorepo
+    query       Query information about the repository
     run         Run tasks across projects in your monorepo
     unlink      Unlink the current directory from your Vercel organization and disable Remote Caching
+    watch       Run tasks and re-run them as their inputs change
+    worker      Run tasks on behalf of `turbo run --workers`
   
   Options:
         --version                         
@@ -43,6 +49,12 @@ Test help flag
             Override the filesystem cache directory
         --cache-workers <CACHE_WORKERS>
             Set the number of concurrent cache operations (default 10) [default: 10]
+        --cache-max-size <CACHE_MAX_SIZE>
+            Evict the least recently used entries from the filesystem cache before running once it is larger than this, e.g. 10GB or 500MB [env: TURBO_CACHE_MAX_SIZE=]
+        --cache-max-age <CACHE_MAX_AGE>
+            Evict entries from the filesystem cache before running if they haven't been used for this long, e.g. 7d or 12h [env: TURBO_CACHE_MAX_AGE=]
+        --cache-format <CACHE_FORMAT>
+            Set how artifacts are stored in the cache. "content-addressed" stores each distinct file once, shared between artifacts, and only uploads files the remote cache doesn't have. (default tarball) [env: TURBO_CACHE_FORMAT=] [possible values: tarball, content-addressed]
         --concurrency <CONCURRENCY>
             Limit the concurrency of task execution. Use 1 for serial (i.e. one-at-a-time) execution
         --continue
@@ -79,8 +91,12 @@ Test help flag
             Set type of process output logging. Use "full" to show all output. Use "hash-only" to show only turbo-computed task hashes. Use "new-only" to show only new output with only hashes for cached tasks. Use "none" to hide process output. (default full) [possible values: full, none, hash-only, new-only, errors-only]
         --log-order <LOG_ORDER>
             Set type of task output order. Use "stream" to show output as soon as it is available. Use "grouped" to show output when a command has finished execution. Use "auto" to let turbo decide based on its own heuristics. (default auto) [env: TURBO_LOG_ORDER=] [default: auto] [possible values: auto, stream, grouped]
+        --ui <UI>
+            Set how task progress and output are displayed. Use "stream" to print task output as it is logged. Use "tui" for an interactive dashboard with a scrollable log pane per task, which falls back to "stream" when not run in a terminal. (default stream) [env: TURBO_UI=] [default: stream] [possible values: stream, tui]
         --only
             Only executes the tasks specified, does not execute parent tasks
+        --otel-endpoint <URL>
+            Export a trace of the run, with a span for each task, to this OpenTelemetry collector over OTLP/HTTP, e.g. http://localhost:4318 [env: TURBO_OTEL_ENDPOINT=]
         --parallel
             Execute all tasks in parallel
         --profile <PROFILE>
@@ -91,12 +107,18 @@ Test help flag
             Ignore the local filesystem cache for all tasks. Only allow reading and caching artifacts using the remote cache [env: TURBO_REMOTE_ONLY=] [default: false] [possible values: true, false]
         --remote-cache-read-only [<BOOL>]
             Treat remote cache as read only [env: TURBO_REMOTE_CACHE_READ_ONLY=] [default: false] [possible values: true, false]
+        --report <FORMAT:PATH>
+            Write a report of each task's result once the run finishes, given as <FORMAT>:<PATH>. Supported formats are "junit" and "tap". Can be passed more than once [env: TURBO_REPORT=]
         --scope <SCOPE>
             Specify package(s) to act as entry points for task execution. Supports globs
         --since <SINCE>
             Limit/Set scope to changed packages since a mergebase. This uses the git diff ${target_branch}... mechanism to identify which packages have changed
         --summarize [<SUMMARIZE>]
             Generate a summary of the turbo run [env: TURBO_RUN_SUMMARY=] [possible values: true, false]
+        --explain-hash [<SUMMARY>]
+            Explain why tasks missed the cache by comparing their hash inputs against a previous run summary. Defaults to the most recent summary in .turbo/runs. Saves a summary of this run
+        --workers <ADDRESSES>
+            Run tasks on `turbo worker` processes at these addresses, given as host:port, rather than locally. Workers must share a cache with this run, so their outputs can be restored here [env: TURBO_WORKERS=]
         --log-prefix <LOG_PREFIX>
             Use "none" to remove prefixes from task logs. Use "task" to get task id prefixing. Use "auto" to let turbo decide how to prefix the logs based on the execution environment. In most cases this will be the same as "task". Note that tasks running in parallel interleave their logs, so removing prefixes can make it difficult to associate logs with tasks. Use --log-order=grouped to prevent interleaving. (default auto) [default: auto] [possible values: auto, none, task]
 
@@ -112,15 +134,21 @@ Test help flag
   
   Commands:
     bin         Get the path to the Turbo binary
+    boundaries  Check that workspaces only depend on the packages they're allowed to
+    cache       Inspect and clean up the local filesystem cache
     completion  Generate the autocompletion script for the specified shell
     daemon      Runs the Turborepo background daemon
+    deps        List external packages that are resolved to more than one version
     generate    Generate a new app / package
     link        Link your local directory to a Vercel organization and enable remote caching
     login       Login to your Vercel account
     logout      Logout to your Vercel account
     prune       Prepare a subset of your monorepo
+    query       Query information about the repository
     run         Run tasks across projects in your monorepo
     unlink      Unlink the current directory from your Vercel organization and disable Remote Caching
+    watch       Run tasks and re-run them as their inputs change
+    worker      Run tasks on behalf of `turbo run --workers`
   
   Options:
         --version                         
@@ -146,6 +174,12 @@ Test help flag
             Override the filesystem cache directory
         --cache-workers <CACHE_WORKERS>
             Set the number of concurrent cache operations (default 10) [default: 10]
+        --cache-max-size <CACHE_MAX_SIZE>
+            Evict the least recently used entries from the filesystem cache before running once it is larger than this, e.g. 10GB or 500MB [env: TURBO_CACHE_MAX_SIZE=]
+        --cache-max-age <CACHE_MAX_AGE>
+            Evict entries from the filesystem cache before running if they haven't been used for this long, e.g. 7d or 12h [env: TURBO_CACHE_MAX_AGE=]
+        --cache-format <CACHE_FORMAT>
+            Set how artifacts are stored in the cache. "content-addressed" stores each distinct file once, shared between artifacts, and only uploads files the remote cache doesn't have. (default tarball) [env: TURBO_CACHE_FORMAT=] [possible values: tarball, content-addressed]
         --concurrency <CONCURRENCY>
             Limit the concurrency of task execution. Use 1 for serial (i.e. one-at-a-time) execution
         --continue
@@ -182,8 +216,12 @@ Test help flag
             Set type of process output logging. Use "full" to show all output. Use "hash-only" to show only turbo-computed task hashes. Use "new-only" to show only new output with only hashes for cached tasks. Use "none" to hide process output. (default full) [possible values: full, none, hash-only, new-only, errors-only]
         --log-order <LOG_ORDER>
             Set type of task output order. Use "stream" to show output as soon as it is available. Use "grouped" to show output when a command has finished execution. Use "auto" to let turbo decide based on its own heuristics. (default auto) [env: TURBO_LOG_ORDER=] [default: auto] [possible values: auto, stream, grouped]
+        --ui <UI>
+            Set how task progress and output are displayed. Use "stream" to print task output as it is logged. Use "tui" for an interactive dashboard with a scrollable log pane per task, which falls back to "stream" when not run in a terminal. (default stream) [env: TURBO_UI=] [default: stream] [possible values: stream, tui]
         --only
             Only executes the tasks specified, does not execute parent tasks
+        --otel-endpoint <URL>
+            Export a trace of the run, with a span for each task, to this OpenTelemetry collector over OTLP/HTTP, e.g. http://localhost:4318 [env: TURBO_OTEL_ENDPOINT=]
         --parallel
             Execute all tasks in parallel
         --profile <PROFILE>
@@ -194,12 +232,18 @@ Test help flag
             Ignore the local filesystem cache for all tasks. Only allow reading and caching artifacts using the remote cache [env: TURBO_REMOTE_ONLY=] [default: false] [possible values: true, false]
         --remote-cache-read-only [<BOOL>]
             Treat remote cache as read only [env: TURBO_REMOTE_CACHE_READ_ONLY=] [default: false] [possible values: true, false]
+        --report <FORMAT:PATH>
+            Write a report of each task's result once the run finishes, given as <FORMAT>:<PATH>. Supported formats are "junit" and "tap". Can be passed more than once [env: TURBO_REPORT=]
         --scope <SCOPE>
             Specify package(s) to act as entry points for task execution. Supports globs
         --since <SINCE>
             Limit/Set scope to changed packages since a mergebase. This uses the git diff ${target_branch}... mechanism to identify which packages have changed
         --summarize [<SUMMARIZE>]
             Generate a summary of the turbo run [env: TURBO_RUN_SUMMARY=] [possible values: true, false]
+        --explain-hash [<SUMMARY>]
+            Explain why tasks missed the cache by comparing their hash inputs against a previous run summary. Defaults to the most recent summary in .turbo/runs. Saves a summary of this run
+        --workers <ADDRESSES>
+            Run tasks on `turbo worker` processes at these addresses, given as host:port, rather than locally. Workers must share a cache with this run, so their outputs can be restored here [env: TURBO_WORKERS=]
         --log-prefix <LOG_PREFIX>
             Use "none" to remove prefixes from task logs. Use "task" to get task id prefixing. Use "auto" to let turbo decide how to prefix the logs based on the execution environment. In most cases this will be the same as "task". Note that tasks running in parallel interleave their logs, so removing prefixes can make it difficult to associate logs with tasks. Use --log-order=grouped to prevent interleaving. (default auto) [default: auto] [possible values: auto, none, task]
 
@@ -34,7 +34,10 @@ Setup
     "persistent": false,
     "env": [],
     "passThroughEnv": null,
-    "dotEnv": null
+    "dotEnv": null,
+    "definedIn": [
+      "override-values"
+    ]
   }
 
 # This task is similar, but `dependsOn` in the root turbo.json _only_ has a topological dependency
@@ -49,5 +52,8 @@ Setup
     "persistent": false,
     "env": [],
     "passThroughEnv": null,
-    "dotEnv": null
+    "dotEnv": null,
+    "definedIn": [
+      "override-values"
+    ]
   }